@@ -13,19 +13,41 @@ extern "C" {
 /// Information about receiver Identity can be saved in the wallet with sovrin_store_their_did
 /// call before establishing of connection. If there is no corresponded wallet record for receiver Identity
 /// than this call will lookup Identity Ledger and cache this information in the wallet.
+/// Endpoint of receiver Identity stored in the wallet (see sovrin_set_endpoint_for_did) is used while
+/// it is fresh, otherwise it is resolved on the Ledger and stored in the wallet for next connections.
 ///
 /// Note that messages encryption/decryption will be performed automatically.
 ///
+/// Instead of sender and receiver Identities pairwise (see sovrin_create_pairwise) can be passed:
+/// my DID of pairwise is used as sender Identity and endpoint of pairwise (if set) is used
+/// instead of endpoint of receiver Identity.
+///
+/// Connection can deliver messages reliably. In this mode each message gets id and is resent
+/// until connected agent acknowledges it, duplicates of resent messages are dropped by receiver.
+/// Connection is restored automatically if network is lost or connected agent is restarted.
+///
 /// #Params
 /// command_handle: Command handle to map callback to caller context.
 /// pool_handle: Pool handle (created by open_pool).
 /// wallet_handle: Wallet handle (created by open_wallet).
-/// sender_did: Id of sender Identity stored in secured Wallet.
-/// receiver_did: Id of receiver Identity.
+/// sender_did (optional): Id of sender Identity stored in secured Wallet.
+///     If not set, receiver_did is their DID of pairwise.
+/// receiver_did: Id of receiver Identity or their DID of pairwise.
+/// options_json (optional): connection options. If not set messages are sent without delivery acknowledgement.
+///     {
+///         "reliable": bool, (optional, false by default) enables reliable delivery,
+///         "ack_timeout": int, (optional, 3000 by default) timeout in ms to wait acknowledgement
+///             before first resend, it is doubled after each resend,
+///         "max_interval": int, (optional, 30000 by default) max interval in ms between resends
+///             and between reconnect attempts,
+///         "max_attempts": int, (optional, 10 by default) max count of message sending attempts
+///     }
 /// connection_cb: Callback that will be called after establishing of connection or on error.
 ///     Will be called exactly once with result of connect operation.
 /// message_cb: Callback that will be called on receiving of an incoming message.
 ///     Can be called multiply times: once for each incoming message.
+/// delivery_cb (optional): Callback that will be called with delivery status of message
+///     sent by reliable connection. Will be called exactly once for each sent message.
 ///
 /// #Returns
 /// Error code
@@ -35,14 +57,29 @@ extern "C" {
 /// - connection_handle: Connection handle to use for messages sending and mapping of incomming messages to this connection.
 /// message_cb:
 /// - xconnection_handle: Connection handle. Indetnifies connection.
-/// - err: Error code.
-/// - message: Received message.
+/// - err: Error code. CommonInvalidStructure if message signature is invalid.
+/// - message: Received message envelope json:
+///     {
+///         "version": int, version of envelope format,
+///         "id": string, unique id of message,
+///         "type": string, type of message, "message" for messages sent by sovrin_agent_send,
+///         "thread_id": string, (optional) id of thread the message belongs to,
+///         "timestamp": int, sending time in seconds since epoch,
+///         "signature": string, (optional) base58 signature by sender Identity verkey, verified on receiving,
+///         "body": string, message body encoded as base64url without padding
+///     }
+/// delivery_cb:
+/// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. Success if message is acknowledged by connected agent,
+///     CommonIOError if message isn't acknowledged after all attempts or connection is closed.
+/// - message_id: Id of message returned by sovrin_agent_send.
 
 extern sovrin_error_t sovrin_agent_connect(sovrin_handle_t command_handle,
                                            sovrin_handle_t pool_handle,
                                            sovrin_handle_t wallet_handle,
                                            const char *    sender_did,
                                            const char *    receiver_did,
+                                           const char *    options_json,
 
                                            void  (*connection_cb)(sovrin_handle_t xcommand_handle,
                                                                   sovrin_error_t  err,
@@ -50,7 +87,11 @@ extern sovrin_error_t sovrin_agent_connect(sovrin_handle_t command_handle,
                                            
                                            void     (*message_cb)(sovrin_handle_t xconnection_handle,
                                                                   sovrin_error_t  err,
-                                                                  const char *    message)
+                                                                  const char *    message),
+
+                                           void    (*delivery_cb)(sovrin_handle_t xconnection_handle,
+                                                                  sovrin_error_t  err,
+                                                                  sovrin_handle_t message_id)
                                            );
 
 /// Starts listening of agent connections.
@@ -64,18 +105,45 @@ extern sovrin_error_t sovrin_agent_connect(sovrin_handle_t command_handle,
 /// wallet record for sender Identity than listener will lookup Identity Ledger and cache this
 /// information in the wallet.
 ///
+/// Incoming connection is accepted only if sender proves ownership of sender Identity: it must
+/// sign listener challenge with Identity verkey and use Identity public key as transport key.
+/// Otherwise connection is rejected and connection_cb isn't called.
+///
+/// Listener policy restricts senders that can connect to the listener. Policy is enforced by
+/// transport layer, so connections of unauthorized senders are dropped before any message from them
+/// is received. Each rejected connection is reported by event_cb.
+///
 /// Note that messages encryption/decryption will be performed automatically.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger) to use for sender Identity lookup.
 /// wallet_handle: wallet handle (created by open_wallet).
 /// endpoint: endpoint to use in starting listener.
+/// identities_json (optional): json array of receiver Identities DIDs served by listener.
+///     All Identities must be created and stored in the wallet with sovrin_create_and_store_my_did.
+///     If not set all Identities stored in the wallet are served. Listener uses keys of the first
+///     Identity as transport keys, so this key must be published as transport key in endpoints
+///     of all served Identities (see sovrin_set_endpoint_for_did).
+/// policy_json (optional): listener policy. If not set any sender is allowed. One of:
+///     {"type": "any"} - allow any sender,
+///     {"type": "known"} - allow only senders stored in the wallet with sovrin_store_their_did
+///         before listener start. Transport keys of known senders are read from the wallet once
+///         at listener start, so senders stored later are rejected until listener is restarted,
+///     {"type": "list", "allow": [<did>, ...], "deny": [<did>, ...]} - allow only senders from
+///         optional allow list that aren't in optional deny list. DIDs from allow list must be
+///         stored in the wallet with sovrin_store_their_did.
 /// listener_cb: Callback that will be called after listening started or on error.
 ///     Will be called exactly once with result of start listen operation.
 /// connection_cb: Callback that will be called after establishing of incoming connection.
 ///     Can be called multiply times: once for each incoming connection.
 /// message_cb: Callback that will be called on receiving of an incoming message.
 ///     Can be called multiply times: once for each incoming message.
+/// event_cb: Callback that will be called on listener events.
+///     Can be called multiply times: once for each event.
+/// delivery_cb (optional): Callback that will be called with delivery status of message
+///     sent by incoming connection if connected agent requested reliable delivery
+///     (see sovrin_agent_connect). Will be called exactly once for each such message.
 ///
 /// #Returns
 /// Error code
@@ -88,15 +156,39 @@ extern sovrin_error_t sovrin_agent_connect(sovrin_handle_t command_handle,
 /// - err: Error code
 /// - connection_handle: Connection handle to use for messages sending and mapping of incomming messages to to this connection.
 /// - sender_did: Id of sender Identity stored in secured Wallet.
-/// - receiver_did: Id of receiver Identity.
+/// - receiver_did: Id of receiver Identity the connection is addressed to.
 /// message_cb:
 /// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. CommonInvalidStructure if message signature is invalid.
+/// - message: Received message envelope json (see sovrin_agent_connect).
+/// event_cb:
+/// - xlistener_handle: Listener handle. Identifies listener.
 /// - err: Error code.
-/// - message: Received message.
+/// - event_json: Listener event, one of:
+///     {
+///         "type": "connection_rejected",
+///         "did": string, (optional) Id of sender Identity if known,
+///         "client_key": string, (optional) Sender transport key,
+///         "reason": string, Reason of rejection
+///     }
+///     {
+///         "type": "invitation_accepted", (see sovrin_agent_create_invitation)
+///         "my_did": string, Invited Identity,
+///         "their_did": string, Identity of invitee,
+///         "label": string, (optional) Label sent by invitee
+///     }
+/// delivery_cb:
+/// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. Success if message is acknowledged by connected agent,
+///     CommonIOError if message isn't acknowledged after all attempts or connection is closed.
+/// - message_id: Id of message returned by sovrin_agent_send.
 
 extern sovrin_error_t sovrin_agent_listen(sovrin_handle_t command_handle,
+                                          sovrin_handle_t pool_handle,
                                           sovrin_handle_t wallet_handle,
                                           const char *    endpoint,
+                                          const char *    identities_json,
+                                          const char *    policy_json,
 
                                           void     (*listener_cb)(sovrin_handle_t xcommand_handle,
                                                                   sovrin_error_t  err,
@@ -110,18 +202,87 @@ extern sovrin_error_t sovrin_agent_listen(sovrin_handle_t command_handle,
 
                                           void      (*message_cb)(sovrin_handle_t xconnection_handle,
                                                                   sovrin_error_t  err,
-                                                                  const char *    message)
+                                                                  const char *    message),
+
+                                          void        (*event_cb)(sovrin_handle_t xlistener_handle,
+                                                                  sovrin_error_t  err,
+                                                                  const char *    event_json),
+
+                                          void     (*delivery_cb)(sovrin_handle_t xconnection_handle,
+                                                                  sovrin_error_t  err,
+                                                                  sovrin_handle_t message_id)
                                           );
 
+/// Adds Identity to listener, so incoming connections addressed to this Identity will be accepted.
+///
+/// Note that transport key of the listener must be published in endpoint of the Identity
+/// (see sovrin_set_endpoint_for_did).
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// listener_handle: Listener handle returned by sovrin_agent_listen call.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// did: Id of Identity to add. Identity must be created and stored in the wallet with
+///     sovrin_create_and_store_my_did.
+/// cb: Callback that will be called after Identity added or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_add_identity(sovrin_handle_t command_handle,
+                                                sovrin_handle_t listener_handle,
+                                                sovrin_handle_t wallet_handle,
+                                                const char *    did,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err)
+                                                );
+
+/// Removes Identity from listener, so new incoming connections addressed to this Identity
+/// will be rejected.
+///
+/// Note that already established connections aren't closed.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// listener_handle: Listener handle returned by sovrin_agent_listen call.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// did: Id of Identity to remove.
+/// cb: Callback that will be called after Identity removed or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_remove_identity(sovrin_handle_t command_handle,
+                                                   sovrin_handle_t listener_handle,
+                                                   sovrin_handle_t wallet_handle,
+                                                   const char *    did,
+
+                                                   void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                        sovrin_error_t  err)
+                                                   );
+
 /// Sends message to connected agent.
 ///
 /// Note that this call works for both incoming and outgoing connections.
 /// Note that messages encryption/decryption will be performed automatically.
+/// Note that for reliable connection callback is called after message is queued for sending,
+/// delivery of the message is reported by delivery_cb of the connection.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// connection_handle: Connection handle returned by sovrin_agent_connect or sovrin_agent_listen calls.
-/// message: Message to send.
+/// message: Message to send. It is sent as body of envelope of "message" type.
 /// cb: Callback that will be called after message sent or on error. Will be called exactly once.
 ///
 /// #Returns
@@ -129,6 +290,8 @@ extern sovrin_error_t sovrin_agent_listen(sovrin_handle_t command_handle,
 /// cb:
 /// - xcommand_handle: Command handle to map callback to caller context.
 /// - err: Error code
+/// - message_id: Id of message in reliable connection to map delivery status to this message,
+///     0 for connection without reliable delivery.
 ///
 /// #Errors
 
@@ -137,9 +300,50 @@ extern sovrin_error_t sovrin_agent_send(sovrin_handle_t command_handle,
                                         const char *    message,
 
                                         void     (*cb)(sovrin_handle_t xcommand_handle,
-                                                       sovrin_error_t  err)
+                                                       sovrin_error_t  err,
+                                                       sovrin_handle_t message_id)
                                        );
 
+/// Sends message of given type with binary body to connected agent.
+///
+/// Note that this call works for both incoming and outgoing connections.
+/// Note that types with "sovrin/" prefix are reserved for control messages.
+/// Note that for reliable connection callback is called after message is queued for sending,
+/// delivery of the message is reported by delivery_cb of the connection.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle returned by sovrin_agent_connect or sovrin_agent_listen calls.
+/// message_type: Type of message.
+/// thread_id (optional): Id of thread the message belongs to, e.g. id of message it replies to.
+/// body_raw: a pointer to first byte of message body.
+/// body_len: a message body length.
+/// sign: Sign message by verkey of my Identity of the connection.
+/// cb: Callback that will be called after message sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// err: Error code
+/// cb:
+/// - xcommand_handle: Command handle to map callback to caller context.
+/// - err: Error code
+/// - message_id: Id of message in reliable connection to map delivery status to this message,
+///     0 for connection without reliable delivery.
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_send_typed(sovrin_handle_t     command_handle,
+                                              sovrin_handle_t     connection_handle,
+                                              const char *        message_type,
+                                              const char *        thread_id,
+                                              const sovrin_u8_t * body_raw,
+                                              sovrin_u32_t        body_len,
+                                              sovrin_bool_t       sign,
+
+                                              void     (*cb)(sovrin_handle_t xcommand_handle,
+                                                             sovrin_error_t  err,
+                                                             sovrin_handle_t message_id)
+                                             );
+
 /// Closes agent connection.
 ///
 /// Note that this call works for both incoming and outgoing connections.
@@ -189,6 +393,518 @@ extern sovrin_error_t sovrin_agent_close_listener(sovrin_handle_t command_handle
                                                                  sovrin_error_t  err)
                                                   );
 
+/// Starts hosting of mailbox for Identity by relay agent.
+///
+/// Relay agent is an agent that listens for connections (see sovrin_agent_listen) with
+/// Identity stored in this wallet. Agents connected to relay can forward messages for hosted Identity
+/// with sovrin_agent_mailbox_forward. These messages are stored in relay wallet until hosted Identity
+/// connects to relay and pulls them with sovrin_agent_mailbox_pull.
+///
+/// Note that messages are encrypted for hosted Identity, so relay can't read them.
+/// Messages of already hosted Identity are kept.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of relay Identity.
+/// did: Id of hosted Identity.
+/// cb: Callback that will be called after mailbox is hosted or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_mailbox_host(sovrin_handle_t command_handle,
+                                                sovrin_handle_t wallet_handle,
+                                                const char *    did,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err)
+                                                );
+
+/// Forwards message to mailbox of Identity hosted by relay agent.
+///
+/// Message is encrypted for receiver Identity, so relay can't read it. Receiver Identity
+/// must be saved in the wallet with sovrin_store_their_did call.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle to relay agent returned by sovrin_agent_connect call.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// receiver_did: Id of receiver Identity hosted by relay.
+/// message: Message to forward.
+/// cb: Callback that will be called after relay stored message or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidState if relay doesn't host receiver Identity.
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_mailbox_forward(sovrin_handle_t command_handle,
+                                                   sovrin_handle_t connection_handle,
+                                                   sovrin_handle_t wallet_handle,
+                                                   const char *    receiver_did,
+                                                   const char *    message,
+
+                                                   void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                        sovrin_error_t  err)
+                                                   );
+
+/// Pulls all messages stored in mailbox of Identity hosted by relay agent.
+///
+/// Mailbox is identified by sender Identity of the connection. Pulled messages are decrypted
+/// with the key of the Identity and then removed from relay wallet, so messages of failed pull
+/// are returned again by the next pull.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle to relay agent returned by sovrin_agent_connect call.
+/// wallet_handle: wallet handle (created by open_wallet) of hosted Identity.
+/// cb: Callback that will be called with pulled messages or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidState if relay doesn't host Identity.
+/// - messages_json: messages in order of receiving by relay:
+///     [{
+///         "id": int, id of message in mailbox,
+///         "sender_did": string, Id of Identity that forwarded message,
+///         "message": string, decrypted message
+///     }]
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_mailbox_pull(sovrin_handle_t command_handle,
+                                                sovrin_handle_t connection_handle,
+                                                sovrin_handle_t wallet_handle,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err,
+                                                                     const char *    messages_json)
+                                                );
+
+/// Subscribes wallet to events of claim issuance protocol.
+///
+/// Claims are issued over agent connections: issuer offers claim with sovrin_agent_claim_offer,
+/// prover stores the offer and sends claim request with sovrin_agent_claim_request, issuer creates
+/// claim with sovrin_agent_claim_issue and prover stores received claim. State of each exchange is kept
+/// in the wallets of both sides. Incoming protocol messages are handled only for subscribed wallets.
+///
+/// Previous subscription of the wallet is replaced.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// options_json: (optional) claim exchange options:
+///     {
+///         "master_secret_name": string, (optional) if set, claim request is created and sent
+///                               with this master secret for each received offer automatically
+///     }
+/// cb: Callback that will be called after subscription or on error. Will be called exactly once.
+/// event_cb: Callback that will be called on each claim exchange event of the wallet.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// event_cb:
+/// - xwallet_handle: wallet handle of the exchange.
+/// - err: Error code
+/// - event_json: one of events:
+///     {"type": "offer_received", "exchange_id": string, "claim_offer": string}
+///     {"type": "request_sent", "exchange_id": string} - only for automatically created requests
+///     {"type": "request_received", "exchange_id": string, "claim_req": string}
+///     {"type": "claim_stored", "exchange_id": string}
+///     {"type": "failed", "exchange_id": string, "reason": string}
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_claim_subscribe(sovrin_handle_t command_handle,
+                                                   sovrin_handle_t wallet_handle,
+                                                   const char *    options_json,
+
+                                                   void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                        sovrin_error_t  err),
+
+                                                   void           (*event_cb)(sovrin_handle_t xwallet_handle,
+                                                                              sovrin_error_t  err,
+                                                                              const char *    event_json)
+                                                   );
+
+/// Offers claim to the receiver Identity of the connection.
+///
+/// Starts claim exchange on issuer side. Issuer DID of the offer must be sender DID of the connection.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle returned by sovrin_agent_connect call or by connection callback of listener.
+/// claim_offer_json: claim offer created by sovrin_issuer_create_claim_offer.
+/// claim_def_json: claim definition as it is stored on the ledger (with seqNo), needed by prover to create claim request.
+/// cb: Callback that will be called after offer is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchange_id: Id of claim exchange.
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_claim_offer(sovrin_handle_t command_handle,
+                                               sovrin_handle_t connection_handle,
+                                               const char *    claim_offer_json,
+                                               const char *    claim_def_json,
+
+                                               void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                    sovrin_error_t  err,
+                                                                    const char *    exchange_id)
+                                               );
+
+/// Creates claim request for received claim offer and sends it to issuer.
+///
+/// Request is created with sovrin_prover_create_and_store_claim_req logic. Connection to issuer
+/// must be established.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of prover.
+/// exchange_id: Id of claim exchange reported by offer_received event.
+/// master_secret_name: name of master secret stored in the wallet.
+/// cb: Callback that will be called after request is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidStructure if exchange is unknown or offer is already requested.
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_claim_request(sovrin_handle_t command_handle,
+                                                 sovrin_handle_t wallet_handle,
+                                                 const char *    exchange_id,
+                                                 const char *    master_secret_name,
+
+                                                 void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                      sovrin_error_t  err)
+                                                 );
+
+/// Creates claim for received claim request and sends it to prover.
+///
+/// Claim is created with sovrin_issuer_create_claim logic. Connection to prover must be established.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of issuer.
+/// exchange_id: Id of claim exchange reported by request_received event.
+/// claim_json: claim attributes values as for sovrin_issuer_create_claim.
+/// revoc_reg_seq_no: (optional, pass -1 if revoc_reg_seq_no is absentee) seq no of a revocation registry transaction in Ledger
+/// user_revoc_index: index of a new user in the revocation registry (optional, pass -1 if user_revoc_index is absentee; default one is used if not provided)
+/// cb: Callback that will be called after claim is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidStructure if exchange is unknown or request isn't received.
+/// - revoc_reg_update_json: Revocation registry update json with a newly issued claim
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_claim_issue(sovrin_handle_t command_handle,
+                                               sovrin_handle_t wallet_handle,
+                                               const char *    exchange_id,
+                                               const char *    claim_json,
+                                               sovrin_i32_t    revoc_reg_seq_no,
+                                               sovrin_i32_t    user_revoc_index,
+
+                                               void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                    sovrin_error_t  err,
+                                                                    const char *    revoc_reg_update_json)
+                                               );
+
+/// Lists claim exchanges stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cb: Callback that will be called with claim exchanges or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchanges_json:
+///     [{
+///         "id": string, Id of exchange,
+///         "role": "issuer" or "prover",
+///         "state": one of "offered", "offer_received", "requested", "request_received", "issued", "stored", "failed",
+///         "my_did": string,
+///         "their_did": string,
+///         "claim_offer": string,
+///         "claim_def": optional string,
+///         "claim_req": optional string,
+///         "reason": optional string, reason of failure
+///     }]
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_claim_exchanges(sovrin_handle_t command_handle,
+                                                   sovrin_handle_t wallet_handle,
+
+                                                   void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                        sovrin_error_t  err,
+                                                                        const char *    exchanges_json)
+                                                   );
+
+/// Subscribes wallet to events of proof presentation protocol.
+///
+/// Proofs are presented over agent connections: verifier sends proof request with sovrin_agent_proof_request,
+/// prover gets claims matching the request in request_received event and presents proof with chosen claims
+/// by sovrin_agent_proof_present. Verifier checks received proof automatically. Schemas and claim definitions
+/// of used claims are resolved from the ledger of the pool on both sides. State of each exchange is kept
+/// in the wallets of both sides. Incoming protocol messages are handled only for subscribed wallets.
+///
+/// Previous subscription of the wallet is replaced.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger) used to resolve schemas and claim definitions.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cb: Callback that will be called after subscription or on error. Will be called exactly once.
+/// event_cb: Callback that will be called on each proof exchange event of the wallet.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// event_cb:
+/// - xwallet_handle: wallet handle of the exchange.
+/// - err: Error code
+/// - event_json: one of events:
+///     {"type": "request_received", "exchange_id": string, "proof_req": string, "claims": string} - claims
+///         are in sovrin_prover_get_claims_for_proof_req format
+///     {"type": "proof_received", "exchange_id": string, "proof": string, "valid": bool} - for verifier
+///     {"type": "proof_result", "exchange_id": string, "valid": bool} - for prover
+///     {"type": "failed", "exchange_id": string, "reason": string}
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_proof_subscribe(sovrin_handle_t command_handle,
+                                                   sovrin_handle_t pool_handle,
+                                                   sovrin_handle_t wallet_handle,
+
+                                                   void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                        sovrin_error_t  err),
+
+                                                   void           (*event_cb)(sovrin_handle_t xwallet_handle,
+                                                                              sovrin_error_t  err,
+                                                                              const char *    event_json)
+                                                   );
+
+/// Sends proof request to the receiver Identity of the connection.
+///
+/// Starts proof exchange on verifier side. Nonce of the request is replaced with fresh one
+/// and received proof is verified only against this nonce, so proof can't be replayed.
+/// Wallet of the connection must be subscribed with sovrin_agent_proof_subscribe.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle returned by sovrin_agent_connect call or by connection callback of listener.
+/// proof_req_json: proof request in sovrin_prover_get_claims_for_proof_req format.
+/// cb: Callback that will be called after request is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchange_id: Id of started proof exchange.
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_proof_request(sovrin_handle_t command_handle,
+                                                 sovrin_handle_t connection_handle,
+                                                 const char *    proof_req_json,
+
+                                                 void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                      sovrin_error_t  err,
+                                                                      const char *    exchange_id)
+                                                 );
+
+/// Creates proof for received proof request with chosen claims and sends it to verifier.
+///
+/// Proof is created with sovrin_prover_create_proof logic. Only claims reported by request_received
+/// event can be used. Connection to verifier must be established.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of prover.
+/// exchange_id: Id of proof exchange reported by request_received event.
+/// requested_claims_json: claims chosen for the proof in sovrin_prover_create_proof format.
+/// master_secret_name: name of master secret stored in the wallet.
+/// cb: Callback that will be called after proof is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidStructure if exchange is unknown, proof is already presented
+///   or claims don't match proof request.
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_proof_present(sovrin_handle_t command_handle,
+                                                 sovrin_handle_t wallet_handle,
+                                                 const char *    exchange_id,
+                                                 const char *    requested_claims_json,
+                                                 const char *    master_secret_name,
+
+                                                 void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                      sovrin_error_t  err)
+                                                 );
+
+/// Lists proof exchanges stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cb: Callback that will be called with proof exchanges or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchanges_json:
+///     [{
+///         "id": string, Id of exchange,
+///         "role": "verifier" or "prover",
+///         "state": one of "requested", "request_received", "presented", "proof_received", "verified", "rejected", "failed",
+///         "my_did": string,
+///         "their_did": string,
+///         "proof_req": string, proof request with nonce of the exchange,
+///         "claims": optional string, claims found for proof request (prover only),
+///         "proof": optional string,
+///         "identifiers": optional {claim_uuid: {"issuer_did": string, "schema_seq_no": int}},
+///         "reason": optional string, reason of failure
+///     }]
+///
+/// #Errors
+
+extern sovrin_error_t sovrin_agent_proof_exchanges(sovrin_handle_t command_handle,
+                                                   sovrin_handle_t wallet_handle,
+
+                                                   void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                        sovrin_error_t  err,
+                                                                        const char *    exchanges_json)
+                                                   );
+
+/// Creates invitation to pairwise relationship. Fresh Identity is created in the wallet
+/// and added to identities served by the listener. Invitation is passed to invitee out of band.
+/// It can be accepted only once: the first Identity connected to invited one is stored
+/// without resolving it on the ledger and pairwise with it is created (see sovrin_create_pairwise).
+/// Acceptance is reported by listener event_cb. Transport key of invitee isn't known in advance,
+/// so invitation can't be accepted by listener with "known" policy or with allow list.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// listener_handle: Listener handle (created by sovrin_agent_listen).
+/// wallet_handle: Wallet handle (created by open_wallet).
+/// label (optional): label of inviter passed to invitee.
+/// cb: Callback that will be called with invitation or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - invitation_json:
+///     {
+///         "did": string, Invited Identity,
+///         "verkey": string, verkey of invited Identity,
+///         "endpoint": string, endpoint of the listener,
+///         "transport_key": string, transport key of the listener,
+///         "label": string, (optional) label of inviter
+///     }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_agent_create_invitation(sovrin_handle_t command_handle,
+                                                     sovrin_handle_t listener_handle,
+                                                     sovrin_handle_t wallet_handle,
+                                                     const char *    label,
+
+                                                     void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                          sovrin_error_t  err,
+                                                                          const char *    invitation_json)
+                                                     );
+
+/// Accepts invitation created by sovrin_agent_create_invitation. Fresh Identity is created
+/// in the wallet, invited Identity is stored with endpoint from invitation and connection to it
+/// is established. Pairwise with invited Identity is created (see sovrin_create_pairwise).
+/// Neither Identity is written to the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: Wallet handle (created by open_wallet).
+/// invitation_json: invitation (see sovrin_agent_create_invitation).
+/// options_json (optional): accepting options:
+///     {
+///         "label": string, (optional) label of invitee passed to inviter,
+///         "endpoint": string, (optional) endpoint of invitee stored by inviter,
+///         "transport_key": string, (optional) transport key of invitee endpoint
+///     }
+/// connection_cb: Callback that will be called after establishing of connection and sending
+///     of acceptance or on error. Will be called exactly once.
+/// message_cb: Callback that will be called on receiving of an incoming message.
+///     Can be called multiply times: once for each incoming message.
+///
+/// #Returns
+/// Error code
+/// connection_cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code.
+/// - connection_handle: Connection handle to use for messages sending and mapping of incomming messages to this connection.
+/// - my_did: Created Identity of invitee.
+/// message_cb:
+/// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. CommonInvalidStructure if message signature is invalid.
+/// - message: Received message envelope json (see sovrin_agent_connect).
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_agent_accept_invitation(sovrin_handle_t command_handle,
+                                                     sovrin_handle_t wallet_handle,
+                                                     const char *    invitation_json,
+                                                     const char *    options_json,
+
+                                                     void           (*connection_cb)(sovrin_handle_t xcommand_handle,
+                                                                                     sovrin_error_t  err,
+                                                                                     sovrin_handle_t connection_handle,
+                                                                                     const char *    my_did),
+
+                                                     void           (*message_cb)(sovrin_handle_t xconnection_handle,
+                                                                                  sovrin_error_t  err,
+                                                                                  const char *    message)
+                                                     );
+
 #ifdef __cplusplus
 }
 #endif
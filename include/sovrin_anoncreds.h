@@ -52,6 +52,19 @@ extern "C" {
                                                                           sovrin_error_t  err,
                                                                           const char*     revoc_reg_update_json)
                                                      );
+
+    extern sovrin_error_t sovrin_issuer_revoke_claim_with_ledger_update(sovrin_handle_t command_handle,
+                                                                        sovrin_handle_t wallet_handle,
+                                                                        const char *    submitter_did,
+                                                                        sovrin_i32_t    claim_def_seq_no,
+                                                                        sovrin_i32_t    revoc_reg_seq_no,
+                                                                        sovrin_i32_t    user_revoc_index,
+
+                                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                             sovrin_error_t  err,
+                                                                                             const char*     revoc_reg_update_json,
+                                                                                             const char*     revoc_reg_entry_request_json)
+                                                                        );
     
     extern sovrin_error_t sovrin_prover_store_claim_offer(sovrin_handle_t command_handle,
                                                           sovrin_handle_t wallet_handle,
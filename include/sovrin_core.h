@@ -8,5 +8,6 @@
 #include "sovrin_ledger.h"
 #include "sovrin_signus.h"
 #include "sovrin_agent.h"
+#include "sovrin_pairwise.h"
 
 #endif
@@ -68,6 +68,110 @@ extern "C" {
                                                                      const char*     request_result_json)
                                                );
 
+    /// Publishes GET_NYM, GET_SCHEMA, GET_CLAIM_DEF or GET_TXN request to validator pool
+    /// through the read-through cache stored in the wallet.
+    ///
+    /// If the wallet contains a fresh enough reply for the same request it is returned
+    /// without contacting the pool, reqId in its result is replaced with reqId of the request.
+    /// Otherwise the request is sent as is and a successful reply is stored in the wallet.
+    /// Requests of other types are just sent to the pool.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// pool_handle: pool handle (created by open_pool_ledger).
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// request_json: Request data json.
+    /// options_json: cache options json (optional):
+    /// {
+    ///     "refresh": bool, (optional; false by default) don't read cached reply, but store the new one
+    ///     "bypass": bool, (optional; false by default) don't read or store cached reply
+    ///     "ttl": int, (optional) max age of cached reply in seconds. By default
+    ///         300 is used for GET_NYM and 86400 for GET_SCHEMA, GET_CLAIM_DEF and GET_TXN
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_submit_request_with_cache(sovrin_handle_t command_handle,
+                                                           sovrin_handle_t pool_handle,
+                                                           sovrin_handle_t wallet_handle,
+                                                           const char *    request_json,
+                                                           const char *    options_json,
+
+                                                           void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                sovrin_error_t  err,
+                                                                                const char*     request_result_json)
+                                                          );
+
+    /// Removes cached ledger replies from the wallet.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// cache_type: type of cached replies to remove: "nym", "schema", "claim_def" or "txn" (optional; all by default)
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_purge_ledger_cache(sovrin_handle_t command_handle,
+                                                    sovrin_handle_t wallet_handle,
+                                                    const char *    cache_type,
+
+                                                    void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                         sovrin_error_t  err)
+                                                   );
+
+    /// Resolves DID document (DDO) of the given DID using the ledger.
+    ///
+    /// DDO is assembled from the NYM record of the DID (verkey and role) and
+    /// its "endpoint" ATTRIB (agent address and transport key). Abbreviated verkey is
+    /// expanded to the full one.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// pool_handle: pool handle (created by open_pool_ledger).
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// did: DID to resolve.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// DDO json:
+    /// {
+    ///     "did": string,
+    ///     "verkey": string, (null if NYM has no verkey)
+    ///     "role": string, ("TRUSTEE", "STEWARD" or null for other roles)
+    ///     "endpoint": { (null if endpoint ATTRIB isn't set)
+    ///         "address": string,
+    ///         "transport_key": string (null if not set)
+    ///     }
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_resolve_did(sovrin_handle_t command_handle,
+                                             sovrin_handle_t pool_handle,
+                                             sovrin_handle_t wallet_handle,
+                                             const char *    did,
+
+                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                  sovrin_error_t  err,
+                                                                  const char*     ddo_json)
+                                            );
+
     /// Builds a request to get a DDO.
     ///
     /// #Params
@@ -299,13 +403,126 @@ extern "C" {
                                                           );
 
 
+    /// Builds a REVOC_REG_DEF request.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// xref: Seq. number of claim definition
+    /// data: revocation registry json as returned by sovrin_issuer_create_and_store_revoc_reg
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_revoc_reg_def_request(sovrin_handle_t command_handle,
+                                                             const char *    submitter_did,
+                                                             sovrin_i32_t    xref,
+                                                             const char *    data,
+
+                                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                  sovrin_error_t  err,
+                                                                                  const char*     request_json)
+                                                            );
+
+    /// Builds a GET_REVOC_REG_DEF request.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// xref: Seq. number of claim definition
+    /// origin: issuer did
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_get_revoc_reg_def_request(sovrin_handle_t command_handle,
+                                                                 const char *    submitter_did,
+                                                                 sovrin_i32_t    xref,
+                                                                 const char *    origin,
+
+                                                                 void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                      sovrin_error_t  err,
+                                                                                      const char*     request_json)
+                                                                );
+
+    /// Builds a REVOC_REG_ENTRY request.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// xref: Seq. number of revocation registry definition
+    /// data: accumulator delta json:
+    /// {
+    ///     "accum": string, current accumulator value
+    ///     "issued": array<int>, indexes issued since the previous entry
+    ///     "revoked": array<int>, indexes revoked since the previous entry
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_revoc_reg_entry_request(sovrin_handle_t command_handle,
+                                                               const char *    submitter_did,
+                                                               sovrin_i32_t    xref,
+                                                               const char *    data,
+
+                                                               void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                    sovrin_error_t  err,
+                                                                                    const char*     request_json)
+                                                              );
+
+    /// Builds a GET_REVOC_REG request to fetch the current accumulator of a revocation registry.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// xref: Seq. number of revocation registry definition
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_get_revoc_reg_request(sovrin_handle_t command_handle,
+                                                             const char *    submitter_did,
+                                                             sovrin_i32_t    xref,
+
+                                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                  sovrin_error_t  err,
+                                                                                  const char*     request_json)
+                                                            );
+
     /// Builds a NODE request.
     ///
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
     /// target_did: Id of Identity stored in secured Wallet.
-    /// data: id of a target NYM record
+    /// data: node data json:
+    /// {
+    ///     "node_ip": string, (optional; if set client_ip, node_port and client_port must be set too)
+    ///     "node_port": int, (optional)
+    ///     "client_ip": string, (optional)
+    ///     "client_port": int, (optional)
+    ///     "alias": string,
+    ///     "services": array<string>, (optional if address is set; "VALIDATOR" and/or "OBSERVER")
+    /// }
+    /// A node can be demoted by sending only alias and empty services
+    /// and promoted again by sending alias and ["VALIDATOR"] services.
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -323,6 +540,72 @@ extern "C" {
                                                                          sovrin_error_t  err,
                                                                          const char*     request_json)
                                                    );
+
+    /// Builds a POOL_UPGRADE request.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// name: Human-readable name for the upgrade.
+    /// version: The version of Sovrin node package we perform upgrade to.
+    /// action: Either "start" or "cancel".
+    /// sha256: sha256 hash of the package (64 hex characters).
+    /// timeout: Limits upgrade time on each Node, in minutes (optional; negative value means not set).
+    /// schedule: Schedule of when to perform upgrade on each node as json map of node did to datetime
+    ///     (required for "start" action).
+    /// justification: Justification string for this particular Upgrade (optional).
+    /// reinstall: Whether it's allowed to re-install the same version.
+    /// force: Whether we should apply transaction without waiting for consensus of this transaction.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_pool_upgrade_request(sovrin_handle_t command_handle,
+                                                            const char *    submitter_did,
+                                                            const char *    name,
+                                                            const char *    version,
+                                                            const char *    action,
+                                                            const char *    sha256,
+                                                            sovrin_i32_t    timeout,
+                                                            const char *    schedule,
+                                                            const char *    justification,
+                                                            sovrin_bool_t   reinstall,
+                                                            sovrin_bool_t   force,
+
+                                                            void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                 sovrin_error_t  err,
+                                                                                 const char*     request_json)
+                                                           );
+
+    /// Builds a POOL_CONFIG request.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// writes: Whether any write requests can be processed by the pool
+    ///     (if false, then pool goes to read-only state).
+    /// force: Whether we should apply transaction without waiting for consensus of this transaction.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_pool_config_request(sovrin_handle_t command_handle,
+                                                           const char *    submitter_did,
+                                                           sovrin_bool_t   writes,
+                                                           sovrin_bool_t   force,
+
+                                                           void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                sovrin_error_t  err,
+                                                                                const char*     request_json)
+                                                          );
     
 #ifdef __cplusplus
 }
@@ -43,6 +43,15 @@ typedef enum
     // IO Error
     CommonIOError,
 
+    // Caller passed invalid value as param 10 (null, invalid json and etc..)
+    CommonInvalidParam10,
+
+    // Caller passed invalid value as param 11 (null, invalid json and etc..)
+    CommonInvalidParam11,
+
+    // Caller passed invalid value as param 12 (null, invalid json and etc..)
+    CommonInvalidParam12,
+
     // Wallet errors
     // Caller passed invalid wallet handle
     WalletInvalidHandle = 200,
@@ -81,6 +90,9 @@ typedef enum
     // Attempt to send transaction without the necessary privileges
     LedgerSecurityError,
 
+    // Requested entity (e.g. DID) is not found on the ledger
+    LedgerNotFoundError,
+
     // Revocation registry is full and creation of new registry is necessary
     AnoncredsRevocationRegistryFullError = 400,
 
@@ -0,0 +1,150 @@
+#ifndef __sovrin_pairwise__included__
+#define __sovrin_pairwise__included__
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// Creates pairwise: persistent relationship between my DID and their DID stored in the wallet.
+/// Pairwise is identified by their DID, so only one pairwise can be created for their DID.
+/// It can be passed to sovrin_agent_connect instead of sender and receiver DIDs.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID stored in the wallet by sovrin_store_their_did.
+/// my_did: my DID stored in the wallet by sovrin_create_and_store_my_did.
+/// pairwise_json (optional): pairwise info as json:
+///     {
+///         "endpoint": string, (optional) endpoint of their agent,
+///         "transport_key": string, (optional) transport key of their agent endpoint,
+///         "metadata": string, (optional) arbitrary protocol metadata
+///     }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_create_pairwise(sovrin_handle_t command_handle,
+                                             sovrin_handle_t wallet_handle,
+                                             const char *    their_did,
+                                             const char *    my_did,
+                                             const char *    pairwise_json,
+
+                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                  sovrin_error_t  err)
+                                             );
+
+/// Returns pairwise created by sovrin_create_pairwise.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID identifying pairwise.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// pairwise_json: pairwise as json:
+///     {
+///         "my_did": string,
+///         "their_did": string,
+///         "endpoint": string, (optional)
+///         "transport_key": string, (optional)
+///         "metadata": string, (optional)
+///         "state": one of "created", "connected", "disconnected", last known state
+///             of agent connection between pairwise DIDs
+///     }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_get_pairwise(sovrin_handle_t command_handle,
+                                          sovrin_handle_t wallet_handle,
+                                          const char *    their_did,
+
+                                          void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                               sovrin_error_t  err,
+                                                               const char *    pairwise_json)
+                                          );
+
+/// Lists pairwise stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// pairwise_list_json: list of pairwise as json (see sovrin_get_pairwise).
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_list_pairwise(sovrin_handle_t command_handle,
+                                           sovrin_handle_t wallet_handle,
+
+                                           void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                sovrin_error_t  err,
+                                                                const char *    pairwise_list_json)
+                                           );
+
+/// Updates pairwise created by sovrin_create_pairwise.
+/// Fields set in pairwise json replace previous values, other fields are kept.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID identifying pairwise.
+/// pairwise_json: pairwise info as json (see sovrin_create_pairwise).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_update_pairwise(sovrin_handle_t command_handle,
+                                             sovrin_handle_t wallet_handle,
+                                             const char *    their_did,
+                                             const char *    pairwise_json,
+
+                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                  sovrin_error_t  err)
+                                             );
+
+/// Deletes pairwise created by sovrin_create_pairwise. My and their DIDs are kept in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID identifying pairwise.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+
+extern sovrin_error_t sovrin_delete_pairwise(sovrin_handle_t command_handle,
+                                             sovrin_handle_t wallet_handle,
+                                             const char *    their_did,
+
+                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                  sovrin_error_t  err)
+                                             );
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
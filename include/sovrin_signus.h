@@ -20,12 +20,13 @@ extern "C" {
     ///             as a new DID; if provided, then keys will be replaced - key rotation use case)
     ///     "seed": string, (optional; if not provide then a random one will be created)
     ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
-    ///               currently only 'ed25519' value is supported for this field)
+    ///               'secp256k1' is also supported if built with secp256k1_rust feature)
     /// }
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
     /// DID, verkey (for verification of signature) and public_key (for decryption)
+    /// Verkey is abbreviated ("~" followed by the last 16 bytes) if DID is built from ed25519 verkey.
     ///
     /// #Errors
     /// Common*
@@ -53,12 +54,13 @@ extern "C" {
     /// {
     ///     "seed": string, (optional; if not provide then a random one will be created)
     ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
-    ///               currently only 'ed25519' value is supported for this field)
+    ///               'secp256k1' is also supported if built with secp256k1_rust feature)
     /// }
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
     /// verkey (for verification of signature) and public_key (for decryption)
+    /// Verkey is abbreviated in the same way as by sovrin_create_and_store_my_did.
     ///
     /// #Errors
     /// Common*
@@ -76,6 +78,108 @@ extern "C" {
                                                                    const char*     pk)
                                              );
 
+    /// Generates new keys (signing and encryption keys) for an existing
+    /// DID (owned by the caller of the library) and stores them as pending
+    /// next to the current ones. Current keys stay in use until
+    /// sovrin_replace_keys_apply is called, so NYM with the new verkey can be
+    /// signed by the old key and written to the ledger first.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// command_handle: command handle to map callback to user context.
+    /// did: existing DID owned by the caller.
+    /// identity_json: Identity information as json. Example:
+    /// {
+    ///     "seed": string, (optional; if not provide then a random one will be created)
+    ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
+    ///               'secp256k1' is also supported if built with secp256k1_rust feature)
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// pending verkey (for verification of signature) and public_key (for decryption)
+    /// Verkey is abbreviated in the same way as by sovrin_create_and_store_my_did.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_replace_keys_start(sovrin_handle_t command_handle,
+                                                    sovrin_handle_t wallet_handle,
+                                                    const char *    did,
+                                                    const char *    identity_json,
+
+                                                    void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                         sovrin_error_t  err,
+                                                                         const char*     verkey,
+                                                                         const char*     pk)
+                                                   );
+
+    /// Promotes keys created by sovrin_replace_keys_start to be the current
+    /// keys of DID. Previous keys are dropped.
+    ///
+    /// #Params
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// command_handle: command handle to map callback to user context.
+    /// did: DID with pending keys.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// None
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_replace_keys_apply(sovrin_handle_t command_handle,
+                                                    sovrin_handle_t wallet_handle,
+                                                    const char *    did,
+
+                                                    void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                         sovrin_error_t  err)
+                                                   );
+
+    /// Rotates keys of DID synchronized with the ledger: generates pending keys,
+    /// sends NYM transaction with the new verkey signed by the current key and
+    /// applies the new keys only after the pool confirms the transaction.
+    /// If the transaction fails current keys stay in use.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// pool_handle: pool handle (created by open_pool_ledger).
+    /// did: existing DID owned by the caller.
+    /// identity_json: Identity information as json. Example:
+    /// {
+    ///     "seed": string, (optional; if not provide then a random one will be created)
+    ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
+    ///               'secp256k1' is also supported if built with secp256k1_rust feature)
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// new verkey (for verification of signature) and public_key (for decryption)
+    /// Verkey is abbreviated in the same way as by sovrin_create_and_store_my_did.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_replace_keys_on_ledger(sovrin_handle_t command_handle,
+                                                        sovrin_handle_t wallet_handle,
+                                                        sovrin_handle_t pool_handle,
+                                                        const char *    did,
+                                                        const char *    identity_json,
+
+                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                             sovrin_error_t  err,
+                                                                             const char*     verkey,
+                                                                             const char*     pk)
+                                                       );
+
     /// Saves their DID for a pairwise connection in a secured Wallet,
     /// so that it can be used to verify transaction.
     ///
@@ -85,10 +189,10 @@ extern "C" {
     /// identity_json: Identity information as json. Example:
     ///     {
     ///        "did": string, (required)
-    ///        "verkey": string (optional, if only pk is provided),
+    ///        "verkey": string (optional, if only pk is provided; can be abbreviated),
     ///        "pk": string (optional, if only verification key is provided),
     ///        "crypto_type": string, (optional; if not set then ed25519 curve is used;
-    ///               currently only 'ed25519' value is supported for this field)
+    ///               'secp256k1' is also supported if built with secp256k1_rust feature)
     ///     }
     /// cb: Callback that takes command result as parameter.
     ///
@@ -136,6 +240,68 @@ extern "C" {
                                                            const char* signature)
                                      );
     
+    /// Signs arbitrary bytes by a signing key associated with my DID and returns just the signature
+    /// (detached signature). The message itself isn't changed or included to the result.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// did: signing DID
+    /// message_raw: a pointer to first byte of message to be signed (can be null for empty message)
+    /// message_len: a message length
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// a signature as byte array
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_sign_detached(sovrin_handle_t      command_handle,
+                                               sovrin_handle_t      wallet_handle,
+                                               const char *         did,
+                                               const sovrin_u8_t *  message_raw,
+                                               sovrin_u32_t         message_len,
+
+                                               void           (*cb)(sovrin_handle_t     xcommand_handle,
+                                                                    sovrin_error_t      err,
+                                                                    const sovrin_u8_t*  signature_raw,
+                                                                    sovrin_u32_t        signature_len)
+                                              );
+
+    /// Signs arbitrary payload by a signing key associated with my DID and returns compact JWS
+    /// ("header.payload.signature", base64url encoded without padding) with EdDSA alg.
+    /// Only DIDs with ed25519 keys can sign JWS.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// did: signing DID
+    /// payload_raw: a pointer to first byte of payload to be signed (can be null for empty payload)
+    /// payload_len: a payload length
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// compact JWS string
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_sign_jws(sovrin_handle_t      command_handle,
+                                          sovrin_handle_t      wallet_handle,
+                                          const char *         did,
+                                          const sovrin_u8_t *  payload_raw,
+                                          sovrin_u32_t         payload_len,
+
+                                          void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                               sovrin_error_t  err,
+                                                               const char*     jws)
+                                         );
+
     /// Verify a signature created by a key associated with a DID.
     /// If a secure wallet doesn't contain a verkey associated with the given DID,
     /// then verkey is read from the Ledger.
@@ -148,7 +314,7 @@ extern "C" {
     /// command_handle: command handle to map callback to user context.
     /// pool_handle: pool handle.
     /// did: DID that signed the message
-    /// signature: a signature to be verified
+    /// signed_msg: json message signed by sovrin_sign or compact JWS created by sovrin_sign_jws
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -242,6 +408,483 @@ extern "C" {
                                                               const char*     decrypted_msg)
                                         );    
 
+    /// Encrypts a message by a public key associated with a DID using anonymous (sealed box)
+    /// encryption: the sender isn't authenticated and doesn't need a DID.
+    /// The public key is taken from the wallet (see wallet_store_their_identity)
+    /// or read from the Ledger if the wallet doesn't contain it.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// pool_handle: pool handle (created by open_pool_ledger).
+    /// did: DID of the recipient
+    /// msg: a message to be encrypted
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// an encrypted message
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_encrypt_sealed(sovrin_handle_t command_handle,
+                                                sovrin_handle_t wallet_handle,
+                                                sovrin_handle_t pool_handle,
+                                                const char *    did,
+                                                const char *    msg,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err,
+                                                                     const char*     encrypted_msg)
+                                               );
+
+    /// Decrypts a message encrypted by sovrin_encrypt_sealed with a public key associated with my DID.
+    /// The DID with a secret key must be already created and
+    /// stored in a secured wallet (see wallet_create_and_store_my_identity)
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// my_did: DID
+    /// encrypted_msg: encrypted message
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// decrypted message
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_decrypt_sealed(sovrin_handle_t command_handle,
+                                                sovrin_handle_t wallet_handle,
+                                                const char *    my_did,
+                                                const char *    encrypted_msg,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err,
+                                                                     const char*     decrypted_msg)
+                                               );
+
+    /// Encrypts a message for a group of DIDs at once. The message is encrypted only once
+    /// by a random content key and this key is encrypted (sealed box) by public key of every recipient.
+    /// Recipients must be already stored in a secured wallet with verkey (see sovrin_store_their_did).
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// dids_json: list of recipient DIDs as json. Example: ["did1", "did2"]
+    /// msg: a message to be encrypted
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// envelope json:
+    ///     {
+    ///         "ciphertext": string, (base58 encoded message encrypted by content key)
+    ///         "nonce": string, (base58 encoded nonce)
+    ///         "recipients": [{
+    ///             "did": string,
+    ///             "encrypted_key": string (base58 encoded content key sealed for the did)
+    ///         }]
+    ///     }
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_encrypt_envelope(sovrin_handle_t command_handle,
+                                                  sovrin_handle_t wallet_handle,
+                                                  const char *    dids_json,
+                                                  const char *    msg,
+
+                                                  void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                       sovrin_error_t  err,
+                                                                       const char*     envelope_json)
+                                                 );
+
+    /// Decrypts an envelope created by sovrin_encrypt_envelope. The entry for my DID is found
+    /// in the envelope recipients and the content key is decrypted by secret key of my DID.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// my_did: DID
+    /// envelope_json: envelope created by sovrin_encrypt_envelope
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// decrypted message
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_decrypt_envelope(sovrin_handle_t command_handle,
+                                                  sovrin_handle_t wallet_handle,
+                                                  const char *    my_did,
+                                                  const char *    envelope_json,
+
+                                                  void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                       sovrin_error_t  err,
+                                                                       const char*     decrypted_msg)
+                                                 );
+
+    /// Lists my DIDs stored in the wallet together with their metadata.
+    /// Secret keys are never included.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// dids_json: list of my DIDs as json. Example:
+    ///     [{
+    ///         "did": string,
+    ///         "verkey": string, (abbreviated as by sovrin_create_and_store_my_did)
+    ///         "metadata": string (optional; metadata set by sovrin_set_did_metadata)
+    ///     }]
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_list_my_dids_with_meta(sovrin_handle_t command_handle,
+                                                        sovrin_handle_t wallet_handle,
+
+                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                             sovrin_error_t  err,
+                                                                             const char*     dids_json)
+                                                       );
+
+    /// Lists their DIDs stored in the wallet together with their metadata.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// dids_json: list of their DIDs as json. Example:
+    ///     [{
+    ///         "did": string,
+    ///         "verkey": string, (optional)
+    ///         "endpoint": string, (optional)
+    ///         "metadata": string (optional; metadata set by sovrin_set_did_metadata)
+    ///     }]
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_list_their_dids_with_meta(sovrin_handle_t command_handle,
+                                                           sovrin_handle_t wallet_handle,
+
+                                                           void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                sovrin_error_t  err,
+                                                                                const char*     dids_json)
+                                                          );
+
+    /// Saves application metadata for my or their DID stored in the wallet.
+    /// Previous metadata of the DID is replaced.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// did: my or their DID
+    /// metadata: arbitrary json
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// None
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_set_did_metadata(sovrin_handle_t command_handle,
+                                                  sovrin_handle_t wallet_handle,
+                                                  const char *    did,
+                                                  const char *    metadata,
+
+                                                  void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                       sovrin_error_t  err)
+                                                 );
+
+    /// Returns application metadata saved for the DID by sovrin_set_did_metadata.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// did: my or their DID
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// metadata json
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_get_did_metadata(sovrin_handle_t command_handle,
+                                                  sovrin_handle_t wallet_handle,
+                                                  const char *    did,
+
+                                                  void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                       sovrin_error_t  err,
+                                                                       const char*     metadata)
+                                                 );
+
+    /// Creates keys pair (signing and encryption keys) not tied to any DID
+    /// and stores it in a secured Wallet.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// key_json: Key information as json. Example:
+    /// {
+    ///     "seed": string, (optional; if not provide then a random one will be created)
+    ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
+    ///               'secp256k1' is also supported if built with secp256k1_rust feature)
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// verkey of the created key. For non-default crypto type it is followed
+    /// by ":<crypto_type>" suffix, for example "<verkey>:secp256k1".
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_create_key(sovrin_handle_t command_handle,
+                                            sovrin_handle_t wallet_handle,
+                                            const char *    key_json,
+
+                                            void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                 sovrin_error_t  err,
+                                                                 const char*     verkey)
+                                           );
+
+    /// Signs a message by a key created by sovrin_create_key (or a key of my DID).
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// verkey: verkey of the key to sign with. Abbreviated verkey of my DID is accepted
+    /// msg: a message to be signed
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// a signed message
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_sign_by_key(sovrin_handle_t command_handle,
+                                             sovrin_handle_t wallet_handle,
+                                             const char *    verkey,
+                                             const char *    msg,
+
+                                             void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                  sovrin_error_t  err,
+                                                                  const char*     signed_msg)
+                                            );
+
+    /// Verifies a signature of a message by the given verkey.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// verkey: verkey to verify signature with. Can be followed by ":<crypto_type>" suffix.
+    ///         Abbreviated verkey should be expanded to the full one as it doesn't contain DID.
+    /// signed_msg: json message signed by sovrin_sign_by_key or sovrin_sign
+    ///             or compact JWS created by sovrin_sign_jws
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// valid: true - if signature is valid, false - otherwise
+    ///
+    /// #Errors
+    /// Common*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_verify_by_key(sovrin_handle_t command_handle,
+                                               const char *    verkey,
+                                               const char *    signed_msg,
+
+                                               void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                    sovrin_error_t  err,
+                                                                    sovrin_bool_t   valid)
+                                              );
+
+    /// Verifies a detached signature (see sovrin_sign_detached) of arbitrary bytes by the given verkey.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// verkey: verkey to verify signature with. Can be followed by ":<crypto_type>" suffix.
+    ///         Abbreviated verkey should be expanded to the full one as it doesn't contain DID.
+    /// message_raw: a pointer to first byte of signed message (can be null for empty message)
+    /// message_len: a message length
+    /// signature_raw: a pointer to first byte of signature
+    /// signature_len: a signature length
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// valid: true - if signature is valid, false - otherwise
+    ///
+    /// #Errors
+    /// Common*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_verify_detached_by_key(sovrin_handle_t      command_handle,
+                                                        const char *         verkey,
+                                                        const sovrin_u8_t *  message_raw,
+                                                        sovrin_u32_t         message_len,
+                                                        const sovrin_u8_t *  signature_raw,
+                                                        sovrin_u32_t         signature_len,
+
+                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                             sovrin_error_t  err,
+                                                                             sovrin_bool_t   valid)
+                                                       );
+
+    /// Encrypts a message from my key to their verkey.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// my_verkey: verkey of my key created by sovrin_create_key (or a key of my DID, can be abbreviated)
+    /// their_verkey: verkey of the recipient. Can be followed by ":<crypto_type>" suffix.
+    ///               Abbreviated verkey is expanded using DIDs stored in the wallet.
+    /// msg: a message to be encrypted
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// an encrypted message and nonce
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_encrypt_by_key(sovrin_handle_t command_handle,
+                                                sovrin_handle_t wallet_handle,
+                                                const char *    my_verkey,
+                                                const char *    their_verkey,
+                                                const char *    msg,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err,
+                                                                     const char*     encrypted_msg,
+                                                                     const char*     nonce)
+                                               );
+
+    /// Decrypts a message encrypted by sovrin_encrypt_by_key (or sovrin_encrypt) for my key.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// my_verkey: verkey of my key created by sovrin_create_key (or a key of my DID, can be abbreviated)
+    /// their_verkey: verkey of the sender. Can be followed by ":<crypto_type>" suffix.
+    ///               Abbreviated verkey is expanded using DIDs stored in the wallet.
+    /// encrypted_msg: encrypted message
+    /// nonce: nonce that encrypted message
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// decrypted message
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_decrypt_by_key(sovrin_handle_t command_handle,
+                                                sovrin_handle_t wallet_handle,
+                                                const char *    my_verkey,
+                                                const char *    their_verkey,
+                                                const char *    encrypted_msg,
+                                                const char *    nonce,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err,
+                                                                     const char*     decrypted_msg)
+                                               );
+
+    /// Sets agent endpoint (address and transport key) for the DID.
+    /// The endpoint is always stored in the wallet. If pool handle is passed, the endpoint
+    /// is also published to the ledger as "endpoint" ATTRIB of the DID, so the DID must be
+    /// created and stored in the wallet (see wallet_create_and_store_my_identity) to sign
+    /// the transaction. In this case the wallet is updated only after the ledger accepts it.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// pool_handle: pool handle (created by open_pool_ledger) or negative value to store endpoint in the wallet only.
+    /// did: DID the endpoint belongs to
+    /// address: agent address, for example "127.0.0.1:9700"
+    /// transport_key: (optional) public key used by agent listener
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_set_endpoint_for_did(sovrin_handle_t command_handle,
+                                                      sovrin_handle_t wallet_handle,
+                                                      sovrin_handle_t pool_handle,
+                                                      const char *    did,
+                                                      const char *    address,
+                                                      const char *    transport_key,
+
+                                                      void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                           sovrin_error_t  err)
+                                                     );
+
+    /// Returns agent endpoint (address and transport key) of the DID.
+    /// Endpoint stored in the wallet is used if it is still fresh (according to freshness settings
+    /// set during initialization). Otherwise, if pool handle is passed, "endpoint" ATTRIB of the DID
+    /// is read from the ledger and stored in the wallet.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to user context.
+    /// wallet_handle: wallet handler (created by open_wallet).
+    /// pool_handle: pool handle (created by open_pool_ledger) or negative value to look up the wallet only.
+    /// did: DID to get endpoint for
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// agent address and transport key (empty string if it isn't set)
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_get_endpoint_for_did(sovrin_handle_t command_handle,
+                                                      sovrin_handle_t wallet_handle,
+                                                      sovrin_handle_t pool_handle,
+                                                      const char *    did,
+
+                                                      void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                           sovrin_error_t  err,
+                                                                           const char*     address,
+                                                                           const char*     transport_key)
+                                                     );
+
 #ifdef __cplusplus
 }
 #endif
@@ -4,6 +4,8 @@
 #include <stdint.h>
 
 typedef int32_t       sovrin_i32_t;     
+typedef uint32_t      sovrin_u32_t;
+typedef uint8_t       sovrin_u8_t;
 typedef int32_t       sovrin_handle_t;
 typedef unsigned int  sovrin_bool_t;
 
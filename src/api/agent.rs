@@ -2,8 +2,9 @@ extern crate libc;
 
 use api::ErrorCode;
 use commands::{Command, CommandExecutor};
-use commands::agent::AgentCommand;
+use commands::agent::{AgentCommand, AgentDeliveryCB};
 use errors::ToErrorCode;
+use services::agent::USER_MESSAGE_TYPE;
 use utils::cstring::CStringUtils;
 
 use self::libc::c_char;
@@ -16,19 +17,41 @@ use self::libc::c_char;
 /// Information about receiver Identity can be saved in the wallet with sovrin_store_their_did
 /// call before establishing of connection. If there is no corresponded wallet record for receiver Identity
 /// than this call will lookup Identity Ledger and cache this information in the wallet.
+/// Endpoint of receiver Identity stored in the wallet (see sovrin_set_endpoint_for_did) is used while
+/// it is fresh, otherwise it is resolved on the Ledger and stored in the wallet for next connections.
 ///
 /// Note that messages encryption/decryption will be performed automatically.
 ///
+/// Instead of sender and receiver Identities pairwise (see sovrin_create_pairwise) can be passed:
+/// my DID of pairwise is used as sender Identity and endpoint of pairwise (if set) is used
+/// instead of endpoint of receiver Identity.
+///
+/// Connection can deliver messages reliably. In this mode each message gets id and is resent
+/// until connected agent acknowledges it, duplicates of resent messages are dropped by receiver.
+/// Connection is restored automatically if network is lost or connected agent is restarted.
+///
 /// #Params
 /// command_handle: Command handle to map callback to caller context.
 /// pool_handle: Pool handle (created by open_pool_ledger).
 /// wallet_handle: Wallet handle (created by open_wallet).
-/// sender_did: Id of sender Identity stored in secured Wallet.
-/// receiver_did: Id of receiver Identity.
+/// sender_did (optional): Id of sender Identity stored in secured Wallet.
+///     If not set, receiver_did is their DID of pairwise.
+/// receiver_did: Id of receiver Identity or their DID of pairwise.
+/// options_json (optional): connection options. If not set messages are sent without delivery acknowledgement.
+///     {
+///         "reliable": bool, (optional, false by default) enables reliable delivery,
+///         "ack_timeout": int, (optional, 3000 by default) timeout in ms to wait acknowledgement
+///             before first resend, it is doubled after each resend,
+///         "max_interval": int, (optional, 30000 by default) max interval in ms between resends
+///             and between reconnect attempts,
+///         "max_attempts": int, (optional, 10 by default) max count of message sending attempts
+///     }
 /// connection_cb: Callback that will be called after establishing of connection or on error.
 ///     Will be called exactly once with result of connect operation.
 /// message_cb: Callback that will be called on receiving of an incoming message.
 ///     Can be called multiply times: once for each incoming message.
+/// delivery_cb (optional): Callback that will be called with delivery status of message
+///     sent by reliable connection. Will be called exactly once for each sent message.
 ///
 /// #Returns
 /// Error code
@@ -38,24 +61,43 @@ use self::libc::c_char;
 /// - connection_handle: Connection handle to use for messages sending and mapping of incomming messages to this connection.
 /// message_cb:
 /// - xconnection_handle: Connection handle. Indetnifies connection.
-/// - err: Error code.
-/// - message: Received message.
+/// - err: Error code. CommonInvalidStructure if message signature is invalid.
+/// - message: Received message envelope json:
+///     {
+///         "version": int, version of envelope format,
+///         "id": string, unique id of message,
+///         "type": string, type of message, "message" for messages sent by sovrin_agent_send,
+///         "thread_id": string, (optional) id of thread the message belongs to,
+///         "timestamp": int, sending time in seconds since epoch,
+///         "signature": string, (optional) base58 signature by sender Identity verkey, verified on receiving,
+///         "body": string, message body encoded as base64url without padding
+///     }
+/// delivery_cb:
+/// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. Success if message is acknowledged by connected agent,
+///     CommonIOError if message isn't acknowledged after all attempts or connection is closed.
+/// - message_id: Id of message returned by sovrin_agent_send.
 #[no_mangle]
 pub extern fn sovrin_agent_connect(command_handle: i32,
                                    pool_handle: i32,
                                    wallet_handle: i32,
                                    sender_did: *const c_char,
                                    receiver_did: *const c_char,
+                                   options_json: *const c_char,
                                    connection_cb: Option<extern fn(xcommand_handle: i32,
                                                                    err: ErrorCode,
                                                                    connection_handle: i32)>,
                                    message_cb: Option<extern fn(xconnection_handle: i32,
                                                                 err: ErrorCode,
-                                                                message: *const c_char)>) -> ErrorCode {
-    check_useful_c_str!(sender_did, ErrorCode::CommonInvalidParam3);
+                                                                message: *const c_char)>,
+                                   delivery_cb: Option<extern fn(xconnection_handle: i32,
+                                                                 err: ErrorCode,
+                                                                 message_id: i32)>) -> ErrorCode {
+    check_useful_opt_c_str!(sender_did, ErrorCode::CommonInvalidParam3);
     check_useful_c_str!(receiver_did, ErrorCode::CommonInvalidParam4);
-    check_useful_c_callback!(connection_cb, ErrorCode::CommonInvalidParam5);
-    check_useful_c_callback!(message_cb, ErrorCode::CommonInvalidParam6);
+    check_useful_opt_c_str!(options_json, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(connection_cb, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(message_cb, ErrorCode::CommonInvalidParam7);
 
     let result = CommandExecutor::instance().send(
         Command::Agent(
@@ -64,6 +106,7 @@ pub extern fn sovrin_agent_connect(command_handle: i32,
                 wallet_handle,
                 sender_did,
                 receiver_did,
+                options_json,
                 Box::new(move |result| {
                     let (err, handle) = result_to_err_code_1!(result, 0);
                     connection_cb(command_handle, err, handle);
@@ -72,7 +115,8 @@ pub extern fn sovrin_agent_connect(command_handle: i32,
                     let (err, handle, msg) = result_to_err_code_2!(result, 0, String::new());
                     let msg = CStringUtils::string_to_cstring(msg);
                     message_cb(handle, err, msg.as_ptr());
-                })
+                }),
+                delivery_cb.map(_delivery_cb)
             )
         )
     );
@@ -91,18 +135,45 @@ pub extern fn sovrin_agent_connect(command_handle: i32,
 /// wallet record for sender Identity than listener will lookup Identity Ledger and cache this
 /// information in the wallet.
 ///
+/// Incoming connection is accepted only if sender proves ownership of sender Identity: it must
+/// sign listener challenge with Identity verkey and use Identity public key as transport key.
+/// Otherwise connection is rejected and connection_cb isn't called.
+///
+/// Listener policy restricts senders that can connect to the listener. Policy is enforced by
+/// transport layer, so connections of unauthorized senders are dropped before any message from them
+/// is received. Each rejected connection is reported by event_cb.
+///
 /// Note that messages encryption/decryption will be performed automatically.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger) to use for sender Identity lookup.
 /// wallet_handle: wallet handle (created by open_wallet).
 /// endpoint: endpoint to use in starting listener.
+/// identities_json (optional): json array of receiver Identities DIDs served by listener.
+///     All Identities must be created and stored in the wallet with sovrin_create_and_store_my_did.
+///     If not set all Identities stored in the wallet are served. Listener uses keys of the first
+///     Identity as transport keys, so this key must be published as transport key in endpoints
+///     of all served Identities (see sovrin_set_endpoint_for_did).
+/// policy_json (optional): listener policy. If not set any sender is allowed. One of:
+///     {"type": "any"} - allow any sender,
+///     {"type": "known"} - allow only senders stored in the wallet with sovrin_store_their_did
+///         before listener start. Transport keys of known senders are read from the wallet once
+///         at listener start, so senders stored later are rejected until listener is restarted,
+///     {"type": "list", "allow": [<did>, ...], "deny": [<did>, ...]} - allow only senders from
+///         optional allow list that aren't in optional deny list. DIDs from allow list must be
+///         stored in the wallet with sovrin_store_their_did.
 /// listener_cb: Callback that will be called after listening started or on error.
 ///     Will be called exactly once with result of start listen operation.
 /// connection_cb: Callback that will be called after establishing of incoming connection.
 ///     Can be called multiply times: once for each incoming connection.
 /// message_cb: Callback that will be called on receiving of an incoming message.
 ///     Can be called multiply times: once for each incoming message.
+/// event_cb: Callback that will be called on listener events.
+///     Can be called multiply times: once for each event.
+/// delivery_cb (optional): Callback that will be called with delivery status of message
+///     sent by incoming connection if connected agent requested reliable delivery
+///     (see sovrin_agent_connect). Will be called exactly once for each such message.
 ///
 /// #Returns
 /// Error code
@@ -115,15 +186,39 @@ pub extern fn sovrin_agent_connect(command_handle: i32,
 /// - err: Error code
 /// - connection_handle: Connection handle to use for messages sending and mapping of incomming messages to to this connection.
 /// - sender_did: Id of sender Identity stored in secured Wallet.
-/// - receiver_did: Id of receiver Identity.
+/// - receiver_did: Id of receiver Identity the connection is addressed to.
 /// message_cb:
 /// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. CommonInvalidStructure if message signature is invalid.
+/// - message: Received message envelope json (see sovrin_agent_connect).
+/// event_cb:
+/// - xlistener_handle: Listener handle. Identifies listener.
 /// - err: Error code.
-/// - message: Received message.
+/// - event_json: Listener event, one of:
+///     {
+///         "type": "connection_rejected",
+///         "did": string, (optional) Id of sender Identity if known,
+///         "client_key": string, (optional) Sender transport key,
+///         "reason": string, Reason of rejection
+///     }
+///     {
+///         "type": "invitation_accepted", (see sovrin_agent_create_invitation)
+///         "my_did": string, Invited Identity,
+///         "their_did": string, Identity of invitee,
+///         "label": string, (optional) Label sent by invitee
+///     }
+/// delivery_cb:
+/// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. Success if message is acknowledged by connected agent,
+///     CommonIOError if message isn't acknowledged after all attempts or connection is closed.
+/// - message_id: Id of message returned by sovrin_agent_send.
 #[no_mangle]
 pub extern fn sovrin_agent_listen(command_handle: i32,
+                                  pool_handle: i32,
                                   wallet_handle: i32,
                                   endpoint: *const c_char,
+                                  identities_json: *const c_char,
+                                  policy_json: *const c_char,
                                   listener_cb: Option<extern fn(xcommand_handle: i32,
                                                                 err: ErrorCode,
                                                                 listener_handle: i32)>,
@@ -134,15 +229,27 @@ pub extern fn sovrin_agent_listen(command_handle: i32,
                                                                   receiver_did: *const c_char)>,
                                   message_cb: Option<extern fn(xconnection_handle: i32,
                                                                err: ErrorCode,
-                                                               message: *const c_char)>) -> ErrorCode {
-    check_useful_c_str!(endpoint, ErrorCode::CommonInvalidParam3);
-    check_useful_c_callback!(listener_cb, ErrorCode::CommonInvalidParam4);
-    check_useful_c_callback!(connection_cb, ErrorCode::CommonInvalidParam5);
-    check_useful_c_callback!(message_cb, ErrorCode::CommonInvalidParam6);
+                                                               message: *const c_char)>,
+                                  event_cb: Option<extern fn(xlistener_handle: i32,
+                                                             err: ErrorCode,
+                                                             event_json: *const c_char)>,
+                                  delivery_cb: Option<extern fn(xconnection_handle: i32,
+                                                                err: ErrorCode,
+                                                                message_id: i32)>) -> ErrorCode {
+    check_useful_c_str!(endpoint, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(identities_json, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_c_str!(policy_json, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(listener_cb, ErrorCode::CommonInvalidParam7);
+    check_useful_c_callback!(connection_cb, ErrorCode::CommonInvalidParam8);
+    check_useful_c_callback!(message_cb, ErrorCode::CommonInvalidParam9);
+    check_useful_c_callback!(event_cb, ErrorCode::CommonInvalidParam10);
 
     let cmd = Command::Agent(AgentCommand::Listen(
+        pool_handle,
         wallet_handle,
         endpoint,
+        identities_json,
+        policy_json,
         Box::new(move |result| {
             let (err, handle) = result_to_err_code_1!(result, 0);
             listener_cb(command_handle, err, handle);
@@ -158,7 +265,13 @@ pub extern fn sovrin_agent_listen(command_handle: i32,
             let (err, handle, msg) = result_to_err_code_2!(result, 0, String::new());
             let msg = CStringUtils::string_to_cstring(msg);
             message_cb(handle, err, msg.as_ptr());
-        })
+        }),
+        Box::new(move |result| {
+            let (err, handle, event_json) = result_to_err_code_2!(result, 0, String::new());
+            let event_json = CStringUtils::string_to_cstring(event_json);
+            event_cb(handle, err, event_json.as_ptr());
+        }),
+        delivery_cb.map(_delivery_cb)
     ));
 
     let result = CommandExecutor::instance().send(cmd);
@@ -166,15 +279,102 @@ pub extern fn sovrin_agent_listen(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Adds Identity to listener, so incoming connections addressed to this Identity will be accepted.
+///
+/// Note that transport key of the listener must be published in endpoint of the Identity
+/// (see sovrin_set_endpoint_for_did).
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// listener_handle: Listener handle returned by sovrin_agent_listen call.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// did: Id of Identity to add. Identity must be created and stored in the wallet with
+///     sovrin_create_and_store_my_did.
+/// cb: Callback that will be called after Identity added or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_add_identity(command_handle: i32,
+                                        listener_handle: i32,
+                                        wallet_handle: i32,
+                                        did: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: i32,
+                                                             err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::AddIdentity(
+        listener_handle,
+        wallet_handle,
+        did,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Removes Identity from listener, so new incoming connections addressed to this Identity
+/// will be rejected.
+///
+/// Note that already established connections aren't closed.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// listener_handle: Listener handle returned by sovrin_agent_listen call.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// did: Id of Identity to remove.
+/// cb: Callback that will be called after Identity removed or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_remove_identity(command_handle: i32,
+                                           listener_handle: i32,
+                                           wallet_handle: i32,
+                                           did: *const c_char,
+                                           cb: Option<extern fn(xcommand_handle: i32,
+                                                                err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::RemoveIdentity(
+        listener_handle,
+        wallet_handle,
+        did,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
 /// Sends message to connected agent.
 ///
 /// Note that this call works for both incoming and outgoing connections.
 /// Note that messages encryption/decryption will be performed automatically.
+/// Note that for reliable connection callback is called after message is queued for sending,
+/// delivery of the message is reported by delivery_cb of the connection.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// connection_handle: Connection handle returned by sovrin_agent_connect or sovrin_agent_listen calls.
-/// message: Message to send.
+/// message: Message to send. It is sent as body of envelope of "message" type.
 /// cb: Callback that will be called after message sent or on error. Will be called exactly once.
 ///
 /// #Returns
@@ -182,6 +382,8 @@ pub extern fn sovrin_agent_listen(command_handle: i32,
 /// cb:
 /// - xcommand_handle: Command handle to map callback to caller context.
 /// - err: Error code
+/// - message_id: Id of message in reliable connection to map delivery status to this message,
+///     0 for connection without reliable delivery.
 ///
 /// #Errors
 #[no_mangle]
@@ -189,15 +391,78 @@ pub extern fn sovrin_agent_send(command_handle: i32,
                                 connection_handle: i32,
                                 message: *const c_char,
                                 cb: Option<extern fn(xcommand_handle: i32,
-                                                     err: ErrorCode)>) -> ErrorCode {
+                                                     err: ErrorCode,
+                                                     message_id: i32)>) -> ErrorCode {
     check_useful_opt_c_str!(message, ErrorCode::CommonInvalidParam3);
     check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
 
     let cmd = Command::Agent(AgentCommand::Send(
         connection_handle,
-        message,
+        USER_MESSAGE_TYPE.to_string(),
+        None,
+        message.map(String::into_bytes).unwrap_or(Vec::new()),
+        false,
         Box::new(move |result| {
-            cb(command_handle, result_to_err_code!(result))
+            let (err, message_id) = result_to_err_code_1!(result, 0);
+            cb(command_handle, err, message_id)
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Sends message of given type with binary body to connected agent.
+///
+/// Note that this call works for both incoming and outgoing connections.
+/// Note that types with "sovrin/" prefix are reserved for control messages.
+/// Note that for reliable connection callback is called after message is queued for sending,
+/// delivery of the message is reported by delivery_cb of the connection.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle returned by sovrin_agent_connect or sovrin_agent_listen calls.
+/// message_type: Type of message.
+/// thread_id (optional): Id of thread the message belongs to, e.g. id of message it replies to.
+/// body_raw: a pointer to first byte of message body.
+/// body_len: a message body length.
+/// sign: Sign message by verkey of my Identity of the connection.
+/// cb: Callback that will be called after message sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// err: Error code
+/// cb:
+/// - xcommand_handle: Command handle to map callback to caller context.
+/// - err: Error code
+/// - message_id: Id of message in reliable connection to map delivery status to this message,
+///     0 for connection without reliable delivery.
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_send_typed(command_handle: i32,
+                                      connection_handle: i32,
+                                      message_type: *const c_char,
+                                      thread_id: *const c_char,
+                                      body_raw: *const u8,
+                                      body_len: u32,
+                                      sign: bool,
+                                      cb: Option<extern fn(xcommand_handle: i32,
+                                                           err: ErrorCode,
+                                                           message_id: i32)>) -> ErrorCode {
+    check_useful_c_str!(message_type, ErrorCode::CommonInvalidParam3);
+    check_useful_opt_c_str!(thread_id, ErrorCode::CommonInvalidParam4);
+    check_useful_c_byte_array!(body_raw, body_len, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam8);
+
+    let cmd = Command::Agent(AgentCommand::Send(
+        connection_handle,
+        message_type,
+        thread_id,
+        body_raw,
+        sign,
+        Box::new(move |result| {
+            let (err, message_id) = result_to_err_code_1!(result, 0);
+            cb(command_handle, err, message_id)
         })
     ));
 
@@ -271,4 +536,736 @@ pub extern fn sovrin_agent_close_listener(command_handle: i32,
 
     let res = CommandExecutor::instance().send(cmd);
     result_to_err_code!(res)
-}
\ No newline at end of file
+}
+
+/// Starts hosting of mailbox for Identity by relay agent.
+///
+/// Relay agent is an agent that listens for connections (see sovrin_agent_listen) with
+/// Identity stored in this wallet. Agents connected to relay can forward messages for hosted Identity
+/// with sovrin_agent_mailbox_forward. These messages are stored in relay wallet until hosted Identity
+/// connects to relay and pulls them with sovrin_agent_mailbox_pull.
+///
+/// Note that messages are encrypted for hosted Identity, so relay can't read them.
+/// Messages of already hosted Identity are kept.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of relay Identity.
+/// did: Id of hosted Identity.
+/// cb: Callback that will be called after mailbox is hosted or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_mailbox_host(command_handle: i32,
+                                        wallet_handle: i32,
+                                        did: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: i32,
+                                                             err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let cmd = Command::Agent(AgentCommand::MailboxHost(
+        wallet_handle,
+        did,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Forwards message to mailbox of Identity hosted by relay agent.
+///
+/// Message is encrypted for receiver Identity, so relay can't read it. Receiver Identity
+/// must be saved in the wallet with sovrin_store_their_did call.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle to relay agent returned by sovrin_agent_connect call.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// receiver_did: Id of receiver Identity hosted by relay.
+/// message: Message to forward.
+/// cb: Callback that will be called after relay stored message or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidState if relay doesn't host receiver Identity.
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_mailbox_forward(command_handle: i32,
+                                           connection_handle: i32,
+                                           wallet_handle: i32,
+                                           receiver_did: *const c_char,
+                                           message: *const c_char,
+                                           cb: Option<extern fn(xcommand_handle: i32,
+                                                                err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(receiver_did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(message, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let cmd = Command::Agent(AgentCommand::MailboxForward(
+        connection_handle,
+        wallet_handle,
+        receiver_did,
+        message,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Pulls all messages stored in mailbox of Identity hosted by relay agent.
+///
+/// Mailbox is identified by sender Identity of the connection. Pulled messages are decrypted
+/// with the key of the Identity and then removed from relay wallet, so messages of failed pull
+/// are returned again by the next pull.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle to relay agent returned by sovrin_agent_connect call.
+/// wallet_handle: wallet handle (created by open_wallet) of hosted Identity.
+/// cb: Callback that will be called with pulled messages or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidState if relay doesn't host Identity.
+/// - messages_json: messages in order of receiving by relay:
+///     [{
+///         "id": int, id of message in mailbox,
+///         "sender_did": string, Id of Identity that forwarded message,
+///         "message": string, decrypted message
+///     }]
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_mailbox_pull(command_handle: i32,
+                                        connection_handle: i32,
+                                        wallet_handle: i32,
+                                        cb: Option<extern fn(xcommand_handle: i32,
+                                                             err: ErrorCode,
+                                                             messages_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let cmd = Command::Agent(AgentCommand::MailboxPull(
+        connection_handle,
+        wallet_handle,
+        Box::new(move |result| {
+            let (err, messages_json) = result_to_err_code_1!(result, String::new());
+            let messages_json = CStringUtils::string_to_cstring(messages_json);
+            cb(command_handle, err, messages_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Subscribes wallet to events of claim issuance protocol.
+///
+/// Claims are issued over agent connections: issuer offers claim with sovrin_agent_claim_offer,
+/// prover stores the offer and sends claim request with sovrin_agent_claim_request, issuer creates
+/// claim with sovrin_agent_claim_issue and prover stores received claim. State of each exchange is kept
+/// in the wallets of both sides. Incoming protocol messages are handled only for subscribed wallets.
+///
+/// Previous subscription of the wallet is replaced.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// options_json: (optional) claim exchange options:
+///     {
+///         "master_secret_name": string, (optional) if set, claim request is created and sent
+///                               with this master secret for each received offer automatically
+///     }
+/// cb: Callback that will be called after subscription or on error. Will be called exactly once.
+/// event_cb: Callback that will be called on each claim exchange event of the wallet.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// event_cb:
+/// - xwallet_handle: wallet handle of the exchange.
+/// - err: Error code
+/// - event_json: one of events:
+///     {"type": "offer_received", "exchange_id": string, "claim_offer": string}
+///     {"type": "request_sent", "exchange_id": string} - only for automatically created requests
+///     {"type": "request_received", "exchange_id": string, "claim_req": string}
+///     {"type": "claim_stored", "exchange_id": string}
+///     {"type": "failed", "exchange_id": string, "reason": string}
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_claim_subscribe(command_handle: i32,
+                                           wallet_handle: i32,
+                                           options_json: *const c_char,
+                                           cb: Option<extern fn(xcommand_handle: i32,
+                                                                err: ErrorCode)>,
+                                           event_cb: Option<extern fn(xwallet_handle: i32,
+                                                                      err: ErrorCode,
+                                                                      event_json: *const c_char)>) -> ErrorCode {
+    check_useful_opt_c_str!(options_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(event_cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::ClaimSubscribe(
+        wallet_handle,
+        options_json,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        }),
+        Box::new(move |result| {
+            let (err, event_json) = result_to_err_code_1!(result, String::new());
+            let event_json = CStringUtils::string_to_cstring(event_json);
+            event_cb(wallet_handle, err, event_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Offers claim to the receiver Identity of the connection.
+///
+/// Starts claim exchange on issuer side. Issuer DID of the offer must be sender DID of the connection.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle returned by sovrin_agent_connect call or by connection callback of listener.
+/// claim_offer_json: claim offer created by sovrin_issuer_create_claim_offer.
+/// claim_def_json: claim definition as it is stored on the ledger (with seqNo), needed by prover to create claim request.
+/// cb: Callback that will be called after offer is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchange_id: Id of claim exchange.
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_claim_offer(command_handle: i32,
+                                       connection_handle: i32,
+                                       claim_offer_json: *const c_char,
+                                       claim_def_json: *const c_char,
+                                       cb: Option<extern fn(xcommand_handle: i32,
+                                                            err: ErrorCode,
+                                                            exchange_id: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(claim_offer_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(claim_def_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::ClaimOffer(
+        connection_handle,
+        claim_offer_json,
+        claim_def_json,
+        Box::new(move |result| {
+            let (err, exchange_id) = result_to_err_code_1!(result, String::new());
+            let exchange_id = CStringUtils::string_to_cstring(exchange_id);
+            cb(command_handle, err, exchange_id.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Creates claim request for received claim offer and sends it to issuer.
+///
+/// Request is created with sovrin_prover_create_and_store_claim_req logic. Connection to issuer
+/// must be established.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of prover.
+/// exchange_id: Id of claim exchange reported by offer_received event.
+/// master_secret_name: name of master secret stored in the wallet.
+/// cb: Callback that will be called after request is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidStructure if exchange is unknown or offer is already requested.
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_claim_request(command_handle: i32,
+                                         wallet_handle: i32,
+                                         exchange_id: *const c_char,
+                                         master_secret_name: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: i32,
+                                                              err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(exchange_id, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(master_secret_name, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::ClaimRequest(
+        wallet_handle,
+        exchange_id,
+        master_secret_name,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Creates claim for received claim request and sends it to prover.
+///
+/// Claim is created with sovrin_issuer_create_claim logic. Connection to prover must be established.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of issuer.
+/// exchange_id: Id of claim exchange reported by request_received event.
+/// claim_json: claim attributes values as for sovrin_issuer_create_claim.
+/// revoc_reg_seq_no: (optional, pass -1 if revoc_reg_seq_no is absentee) seq no of a revocation registry transaction in Ledger
+/// user_revoc_index: index of a new user in the revocation registry (optional, pass -1 if user_revoc_index is absentee; default one is used if not provided)
+/// cb: Callback that will be called after claim is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidStructure if exchange is unknown or request isn't received.
+/// - revoc_reg_update_json: Revocation registry update json with a newly issued claim
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_claim_issue(command_handle: i32,
+                                       wallet_handle: i32,
+                                       exchange_id: *const c_char,
+                                       claim_json: *const c_char,
+                                       revoc_reg_seq_no: i32,
+                                       user_revoc_index: i32,
+                                       cb: Option<extern fn(xcommand_handle: i32,
+                                                            err: ErrorCode,
+                                                            revoc_reg_update_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(exchange_id, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(claim_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    let revoc_reg_seq_no = if revoc_reg_seq_no != -1 { Some(revoc_reg_seq_no) } else { None };
+    let user_revoc_index = if user_revoc_index != -1 { Some(user_revoc_index) } else { None };
+
+    let cmd = Command::Agent(AgentCommand::ClaimIssue(
+        wallet_handle,
+        exchange_id,
+        claim_json,
+        revoc_reg_seq_no,
+        user_revoc_index,
+        Box::new(move |result| {
+            let (err, revoc_reg_update_json) = result_to_err_code_1!(result, String::new());
+            let revoc_reg_update_json = CStringUtils::string_to_cstring(revoc_reg_update_json);
+            cb(command_handle, err, revoc_reg_update_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Lists claim exchanges stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cb: Callback that will be called with claim exchanges or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchanges_json:
+///     [{
+///         "id": string, Id of exchange,
+///         "role": "issuer" or "prover",
+///         "state": one of "offered", "offer_received", "requested", "request_received", "issued", "stored", "failed",
+///         "my_did": string,
+///         "their_did": string,
+///         "claim_offer": string,
+///         "claim_def": optional string,
+///         "claim_req": optional string,
+///         "reason": optional string, reason of failure
+///     }]
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_claim_exchanges(command_handle: i32,
+                                           wallet_handle: i32,
+                                           cb: Option<extern fn(xcommand_handle: i32,
+                                                                err: ErrorCode,
+                                                                exchanges_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let cmd = Command::Agent(AgentCommand::ClaimExchanges(
+        wallet_handle,
+        Box::new(move |result| {
+            let (err, exchanges_json) = result_to_err_code_1!(result, String::new());
+            let exchanges_json = CStringUtils::string_to_cstring(exchanges_json);
+            cb(command_handle, err, exchanges_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Subscribes wallet to events of proof presentation protocol.
+///
+/// Proofs are presented over agent connections: verifier sends proof request with sovrin_agent_proof_request,
+/// prover gets claims matching the request in request_received event and presents proof with chosen claims
+/// by sovrin_agent_proof_present. Verifier checks received proof automatically. Schemas and claim definitions
+/// of used claims are resolved from the ledger of the pool on both sides. State of each exchange is kept
+/// in the wallets of both sides. Incoming protocol messages are handled only for subscribed wallets.
+///
+/// Previous subscription of the wallet is replaced.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger) used to resolve schemas and claim definitions.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cb: Callback that will be called after subscription or on error. Will be called exactly once.
+/// event_cb: Callback that will be called on each proof exchange event of the wallet.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// event_cb:
+/// - xwallet_handle: wallet handle of the exchange.
+/// - err: Error code
+/// - event_json: one of events:
+///     {"type": "request_received", "exchange_id": string, "proof_req": string, "claims": string} - claims
+///         are in sovrin_prover_get_claims_for_proof_req format
+///     {"type": "proof_received", "exchange_id": string, "proof": string, "valid": bool} - for verifier
+///     {"type": "proof_result", "exchange_id": string, "valid": bool} - for prover
+///     {"type": "failed", "exchange_id": string, "reason": string}
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_proof_subscribe(command_handle: i32,
+                                           pool_handle: i32,
+                                           wallet_handle: i32,
+                                           cb: Option<extern fn(xcommand_handle: i32,
+                                                                err: ErrorCode)>,
+                                           event_cb: Option<extern fn(xwallet_handle: i32,
+                                                                      err: ErrorCode,
+                                                                      event_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(event_cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::ProofSubscribe(
+        pool_handle,
+        wallet_handle,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        }),
+        Box::new(move |result| {
+            let (err, event_json) = result_to_err_code_1!(result, String::new());
+            let event_json = CStringUtils::string_to_cstring(event_json);
+            event_cb(wallet_handle, err, event_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Sends proof request to the receiver Identity of the connection.
+///
+/// Starts proof exchange on verifier side. Nonce of the request is replaced with fresh one
+/// and received proof is verified only against this nonce, so proof can't be replayed.
+/// Wallet of the connection must be subscribed with sovrin_agent_proof_subscribe.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// connection_handle: Connection handle returned by sovrin_agent_connect call or by connection callback of listener.
+/// proof_req_json: proof request in sovrin_prover_get_claims_for_proof_req format.
+/// cb: Callback that will be called after request is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchange_id: Id of started proof exchange.
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_proof_request(command_handle: i32,
+                                         connection_handle: i32,
+                                         proof_req_json: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: i32,
+                                                              err: ErrorCode,
+                                                              exchange_id: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(proof_req_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let cmd = Command::Agent(AgentCommand::ProofRequest(
+        connection_handle,
+        proof_req_json,
+        Box::new(move |result| {
+            let (err, exchange_id) = result_to_err_code_1!(result, String::new());
+            let exchange_id = CStringUtils::string_to_cstring(exchange_id);
+            cb(command_handle, err, exchange_id.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Creates proof for received proof request with chosen claims and sends it to verifier.
+///
+/// Proof is created with sovrin_prover_create_proof logic. Only claims reported by request_received
+/// event can be used. Connection to verifier must be established.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet) of prover.
+/// exchange_id: Id of proof exchange reported by request_received event.
+/// requested_claims_json: claims chosen for the proof in sovrin_prover_create_proof format.
+/// master_secret_name: name of master secret stored in the wallet.
+/// cb: Callback that will be called after proof is sent or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code. CommonInvalidStructure if exchange is unknown, proof is already presented
+///   or claims don't match proof request.
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_proof_present(command_handle: i32,
+                                         wallet_handle: i32,
+                                         exchange_id: *const c_char,
+                                         requested_claims_json: *const c_char,
+                                         master_secret_name: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: i32,
+                                                              err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(exchange_id, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(requested_claims_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(master_secret_name, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let cmd = Command::Agent(AgentCommand::ProofPresent(
+        wallet_handle,
+        exchange_id,
+        requested_claims_json,
+        master_secret_name,
+        Box::new(move |result| {
+            cb(command_handle, result_to_err_code!(result))
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Lists proof exchanges stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cb: Callback that will be called with proof exchanges or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - exchanges_json:
+///     [{
+///         "id": string, Id of exchange,
+///         "role": "verifier" or "prover",
+///         "state": one of "requested", "request_received", "presented", "proof_received", "verified", "rejected", "failed",
+///         "my_did": string,
+///         "their_did": string,
+///         "proof_req": string, proof request with nonce of the exchange,
+///         "claims": optional string, claims found for proof request (prover only),
+///         "proof": optional string,
+///         "identifiers": optional {claim_uuid: {"issuer_did": string, "schema_seq_no": int}},
+///         "reason": optional string, reason of failure
+///     }]
+///
+/// #Errors
+#[no_mangle]
+pub extern fn sovrin_agent_proof_exchanges(command_handle: i32,
+                                           wallet_handle: i32,
+                                           cb: Option<extern fn(xcommand_handle: i32,
+                                                                err: ErrorCode,
+                                                                exchanges_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let cmd = Command::Agent(AgentCommand::ProofExchanges(
+        wallet_handle,
+        Box::new(move |result| {
+            let (err, exchanges_json) = result_to_err_code_1!(result, String::new());
+            let exchanges_json = CStringUtils::string_to_cstring(exchanges_json);
+            cb(command_handle, err, exchanges_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Creates invitation to pairwise relationship. Fresh Identity is created in the wallet
+/// and added to identities served by the listener. Invitation is passed to invitee out of band.
+/// It can be accepted only once: the first Identity connected to invited one is stored
+/// without resolving it on the ledger and pairwise with it is created (see sovrin_create_pairwise).
+/// Acceptance is reported by listener event_cb. Transport key of invitee isn't known in advance,
+/// so invitation can't be accepted by listener with "known" policy or with allow list.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// listener_handle: Listener handle (created by sovrin_agent_listen).
+/// wallet_handle: Wallet handle (created by open_wallet).
+/// label (optional): label of inviter passed to invitee.
+/// cb: Callback that will be called with invitation or on error. Will be called exactly once.
+///
+/// #Returns
+/// Error code
+/// cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code
+/// - invitation_json:
+///     {
+///         "did": string, Invited Identity,
+///         "verkey": string, verkey of invited Identity,
+///         "endpoint": string, endpoint of the listener,
+///         "transport_key": string, transport key of the listener,
+///         "label": string, (optional) label of inviter
+///     }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_agent_create_invitation(command_handle: i32,
+                                             listener_handle: i32,
+                                             wallet_handle: i32,
+                                             label: *const c_char,
+                                             cb: Option<extern fn(xcommand_handle: i32,
+                                                                  err: ErrorCode,
+                                                                  invitation_json: *const c_char)>) -> ErrorCode {
+    check_useful_opt_c_str!(label, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let cmd = Command::Agent(AgentCommand::CreateInvitation(
+        listener_handle,
+        wallet_handle,
+        label,
+        Box::new(move |result| {
+            let (err, invitation_json) = result_to_err_code_1!(result, String::new());
+            let invitation_json = CStringUtils::string_to_cstring(invitation_json);
+            cb(command_handle, err, invitation_json.as_ptr())
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+/// Accepts invitation created by sovrin_agent_create_invitation. Fresh Identity is created
+/// in the wallet, invited Identity is stored with endpoint from invitation and connection to it
+/// is established. Pairwise with invited Identity is created (see sovrin_create_pairwise).
+/// Neither Identity is written to the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: Wallet handle (created by open_wallet).
+/// invitation_json: invitation (see sovrin_agent_create_invitation).
+/// options_json (optional): accepting options:
+///     {
+///         "label": string, (optional) label of invitee passed to inviter,
+///         "endpoint": string, (optional) endpoint of invitee stored by inviter,
+///         "transport_key": string, (optional) transport key of invitee endpoint
+///     }
+/// connection_cb: Callback that will be called after establishing of connection and sending
+///     of acceptance or on error. Will be called exactly once.
+/// message_cb: Callback that will be called on receiving of an incoming message.
+///     Can be called multiply times: once for each incoming message.
+///
+/// #Returns
+/// Error code
+/// connection_cb:
+/// - xcommand_handle: command handle to map callback to caller context.
+/// - err: Error code.
+/// - connection_handle: Connection handle to use for messages sending and mapping of incomming messages to this connection.
+/// - my_did: Created Identity of invitee.
+/// message_cb:
+/// - xconnection_handle: Connection handle. Indetnifies connection.
+/// - err: Error code. CommonInvalidStructure if message signature is invalid.
+/// - message: Received message envelope json (see sovrin_agent_connect).
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_agent_accept_invitation(command_handle: i32,
+                                             wallet_handle: i32,
+                                             invitation_json: *const c_char,
+                                             options_json: *const c_char,
+                                             connection_cb: Option<extern fn(xcommand_handle: i32,
+                                                                             err: ErrorCode,
+                                                                             connection_handle: i32,
+                                                                             my_did: *const c_char)>,
+                                             message_cb: Option<extern fn(xconnection_handle: i32,
+                                                                          err: ErrorCode,
+                                                                          message: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(invitation_json, ErrorCode::CommonInvalidParam3);
+    check_useful_opt_c_str!(options_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(connection_cb, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(message_cb, ErrorCode::CommonInvalidParam6);
+
+    let cmd = Command::Agent(AgentCommand::AcceptInvitation(
+        wallet_handle,
+        invitation_json,
+        options_json,
+        Box::new(move |result| {
+            let (err, handle, my_did) = result_to_err_code_2!(result, 0, String::new());
+            let my_did = CStringUtils::string_to_cstring(my_did);
+            connection_cb(command_handle, err, handle, my_did.as_ptr())
+        }),
+        Box::new(move |result| {
+            let (err, handle, msg) = result_to_err_code_2!(result, 0, String::new());
+            let msg = CStringUtils::string_to_cstring(msg);
+            message_cb(handle, err, msg.as_ptr());
+        })
+    ));
+
+    let res = CommandExecutor::instance().send(cmd);
+    result_to_err_code!(res)
+}
+
+fn _delivery_cb(delivery_cb: extern fn(xconnection_handle: i32, err: ErrorCode, message_id: i32)) -> AgentDeliveryCB {
+    Box::new(move |connection_handle, message_id, result| {
+        delivery_cb(connection_handle, result_to_err_code!(result), message_id)
+    })
+}
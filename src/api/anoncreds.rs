@@ -224,11 +224,12 @@ pub extern fn sovrin_issuer_revoke_claim(command_handle: i32,
             AnoncredsCommand::Issuer(
                 IssuerCommand::RevokeClaim(
                     wallet_handle,
+                    None,
                     claim_def_seq_no,
                     revoc_reg_seq_no,
                     user_revoc_index,
                     Box::new(move |result| {
-                        let (err, revoc_reg_update_json) = result_to_err_code_1!(result, String::new());
+                        let (err, revoc_reg_update_json, _) = result_to_err_code_2!(result, String::new(), None);
                         let revoc_reg_update_json = CStringUtils::string_to_cstring(revoc_reg_update_json);
                         cb(command_handle, err, revoc_reg_update_json.as_ptr())
                     })
@@ -237,6 +238,62 @@ pub extern fn sovrin_issuer_revoke_claim(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Revokes a user identified by a revoc_id in a given revoc-registry and
+/// builds a REVOC_REG_ENTRY request with the resulting accumulator delta,
+/// so verifiers can fetch the current accumulator from the Ledger.
+/// The request isn't signed or sent, use sovrin_sign_and_submit_request for this.
+///
+/// #Params
+/// wallet_handle: wallet handler (created by open_wallet).
+/// command_handle: command handle to map callback to user context.
+/// submitter_did: a DID of the issuer signing transactions to the Ledger
+/// claim_def_seq_no: seq no of a claim definition transaction in Ledger
+/// revoc_reg_seq_no: seq no of a revocation registry transaction in Ledger
+/// user_revoc_index: index of the user in the revocation registry
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Revocation registry update json with a revoked claim
+/// REVOC_REG_ENTRY request json
+///
+/// #Errors
+/// Annoncreds*
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_issuer_revoke_claim_with_ledger_update(command_handle: i32,
+                                                            wallet_handle: i32,
+                                                            submitter_did: *const c_char,
+                                                            claim_def_seq_no: i32,
+                                                            revoc_reg_seq_no: i32,
+                                                            user_revoc_index: i32,
+                                                            cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                                 revoc_reg_update_json: *const c_char,
+                                                                                 revoc_reg_entry_request_json: *const c_char
+                                                            )>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Anoncreds(
+            AnoncredsCommand::Issuer(
+                IssuerCommand::RevokeClaim(
+                    wallet_handle,
+                    Some(submitter_did),
+                    claim_def_seq_no,
+                    revoc_reg_seq_no,
+                    user_revoc_index,
+                    Box::new(move |result| {
+                        let (err, revoc_reg_update_json, revoc_reg_entry_request_json) = result_to_err_code_2!(result, String::new(), None);
+                        let revoc_reg_update_json = CStringUtils::string_to_cstring(revoc_reg_update_json);
+                        let revoc_reg_entry_request_json = CStringUtils::string_to_cstring(revoc_reg_entry_request_json.unwrap_or(String::new()));
+                        cb(command_handle, err, revoc_reg_update_json.as_ptr(), revoc_reg_entry_request_json.as_ptr())
+                    })
+                ))));
+
+    result_to_err_code!(result)
+}
+
 /// Stores a claim offer from the given issuer in a secure storage.
 ///
 /// #Params
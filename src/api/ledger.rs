@@ -98,6 +98,152 @@ pub extern fn sovrin_submit_request(command_handle: i32,
 }
 
 
+/// Publishes GET_NYM, GET_SCHEMA, GET_CLAIM_DEF or GET_TXN request to validator pool
+/// through the read-through cache stored in the wallet.
+///
+/// If the wallet contains a fresh enough reply for the same request it is returned
+/// without contacting the pool, reqId in its result is replaced with reqId of the request.
+/// Otherwise the request is sent as is and a successful reply is stored in the wallet.
+/// Requests of other types are just sent to the pool.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger).
+/// wallet_handle: wallet handle (created by open_wallet).
+/// request_json: Request data json.
+/// options_json: cache options json (optional):
+/// {
+///     "refresh": bool, (optional; false by default) don't read cached reply, but store the new one
+///     "bypass": bool, (optional; false by default) don't read or store cached reply
+///     "ttl": int, (optional) max age of cached reply in seconds. By default
+///         300 is used for GET_NYM and 86400 for GET_SCHEMA, GET_CLAIM_DEF and GET_TXN
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_submit_request_with_cache(command_handle: i32,
+                                               pool_handle: i32,
+                                               wallet_handle: i32,
+                                               request_json: *const c_char,
+                                               options_json: *const c_char,
+                                               cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                    request_result_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(request_json, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(options_json, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::SubmitRequestWithCache(
+            pool_handle,
+            wallet_handle,
+            request_json,
+            options_json,
+            Box::new(move |result| {
+                let (err, request_result_json) = result_to_err_code_1!(result, String::new());
+                let request_result_json = CStringUtils::string_to_cstring(request_result_json);
+                cb(command_handle, err, request_result_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Removes cached ledger replies from the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// cache_type: type of cached replies to remove: "nym", "schema", "claim_def" or "txn" (optional; all by default)
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_purge_ledger_cache(command_handle: i32,
+                                        wallet_handle: i32,
+                                        cache_type: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_opt_c_str!(cache_type, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::PurgeCache(
+            wallet_handle,
+            cache_type,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Resolves DID document (DDO) of the given DID using the ledger.
+///
+/// DDO is assembled from the NYM record of the DID (verkey and role) and
+/// its "endpoint" ATTRIB (agent address and transport key). Abbreviated verkey is
+/// expanded to the full one.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger).
+/// wallet_handle: wallet handle (created by open_wallet).
+/// did: DID to resolve.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// DDO json:
+/// {
+///     "did": string,
+///     "verkey": string, (null if NYM has no verkey)
+///     "role": string, ("TRUSTEE", "STEWARD" or null for other roles)
+///     "endpoint": { (null if endpoint ATTRIB isn't set)
+///         "address": string,
+///         "transport_key": string (null if not set)
+///     }
+/// }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_resolve_did(command_handle: i32,
+                                 pool_handle: i32,
+                                 wallet_handle: i32,
+                                 did: *const c_char,
+                                 cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                      ddo_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::ResolveDid(
+            pool_handle,
+            wallet_handle,
+            did,
+            Box::new(move |result| {
+                let (err, ddo_json) = result_to_err_code_1!(result, String::new());
+                let ddo_json = CStringUtils::string_to_cstring(ddo_json);
+                cb(command_handle, err, ddo_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Builds a request to get a DDO.
 ///
 /// #Params
@@ -478,13 +624,184 @@ pub extern fn sovrin_build_get_claim_def_txn(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Builds a REVOC_REG_DEF request.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// xref: Seq. number of claim definition
+/// data: revocation registry json as returned by sovrin_issuer_create_and_store_revoc_reg
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_revoc_reg_def_request(command_handle: i32,
+                                                 submitter_did: *const c_char,
+                                                 xref: i32,
+                                                 data: *const c_char,
+                                                 cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                      request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(data, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildRevocRegDefRequest(
+            submitter_did,
+            xref,
+            data,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Builds a GET_REVOC_REG_DEF request.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// xref: Seq. number of claim definition
+/// origin: issuer did
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_get_revoc_reg_def_request(command_handle: i32,
+                                                     submitter_did: *const c_char,
+                                                     xref: i32,
+                                                     origin: *const c_char,
+                                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                          request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(origin, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildGetRevocRegDefRequest(
+            submitter_did,
+            xref,
+            origin,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Builds a REVOC_REG_ENTRY request.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// xref: Seq. number of revocation registry definition
+/// data: accumulator delta json:
+/// {
+///     "accum": string, current accumulator value
+///     "issued": array<int>, indexes issued since the previous entry
+///     "revoked": array<int>, indexes revoked since the previous entry
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_revoc_reg_entry_request(command_handle: i32,
+                                                   submitter_did: *const c_char,
+                                                   xref: i32,
+                                                   data: *const c_char,
+                                                   cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                        request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(data, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildRevocRegEntryRequest(
+            submitter_did,
+            xref,
+            data,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Builds a GET_REVOC_REG request to fetch the current accumulator of a revocation registry.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// xref: Seq. number of revocation registry definition
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_get_revoc_reg_request(command_handle: i32,
+                                                 submitter_did: *const c_char,
+                                                 xref: i32,
+                                                 cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                      request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildGetRevocRegRequest(
+            submitter_did,
+            xref,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Builds a NODE request.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
 /// target_did: Id of Identity stored in secured Wallet.
-/// data: id of a target NYM record
+/// data: node data json:
+/// {
+///     "node_ip": string, (optional; if set client_ip, node_port and client_port must be set too)
+///     "node_port": int, (optional)
+///     "client_ip": string, (optional)
+///     "client_port": int, (optional)
+///     "alias": string,
+///     "services": array<string>, (optional if address is set; "VALIDATOR" and/or "OBSERVER")
+/// }
+/// A node can be demoted by sending only alias and empty services
+/// and promoted again by sending alias and ["VALIDATOR"] services.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -517,4 +834,113 @@ pub extern fn sovrin_build_node_request(command_handle: i32,
         )));
 
     result_to_err_code!(result)
-}
\ No newline at end of file
+}
+
+/// Builds a POOL_UPGRADE request.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// name: Human-readable name for the upgrade.
+/// version: The version of Sovrin node package we perform upgrade to.
+/// action: Either "start" or "cancel".
+/// sha256: sha256 hash of the package (64 hex characters).
+/// timeout: Limits upgrade time on each Node, in minutes (optional; negative value means not set).
+/// schedule: Schedule of when to perform upgrade on each node as json map of node did to datetime
+///     (required for "start" action).
+/// justification: Justification string for this particular Upgrade (optional).
+/// reinstall: Whether it's allowed to re-install the same version.
+/// force: Whether we should apply transaction without waiting for consensus of this transaction.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_pool_upgrade_request(command_handle: i32,
+                                                submitter_did: *const c_char,
+                                                name: *const c_char,
+                                                version: *const c_char,
+                                                action: *const c_char,
+                                                sha256: *const c_char,
+                                                timeout: i32,
+                                                schedule: *const c_char,
+                                                justification: *const c_char,
+                                                reinstall: bool,
+                                                force: bool,
+                                                cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                     request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(name, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(version, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(action, ErrorCode::CommonInvalidParam5);
+    check_useful_c_str!(sha256, ErrorCode::CommonInvalidParam6);
+    check_useful_opt_c_str!(schedule, ErrorCode::CommonInvalidParam8);
+    check_useful_opt_c_str!(justification, ErrorCode::CommonInvalidParam9);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam12);
+
+    let timeout = if timeout >= 0 { Some(timeout as u32) } else { None };
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildPoolUpgradeRequest(
+            submitter_did,
+            name,
+            version,
+            action,
+            sha256,
+            timeout,
+            schedule,
+            justification,
+            reinstall,
+            force,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Builds a POOL_CONFIG request.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// writes: Whether any write requests can be processed by the pool
+///     (if false, then pool goes to read-only state).
+/// force: Whether we should apply transaction without waiting for consensus of this transaction.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_pool_config_request(command_handle: i32,
+                                               submitter_did: *const c_char,
+                                               writes: bool,
+                                               force: bool,
+                                               cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                    request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildPoolConfigRequest(
+            submitter_did,
+            writes,
+            force,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
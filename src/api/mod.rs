@@ -6,6 +6,7 @@ pub mod agent;
 pub mod anoncreds;
 pub mod signus;
 pub mod ledger;
+pub mod pairwise;
 pub mod pool;
 pub mod wallet;
 
@@ -53,6 +54,15 @@ pub enum ErrorCode
     // IO Error
     CommonIOError,
 
+    // Caller passed invalid value as param 10 (null, invalid json and etc..)
+    CommonInvalidParam10,
+
+    // Caller passed invalid value as param 11 (null, invalid json and etc..)
+    CommonInvalidParam11,
+
+    // Caller passed invalid value as param 12 (null, invalid json and etc..)
+    CommonInvalidParam12,
+
     // Wallet errors
     // Caller passed invalid wallet handle
     WalletInvalidHandle = 200,
@@ -91,6 +101,9 @@ pub enum ErrorCode
     // Attempt to send transaction without the necessary privileges
     LedgerSecurityError,
 
+    // Requested entity (e.g. DID) is not found on the ledger
+    LedgerNotFoundError,
+
     // Revocation registry is full and creation of new registry is necessary
     AnoncredsRevocationRegistryFullError = 400,
 
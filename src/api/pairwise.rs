@@ -0,0 +1,213 @@
+extern crate libc;
+
+use api::ErrorCode;
+use errors::ToErrorCode;
+use commands::{Command, CommandExecutor};
+use commands::pairwise::PairwiseCommand;
+use utils::cstring::CStringUtils;
+
+use self::libc::c_char;
+
+/// Creates pairwise: persistent relationship between my DID and their DID stored in the wallet.
+/// Pairwise is identified by their DID, so only one pairwise can be created for their DID.
+/// It can be passed to sovrin_agent_connect instead of sender and receiver DIDs.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID stored in the wallet by sovrin_store_their_did.
+/// my_did: my DID stored in the wallet by sovrin_create_and_store_my_did.
+/// pairwise_json (optional): pairwise info as json:
+///     {
+///         "endpoint": string, (optional) endpoint of their agent,
+///         "transport_key": string, (optional) transport key of their agent endpoint,
+///         "metadata": string, (optional) arbitrary protocol metadata
+///     }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_create_pairwise(command_handle: i32,
+                                     wallet_handle: i32,
+                                     their_did: *const c_char,
+                                     my_did: *const c_char,
+                                     pairwise_json: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(their_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(my_did, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(pairwise_json, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pairwise(PairwiseCommand::CreatePairwise(
+            wallet_handle,
+            their_did,
+            my_did,
+            pairwise_json,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Returns pairwise created by sovrin_create_pairwise.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID identifying pairwise.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// pairwise_json: pairwise as json:
+///     {
+///         "my_did": string,
+///         "their_did": string,
+///         "endpoint": string, (optional)
+///         "transport_key": string, (optional)
+///         "metadata": string, (optional)
+///         "state": one of "created", "connected", "disconnected", last known state
+///             of agent connection between pairwise DIDs
+///     }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_get_pairwise(command_handle: i32,
+                                  wallet_handle: i32,
+                                  their_did: *const c_char,
+                                  cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                       pairwise_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(their_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pairwise(PairwiseCommand::GetPairwise(
+            wallet_handle,
+            their_did,
+            Box::new(move |result| {
+                let (err, pairwise_json) = result_to_err_code_1!(result, String::new());
+                let pairwise_json = CStringUtils::string_to_cstring(pairwise_json);
+                cb(command_handle, err, pairwise_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Lists pairwise stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// pairwise_list_json: list of pairwise as json (see sovrin_get_pairwise).
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_list_pairwise(command_handle: i32,
+                                   wallet_handle: i32,
+                                   cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                        pairwise_list_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pairwise(PairwiseCommand::ListPairwise(
+            wallet_handle,
+            Box::new(move |result| {
+                let (err, pairwise_list_json) = result_to_err_code_1!(result, String::new());
+                let pairwise_list_json = CStringUtils::string_to_cstring(pairwise_list_json);
+                cb(command_handle, err, pairwise_list_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Updates pairwise created by sovrin_create_pairwise.
+/// Fields set in pairwise json replace previous values, other fields are kept.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID identifying pairwise.
+/// pairwise_json: pairwise info as json (see sovrin_create_pairwise).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_update_pairwise(command_handle: i32,
+                                     wallet_handle: i32,
+                                     their_did: *const c_char,
+                                     pairwise_json: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(their_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(pairwise_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pairwise(PairwiseCommand::UpdatePairwise(
+            wallet_handle,
+            their_did,
+            pairwise_json,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Deletes pairwise created by sovrin_create_pairwise. My and their DIDs are kept in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// their_did: their DID identifying pairwise.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_delete_pairwise(command_handle: i32,
+                                     wallet_handle: i32,
+                                     their_did: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(their_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pairwise(PairwiseCommand::DeletePairwise(
+            wallet_handle,
+            their_did,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
@@ -25,13 +25,14 @@ use self::libc::c_char;
 ///             if provided, then keys will be replaced - key rotation use case)
 ///     "seed": string, (optional; if not provide then a random one will be created)
 ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
-///               currently only 'ed25519' value is supported for this field)
+///               'secp256k1' is also supported if built with secp256k1_rust feature)
 ///     "cid": bool, (optional; if not set then false is used;)
 /// }
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
 /// DID, verkey (for verification of signature) and public_key (for decryption)
+/// Verkey is abbreviated ("~" followed by the last 16 bytes) if DID is built from ed25519 verkey.
 ///
 /// #Errors
 /// Common*
@@ -74,12 +75,13 @@ pub  extern fn sovrin_create_and_store_my_did(command_handle: i32,
 /// {
 ///     "seed": string, (optional; if not provide then a random one will be created)
 ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
-///               currently only 'ed25519' value is supported for this field)
+///               'secp256k1' is also supported if built with secp256k1_rust feature)
 /// }
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
 /// verkey (for verification of signature) and public_key (for decryption)
+/// Verkey is abbreviated in the same way as by sovrin_create_and_store_my_did.
 ///
 /// #Errors
 /// Common*
@@ -113,6 +115,153 @@ pub  extern fn sovrin_replace_keys(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Generates new keys (signing and encryption keys) for an existing
+/// DID (owned by the caller of the library) and stores them as pending
+/// next to the current ones. Current keys stay in use until
+/// sovrin_replace_keys_apply is called, so NYM with the new verkey can be
+/// signed by the old key and written to the ledger first.
+///
+/// #Params
+/// wallet_handle: wallet handler (created by open_wallet).
+/// command_handle: command handle to map callback to user context.
+/// did: existing DID owned by the caller.
+/// identity_json: Identity information as json. Example:
+/// {
+///     "seed": string, (optional; if not provide then a random one will be created)
+///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
+///               'secp256k1' is also supported if built with secp256k1_rust feature)
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// pending verkey (for verification of signature) and public_key (for decryption)
+/// Verkey is abbreviated in the same way as by sovrin_create_and_store_my_did.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_replace_keys_start(command_handle: i32,
+                                         wallet_handle: i32,
+                                         did: *const c_char,
+                                         identity_json: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                              verkey: *const c_char,
+                                                              pk: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(identity_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::ReplaceKeysStart(
+            wallet_handle,
+            identity_json,
+            did,
+            Box::new(move |result| {
+                let (err, verkey, pk) = result_to_err_code_2!(result, String::new(), String::new());
+                let verkey = CStringUtils::string_to_cstring(verkey);
+                let pk = CStringUtils::string_to_cstring(pk);
+                cb(command_handle, err, verkey.as_ptr(), pk.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Promotes keys created by sovrin_replace_keys_start to be the current
+/// keys of DID. Previous keys are dropped.
+///
+/// #Params
+/// wallet_handle: wallet handler (created by open_wallet).
+/// command_handle: command handle to map callback to user context.
+/// did: DID with pending keys.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub  extern fn sovrin_replace_keys_apply(command_handle: i32,
+                                         wallet_handle: i32,
+                                         did: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::ReplaceKeysApply(
+            wallet_handle,
+            did,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Rotates keys of DID synchronized with the ledger: generates pending keys,
+/// sends NYM transaction with the new verkey signed by the current key and
+/// applies the new keys only after the pool confirms the transaction.
+/// If the transaction fails current keys stay in use.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// pool_handle: pool handle (created by open_pool_ledger).
+/// did: existing DID owned by the caller.
+/// identity_json: Identity information as json. Example:
+/// {
+///     "seed": string, (optional; if not provide then a random one will be created)
+///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
+///               'secp256k1' is also supported if built with secp256k1_rust feature)
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// new verkey (for verification of signature) and public_key (for decryption)
+/// Verkey is abbreviated in the same way as by sovrin_create_and_store_my_did.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_replace_keys_on_ledger(command_handle: i32,
+                                             wallet_handle: i32,
+                                             pool_handle: i32,
+                                             did: *const c_char,
+                                             identity_json: *const c_char,
+                                             cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                  verkey: *const c_char,
+                                                                  pk: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(identity_json, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::ReplaceKeysOnLedger(
+            wallet_handle,
+            pool_handle,
+            identity_json,
+            did,
+            Box::new(move |result| {
+                let (err, verkey, pk) = result_to_err_code_2!(result, String::new(), String::new());
+                let verkey = CStringUtils::string_to_cstring(verkey);
+                let pk = CStringUtils::string_to_cstring(pk);
+                cb(command_handle, err, verkey.as_ptr(), pk.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Saves their DID for a pairwise connection in a secured Wallet,
 /// so that it can be used to verify transaction.
 ///
@@ -122,9 +271,9 @@ pub  extern fn sovrin_replace_keys(command_handle: i32,
 /// identity_json: Identity information as json. Example:
 ///     {
 ///        "did": string, (required)
-///        "verkey": string (optional, if only pk is provided),
+///        "verkey": string (optional, if only pk is provided; can be abbreviated),
 ///        "crypto_type": string, (optional; if not set then ed25519 curve is used;
-///               currently only 'ed25519' value is supported for this field)
+///               'secp256k1' is also supported if built with secp256k1_rust feature)
 ///     }
 /// cb: Callback that takes command result as parameter.
 ///
@@ -199,6 +348,96 @@ pub  extern fn sovrin_sign(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Signs arbitrary bytes by a signing key associated with my DID and returns just the signature
+/// (detached signature). The message itself isn't changed or included to the result.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// did: signing DID
+/// message_raw: a pointer to first byte of message to be signed (can be null for empty message)
+/// message_len: a message length
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// a signature as byte array
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_sign_detached(command_handle: i32,
+                                    wallet_handle: i32,
+                                    did: *const c_char,
+                                    message_raw: *const u8,
+                                    message_len: u32,
+                                    cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                         signature_raw: *const u8, signature_len: u32)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_byte_array!(message_raw, message_len, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::SignDetached(
+            wallet_handle,
+            did,
+            message_raw,
+            Box::new(move |result| {
+                let (err, signature) = result_to_err_code_1!(result, Vec::new());
+                cb(command_handle, err, signature.as_ptr(), signature.len() as u32)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Signs arbitrary payload by a signing key associated with my DID and returns compact JWS
+/// ("header.payload.signature", base64url encoded without padding) with EdDSA alg.
+/// Only DIDs with ed25519 keys can sign JWS.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// did: signing DID
+/// payload_raw: a pointer to first byte of payload to be signed (can be null for empty payload)
+/// payload_len: a payload length
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// compact JWS string
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_sign_jws(command_handle: i32,
+                               wallet_handle: i32,
+                               did: *const c_char,
+                               payload_raw: *const u8,
+                               payload_len: u32,
+                               cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                    jws: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_byte_array!(payload_raw, payload_len, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::SignJws(
+            wallet_handle,
+            did,
+            payload_raw,
+            Box::new(move |result| {
+                let (err, jws) = result_to_err_code_1!(result, String::new());
+                let jws = CStringUtils::string_to_cstring(jws);
+                cb(command_handle, err, jws.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Verify a signature created by a key associated with a DID.
 /// If a secure wallet doesn't contain a verkey associated with the given DID,
 /// then verkey is read from the Ledger.
@@ -211,8 +450,7 @@ pub  extern fn sovrin_sign(command_handle: i32,
 /// command_handle: command handle to map callback to user context.
 /// pool_handle: pool handle.
 /// did: DID that signed the message
-/// msg: message
-/// signature: a signature to be verified
+/// signed_msg: json message signed by sovrin_sign or compact JWS created by sovrin_sign_jws
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -336,16 +574,16 @@ pub  extern fn sovrin_decrypt(command_handle: i32,
                               cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
                                                    decrypted_msg: *const c_char)>) -> ErrorCode {
     check_useful_c_str!(my_did, ErrorCode::CommonInvalidParam3);
-    check_useful_c_str!(did, ErrorCode::CommonInvalidParam5);
-    check_useful_c_str!(encrypted_msg, ErrorCode::CommonInvalidParam4);
-    check_useful_c_str!(nonce, ErrorCode::CommonInvalidParam5);
-    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(encrypted_msg, ErrorCode::CommonInvalidParam5);
+    check_useful_c_str!(nonce, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
 
     let result = CommandExecutor::instance()
         .send(Command::Signus(SignusCommand::Decrypt(
             wallet_handle,
-            did,
             my_did,
+            did,
             encrypted_msg,
             nonce,
             Box::new(move |result| {
@@ -356,4 +594,720 @@ pub  extern fn sovrin_decrypt(command_handle: i32,
         )));
 
     result_to_err_code!(result)
-}
\ No newline at end of file
+}
+
+/// Encrypts a message by a public key associated with a DID using anonymous (sealed box)
+/// encryption: the sender isn't authenticated and doesn't need a DID.
+/// The public key is taken from the wallet (see wallet_store_their_identity)
+/// or read from the Ledger if the wallet doesn't contain it.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// pool_handle: pool handle (created by open_pool_ledger).
+/// did: DID of the recipient
+/// msg: a message to be encrypted
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// an encrypted message
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_encrypt_sealed(command_handle: i32,
+                                     wallet_handle: i32,
+                                     pool_handle: i32,
+                                     did: *const c_char,
+                                     msg: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                          encrypted_msg: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(msg, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::EncryptSealed(
+            wallet_handle,
+            pool_handle,
+            did,
+            msg,
+            Box::new(move |result| {
+                let (err, encrypted_msg) = result_to_err_code_1!(result, String::new());
+                let encrypted_msg = CStringUtils::string_to_cstring(encrypted_msg);
+                cb(command_handle, err, encrypted_msg.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Decrypts a message encrypted by sovrin_encrypt_sealed with a public key associated with my DID.
+/// The DID with a secret key must be already created and
+/// stored in a secured wallet (see wallet_create_and_store_my_identity)
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// my_did: DID
+/// encrypted_msg: encrypted message
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// decrypted message
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_decrypt_sealed(command_handle: i32,
+                                     wallet_handle: i32,
+                                     my_did: *const c_char,
+                                     encrypted_msg: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                          decrypted_msg: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(my_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(encrypted_msg, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::DecryptSealed(
+            wallet_handle,
+            my_did,
+            encrypted_msg,
+            Box::new(move |result| {
+                let (err, decrypted_msg) = result_to_err_code_1!(result, String::new());
+                let decrypted_msg = CStringUtils::string_to_cstring(decrypted_msg);
+                cb(command_handle, err, decrypted_msg.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Encrypts a message for a group of DIDs at once. The message is encrypted only once
+/// by a random content key and this key is encrypted (sealed box) by public key of every recipient.
+/// Recipients must be already stored in a secured wallet with verkey (see sovrin_store_their_did).
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// dids_json: list of recipient DIDs as json. Example: ["did1", "did2"]
+/// msg: a message to be encrypted
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// envelope json:
+///     {
+///         "ciphertext": string, (base58 encoded message encrypted by content key)
+///         "nonce": string, (base58 encoded nonce)
+///         "recipients": [{
+///             "did": string,
+///             "encrypted_key": string (base58 encoded content key sealed for the did)
+///         }]
+///     }
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_encrypt_envelope(command_handle: i32,
+                                       wallet_handle: i32,
+                                       dids_json: *const c_char,
+                                       msg: *const c_char,
+                                       cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                            envelope_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(dids_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(msg, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::EncryptEnvelope(
+            wallet_handle,
+            dids_json,
+            msg,
+            Box::new(move |result| {
+                let (err, envelope_json) = result_to_err_code_1!(result, String::new());
+                let envelope_json = CStringUtils::string_to_cstring(envelope_json);
+                cb(command_handle, err, envelope_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Decrypts an envelope created by sovrin_encrypt_envelope. The entry for my DID is found
+/// in the envelope recipients and the content key is decrypted by secret key of my DID.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// my_did: DID
+/// envelope_json: envelope created by sovrin_encrypt_envelope
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// decrypted message
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_decrypt_envelope(command_handle: i32,
+                                       wallet_handle: i32,
+                                       my_did: *const c_char,
+                                       envelope_json: *const c_char,
+                                       cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                            decrypted_msg: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(my_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(envelope_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::DecryptEnvelope(
+            wallet_handle,
+            my_did,
+            envelope_json,
+            Box::new(move |result| {
+                let (err, decrypted_msg) = result_to_err_code_1!(result, String::new());
+                let decrypted_msg = CStringUtils::string_to_cstring(decrypted_msg);
+                cb(command_handle, err, decrypted_msg.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Lists my DIDs stored in the wallet together with their metadata.
+/// Secret keys are never included.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// dids_json: list of my DIDs as json. Example:
+///     [{
+///         "did": string,
+///         "verkey": string, (abbreviated as by sovrin_create_and_store_my_did)
+///         "metadata": string (optional; metadata set by sovrin_set_did_metadata)
+///     }]
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub  extern fn sovrin_list_my_dids_with_meta(command_handle: i32,
+                                             wallet_handle: i32,
+                                             cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                  dids_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::ListMyDidsWithMeta(
+            wallet_handle,
+            Box::new(move |result| {
+                let (err, dids_json) = result_to_err_code_1!(result, String::new());
+                let dids_json = CStringUtils::string_to_cstring(dids_json);
+                cb(command_handle, err, dids_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Lists their DIDs stored in the wallet together with their metadata.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// dids_json: list of their DIDs as json. Example:
+///     [{
+///         "did": string,
+///         "verkey": string, (optional)
+///         "endpoint": string, (optional)
+///         "metadata": string (optional; metadata set by sovrin_set_did_metadata)
+///     }]
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub  extern fn sovrin_list_their_dids_with_meta(command_handle: i32,
+                                                wallet_handle: i32,
+                                                cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                     dids_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::ListTheirDidsWithMeta(
+            wallet_handle,
+            Box::new(move |result| {
+                let (err, dids_json) = result_to_err_code_1!(result, String::new());
+                let dids_json = CStringUtils::string_to_cstring(dids_json);
+                cb(command_handle, err, dids_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Saves application metadata for my or their DID stored in the wallet.
+/// Previous metadata of the DID is replaced.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// did: my or their DID
+/// metadata: arbitrary json
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// None
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub  extern fn sovrin_set_did_metadata(command_handle: i32,
+                                       wallet_handle: i32,
+                                       did: *const c_char,
+                                       metadata: *const c_char,
+                                       cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(metadata, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::SetDidMetadata(
+            wallet_handle,
+            did,
+            metadata,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Returns application metadata saved for the DID by sovrin_set_did_metadata.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// did: my or their DID
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// metadata json
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub  extern fn sovrin_get_did_metadata(command_handle: i32,
+                                       wallet_handle: i32,
+                                       did: *const c_char,
+                                       cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                            metadata: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::GetDidMetadata(
+            wallet_handle,
+            did,
+            Box::new(move |result| {
+                let (err, metadata) = result_to_err_code_1!(result, String::new());
+                let metadata = CStringUtils::string_to_cstring(metadata);
+                cb(command_handle, err, metadata.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Creates keys pair (signing and encryption keys) not tied to any DID
+/// and stores it in a secured Wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// key_json: Key information as json. Example:
+/// {
+///     "seed": string, (optional; if not provide then a random one will be created)
+///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
+///               'secp256k1' is also supported if built with secp256k1_rust feature)
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// verkey of the created key. For non-default crypto type it is followed
+/// by ":<crypto_type>" suffix, for example "<verkey>:secp256k1".
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_create_key(command_handle: i32,
+                                 wallet_handle: i32,
+                                 key_json: *const c_char,
+                                 cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                      verkey: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(key_json, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::CreateKey(
+            wallet_handle,
+            key_json,
+            Box::new(move |result| {
+                let (err, verkey) = result_to_err_code_1!(result, String::new());
+                let verkey = CStringUtils::string_to_cstring(verkey);
+                cb(command_handle, err, verkey.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Signs a message by a key created by sovrin_create_key (or a key of my DID).
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// verkey: verkey of the key to sign with. Abbreviated verkey of my DID is accepted
+/// msg: a message to be signed
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// a signed message
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_sign_by_key(command_handle: i32,
+                                  wallet_handle: i32,
+                                  verkey: *const c_char,
+                                  msg: *const c_char,
+                                  cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                       signed_msg: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(verkey, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(msg, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::SignByKey(
+            wallet_handle,
+            verkey,
+            msg,
+            Box::new(move |result| {
+                let (err, signed_msg) = result_to_err_code_1!(result, String::new());
+                let signed_msg = CStringUtils::string_to_cstring(signed_msg);
+                cb(command_handle, err, signed_msg.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Verifies a signature of a message by the given verkey.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// verkey: verkey to verify signature with. Can be followed by ":<crypto_type>" suffix.
+///         Abbreviated verkey should be expanded to the full one as it doesn't contain DID.
+/// signed_msg: json message signed by sovrin_sign_by_key or sovrin_sign
+///             or compact JWS created by sovrin_sign_jws
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// valid: true - if signature is valid, false - otherwise
+///
+/// #Errors
+/// Common*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_verify_by_key(command_handle: i32,
+                                    verkey: *const c_char,
+                                    signed_msg: *const c_char,
+                                    cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                         valid: bool)>) -> ErrorCode {
+    check_useful_c_str!(verkey, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(signed_msg, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::VerifyByKey(
+            verkey,
+            signed_msg,
+            Box::new(move |result| {
+                let (err, valid) = result_to_err_code_1!(result, false);
+                cb(command_handle, err, valid)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Verifies a detached signature (see sovrin_sign_detached) of arbitrary bytes by the given verkey.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// verkey: verkey to verify signature with. Can be followed by ":<crypto_type>" suffix.
+///         Abbreviated verkey should be expanded to the full one as it doesn't contain DID.
+/// message_raw: a pointer to first byte of signed message (can be null for empty message)
+/// message_len: a message length
+/// signature_raw: a pointer to first byte of signature
+/// signature_len: a signature length
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// valid: true - if signature is valid, false - otherwise
+///
+/// #Errors
+/// Common*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_verify_detached_by_key(command_handle: i32,
+                                             verkey: *const c_char,
+                                             message_raw: *const u8,
+                                             message_len: u32,
+                                             signature_raw: *const u8,
+                                             signature_len: u32,
+                                             cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                  valid: bool)>) -> ErrorCode {
+    check_useful_c_str!(verkey, ErrorCode::CommonInvalidParam2);
+    check_useful_c_byte_array!(message_raw, message_len, ErrorCode::CommonInvalidParam3);
+    check_useful_c_byte_array!(signature_raw, signature_len, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::VerifyDetachedByKey(
+            verkey,
+            message_raw,
+            signature_raw,
+            Box::new(move |result| {
+                let (err, valid) = result_to_err_code_1!(result, false);
+                cb(command_handle, err, valid)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Encrypts a message from my key to their verkey.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// my_verkey: verkey of my key created by sovrin_create_key (or a key of my DID, can be abbreviated)
+/// their_verkey: verkey of the recipient. Can be followed by ":<crypto_type>" suffix.
+///               Abbreviated verkey is expanded using DIDs stored in the wallet.
+/// msg: a message to be encrypted
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// an encrypted message and nonce
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_encrypt_by_key(command_handle: i32,
+                                     wallet_handle: i32,
+                                     my_verkey: *const c_char,
+                                     their_verkey: *const c_char,
+                                     msg: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                          encrypted_msg: *const c_char,
+                                                          nonce: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(my_verkey, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(their_verkey, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(msg, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::EncryptByKey(
+            wallet_handle,
+            my_verkey,
+            their_verkey,
+            msg,
+            Box::new(move |result| {
+                let (err, encrypted_msg, nonce) = result_to_err_code_2!(result, String::new(), String::new());
+                let encrypted_msg = CStringUtils::string_to_cstring(encrypted_msg);
+                let nonce = CStringUtils::string_to_cstring(nonce);
+                cb(command_handle, err, encrypted_msg.as_ptr(), nonce.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Decrypts a message encrypted by sovrin_encrypt_by_key (or sovrin_encrypt) for my key.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// my_verkey: verkey of my key created by sovrin_create_key (or a key of my DID, can be abbreviated)
+/// their_verkey: verkey of the sender. Can be followed by ":<crypto_type>" suffix.
+///               Abbreviated verkey is expanded using DIDs stored in the wallet.
+/// encrypted_msg: encrypted message
+/// nonce: nonce that encrypted message
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// decrypted message
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub  extern fn sovrin_decrypt_by_key(command_handle: i32,
+                                     wallet_handle: i32,
+                                     my_verkey: *const c_char,
+                                     their_verkey: *const c_char,
+                                     encrypted_msg: *const c_char,
+                                     nonce: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                          decrypted_msg: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(my_verkey, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(their_verkey, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(encrypted_msg, ErrorCode::CommonInvalidParam5);
+    check_useful_c_str!(nonce, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::DecryptByKey(
+            wallet_handle,
+            my_verkey,
+            their_verkey,
+            encrypted_msg,
+            nonce,
+            Box::new(move |result| {
+                let (err, decrypted_msg) = result_to_err_code_1!(result, String::new());
+                let decrypted_msg = CStringUtils::string_to_cstring(decrypted_msg);
+                cb(command_handle, err, decrypted_msg.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Sets agent endpoint (address and transport key) for the DID.
+/// The endpoint is always stored in the wallet. If pool handle is passed, the endpoint
+/// is also published to the ledger as "endpoint" ATTRIB of the DID, so the DID must be
+/// created and stored in the wallet (see wallet_create_and_store_my_identity) to sign
+/// the transaction. In this case the wallet is updated only after the ledger accepts it.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// pool_handle: pool handle (created by open_pool_ledger) or negative value to store endpoint in the wallet only.
+/// did: DID the endpoint belongs to
+/// address: agent address, for example "127.0.0.1:9700"
+/// transport_key: (optional) public key used by agent listener
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_set_endpoint_for_did(command_handle: i32,
+                                          wallet_handle: i32,
+                                          pool_handle: i32,
+                                          did: *const c_char,
+                                          address: *const c_char,
+                                          transport_key: *const c_char,
+                                          cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(address, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_c_str!(transport_key, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
+
+    let pool_handle = if pool_handle >= 0 { Some(pool_handle) } else { None };
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::SetEndpointForDid(
+            wallet_handle,
+            pool_handle,
+            did,
+            address,
+            transport_key,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Returns agent endpoint (address and transport key) of the DID.
+/// Endpoint stored in the wallet is used if it is still fresh (according to freshness settings
+/// set during initialization). Otherwise, if pool handle is passed, "endpoint" ATTRIB of the DID
+/// is read from the ledger and stored in the wallet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+/// wallet_handle: wallet handler (created by open_wallet).
+/// pool_handle: pool handle (created by open_pool_ledger) or negative value to look up the wallet only.
+/// did: DID to get endpoint for
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// agent address and transport key (empty string if it isn't set)
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_get_endpoint_for_did(command_handle: i32,
+                                          wallet_handle: i32,
+                                          pool_handle: i32,
+                                          did: *const c_char,
+                                          cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                               address: *const c_char,
+                                                               transport_key: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let pool_handle = if pool_handle >= 0 { Some(pool_handle) } else { None };
+
+    let result = CommandExecutor::instance()
+        .send(Command::Signus(SignusCommand::GetEndpointForDid(
+            wallet_handle,
+            pool_handle,
+            did,
+            Box::new(move |result| {
+                let (err, address, transport_key) = result_to_err_code_2!(result, String::new(), None);
+                let address = CStringUtils::string_to_cstring(address);
+                let transport_key = CStringUtils::string_to_cstring(transport_key.unwrap_or(String::new()));
+                cb(command_handle, err, address.as_ptr(), transport_key.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
@@ -1,38 +1,79 @@
 #![warn(unused_variables)]
+extern crate serde_json;
+extern crate uuid;
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::rc::Rc;
 
+use self::uuid::Uuid;
+
 use commands::{Command, CommandExecutor};
+use commands::anoncreds::AnoncredsCommand;
+use commands::anoncreds::issuer::IssuerCommand;
+use commands::anoncreds::prover::ProverCommand;
+use commands::anoncreds::verifier::VerifierCommand;
 use commands::ledger::LedgerCommand;
+use commands::pairwise;
+use commands::signus::{self, SignusCommand};
 use commands::utils::check_wallet_and_pool_handles_consistency;
 use errors::sovrin::SovrinError;
 use errors::common::CommonError;
-use services::agent::AgentService;
+use errors::wallet::WalletError;
+use services::agent::{AcceptInvitationOptions, AgentEnvelope, AgentService, ClaimExchange, ClaimExchangeEvent, ClaimExchangeOptions,
+                      ClaimExchangeRole, ClaimExchangeState, ClaimIdentifier, ClaimMsg, ConnectOptions, DeliveryOptions, IncomingConnection,
+                      Invitation, InvitationMsg, InvitationRecord, InvitationState, ListenerEvent, ListenerPolicy, ListenerPolicyInfo,
+                      MailboxInfo, MailboxMessage, MailboxMsg, ProofExchange, ProofExchangeEvent, ProofExchangeRole, ProofExchangeState,
+                      ProofMsg, CLAIM_TYPE, INVITATION_TYPE, MAILBOX_TYPE, PROOF_TYPE};
+use services::anoncreds::constants::LARGE_NONCE;
+use services::anoncreds::types::{ClaimDefinition, ClaimOffer, ProofClaimsJson, ProofJson, ProofRequestJson, RequestedClaimsJson, Schema};
 use services::ledger::LedgerService;
+use services::ledger::types::{Ddo, Endpoint};
+use services::pairwise::types::{Pairwise, PairwiseState};
 use services::pool::PoolService;
-use services::signus::types::{MyDid, TheirDid};
+use services::signus::SignusService;
+use services::signus::types::{MyDid, TheirDid, TheirDidInfo};
 use services::wallet::WalletService;
-use utils::json::JsonDecodable;
+use utils::crypto::base58::Base58;
+use utils::crypto::bn::BigNumber;
+use utils::crypto::memzero::memzero_string;
+use utils::json::{JsonDecodable, JsonEncodable};
 use utils::sequence::SequenceUtils;
 
 pub type AgentConnectCB = Box<Fn(Result<i32, SovrinError>) + Send>;
 pub type AgentMessageCB = Box<Fn(Result<(i32, String), SovrinError>) + Send>;
+pub type AgentListenerEventCB = Box<Fn(Result<(i32, String), SovrinError>) + Send>;
+pub type AgentDeliveryCB = Box<Fn(i32 /* connection handle */, i32 /* message id */, Result<(), SovrinError>) + Send>;
+pub type AgentMailboxPullCB = Box<Fn(Result<String, SovrinError>) + Send>;
+pub type AgentClaimEventCB = Box<Fn(Result<String, SovrinError>) + Send>;
+pub type AgentProofEventCB = Box<Fn(Result<String, SovrinError>) + Send>;
+pub type AgentAcceptInvitationCB = Box<Fn(Result<(i32 /* connection handle */, String /* my did */), SovrinError>) + Send>;
 
 pub enum AgentCommand {
     Connect(
         i32, // pool handle
         i32, // wallet handle
-        String, // sender did
-        String, // receiver did
+        Option<String>, // sender did
+        String, // receiver did or their did of pairwise if sender did isn't set
+        Option<String>, // options json
         AgentConnectCB, // connect cb
         AgentMessageCB, // message cb
+        Option<AgentDeliveryCB>, // delivery cb
     ),
     ResumeConnectProcess(
         i32, // cmd handle
-        Result<(MyConnectInfo, String /* get DDO result JSON */), SovrinError>
+        Result<(MyConnectInfo, (String, Option<String>) /* endpoint address and transport key */), SovrinError>
+    ),
+    ConnectResolveDidAck(
+        i32, // cmd handle
+        Result<(MyConnectInfo, Endpoint /* endpoint of resolved receiver DID */), SovrinError>
+    ),
+    ConnectChallenge(
+        i32, // cmd handle (eq conn handle)
+        String, // sender did
+        String, // challenge nonce
     ),
     ConnectAck(
         i32, // cmd handle (eq conn handle)
@@ -47,23 +88,60 @@ pub enum AgentCommand {
         Result<(), CommonError>,
     ),
     Listen(
+        i32, // pool handle
         i32, // wallet handle
         String, // endpoint
+        Option<String>, // identities json
+        Option<String>, // policy json
         Box<Fn(Result<i32, SovrinError>) + Send>, // listen cb
         Box<Fn(Result<(i32, i32, String, String), SovrinError>) + Send>, // connect cb
         AgentMessageCB, // message cb
+        AgentListenerEventCB, // event cb
+        Option<AgentDeliveryCB>, // delivery cb
     ),
     ListenAck(
         i32, // cmd handle (eq listener handle)
         Result<i32, CommonError> // listener handle or error
     ),
+    AddIdentity(
+        i32, // listener handle
+        i32, // wallet handle
+        String, // did
+        Box<Fn(Result<(), SovrinError>) + Send>, // add identity cb
+    ),
+    RemoveIdentity(
+        i32, // listener handle
+        i32, // wallet handle
+        String, // did
+        Box<Fn(Result<(), SovrinError>) + Send>, // remove identity cb
+    ),
+    IdentityAck(
+        i32, // identity cmd handle
+        Result<(), CommonError>,
+    ),
+    CheckIncomingConnection(
+        IncomingConnection
+    ),
+    CheckIncomingConnectionGetNymAck(
+        IncomingConnection,
+        Result<String, SovrinError> // get nym reply json or error
+    ),
     ListenerOnConnect(
         i32, // listener handle
         Result<(i32, i32, String, String), CommonError>, // (listener handle, new connection handle, sender and receiver did) or error
     ),
+    ListenerOnEvent(
+        i32, // listener handle
+        ListenerEvent,
+    ),
     MessageReceived(
         i32, // connection handle
-        Result<(i32, String), CommonError> // result for message
+        Result<(i32, AgentEnvelope), CommonError> // result for message
+    ),
+    DeliveryStatus(
+        i32, // connection handle
+        i32, // message id
+        Result<(), CommonError> // message is acknowledged by peer or error
     ),
     CloseListener(
         i32, // listener handle
@@ -75,88 +153,349 @@ pub enum AgentCommand {
     ),
     Send(
         i32, // connection handle
-        Option<String>, // message
-        Box<Fn(Result<(), SovrinError>) + Send>, // send cb
+        String, // message type
+        Option<String>, // thread id
+        Vec<u8>, // message body
+        bool, // sign message
+        Box<Fn(Result<i32, SovrinError>) + Send>, // send cb
     ),
     SendAck(
         i32, // send cmd handle
-        Result<(), CommonError>,
-    )
+        Result<i32, CommonError>, // message id or error
+    ),
+    MailboxHost(
+        i32, // wallet handle
+        String, // hosted did
+        Box<Fn(Result<(), SovrinError>) + Send>, // host cb
+    ),
+    MailboxForward(
+        i32, // connection handle
+        i32, // wallet handle
+        String, // receiver did
+        String, // message
+        Box<Fn(Result<(), SovrinError>) + Send>, // forward cb
+    ),
+    MailboxPull(
+        i32, // connection handle
+        i32, // wallet handle
+        AgentMailboxPullCB, // pull cb
+    ),
+    MailboxRequestFailed(
+        i32, // mailbox request id
+        SovrinError,
+    ),
+    ClaimSubscribe(
+        i32, // wallet handle
+        Option<String>, // options json
+        Box<Fn(Result<(), SovrinError>) + Send>, // subscribe cb
+        AgentClaimEventCB, // event cb
+    ),
+    ClaimOffer(
+        i32, // connection handle
+        String, // claim offer json
+        String, // claim def json
+        Box<Fn(Result<String, SovrinError>) + Send>, // offer cb
+    ),
+    ClaimOfferStoredAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<(), SovrinError>,
+    ),
+    ClaimRequest(
+        i32, // wallet handle
+        String, // exchange id
+        String, // master secret name
+        Box<Fn(Result<(), SovrinError>) + Send>, // request cb
+    ),
+    ClaimRequestCreatedAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<String, SovrinError>, // claim request json or error
+    ),
+    ClaimRequestSentAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<(), SovrinError>,
+    ),
+    ClaimIssue(
+        i32, // wallet handle
+        String, // exchange id
+        String, // claim json
+        Option<i32>, // revoc reg seq no
+        Option<i32>, // user revoc index
+        Box<Fn(Result<String, SovrinError>) + Send>, // issue cb
+    ),
+    ClaimCreatedAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<(String, String), SovrinError>, // revoc reg update json and claim json or error
+    ),
+    ClaimStoredAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<(), SovrinError>,
+    ),
+    ClaimExchanges(
+        i32, // wallet handle
+        Box<Fn(Result<String, SovrinError>) + Send>, // exchanges cb
+    ),
+    ProofSubscribe(
+        i32, // pool handle
+        i32, // wallet handle
+        Box<Fn(Result<(), SovrinError>) + Send>, // subscribe cb
+        AgentProofEventCB, // event cb
+    ),
+    ProofRequest(
+        i32, // connection handle
+        String, // proof request json
+        Box<Fn(Result<String, SovrinError>) + Send>, // request cb
+    ),
+    ProofClaimsFoundAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<String, SovrinError>, // claims json or error
+    ),
+    ProofPresent(
+        i32, // wallet handle
+        String, // exchange id
+        String, // requested claims json
+        String, // master secret name
+        Box<Fn(Result<(), SovrinError>) + Send>, // present cb
+    ),
+    ProofLedgerReplyAck(
+        i32, // wallet handle
+        ProofExchangeRole,
+        String, // exchange id
+        String, // claim uuid
+        ProofLedgerRecord,
+        Result<String, SovrinError>, // ledger reply json or error
+    ),
+    ProofCreatedAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<String, SovrinError>, // proof json or error
+    ),
+    ProofVerifiedAck(
+        i32, // wallet handle
+        String, // exchange id
+        Result<bool, SovrinError>, // validity of proof or error
+    ),
+    ProofExchanges(
+        i32, // wallet handle
+        Box<Fn(Result<String, SovrinError>) + Send>, // exchanges cb
+    ),
+    CreateInvitation(
+        i32, // listener handle
+        i32, // wallet handle
+        Option<String>, // label
+        Box<Fn(Result<String, SovrinError>) + Send>, // invitation cb
+    ),
+    InvitationDidCreatedAck(
+        i32, // cmd id
+        Result<(String, String, String), SovrinError>, // (did, verkey, pk) or error
+    ),
+    InvitationIdentityAck(
+        i32, // cmd id
+        Result<(), SovrinError>, // result of adding DID to listener
+    ),
+    AcceptInvitation(
+        i32, // wallet handle
+        String, // invitation json
+        Option<String>, // options json
+        AgentAcceptInvitationCB, // accept cb
+        AgentMessageCB, // message cb
+    ),
+    AcceptInvitationDidCreatedAck(
+        i32, // cmd id
+        Result<(String, String, String), SovrinError>, // (did, verkey, pk) or error
+    ),
+    AcceptInvitationConnectAck(
+        i32, // cmd id
+        Result<i32, SovrinError>, // connection handle or error
+    ),
+}
+
+/// Ledger records a proof depends on. They are requested for each claim of the proof.
+#[derive(Debug, Clone, Copy)]
+pub enum ProofLedgerRecord {
+    Schema,
+    ClaimDef,
 }
 
 pub struct AgentCommandExecutor {
     agent_service: Rc<AgentService>,
     ledger_service: Rc<LedgerService>,
     pool_service: Rc<PoolService>,
+    signus_service: Rc<SignusService>,
     wallet_service: Rc<WalletService>,
 
     out_connections: RefCell<HashMap<i32, AgentMessageCB>>,
+    out_delivery_callbacks: RefCell<HashMap<i32, AgentDeliveryCB>>,
     listeners: RefCell<HashMap<i32, Listener>>,
 
     listen_callbacks: RefCell<HashMap<i32, (
         Box<Fn(Result<i32, SovrinError>) + Send>, // listen cb
         Listener
     )>>,
-    connect_callbacks: RefCell<HashMap<i32, (AgentConnectCB, AgentMessageCB)>>,
-    send_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(), SovrinError>)>>>,
+    connect_callbacks: RefCell<HashMap<i32, (AgentConnectCB, AgentMessageCB, Option<AgentDeliveryCB>)>>,
+    connect_identities: RefCell<HashMap<i32, (i32 /* wallet handle */, String /* sender did */, String /* receiver did */)>>,
+    identity_callbacks: RefCell<HashMap<i32, (
+        Box<Fn(Result<(), SovrinError>) + Send>, // add or remove identity cb
+        i32, // listener handle
+        String, // did
+        Option<i32> // wallet handle of added identity or None for removed
+    )>>,
+    send_callbacks: RefCell<HashMap<i32, Box<Fn(Result<i32, SovrinError>)>>>,
     close_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(), SovrinError>)>>>,
+    mailbox_forward_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(), SovrinError>) + Send>>>,
+    mailbox_pull_callbacks: RefCell<HashMap<i32, (i32 /* wallet handle */, AgentMailboxPullCB)>>,
+    claim_subscribers: RefCell<HashMap<i32 /* wallet handle */, (ClaimExchangeOptions, AgentClaimEventCB)>>,
+    claim_request_callbacks: RefCell<HashMap<String /* exchange id */, Box<Fn(Result<(), SovrinError>) + Send>>>,
+    claim_issue_callbacks: RefCell<HashMap<String /* exchange id */, Box<Fn(Result<String, SovrinError>) + Send>>>,
+    proof_subscribers: RefCell<HashMap<i32 /* wallet handle */, (i32 /* pool handle */, AgentProofEventCB)>>,
+    proof_present_callbacks: RefCell<HashMap<String /* exchange id */, (String /* requested claims json */, String /* master secret name */,
+                                                                        Box<Fn(Result<(), SovrinError>) + Send>)>>,
+    proof_resolutions: RefCell<HashMap<String /* exchange key */, ProofResolution>>,
+    invitation_requests: RefCell<HashMap<i32 /* cmd id */, InvitationRequest>>,
+    accept_invitation_requests: RefCell<HashMap<i32 /* cmd id */, AcceptInvitationRequest>>,
+}
+
+struct ProofResolution {
+    pending: usize,
+    schemas: HashMap<String /* claim uuid */, Schema>,
+    claim_defs: HashMap<String /* claim uuid */, ClaimDefinition>,
+}
+
+struct InvitationRequest {
+    listener_handle: i32,
+    wallet_handle: i32,
+    label: Option<String>,
+    invitation: Option<Invitation>,
+    cb: Box<Fn(Result<String, SovrinError>) + Send>,
+}
+
+struct AcceptInvitationRequest {
+    wallet_handle: i32,
+    invitation: Invitation,
+    options: AcceptInvitationOptions,
+    my_did: Option<String>,
+    cb: AgentAcceptInvitationCB,
+    message_cb: Option<AgentMessageCB>,
 }
 
 struct Listener {
+    pool_handle: i32,
+    endpoint: String,
+    transport_key: String,
+    identities: HashMap<String /* did */, i32 /* wallet handle */>,
+    policy: ListenerPolicyInfo,
     on_connect: Box<Fn(Result<(i32, i32, String, String), SovrinError>) + Send>,
     on_msg: AgentMessageCB,
-    conn_handles: HashSet<i32>,
+    on_event: AgentListenerEventCB,
+    on_delivery: Option<AgentDeliveryCB>,
+    connections: HashMap<i32 /* connection handle */, (String /* sender did */, String /* receiver did */)>,
 }
 
 impl AgentCommandExecutor {
-    pub fn new(agent_service: Rc<AgentService>, ledger_service: Rc<LedgerService>, pool_service: Rc<PoolService>, wallet_service: Rc<WalletService>) -> AgentCommandExecutor {
+    pub fn new(agent_service: Rc<AgentService>, ledger_service: Rc<LedgerService>, pool_service: Rc<PoolService>,
+               signus_service: Rc<SignusService>, wallet_service: Rc<WalletService>) -> AgentCommandExecutor {
         AgentCommandExecutor {
             agent_service: agent_service,
             ledger_service: ledger_service,
             pool_service: pool_service,
+            signus_service: signus_service,
             wallet_service: wallet_service,
             out_connections: RefCell::new(HashMap::new()),
+            out_delivery_callbacks: RefCell::new(HashMap::new()),
             listeners: RefCell::new(HashMap::new()),
             listen_callbacks: RefCell::new(HashMap::new()),
             connect_callbacks: RefCell::new(HashMap::new()),
+            connect_identities: RefCell::new(HashMap::new()),
+            identity_callbacks: RefCell::new(HashMap::new()),
             send_callbacks: RefCell::new(HashMap::new()),
             close_callbacks: RefCell::new(HashMap::new()),
+            mailbox_forward_callbacks: RefCell::new(HashMap::new()),
+            mailbox_pull_callbacks: RefCell::new(HashMap::new()),
+            claim_subscribers: RefCell::new(HashMap::new()),
+            claim_request_callbacks: RefCell::new(HashMap::new()),
+            claim_issue_callbacks: RefCell::new(HashMap::new()),
+            proof_subscribers: RefCell::new(HashMap::new()),
+            proof_present_callbacks: RefCell::new(HashMap::new()),
+            proof_resolutions: RefCell::new(HashMap::new()),
+            invitation_requests: RefCell::new(HashMap::new()),
+            accept_invitation_requests: RefCell::new(HashMap::new()),
         }
     }
 
     pub fn execute(&self, agent_cmd: AgentCommand) {
         match agent_cmd {
-            AgentCommand::Connect(pool_handle, wallet_handle, sender_did, receiver_did, connect_cb, message_cb) => {
+            AgentCommand::Connect(pool_handle, wallet_handle, sender_did, receiver_did, options_json, connect_cb, message_cb, delivery_cb) => {
                 info!(target: "agent_command_executor", "Connect command received");
-                self.connect(pool_handle, wallet_handle, sender_did, receiver_did, connect_cb, message_cb)
+                self.connect(pool_handle, wallet_handle, sender_did, receiver_did, options_json, connect_cb, message_cb, delivery_cb)
             }
             AgentCommand::ResumeConnectProcess(cmd_id, res) => {
                 info!(target: "agent_command_executor", "GetInfoAck command received");
                 self.resume_connect_process(cmd_id, res);
             }
+            AgentCommand::ConnectResolveDidAck(cmd_id, res) => {
+                info!(target: "agent_command_executor", "ConnectResolveDidAck command received");
+                self.on_connect_resolve_did_ack(cmd_id, res);
+            }
+            AgentCommand::ConnectChallenge(cmd_id, did, nonce) => {
+                info!(target: "agent_command_executor", "ConnectChallenge command received");
+                self.on_connect_challenge(cmd_id, &did, &nonce);
+            }
             AgentCommand::ConnectAck(cmd_id, res) => {
                 info!(target: "agent_command_executor", "ConnectAck command received");
                 self.on_connect_ack(cmd_id, res);
             }
-            AgentCommand::Listen(wallet_handle, endpoint, listen_cb, connect_cb, message_cb) => {
+            AgentCommand::Listen(pool_handle, wallet_handle, endpoint, identities_json, policy_json, listen_cb, connect_cb, message_cb, event_cb, delivery_cb) => {
                 info!(target: "agent_command_executor", "Listen command received");
-                self.listen(wallet_handle, endpoint, listen_cb, connect_cb, message_cb);
+                self.listen(pool_handle, wallet_handle, endpoint, identities_json, policy_json, listen_cb, connect_cb, message_cb, event_cb, delivery_cb);
             }
             AgentCommand::ListenAck(cmd_id, res) => {
                 info!(target: "agent_command_executor", "ListenAck command received");
                 self.on_listen_ack(cmd_id, res);
             }
+            AgentCommand::AddIdentity(listener_handle, wallet_handle, did, cb) => {
+                info!(target: "agent_command_executor", "AddIdentity command received");
+                self.add_or_remove_identity(listener_handle, wallet_handle, did, cb, false);
+            }
+            AgentCommand::RemoveIdentity(listener_handle, wallet_handle, did, cb) => {
+                info!(target: "agent_command_executor", "RemoveIdentity command received");
+                self.add_or_remove_identity(listener_handle, wallet_handle, did, cb, true);
+            }
+            AgentCommand::IdentityAck(cmd_id, res) => {
+                info!(target: "agent_command_executor", "IdentityAck command received");
+                self.on_identity_ack(cmd_id, res);
+            }
+            AgentCommand::CheckIncomingConnection(connection) => {
+                info!(target: "agent_command_executor", "CheckIncomingConnection command received");
+                self.check_incoming_connection(connection);
+            }
+            AgentCommand::CheckIncomingConnectionGetNymAck(connection, res) => {
+                info!(target: "agent_command_executor", "CheckIncomingConnectionGetNymAck command received");
+                self.check_incoming_connection_get_nym_ack(connection, res);
+            }
             AgentCommand::ListenerOnConnect(listener_id, res) => {
                 info!(target: "agent_command_executor", "ListenerOnConnect command received");
                 self.on_client_connected(listener_id, res);
             }
+            AgentCommand::ListenerOnEvent(listener_id, event) => {
+                info!(target: "agent_command_executor", "ListenerOnEvent command received");
+                self.on_listener_event(listener_id, event);
+            }
             AgentCommand::MessageReceived(connection_id, res) => {
                 info!(target: "agent_command_executor", "ListenerOnConnect command received");
                 self.on_message_received(connection_id, res);
             }
-            AgentCommand::Send(connection_id, msg, cb) => {
+            AgentCommand::DeliveryStatus(connection_id, msg_id, res) => {
+                info!(target: "agent_command_executor", "DeliveryStatus command received");
+                self.on_delivery_status(connection_id, msg_id, res);
+            }
+            AgentCommand::Send(connection_id, msg_type, thread_id, body, sign, cb) => {
                 info!(target: "agent_command_executor", "Send command received");
-                self.send(connection_id, msg, cb)
+                self.send(connection_id, msg_type, thread_id, body, sign, cb)
             }
             AgentCommand::SendAck(cmd_id, res) => {
                 info!(target: "agent_command_executor", "SendAck command received");
@@ -178,31 +517,176 @@ impl AgentCommandExecutor {
                 info!(target: "agent_command_executor", "CloseListenerAck command received");
                 self.on_close_listener_ack(cmd_id, res);
             }
+            AgentCommand::MailboxHost(wallet_handle, did, cb) => {
+                info!(target: "agent_command_executor", "MailboxHost command received");
+                cb(self.mailbox_host(wallet_handle, &did));
+            }
+            AgentCommand::MailboxForward(connection_id, wallet_handle, receiver_did, message, cb) => {
+                info!(target: "agent_command_executor", "MailboxForward command received");
+                self.mailbox_forward(connection_id, wallet_handle, receiver_did, &message, cb);
+            }
+            AgentCommand::MailboxPull(connection_id, wallet_handle, cb) => {
+                info!(target: "agent_command_executor", "MailboxPull command received");
+                self.mailbox_pull(connection_id, wallet_handle, cb);
+            }
+            AgentCommand::MailboxRequestFailed(request_id, err) => {
+                info!(target: "agent_command_executor", "MailboxRequestFailed command received");
+                if !self.on_mailbox_request_failed(request_id, err) {
+                    error!("Can't handle MailboxRequestFailed cmd - callback not found for {}", request_id);
+                }
+            }
+            AgentCommand::ClaimSubscribe(wallet_handle, options_json, cb, event_cb) => {
+                info!(target: "agent_command_executor", "ClaimSubscribe command received");
+                cb(self.claim_subscribe(wallet_handle, options_json, event_cb));
+            }
+            AgentCommand::ClaimOffer(connection_id, claim_offer_json, claim_def_json, cb) => {
+                info!(target: "agent_command_executor", "ClaimOffer command received");
+                self.claim_offer(connection_id, claim_offer_json, claim_def_json, cb);
+            }
+            AgentCommand::ClaimOfferStoredAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ClaimOfferStoredAck command received");
+                self.on_claim_offer_stored(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ClaimRequest(wallet_handle, exchange_id, master_secret_name, cb) => {
+                info!(target: "agent_command_executor", "ClaimRequest command received");
+                self.claim_request_callbacks.borrow_mut().insert(exchange_id.clone(), cb);
+                self.claim_request(wallet_handle, &exchange_id, &master_secret_name);
+            }
+            AgentCommand::ClaimRequestCreatedAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ClaimRequestCreatedAck command received");
+                self.on_claim_request_created(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ClaimRequestSentAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ClaimRequestSentAck command received");
+                self.on_claim_request_sent(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ClaimIssue(wallet_handle, exchange_id, claim_json, revoc_reg_seq_no, user_revoc_index, cb) => {
+                info!(target: "agent_command_executor", "ClaimIssue command received");
+                self.claim_issue(wallet_handle, exchange_id, claim_json, revoc_reg_seq_no, user_revoc_index, cb);
+            }
+            AgentCommand::ClaimCreatedAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ClaimCreatedAck command received");
+                self.on_claim_created(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ClaimStoredAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ClaimStoredAck command received");
+                self.on_claim_stored(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ClaimExchanges(wallet_handle, cb) => {
+                info!(target: "agent_command_executor", "ClaimExchanges command received");
+                cb(self.claim_exchanges(wallet_handle));
+            }
+            AgentCommand::ProofSubscribe(pool_handle, wallet_handle, cb, event_cb) => {
+                info!(target: "agent_command_executor", "ProofSubscribe command received");
+                self.proof_subscribers.borrow_mut().insert(wallet_handle, (pool_handle, event_cb));
+                cb(Ok(()));
+            }
+            AgentCommand::ProofRequest(connection_id, proof_req_json, cb) => {
+                info!(target: "agent_command_executor", "ProofRequest command received");
+                self.proof_request(connection_id, proof_req_json, cb);
+            }
+            AgentCommand::ProofClaimsFoundAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ProofClaimsFoundAck command received");
+                self.on_proof_claims_found(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ProofPresent(wallet_handle, exchange_id, requested_claims_json, master_secret_name, cb) => {
+                info!(target: "agent_command_executor", "ProofPresent command received");
+                self.proof_present(wallet_handle, exchange_id, requested_claims_json, master_secret_name, cb);
+            }
+            AgentCommand::ProofLedgerReplyAck(wallet_handle, role, exchange_id, claim_uuid, record, res) => {
+                info!(target: "agent_command_executor", "ProofLedgerReplyAck command received");
+                self.on_proof_ledger_reply(wallet_handle, role, &exchange_id, claim_uuid, record, res);
+            }
+            AgentCommand::ProofCreatedAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ProofCreatedAck command received");
+                self.on_proof_created(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ProofVerifiedAck(wallet_handle, exchange_id, res) => {
+                info!(target: "agent_command_executor", "ProofVerifiedAck command received");
+                self.on_proof_verified(wallet_handle, &exchange_id, res);
+            }
+            AgentCommand::ProofExchanges(wallet_handle, cb) => {
+                info!(target: "agent_command_executor", "ProofExchanges command received");
+                cb(self.proof_exchanges(wallet_handle));
+            }
+            AgentCommand::CreateInvitation(listener_handle, wallet_handle, label, cb) => {
+                info!(target: "agent_command_executor", "CreateInvitation command received");
+                self.create_invitation(listener_handle, wallet_handle, label, cb);
+            }
+            AgentCommand::InvitationDidCreatedAck(cmd_id, res) => {
+                info!(target: "agent_command_executor", "InvitationDidCreatedAck command received");
+                self.on_invitation_did_created(cmd_id, res);
+            }
+            AgentCommand::InvitationIdentityAck(cmd_id, res) => {
+                info!(target: "agent_command_executor", "InvitationIdentityAck command received");
+                self.on_invitation_identity_added(cmd_id, res);
+            }
+            AgentCommand::AcceptInvitation(wallet_handle, invitation_json, options_json, cb, message_cb) => {
+                info!(target: "agent_command_executor", "AcceptInvitation command received");
+                self.accept_invitation(wallet_handle, invitation_json, options_json, cb, message_cb);
+            }
+            AgentCommand::AcceptInvitationDidCreatedAck(cmd_id, res) => {
+                info!(target: "agent_command_executor", "AcceptInvitationDidCreatedAck command received");
+                self.on_accept_invitation_did_created(cmd_id, res);
+            }
+            AgentCommand::AcceptInvitationConnectAck(cmd_id, res) => {
+                info!(target: "agent_command_executor", "AcceptInvitationConnectAck command received");
+                self.on_accept_invitation_connected(cmd_id, res);
+            }
         }
     }
 
     fn connect(&self, pool_handle: i32, wallet_handle: i32,
-               sender_did: String, receiver_did: String,
-               connect_cb: AgentConnectCB, message_cb: AgentMessageCB) {
-        match self.get_connection_info_local(wallet_handle, &sender_did, &receiver_did) {
+               sender_did: Option<String>, receiver_did: String, options_json: Option<String>,
+               connect_cb: AgentConnectCB, message_cb: AgentMessageCB, delivery_cb: Option<AgentDeliveryCB>) {
+        let options = match options_json {
+            Some(options_json) => match ConnectOptions::from_json(&options_json) {
+                Ok(options) => options,
+                Err(err) => return connect_cb(Err(SovrinError::CommonError(
+                    CommonError::InvalidStructure(format!("Invalid connect options json: {}", err.description())))))
+            },
+            None => ConnectOptions::default()
+        };
+
+        let (sender_did, pairwise) = match sender_did {
+            Some(sender_did) => (sender_did, None),
+            None => match pairwise::get_pairwise(&self.wallet_service, wallet_handle, &receiver_did) {
+                Ok(pairwise) => (pairwise.my_did.clone(), Some(pairwise)),
+                Err(err) => return connect_cb(Err(err))
+            }
+        };
+
+        let info = self.get_connection_info_local(wallet_handle, &sender_did, &receiver_did, options.delivery_options())
+            .map(|(my_info, info)| match pairwise {
+                // Endpoint of pairwise takes precedence over endpoint of their DID
+                Some(Pairwise { endpoint: Some(endpoint), transport_key: Some(transport_key), .. }) =>
+                    (my_info, Some(ConnectInfo {
+                        endpoint: AgentCommandExecutor::_zmq_endpoint(&endpoint),
+                        server_key: transport_key,
+                    })),
+                _ => (my_info, info)
+            });
+
+        match info {
             Ok(info) => match info {
-                (my_info, Some(info)) => self.do_connect(my_info, info, connect_cb, message_cb),
-                (my_info, None) => self.request_connection_info_from_ledger(pool_handle,
-                                                                            wallet_handle,
-                                                                            my_info,
-                                                                            receiver_did.as_str(),
-                                                                            connect_cb, message_cb),
+                (my_info, Some(info)) => self.do_connect(my_info, info, connect_cb, message_cb, delivery_cb),
+                (my_info, None) => self.request_connection_info(pool_handle,
+                                                                wallet_handle,
+                                                                my_info,
+                                                                receiver_did.as_str(),
+                                                                connect_cb, message_cb, delivery_cb),
             },
             Err(err) => connect_cb(Err(err))
         }
     }
 
     fn do_connect(&self, my_info: MyConnectInfo, info: ConnectInfo,
-                  connect_cb: AgentConnectCB, message_cb: AgentMessageCB) {
+                  connect_cb: AgentConnectCB, message_cb: AgentMessageCB, delivery_cb: Option<AgentDeliveryCB>) {
         debug!("AgentCommandExecutor::connect try to service.connect with {:?}", info);
         let result = self.agent_service
             .connect(my_info.did.as_str(), my_info.secret_key.as_str(), my_info.public_key.as_str(),
-                     info.endpoint.as_str(), info.server_key.as_str())
+                     my_info.receiver_did.as_str(), info.endpoint.as_str(), info.server_key.as_str(),
+                     my_info.delivery.clone())
             .map_err(From::from)
             .and_then(|conn_handle| {
                 match self.connect_callbacks.try_borrow_mut() {
@@ -212,24 +696,26 @@ impl AgentCommandExecutor {
             });
         match result {
             Err(err) => { connect_cb(Err(err).map_err(map_err_err!())); }
-            Ok((mut cbs, handle)) => { cbs.insert(handle, (connect_cb, message_cb)); /* TODO check if map contains same key */ }
+            Ok((mut cbs, handle)) => {
+                cbs.insert(handle, (connect_cb, message_cb, delivery_cb)); /* TODO check if map contains same key */
+                self.connect_identities.borrow_mut().insert(handle, (my_info.wallet_handle, my_info.did.clone(), my_info.receiver_did.clone()));
+            }
         };
     }
 
-    fn resume_connect_process(&self, cmd_id: i32, res: Result<(MyConnectInfo, String), SovrinError>) {
-        if let Some((connect_cb, on_msg)) = self.connect_callbacks.borrow_mut().remove(&cmd_id) {
-            let res = res.and_then(|(my_info, ddo_resp)| -> Result<(MyConnectInfo, ConnectInfo), SovrinError> {
-                let ddo_resp = DDO::from_json(ddo_resp.as_str()).map_err(|err|
-                    CommonError::InvalidStructure(
-                        format!("Can't parse get DDO response {}", err.description())))?;
+    fn resume_connect_process(&self, cmd_id: i32, res: Result<(MyConnectInfo, (String, Option<String>)), SovrinError>) {
+        if let Some((connect_cb, on_msg, on_delivery)) = self.connect_callbacks.borrow_mut().remove(&cmd_id) {
+            let res = res.and_then(|(my_info, (address, transport_key))| -> Result<(MyConnectInfo, ConnectInfo), SovrinError> {
+                let server_key = transport_key.ok_or(
+                    CommonError::InvalidState(format!("Endpoint {} doesn't contain transport key", address)))?;
                 let conn_info = ConnectInfo {
-                    endpoint: ddo_resp.endpoint,
-                    server_key: ddo_resp.verkey,
+                    endpoint: AgentCommandExecutor::_zmq_endpoint(&address),
+                    server_key: server_key,
                 };
                 Ok((my_info, conn_info))
             });
             match res {
-                Ok((my_info, conn_info)) => self.do_connect(my_info, conn_info, connect_cb, on_msg),
+                Ok((my_info, conn_info)) => self.do_connect(my_info, conn_info, connect_cb, on_msg, on_delivery),
                 Err(err) => connect_cb(Err(err))
             }
         } else {
@@ -237,15 +723,19 @@ impl AgentCommandExecutor {
         }
     }
 
-    fn get_connection_info_local(&self, wallet_handle: i32, sender_did: &String, receiver_did: &String)
+    fn get_connection_info_local(&self, wallet_handle: i32, sender_did: &String, receiver_did: &String,
+                                 delivery: Option<DeliveryOptions>)
                                  -> Result<(MyConnectInfo, Option<ConnectInfo>), SovrinError> {
         let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", sender_did))?;
         let my_did: MyDid = MyDid::from_json(&my_did_json)
             .map_err(|_| CommonError::InvalidState((format!("Invalid my did json"))))?;
         let my_connect_info = MyConnectInfo {
+            wallet_handle: wallet_handle,
             did: sender_did.clone(),
-            secret_key: my_did.sk,
-            public_key: my_did.pk,
+            receiver_did: receiver_did.clone(),
+            secret_key: my_did.sk.clone(),
+            public_key: my_did.pk.clone(),
+            delivery: delivery,
         };
 
         let their_did_json = self.wallet_service.get_not_expired(wallet_handle, &format!("their_did::{}", receiver_did));
@@ -269,31 +759,89 @@ impl AgentCommandExecutor {
         }
     }
 
-    fn request_connection_info_from_ledger(&self, pool_handle: i32, wallet_handle: i32,
-                                           my_conn_info: MyConnectInfo, receiver_did: &str,
-                                           connect_cb: AgentConnectCB, message_cb: AgentMessageCB) {
-        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service, wallet_handle, pool_handle, connect_cb);
-        let ddo_request = match self.ledger_service.build_get_ddo_request(my_conn_info.did.as_str(), receiver_did) {
-            Ok(ddo_request) => ddo_request,
-            Err(err) => {
-                return connect_cb(Err(SovrinError::from(err)));
+    fn request_connection_info(&self, pool_handle: i32, wallet_handle: i32,
+                               my_conn_info: MyConnectInfo, receiver_did: &str,
+                               connect_cb: AgentConnectCB, message_cb: AgentMessageCB, delivery_cb: Option<AgentDeliveryCB>) {
+        // Endpoint stored in the wallet is a local shortcut, otherwise DID is resolved using the ledger
+        match signus::get_stored_endpoint(&self.wallet_service, wallet_handle, receiver_did) {
+            Ok(Some(endpoint)) => {
+                let cmd_id = SequenceUtils::get_next_id();
+                self.connect_callbacks.borrow_mut().insert(cmd_id, (connect_cb, message_cb, delivery_cb));
+                return self.resume_connect_process(cmd_id, Ok((my_conn_info, (endpoint.ha, endpoint.verkey))));
             }
-        };
+            Ok(None) => {}
+            Err(err) => return connect_cb(Err(err))
+        }
+
         let cmd_id = SequenceUtils::get_next_id();
-        self.connect_callbacks.borrow_mut().insert(cmd_id, (connect_cb, message_cb));
-        CommandExecutor::instance().send(Command::Ledger(LedgerCommand::SignAndSubmitRequest(
-            pool_handle, wallet_handle, my_conn_info.did.clone(), ddo_request.to_string(),
+        self.connect_callbacks.borrow_mut().insert(cmd_id, (connect_cb, message_cb, delivery_cb));
+        CommandExecutor::instance().send(Command::Ledger(LedgerCommand::ResolveDid(
+            pool_handle, wallet_handle, receiver_did.to_string(),
             Box::new(move |res: Result<String, SovrinError>| {
-                let res = res.and_then(|ddo_resp| { Ok((my_conn_info.clone(), ddo_resp)) });
+                let res = res.and_then(|ddo_json| {
+                    let ddo = Ddo::from_json(&ddo_json)
+                        .map_err(|err| CommonError::InvalidState(format!("Can't parse resolved DDO {}", err.description())))?;
+                    let endpoint = ddo.endpoint.ok_or(
+                        CommonError::InvalidState(format!("DDO of {} doesn't contain endpoint", ddo.did)))?;
+                    Ok((my_conn_info.clone(), Endpoint::new(endpoint.address, endpoint.transport_key)))
+                });
                 CommandExecutor::instance().send(Command::Agent(
-                    AgentCommand::ResumeConnectProcess(cmd_id, res))).unwrap();
+                    AgentCommand::ConnectResolveDidAck(cmd_id, res))).unwrap();
             })))).unwrap();
     }
 
+    /// Stores endpoint of resolved receiver DID, so next connect to the DID doesn't query the ledger.
+    fn on_connect_resolve_did_ack(&self, cmd_id: i32, res: Result<(MyConnectInfo, Endpoint), SovrinError>) {
+        let res = res.map(|(my_conn_info, endpoint)| {
+            if let Err(err) = signus::store_endpoint(&self.wallet_service, my_conn_info.wallet_handle, &my_conn_info.receiver_did, &endpoint) {
+                warn!("Can't store resolved endpoint of DID {}: {}", my_conn_info.receiver_did, err);
+            }
+            (my_conn_info, (endpoint.ha, endpoint.verkey))
+        });
+        self.resume_connect_process(cmd_id, res);
+    }
+
+    /// Ledger endpoints are stored as "host:port", zmq expects "tcp://host:port".
+    fn _zmq_endpoint(address: &str) -> String {
+        if address.contains("://") {
+            address.to_string()
+        } else {
+            format!("tcp://{}", address)
+        }
+    }
+
+    fn on_connect_challenge(&self, cmd_id: i32, did: &str, nonce: &str) {
+        if let Err(err) = self._answer_challenge(cmd_id, did, nonce) {
+            self.on_connect_ack(cmd_id, Err(CommonError::InvalidState(format!("Can't answer challenge: {}", err))));
+        }
+    }
+
+    fn _answer_challenge(&self, cmd_id: i32, did: &str, nonce: &str) -> Result<(), SovrinError> {
+        let wallet_handle = self.connect_identities.borrow().get(&cmd_id)
+            .map(|&(wallet_handle, _, _)| wallet_handle)
+            .ok_or(CommonError::InvalidState(format!("Wallet for connection {} not found", cmd_id)))?;
+
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+        let my_did: MyDid = MyDid::from_json(&my_did_json)
+            .map_err(|_| CommonError::InvalidState((format!("Invalid my did json"))))?;
+
+        let signature = self.signus_service.sign_detached(&my_did, nonce.as_bytes())?;
+        self.agent_service.answer_challenge(cmd_id, did, &my_did.verkey, &Base58::encode(&signature))?;
+        Ok(())
+    }
+
     fn on_connect_ack(&self, cmd_id: i32, res: Result<i32, CommonError>) {
+        // Identities of established connection are kept to answer challenge on reconnect and sign messages
+        if res.is_err() {
+            self.connect_identities.borrow_mut().remove(&cmd_id);
+        }
         if let Some(cbs) = self.connect_callbacks.borrow_mut().remove(&cmd_id) {
             if let &Ok(conn_handle) = &res {
+                self._update_pairwise_state(conn_handle, PairwiseState::Connected);
                 self.out_connections.borrow_mut().insert(conn_handle, cbs.1); /* TODO check insert result */
+                if let Some(delivery_cb) = cbs.2 {
+                    self.out_delivery_callbacks.borrow_mut().insert(conn_handle, delivery_cb);
+                }
             }
             cbs.0(res.map_err(map_err_err!()).map_err(From::from));
         } else {
@@ -302,15 +850,40 @@ impl AgentCommandExecutor {
         }
     }
 
-    fn listen(&self, wallet_handle: i32, endpoint: String,
+    fn listen(&self, pool_handle: i32, wallet_handle: i32, endpoint: String,
+              identities_json: Option<String>, policy_json: Option<String>,
               listen_cb: Box<Fn(Result<i32, SovrinError>) + Send>,
               connect_cb: Box<Fn(Result<(i32, i32, String, String), SovrinError>) + Send>,
-              message_cb: AgentMessageCB) {
-        let my_did_json: String = self.wallet_service.list(wallet_handle, "my_did::").as_ref().unwrap().get(0).as_ref().unwrap().1.clone();
-        let my_did: MyDid = MyDid::from_json(my_did_json.as_str()).map_err(|_| CommonError::InvalidState((format!("Invalid my did json")))).unwrap();
+              message_cb: AgentMessageCB,
+              event_cb: AgentListenerEventCB,
+              delivery_cb: Option<AgentDeliveryCB>) {
+        let identities = match self._get_listener_identities(wallet_handle, identities_json) {
+            Ok(identities) => identities,
+            Err(err) => return listen_cb(Err(err))
+        };
+
+        // Listener transport keys are keys of the first identity
+        let my_did = match self._get_my_did(wallet_handle, &identities[0]) {
+            Ok(my_did) => my_did,
+            Err(err) => return listen_cb(Err(err))
+        };
+
+        let policy_info = match policy_json {
+            Some(policy_json) => match ListenerPolicyInfo::from_json(&policy_json) {
+                Ok(policy_info) => policy_info,
+                Err(err) => return listen_cb(Err(SovrinError::CommonError(
+                    CommonError::InvalidStructure(format!("Invalid listener policy json: {}", err.description())))))
+            },
+            None => ListenerPolicyInfo::Any
+        };
+
+        let policy = match self._build_listener_policy(wallet_handle, &policy_info) {
+            Ok(policy) => policy,
+            Err(err) => return listen_cb(Err(err))
+        };
 
         let result = self.agent_service
-            .listen(endpoint.as_str(), my_did.pk.as_str(), my_did.sk.as_str())
+            .listen(endpoint.as_str(), my_did.did.as_str(), my_did.pk.as_str(), my_did.sk.as_str(), &identities, policy)
             .and_then(|cmd_id| {
                 match self.listen_callbacks.try_borrow_mut() {
                     Ok(cbs) => Ok((cbs, cmd_id)),
@@ -322,14 +895,109 @@ impl AgentCommandExecutor {
             Ok((mut cbs, handle)) => {
                 cbs.insert(handle, (listen_cb,
                                     Listener {
+                                        pool_handle: pool_handle,
+                                        endpoint: endpoint,
+                                        transport_key: my_did.pk.clone(),
+                                        identities: identities.into_iter().map(|did| (did, wallet_handle)).collect(),
+                                        policy: policy_info,
                                         on_connect: connect_cb,
                                         on_msg: message_cb,
-                                        conn_handles: HashSet::new()
+                                        on_event: event_cb,
+                                        on_delivery: delivery_cb,
+                                        connections: HashMap::new()
                                     })); /* TODO check if map contains same key */
             }
         };
     }
 
+    /// Returns DIDs from identities json or all my DIDs from the wallet if it isn't set.
+    fn _get_listener_identities(&self, wallet_handle: i32, identities_json: Option<String>) -> Result<Vec<String>, SovrinError> {
+        let identities: Vec<String> = match identities_json {
+            Some(identities_json) => {
+                let identities: Vec<String> = serde_json::from_str(&identities_json)
+                    .map_err(|err| CommonError::InvalidStructure(format!("Invalid identities json: {}", err.description())))?;
+                for did in &identities {
+                    self._get_my_did(wallet_handle, did)?;
+                }
+                identities
+            }
+            None => {
+                let mut identities = Vec::new();
+                for (_, my_did_json) in self.wallet_service.list(wallet_handle, "my_did::")? {
+                    let my_did = MyDid::from_json(&my_did_json)
+                        .map_err(|_| CommonError::InvalidState((format!("Invalid my did json"))))?;
+                    identities.push(my_did.did.clone());
+                }
+                identities
+            }
+        };
+
+        if identities.is_empty() {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Listener identities are empty"))));
+        }
+        Ok(identities)
+    }
+
+    fn _get_my_did(&self, wallet_handle: i32, did: &str) -> Result<MyDid, SovrinError> {
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(|_| CommonError::InvalidState((format!("Invalid my did json"))))?;
+        Ok(my_did)
+    }
+
+    /// Resolves DIDs of listener policy to transport keys of their DIDs stored in the wallet.
+    /// Note that known DIDs are taken from the wallet at listen start.
+    fn _build_listener_policy(&self, wallet_handle: i32, policy_info: &ListenerPolicyInfo) -> Result<ListenerPolicy, SovrinError> {
+        let mut policy = ListenerPolicy::allow_any();
+        match *policy_info {
+            ListenerPolicyInfo::Any => {}
+            ListenerPolicyInfo::Known => {
+                let mut allowed = HashMap::new();
+                for (_, their_did_json) in self.wallet_service.list(wallet_handle, "their_did::")? {
+                    let their_did = TheirDid::from_json(&their_did_json)
+                        .map_err(|_| CommonError::InvalidState(format!("Invalid their did json")))?;
+                    if let Some(pk) = their_did.pk {
+                        allowed.insert(pk, their_did.did);
+                    }
+                }
+                policy.allowed = Some(allowed);
+            }
+            ListenerPolicyInfo::List { ref allow, ref deny } => {
+                if let Some(ref allow) = *allow {
+                    let mut allowed = HashMap::new();
+                    for did in allow {
+                        let pk = self._get_their_did_pk(wallet_handle, did)?
+                            .ok_or(CommonError::InvalidStructure(
+                                format!("Transport key of DID {} from allow list isn't found in wallet", did)))?;
+                        allowed.insert(pk, did.clone());
+                    }
+                    policy.allowed = Some(allowed);
+                }
+                if let Some(ref deny) = *deny {
+                    // DIDs that aren't in wallet will be rejected on DID handshake
+                    for did in deny {
+                        if let Some(pk) = self._get_their_did_pk(wallet_handle, did)? {
+                            policy.denied.insert(pk, did.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    fn _get_their_did_pk(&self, wallet_handle: i32, did: &str) -> Result<Option<String>, SovrinError> {
+        match self.wallet_service.get(wallet_handle, &format!("their_did::{}", did)) {
+            Ok(their_did_json) => {
+                let their_did = TheirDid::from_json(&their_did_json)
+                    .map_err(|_| CommonError::InvalidState(format!("Invalid their did json")))?;
+                Ok(their_did.pk)
+            }
+            Err(WalletError::NotFound(_)) => Ok(None),
+            Err(err) => Err(SovrinError::WalletError(err))
+        }
+    }
+
     fn on_listen_ack(&self, cmd_id: i32, res: Result<i32, CommonError>) {
         if let Some(cbs) = self.listen_callbacks.borrow_mut().remove(&cmd_id) {
             if let Ok(listener_handle) = res {
@@ -341,88 +1009,1902 @@ impl AgentCommandExecutor {
         }
     }
 
-    fn on_client_connected(&self, listener_id: i32, res: Result<(i32, i32, String, String), CommonError>) {
-        if let Some(mut cbs) = self.listeners.borrow_mut().get_mut(&listener_id) {
-            if let Ok((_, connection_handle, _, _)) = res {
-                cbs.conn_handles.insert(connection_handle);
-            }
-            (cbs.on_connect)(res.map_err(map_err_err!()).map_err(From::from));
-        } else {
-            error!("Can't handle ListenerOnConnect cmd - callback not found for {}", listener_id);
-        }
-    }
+    fn check_incoming_connection(&self, connection: IncomingConnection) {
+        let receiver_did = connection.receiver_did.clone();
+        let (pool_handle, wallet_handle, policy) = match self.listeners.borrow().get(&connection.listener_handle) {
+            Some(listener) => (listener.pool_handle, listener.identities.get(&receiver_did).cloned(), listener.policy.clone()),
+            None => return error!("Can't handle CheckIncomingConnection cmd - listener not found for {}", connection.listener_handle)
+        };
 
-    fn on_message_received(&self, connection_id: i32, res: Result<(i32, String), CommonError>) {
-        let listeners = self.listeners.borrow();
-        let out_connections = self.out_connections.borrow();
-        let cb = match listeners.iter().find(|&(_, listener)| listener.conn_handles.contains(&connection_id)) {
-            Some((_, listener)) => Some(&listener.on_msg),
-            None => out_connections.get(&connection_id),
+        let wallet_handle = match wallet_handle {
+            Some(wallet_handle) => wallet_handle,
+            None => return self.complete_handshake(&connection, &receiver_did,
+                                                   Some(format!("Identity {} isn't served by listener", receiver_did)))
         };
-        if let Some(cb) = cb {
-            cb(res.map_err(From::from));
-        } else {
-            error!("Can't handle MessageReceived cmd - callback not found for {}", connection_id);
+
+        if let Err(reason) = policy.check_did(&connection.did) {
+            return self.complete_handshake(&connection, &receiver_did, Some(reason));
         }
-    }
 
-    fn send(&self, conn_id: i32, msg: Option<String>, cb: Box<Fn(Result<(), SovrinError>)>) {
-        let result = self.agent_service
-            .send(conn_id, msg.as_ref().map(String::as_str))
-            .and_then(|cmd_id| {
-                match self.send_callbacks.try_borrow_mut() {
-                    Ok(cbs) => Ok((cbs, cmd_id)),
-                    Err(err) => Err(CommonError::InvalidState(err.description().to_string())),
+        match self.wallet_service.get_not_expired(wallet_handle, &format!("their_did::{}", connection.did)) {
+            Ok(their_did_json) => {
+                let their_did = TheirDid::from_json(&their_did_json)
+                    .map_err(|_| SovrinError::CommonError(CommonError::InvalidState(format!("Invalid their did json"))));
+                match their_did {
+                    Ok(ref their_did) if their_did.verkey.is_none() =>
+                        self.request_their_did(pool_handle, wallet_handle, &receiver_did, connection),
+                    their_did => self.complete_incoming_connection(connection, &receiver_did, their_did)
                 }
-            });
-        match result {
-            Ok((mut cbs, cmd_id)) => { cbs.insert(cmd_id, cb); /* TODO check if map contains same key */ }
-            Err(err) => cb(Err(From::from(err)).map_err(map_err_err!())),
+            }
+            Err(WalletError::NotFound(_)) if self._is_invitation_pending(wallet_handle, &receiver_did) =>
+                self.accept_invitee(wallet_handle, &receiver_did, connection),
+            Err(WalletError::NotFound(_)) if policy == ListenerPolicyInfo::Known =>
+                self.complete_handshake(&connection, &receiver_did, Some(format!("DID {} isn't known", connection.did))),
+            Err(WalletError::NotFound(_)) => self.request_their_did(pool_handle, wallet_handle, &receiver_did, connection),
+            Err(err) => self.complete_incoming_connection(connection, &receiver_did, Err(SovrinError::WalletError(err)))
         }
     }
 
-    fn on_send_ack(&self, cmd_id: i32, res: Result<(), CommonError>) {
-        match self.send_callbacks.borrow_mut().remove(&cmd_id) {
-            Some(cb) => cb(res.map_err(From::from)),
-            None => error!("Can't handle SendAck cmd - callback not found for {}", cmd_id),
+    fn request_their_did(&self, pool_handle: i32, wallet_handle: i32, receiver_did: &str, connection: IncomingConnection) {
+        if let Err(err) = check_wallet_and_pool_handles_consistency(self.wallet_service.clone(), self.pool_service.clone(),
+                                                                    wallet_handle, pool_handle) {
+            return self.complete_incoming_connection(connection, receiver_did, Err(err));
+        }
+
+        let get_nym_request = match self.ledger_service.build_get_nym_request(receiver_did, &connection.did) {
+            Ok(get_nym_request) => get_nym_request,
+            Err(err) => return self.complete_incoming_connection(connection, receiver_did, Err(SovrinError::CommonError(err)))
         };
-    }
 
-    fn close_connection_or_listener(&self, handle: i32, cb: Box<Fn(Result<(), SovrinError>)>, close_listener: bool) {
-        let result = self.agent_service
-            .close_connection_or_listener(handle, close_listener)
-            .and_then(|cmd_id| {
-                match self.close_callbacks.try_borrow_mut() {
-                    Ok(cbs) => Ok((cbs, cmd_id)),
-                    Err(err) => Err(CommonError::InvalidState(err.description().to_string())),
-                }
-            });
-        match result {
-            Ok((mut cbs, cmd_id)) => { cbs.insert(cmd_id, cb); /* TODO check if map contains same key */ }
-            Err(err) => cb(Err(From::from(err))),
-        }
+        CommandExecutor::instance().send(Command::Ledger(LedgerCommand::SubmitRequest(
+            pool_handle,
+            get_nym_request,
+            Box::new(move |result| {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::CheckIncomingConnectionGetNymAck(connection.clone(), result))).unwrap();
+            })))).unwrap();
     }
 
-    fn on_close_connection_ack(&self, cmd_id: i32, res: Result<(), CommonError>, ) {
-        match self.close_callbacks.borrow_mut().remove(&cmd_id) {
-            Some(cb) => cb(res.map_err(From::from)),
-            None => error!("Can't handle CloseConnectionAck cmd - not found callback for {}", cmd_id)
+    fn check_incoming_connection_get_nym_ack(&self, connection: IncomingConnection, result: Result<String, SovrinError>) {
+        let receiver_did = connection.receiver_did.clone();
+        let wallet_handle = match self.listeners.borrow().get(&connection.listener_handle) {
+            Some(listener) => listener.identities.get(&receiver_did).cloned(),
+            None => return error!("Can't handle CheckIncomingConnectionGetNymAck cmd - listener not found for {}", connection.listener_handle)
         };
-    }
 
-    fn on_close_listener_ack(&self, cmd_id: i32, res: Result<(), CommonError>, ) {
-        match self.close_callbacks.borrow_mut().remove(&cmd_id) {
-            Some(cb) => cb(res.map_err(From::from)),
-            None => error!("Can't handle CloseListenerAck cmd - not found callback for {}", cmd_id)
+        let wallet_handle = match wallet_handle {
+            Some(wallet_handle) => wallet_handle,
+            None => return self.complete_handshake(&connection, &receiver_did,
+                                                   Some(format!("Identity {} isn't served by listener", receiver_did)))
         };
-    }
-}
 
-#[derive(Debug, Clone)]
-pub struct MyConnectInfo {
-    did: String,
+        let their_did = result.and_then(|get_nym_response|
+            signus::their_did_from_get_nym_reply(&self.ledger_service, &self.signus_service, &get_nym_response));
+
+        let their_did = match their_did {
+            Ok(their_did) => their_did,
+            Err(err) => return self.complete_incoming_connection(connection, &receiver_did, Err(err))
+        };
+
+        // Resolved DID is stored only after verification, so rejected handshake leaves the wallet untouched
+        let reject_reason = AgentCommandExecutor::_verify_incoming_connection(&self.signus_service, &connection, &their_did)
+            .and_then(|()| signus::store_their_did(&self.wallet_service, wallet_handle, &their_did)
+                .map_err(|err| format!("Can't store DID {}: {}", their_did.did, err)))
+            .err();
+        self.complete_handshake(&connection, &receiver_did, reject_reason);
+    }
+
+    fn complete_incoming_connection(&self, connection: IncomingConnection, receiver_did: &str,
+                                    their_did: Result<TheirDid, SovrinError>) {
+        let reject_reason = match their_did {
+            Ok(their_did) => AgentCommandExecutor::_verify_incoming_connection(&self.signus_service, &connection, &their_did).err(),
+            Err(err) => Some(format!("Can't resolve DID {}: {}", connection.did, err))
+        };
+        self.complete_handshake(&connection, receiver_did, reject_reason);
+    }
+
+    fn complete_handshake(&self, connection: &IncomingConnection, receiver_did: &str, reject_reason: Option<String>) {
+        if let Err(err) = self.agent_service.complete_handshake(connection.listener_handle, &connection.identity,
+                                                                &connection.client_key, &connection.did, receiver_did,
+                                                                connection.reliable, reject_reason.as_ref().map(String::as_str)) {
+            error!("Can't complete handshake of incoming connection from {}: {}", connection.did, err);
+        }
+    }
+
+    /// Checks that challenge nonce is signed by DID verkey
+    /// and that CurveZMQ key of the connection is the key of this DID.
+    fn _verify_incoming_connection(signus_service: &SignusService, connection: &IncomingConnection,
+                                   their_did: &TheirDid) -> Result<(), String> {
+        let signature = Base58::decode(&connection.signature)
+            .map_err(|_| format!("Invalid challenge signature"))?;
+
+        let valid = signus_service.verify_detached(their_did, connection.nonce.as_bytes(), &signature)
+            .map_err(|err| format!("Can't verify challenge signature: {}", err))?;
+        if !valid {
+            return Err(format!("Challenge signature doesn't match verkey of DID {}", their_did.did));
+        }
+
+        if their_did.pk.as_ref() != Some(&connection.client_key) {
+            return Err(format!("Transport key doesn't match DID {}", their_did.did));
+        }
+
+        Ok(())
+    }
+
+    fn add_or_remove_identity(&self, listener_handle: i32, wallet_handle: i32, did: String,
+                              cb: Box<Fn(Result<(), SovrinError>) + Send>, remove: bool) {
+        if !self.listeners.borrow().contains_key(&listener_handle) {
+            return cb(Err(SovrinError::CommonError(
+                CommonError::InvalidStructure(format!("Listener with id {} not found", listener_handle)))));
+        }
+
+        if !remove {
+            if let Err(err) = self._get_my_did(wallet_handle, &did) {
+                return cb(Err(err));
+            }
+        }
+
+        match self.agent_service.add_or_remove_identity(listener_handle, &did, remove) {
+            Ok(cmd_id) => {
+                let wallet_handle = if remove { None } else { Some(wallet_handle) };
+                self.identity_callbacks.borrow_mut().insert(cmd_id, (cb, listener_handle, did, wallet_handle));
+            }
+            Err(err) => cb(Err(From::from(err)))
+        }
+    }
+
+    fn on_identity_ack(&self, cmd_id: i32, res: Result<(), CommonError>) {
+        match self.identity_callbacks.borrow_mut().remove(&cmd_id) {
+            Some((cb, listener_handle, did, wallet_handle)) => {
+                if res.is_ok() {
+                    if let Some(listener) = self.listeners.borrow_mut().get_mut(&listener_handle) {
+                        match wallet_handle {
+                            Some(wallet_handle) => { listener.identities.insert(did, wallet_handle); }
+                            None => { listener.identities.remove(&did); }
+                        }
+                    }
+                }
+                cb(res.map_err(From::from))
+            }
+            None => error!("Can't handle IdentityAck cmd - not found callback for {}", cmd_id)
+        }
+    }
+
+    fn on_client_connected(&self, listener_id: i32, res: Result<(i32, i32, String, String), CommonError>) {
+        if let Some(mut cbs) = self.listeners.borrow_mut().get_mut(&listener_id) {
+            if let Ok((_, connection_handle, ref sender_did, ref receiver_did)) = res {
+                cbs.connections.insert(connection_handle, (sender_did.clone(), receiver_did.clone()));
+            }
+        }
+        if let Ok((_, connection_handle, _, _)) = res {
+            self._update_pairwise_state(connection_handle, PairwiseState::Connected);
+        }
+        if let Some(cbs) = self.listeners.borrow().get(&listener_id) {
+            (cbs.on_connect)(res.map_err(map_err_err!()).map_err(From::from));
+        } else {
+            error!("Can't handle ListenerOnConnect cmd - callback not found for {}", listener_id);
+        }
+    }
+
+    fn on_listener_event(&self, listener_id: i32, event: ListenerEvent) {
+        if let Some(listener) = self.listeners.borrow().get(&listener_id) {
+            let res = event.to_json()
+                .map(|event_json| (listener_id, event_json))
+                .map_err(|err| SovrinError::CommonError(
+                    CommonError::InvalidState(format!("Can't serialize ListenerEvent: {}", err.description()))));
+            (listener.on_event)(res);
+        } else {
+            error!("Can't handle ListenerOnEvent cmd - listener not found for {}", listener_id);
+        }
+    }
+
+    fn on_message_received(&self, connection_id: i32, res: Result<(i32, AgentEnvelope), CommonError>) {
+        if let Ok((_, ref envelope)) = res {
+            match envelope.msg_type.as_str() {
+                MAILBOX_TYPE => {
+                    match envelope.parse_body() {
+                        Ok(mailbox_msg) => if !self.on_mailbox_msg(connection_id, mailbox_msg) {
+                            warn!("Unexpected mailbox message {} on connection {} dropped", envelope.id, connection_id);
+                        },
+                        Err(err) => warn!("Invalid mailbox message {} on connection {} dropped: {}", envelope.id, connection_id, err)
+                    }
+                    return;
+                }
+                CLAIM_TYPE => {
+                    match envelope.parse_body() {
+                        Ok(claim_msg) => self.on_claim_msg(connection_id, claim_msg),
+                        Err(err) => warn!("Invalid claim message {} on connection {} dropped: {}", envelope.id, connection_id, err)
+                    }
+                    return;
+                }
+                PROOF_TYPE => {
+                    match envelope.parse_body() {
+                        Ok(proof_msg) => self.on_proof_msg(connection_id, proof_msg),
+                        Err(err) => warn!("Invalid proof message {} on connection {} dropped: {}", envelope.id, connection_id, err)
+                    }
+                    return;
+                }
+                INVITATION_TYPE => {
+                    match envelope.parse_body() {
+                        Ok(invitation_msg) => self.on_invitation_msg(connection_id, invitation_msg),
+                        Err(err) => warn!("Invalid invitation message {} on connection {} dropped: {}", envelope.id, connection_id, err)
+                    }
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let res = res.map_err(From::from).and_then(|(conn_handle, envelope)| {
+            self._verify_envelope(connection_id, &envelope)?;
+            let envelope_json = envelope.to_data()?;
+            Ok((conn_handle, envelope_json))
+        });
+
+        let listeners = self.listeners.borrow();
+        let out_connections = self.out_connections.borrow();
+        let cb = match listeners.iter().find(|&(_, listener)| listener.connections.contains_key(&connection_id)) {
+            Some((_, listener)) => Some(&listener.on_msg),
+            None => out_connections.get(&connection_id),
+        };
+        if let Some(cb) = cb {
+            cb(res);
+        } else {
+            error!("Can't handle MessageReceived cmd - callback not found for {}", connection_id);
+        }
+    }
+
+    fn on_delivery_status(&self, connection_id: i32, msg_id: i32, res: Result<(), CommonError>) {
+        let listeners = self.listeners.borrow();
+        let out_delivery_callbacks = self.out_delivery_callbacks.borrow();
+        let cb = match listeners.iter().find(|&(_, listener)| listener.connections.contains_key(&connection_id)) {
+            Some((_, listener)) => listener.on_delivery.as_ref(),
+            None => out_delivery_callbacks.get(&connection_id),
+        };
+        match cb {
+            Some(cb) => cb(connection_id, msg_id, res.map_err(From::from)),
+            None => debug!("DeliveryStatus cmd isn't reported - callback not set for {}", connection_id)
+        }
+    }
+
+    fn send(&self, conn_id: i32, msg_type: String, thread_id: Option<String>, body: Vec<u8>, sign: bool,
+            cb: Box<Fn(Result<i32, SovrinError>)>) {
+        if AgentEnvelope::is_control_type(&msg_type) {
+            return cb(Err(SovrinError::CommonError(
+                CommonError::InvalidStructure(format!("Message type {} is reserved for control messages", msg_type)))));
+        }
+
+        let mut envelope = AgentEnvelope::new(&msg_type, thread_id.as_ref().map(String::as_str), &body);
+        if sign {
+            match self._sign_envelope(conn_id, &envelope) {
+                Ok(signature) => envelope.signature = Some(signature),
+                Err(err) => return cb(Err(err))
+            }
+        }
+        self._send_envelope(conn_id, &envelope, cb)
+    }
+
+    fn _send_envelope(&self, conn_id: i32, envelope: &AgentEnvelope, cb: Box<Fn(Result<i32, SovrinError>)>) {
+        let result = self.agent_service
+            .send(conn_id, envelope)
+            .and_then(|cmd_id| {
+                match self.send_callbacks.try_borrow_mut() {
+                    Ok(cbs) => Ok((cbs, cmd_id)),
+                    Err(err) => Err(CommonError::InvalidState(err.description().to_string())),
+                }
+            });
+        match result {
+            Ok((mut cbs, cmd_id)) => { cbs.insert(cmd_id, cb); /* TODO check if map contains same key */ }
+            Err(err) => cb(Err(From::from(err)).map_err(map_err_err!())),
+        }
+    }
+
+    fn on_send_ack(&self, cmd_id: i32, res: Result<i32, CommonError>) {
+        match self.send_callbacks.borrow_mut().remove(&cmd_id) {
+            Some(cb) => cb(res.map_err(From::from)),
+            None => error!("Can't handle SendAck cmd - callback not found for {}", cmd_id),
+        };
+    }
+
+    fn close_connection_or_listener(&self, handle: i32, cb: Box<Fn(Result<(), SovrinError>)>, close_listener: bool) {
+        if !close_listener {
+            self._update_pairwise_state(handle, PairwiseState::Disconnected);
+            self.connect_identities.borrow_mut().remove(&handle);
+        }
+        let result = self.agent_service
+            .close_connection_or_listener(handle, close_listener)
+            .and_then(|cmd_id| {
+                match self.close_callbacks.try_borrow_mut() {
+                    Ok(cbs) => Ok((cbs, cmd_id)),
+                    Err(err) => Err(CommonError::InvalidState(err.description().to_string())),
+                }
+            });
+        match result {
+            Ok((mut cbs, cmd_id)) => { cbs.insert(cmd_id, cb); /* TODO check if map contains same key */ }
+            Err(err) => cb(Err(From::from(err))),
+        }
+    }
+
+    fn on_close_connection_ack(&self, cmd_id: i32, res: Result<(), CommonError>, ) {
+        match self.close_callbacks.borrow_mut().remove(&cmd_id) {
+            Some(cb) => cb(res.map_err(From::from)),
+            None => error!("Can't handle CloseConnectionAck cmd - not found callback for {}", cmd_id)
+        };
+    }
+
+    fn on_close_listener_ack(&self, cmd_id: i32, res: Result<(), CommonError>, ) {
+        match self.close_callbacks.borrow_mut().remove(&cmd_id) {
+            Some(cb) => cb(res.map_err(From::from)),
+            None => error!("Can't handle CloseListenerAck cmd - not found callback for {}", cmd_id)
+        };
+    }
+
+    /// Starts hosting mailbox of DID in relay wallet. Messages of already hosted mailbox are kept.
+    fn mailbox_host(&self, wallet_handle: i32, did: &str) -> Result<(), SovrinError> {
+        match self.wallet_service.get(wallet_handle, &format!("mailbox::{}", did)) {
+            Ok(_) => return Ok(()),
+            Err(WalletError::NotFound(_)) => {}
+            Err(err) => return Err(SovrinError::WalletError(err))
+        }
+
+        let mailbox_json = MailboxInfo::new(did.to_string()).to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize MailboxInfo: {}", err.description())))?;
+        self.wallet_service.set(wallet_handle, &format!("mailbox::{}", did), &mailbox_json)?;
+        Ok(())
+    }
+
+    fn mailbox_forward(&self, connection_id: i32, wallet_handle: i32, receiver_did: String, message: &str,
+                       cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        // Message is encrypted for receiver, so relay can't read it
+        let message = match self._get_their_did(wallet_handle, &receiver_did)
+            .and_then(|their_did| self.signus_service.encrypt_sealed(&their_did, message).map_err(From::from)) {
+            Ok(message) => message,
+            Err(err) => return cb(Err(err))
+        };
+
+        let request_id = SequenceUtils::get_next_id();
+        self.mailbox_forward_callbacks.borrow_mut().insert(request_id, cb);
+        self._send_mailbox_request(connection_id, request_id, &MailboxMsg::Forward {
+            id: request_id,
+            did: receiver_did,
+            message: message,
+        });
+    }
+
+    fn mailbox_pull(&self, connection_id: i32, wallet_handle: i32, cb: AgentMailboxPullCB) {
+        let request_id = SequenceUtils::get_next_id();
+        self.mailbox_pull_callbacks.borrow_mut().insert(request_id, (wallet_handle, cb));
+        self._send_mailbox_request(connection_id, request_id, &MailboxMsg::Pull { id: request_id });
+    }
+
+    /// Handles mailbox message received by connection. Returns false if message isn't expected
+    /// by the connection.
+    fn on_mailbox_msg(&self, connection_id: i32, msg: MailboxMsg) -> bool {
+        match msg {
+            MailboxMsg::Forward { id, did, message } => {
+                let (wallet_handle, sender_did) = match self._get_incoming_connection(connection_id) {
+                    Some(connection) => connection,
+                    None => return false
+                };
+                let res = wallet_handle
+                    .and_then(|wallet_handle| self._store_mailbox_message(wallet_handle, &did, &sender_did, message))
+                    .map(|()| MailboxMsg::Stored { id: id });
+                self._send_mailbox_reply(connection_id, id, res);
+                true
+            }
+            MailboxMsg::Pull { id } => {
+                let (wallet_handle, sender_did) = match self._get_incoming_connection(connection_id) {
+                    Some(connection) => connection,
+                    None => return false
+                };
+                let res = wallet_handle
+                    .and_then(|wallet_handle| self._get_mailbox_messages(wallet_handle, &sender_did))
+                    .map(|messages| MailboxMsg::Messages { id: id, did: sender_did.clone(), messages: messages });
+                self._send_mailbox_reply(connection_id, id, res);
+                true
+            }
+            MailboxMsg::Ack { ids } => {
+                let (wallet_handle, sender_did) = match self._get_incoming_connection(connection_id) {
+                    Some(connection) => connection,
+                    None => return false
+                };
+                let res = wallet_handle
+                    .and_then(|wallet_handle| self._delete_mailbox_messages(wallet_handle, &sender_did, &ids));
+                if let Err(err) = res {
+                    warn!("Can't remove acknowledged mailbox messages of DID {}: {}", sender_did, err);
+                }
+                true
+            }
+            MailboxMsg::Stored { id } => {
+                match self.mailbox_forward_callbacks.borrow_mut().remove(&id) {
+                    Some(cb) => {
+                        cb(Ok(()));
+                        true
+                    }
+                    None => false
+                }
+            }
+            MailboxMsg::Messages { id, did, messages } => {
+                match self.mailbox_pull_callbacks.borrow_mut().remove(&id) {
+                    Some((wallet_handle, cb)) => {
+                        let ids: Vec<i32> = messages.iter().map(|mailbox_message| mailbox_message.id).collect();
+                        let res = self._decrypt_mailbox_messages(wallet_handle, &did, messages);
+                        // Messages of failed pull are kept by relay, so they are returned by the next pull
+                        if res.is_ok() && !ids.is_empty() {
+                            self._send_mailbox_msg(connection_id, &MailboxMsg::Ack { ids: ids }, Box::new(move |res| {
+                                if let Err(err) = res {
+                                    warn!("Can't acknowledge pulled mailbox messages on connection {}: {}", connection_id, err);
+                                }
+                            }));
+                        }
+                        cb(res);
+                        true
+                    }
+                    None => false
+                }
+            }
+            MailboxMsg::Failed { id, reason } => {
+                self.on_mailbox_request_failed(id, SovrinError::CommonError(
+                    CommonError::InvalidState(format!("Mailbox request is failed by relay: {}", reason))))
+            }
+        }
+    }
+
+    fn on_mailbox_request_failed(&self, request_id: i32, err: SovrinError) -> bool {
+        if let Some(cb) = self.mailbox_forward_callbacks.borrow_mut().remove(&request_id) {
+            cb(Err(err));
+            return true;
+        }
+        if let Some((_, cb)) = self.mailbox_pull_callbacks.borrow_mut().remove(&request_id) {
+            cb(Err(err));
+            return true;
+        }
+        false
+    }
+
+    /// Returns wallet handle of receiver identity and sender DID of incoming connection
+    /// or None if connection isn't incoming.
+    fn _get_incoming_connection(&self, connection_id: i32) -> Option<(Result<i32, SovrinError>, String)> {
+        self.listeners.borrow().values()
+            .filter_map(|listener| listener.connections.get(&connection_id).map(|&(ref sender_did, ref receiver_did)| {
+                let wallet_handle = listener.identities.get(receiver_did).cloned()
+                    .ok_or(SovrinError::CommonError(
+                        CommonError::InvalidState(format!("Identity {} isn't served by listener", receiver_did))));
+                (wallet_handle, sender_did.clone())
+            }))
+            .next()
+    }
+
+    /// Keeps state of pairwise of connection DIDs (if it is created) up to date.
+    fn _update_pairwise_state(&self, connection_id: i32, state: PairwiseState) {
+        let res = self._get_connection_identities(connection_id).and_then(|(wallet_handle, my_did, their_did)| {
+            match pairwise::get_pairwise(&self.wallet_service, wallet_handle, &their_did) {
+                Ok(mut pairwise) => {
+                    if pairwise.my_did != my_did || pairwise.state == state {
+                        return Ok(());
+                    }
+                    pairwise.state = state;
+                    pairwise::store_pairwise(&self.wallet_service, wallet_handle, &pairwise)
+                }
+                Err(SovrinError::WalletError(WalletError::NotFound(_))) => Ok(()),
+                Err(err) => Err(err)
+            }
+        });
+        if let Err(err) = res {
+            warn!("Can't update pairwise state of connection {}: {}", connection_id, err);
+        }
+    }
+
+    /// Returns wallet handle, my DID and their DID of incoming or outgoing connection.
+    fn _get_connection_identities(&self, connection_id: i32) -> Result<(i32, String, String), SovrinError> {
+        for listener in self.listeners.borrow().values() {
+            if let Some(&(ref sender_did, ref receiver_did)) = listener.connections.get(&connection_id) {
+                let wallet_handle = listener.identities.get(receiver_did).cloned()
+                    .ok_or(CommonError::InvalidState(format!("Identity {} isn't served by listener", receiver_did)))?;
+                return Ok((wallet_handle, receiver_did.clone(), sender_did.clone()));
+            }
+        }
+        self.connect_identities.borrow().get(&connection_id).cloned()
+            .ok_or(SovrinError::CommonError(CommonError::InvalidStructure(format!("Connection with id {} not found", connection_id))))
+    }
+
+    fn _sign_envelope(&self, connection_id: i32, envelope: &AgentEnvelope) -> Result<String, SovrinError> {
+        let (wallet_handle, my_did, _) = self._get_connection_identities(connection_id)?;
+        let my_did = self._get_my_did(wallet_handle, &my_did)?;
+        let signature = self.signus_service.sign_detached(&my_did, envelope.signing_input().as_bytes())?;
+        Ok(Base58::encode(&signature))
+    }
+
+    /// Verifies signature of signed envelope by verkey of peer DID of the connection.
+    fn _verify_envelope(&self, connection_id: i32, envelope: &AgentEnvelope) -> Result<(), SovrinError> {
+        let signature = match envelope.signature {
+            Some(ref signature) => Base58::decode(signature)?,
+            None => return Ok(())
+        };
+        let (wallet_handle, _, their_did) = self._get_connection_identities(connection_id)?;
+        let their_did = self._get_their_did(wallet_handle, &their_did)?;
+        if !self.signus_service.verify_detached(&their_did, envelope.signing_input().as_bytes(), &signature)? {
+            return Err(SovrinError::CommonError(
+                CommonError::InvalidStructure(format!("Signature of message {} doesn't match verkey of DID {}", envelope.id, their_did.did))));
+        }
+        Ok(())
+    }
+
+    fn _get_mailbox(&self, wallet_handle: i32, did: &str) -> Result<MailboxInfo, SovrinError> {
+        match self.wallet_service.get(wallet_handle, &format!("mailbox::{}", did)) {
+            Ok(mailbox_json) => MailboxInfo::from_json(&mailbox_json)
+                .map_err(|_| SovrinError::CommonError(CommonError::InvalidState(format!("Invalid mailbox json")))),
+            Err(WalletError::NotFound(_)) =>
+                Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Mailbox of DID {} isn't hosted", did)))),
+            Err(err) => Err(SovrinError::WalletError(err))
+        }
+    }
+
+    fn _store_mailbox_message(&self, wallet_handle: i32, did: &str, sender_did: &str, message: String) -> Result<(), SovrinError> {
+        let mut mailbox = self._get_mailbox(wallet_handle, did)?;
+
+        let mailbox_message = MailboxMessage {
+            id: mailbox.next_id,
+            sender_did: sender_did.to_string(),
+            message: message,
+        };
+        let mailbox_message_json = mailbox_message.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize MailboxMessage: {}", err.description())))?;
+        self.wallet_service.set(wallet_handle, &format!("mailbox_message::{}::{}", did, mailbox_message.id), &mailbox_message_json)?;
+
+        mailbox.next_id += 1;
+        let mailbox_json = mailbox.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize MailboxInfo: {}", err.description())))?;
+        self.wallet_service.set(wallet_handle, &format!("mailbox::{}", did), &mailbox_json)?;
+        Ok(())
+    }
+
+    /// Returns all stored messages of hosted DID in order of receiving. Messages are kept
+    /// in the wallet until puller acknowledges them.
+    fn _get_mailbox_messages(&self, wallet_handle: i32, did: &str) -> Result<Vec<MailboxMessage>, SovrinError> {
+        self._get_mailbox(wallet_handle, did)?;
+
+        let mut messages = Vec::new();
+        for (_, mailbox_message_json) in self.wallet_service.list(wallet_handle, &format!("mailbox_message::{}::", did))? {
+            let mailbox_message = MailboxMessage::from_json(&mailbox_message_json)
+                .map_err(|_| CommonError::InvalidState(format!("Invalid mailbox message json")))?;
+            messages.push(mailbox_message);
+        }
+
+        messages.sort_by_key(|mailbox_message| mailbox_message.id);
+        Ok(messages)
+    }
+
+    /// Removes acknowledged messages of hosted DID. Messages that are already removed are skipped.
+    fn _delete_mailbox_messages(&self, wallet_handle: i32, did: &str, ids: &[i32]) -> Result<(), SovrinError> {
+        for id in ids {
+            match self.wallet_service.delete_record(wallet_handle, &format!("mailbox_message::{}::{}", did, id)) {
+                Ok(()) | Err(WalletError::NotFound(_)) => {}
+                Err(err) => return Err(SovrinError::WalletError(err))
+            }
+        }
+        Ok(())
+    }
+
+    fn _decrypt_mailbox_messages(&self, wallet_handle: i32, did: &str, messages: Vec<MailboxMessage>) -> Result<String, SovrinError> {
+        let my_did = self._get_my_did(wallet_handle, did)?;
+
+        let mut decrypted_messages = Vec::new();
+        for mut mailbox_message in messages {
+            mailbox_message.message = self.signus_service.decrypt_sealed(&my_did, &mailbox_message.message)?;
+            decrypted_messages.push(mailbox_message);
+        }
+
+        serde_json::to_string(&decrypted_messages)
+            .map_err(|err| SovrinError::CommonError(
+                CommonError::InvalidState(format!("Can't serialize mailbox messages: {}", err.description()))))
+    }
+
+    fn _get_their_did(&self, wallet_handle: i32, did: &str) -> Result<TheirDid, SovrinError> {
+        let their_did_json = self.wallet_service.get(wallet_handle, &format!("their_did::{}", did))?;
+        let their_did = TheirDid::from_json(&their_did_json)
+            .map_err(|_| CommonError::InvalidState(format!("Invalid their did json")))?;
+        Ok(their_did)
+    }
+
+    /// Sends mailbox request to relay, callback of the request is failed if request can't be sent.
+    fn _send_mailbox_request(&self, connection_id: i32, request_id: i32, msg: &MailboxMsg) {
+        self._send_mailbox_msg(connection_id, msg, Box::new(move |res| {
+            if let Err(err) = res {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::MailboxRequestFailed(request_id, err))).unwrap();
+            }
+        }));
+    }
+
+    fn _send_mailbox_reply(&self, connection_id: i32, request_id: i32, res: Result<MailboxMsg, SovrinError>) {
+        let reply = res.unwrap_or_else(|err| MailboxMsg::Failed { id: request_id, reason: format!("{}", err) });
+        self._send_mailbox_msg(connection_id, &reply, Box::new(move |res| {
+            if let Err(err) = res {
+                error!("Can't send mailbox reply to connection {}: {}", connection_id, err);
+            }
+        }));
+    }
+
+    fn _send_mailbox_msg(&self, connection_id: i32, msg: &MailboxMsg, cb: Box<Fn(Result<i32, SovrinError>)>) {
+        self._send_control_msg(connection_id, MAILBOX_TYPE, msg, cb)
+    }
+
+    fn _send_control_msg<T: JsonEncodable>(&self, connection_id: i32, msg_type: &str, msg: &T, cb: Box<Fn(Result<i32, SovrinError>)>) {
+        match msg.to_json() {
+            Ok(msg_json) => self._send_envelope(connection_id, &AgentEnvelope::new(msg_type, None, msg_json.as_bytes()), cb),
+            Err(err) => cb(Err(SovrinError::CommonError(
+                CommonError::InvalidState(format!("Can't serialize {} message: {}", msg_type, err.description())))))
+        }
+    }
+
+    fn claim_subscribe(&self, wallet_handle: i32, options_json: Option<String>, event_cb: AgentClaimEventCB) -> Result<(), SovrinError> {
+        let options = match options_json {
+            Some(options_json) => ClaimExchangeOptions::from_json(&options_json)
+                .map_err(|err| CommonError::InvalidStructure(format!("Invalid claim exchange options json: {}", err.description())))?,
+            None => ClaimExchangeOptions::default()
+        };
+        self.claim_subscribers.borrow_mut().insert(wallet_handle, (options, event_cb));
+        Ok(())
+    }
+
+    fn claim_offer(&self, connection_id: i32, claim_offer_json: String, claim_def_json: String,
+                   cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        let exchange = match self._create_issuer_claim_exchange(connection_id, claim_offer_json) {
+            Ok(exchange) => exchange,
+            Err(err) => return cb(Err(err))
+        };
+
+        let exchange_id = exchange.id.clone();
+        self._send_control_msg(connection_id, CLAIM_TYPE, &ClaimMsg::Offer {
+            exchange_id: exchange.id,
+            claim_offer: exchange.claim_offer,
+            claim_def: claim_def_json,
+        }, Box::new(move |res| cb(res.map(|_| exchange_id.clone()))));
+    }
+
+    fn _create_issuer_claim_exchange(&self, connection_id: i32, claim_offer_json: String) -> Result<ClaimExchange, SovrinError> {
+        let (wallet_handle, my_did, their_did) = self._get_connection_identities(connection_id)?;
+
+        let claim_offer = ClaimOffer::from_json(&claim_offer_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid claim_offer_json: {}", err.description())))?;
+        if claim_offer.issuer_did != my_did {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Issuer DID {} of claim offer doesn't match DID {} of connection", claim_offer.issuer_did, my_did))));
+        }
+
+        let exchange = ClaimExchange::new(Uuid::new_v4().to_string(), ClaimExchangeRole::Issuer, ClaimExchangeState::Offered,
+                                          my_did, their_did, claim_offer_json, None);
+        self._set_claim_exchange(wallet_handle, &exchange)?;
+        Ok(exchange)
+    }
+
+    /// Handles message of claim issuance protocol received by connection.
+    /// Problem is sent back if message doesn't match state of the exchange.
+    fn on_claim_msg(&self, connection_id: i32, msg: ClaimMsg) {
+        let (wallet_handle, my_did, their_did) = match self._get_connection_identities(connection_id) {
+            Ok(identities) => identities,
+            Err(err) => return error!("Claim message on connection {} dropped: {}", connection_id, err)
+        };
+
+        let (exchange_id, res) = match msg {
+            ClaimMsg::Offer { exchange_id, claim_offer, claim_def } => {
+                let res = self._on_claim_offer_received(wallet_handle, &exchange_id, my_did, their_did, claim_offer, claim_def);
+                (exchange_id, res)
+            }
+            ClaimMsg::Request { exchange_id, claim_req } => {
+                let res = self._on_claim_request_received(wallet_handle, &exchange_id, &their_did, claim_req);
+                (exchange_id, res)
+            }
+            ClaimMsg::Claim { exchange_id, claim } => {
+                let res = self._on_claim_received(wallet_handle, &exchange_id, &their_did, claim);
+                (exchange_id, res)
+            }
+            ClaimMsg::Problem { exchange_id, reason } => {
+                return self._on_claim_problem_received(wallet_handle, &exchange_id, &their_did, reason);
+            }
+        };
+
+        if let Err(err) = res {
+            warn!("Claim exchange {} on connection {} can't be continued: {}", exchange_id, connection_id, err);
+            self._send_claim_problem(connection_id, &exchange_id, &format!("{}", err));
+        }
+    }
+
+    fn _on_claim_offer_received(&self, wallet_handle: i32, exchange_id: &str, my_did: String, their_did: String,
+                                claim_offer_json: String, claim_def_json: String) -> Result<(), SovrinError> {
+        let claim_offer = ClaimOffer::from_json(&claim_offer_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid claim offer: {}", err.description())))?;
+        if claim_offer.issuer_did != their_did {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Claim offer is issued by {}, but received from {}", claim_offer.issuer_did, their_did))));
+        }
+        if self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id).is_ok() {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Claim exchange {} already exists", exchange_id))));
+        }
+
+        let exchange = ClaimExchange::new(exchange_id.to_string(), ClaimExchangeRole::Prover, ClaimExchangeState::OfferReceived,
+                                          my_did, their_did, claim_offer_json.clone(), Some(claim_def_json));
+        self._set_claim_exchange(wallet_handle, &exchange)?;
+
+        let exchange_id = exchange_id.to_string();
+        CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Prover(ProverCommand::StoreClaimOffer(
+            wallet_handle,
+            claim_offer_json,
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::ClaimOfferStoredAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+            })))))?;
+        Ok(())
+    }
+
+    fn on_claim_offer_stored(&self, wallet_handle: i32, exchange_id: &str, res: Result<(), SovrinError>) {
+        let exchange = match self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id) {
+            Ok(exchange) => exchange,
+            Err(err) => return error!("Can't handle ClaimOfferStoredAck cmd: {}", err)
+        };
+        if let Err(err) = res {
+            return self._fail_claim_exchange(wallet_handle, exchange, format!("Claim offer can't be stored: {}", err), true);
+        }
+
+        self._emit_claim_event(wallet_handle, ClaimExchangeEvent::OfferReceived {
+            exchange_id: exchange.id.clone(),
+            claim_offer: exchange.claim_offer.clone(),
+        });
+
+        let master_secret_name = self.claim_subscribers.borrow().get(&wallet_handle)
+            .and_then(|&(ref options, _)| options.master_secret_name.clone());
+        if let Some(master_secret_name) = master_secret_name {
+            self.claim_request(wallet_handle, exchange_id, &master_secret_name);
+        }
+    }
+
+    /// Creates claim request for received offer. Result is reported to request callback
+    /// if request is made by user or to event callback if it is made automatically.
+    fn claim_request(&self, wallet_handle: i32, exchange_id: &str, master_secret_name: &str) {
+        if let Err(err) = self._create_claim_request(wallet_handle, exchange_id, master_secret_name) {
+            self.on_claim_request_sent(wallet_handle, exchange_id, Err(err));
+        }
+    }
+
+    fn _create_claim_request(&self, wallet_handle: i32, exchange_id: &str, master_secret_name: &str) -> Result<(), SovrinError> {
+        let exchange = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id)?;
+        if exchange.state != ClaimExchangeState::OfferReceived {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Claim exchange {} is in {:?} state", exchange_id, exchange.state))));
+        }
+        let claim_def_json = exchange.claim_def.clone()
+            .ok_or(CommonError::InvalidState(format!("Claim exchange {} doesn't contain claim definition", exchange_id)))?;
+
+        let exchange_id = exchange_id.to_string();
+        CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Prover(ProverCommand::CreateAndStoreClaimRequest(
+            wallet_handle,
+            exchange.my_did,
+            exchange.claim_offer,
+            claim_def_json,
+            master_secret_name.to_string(),
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::ClaimRequestCreatedAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+            })))))?;
+        Ok(())
+    }
+
+    fn on_claim_request_created(&self, wallet_handle: i32, exchange_id: &str, res: Result<String, SovrinError>) {
+        let res = res.and_then(|claim_req_json| {
+            let mut exchange = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id)?;
+            let connection_id = self._find_connection(wallet_handle, &exchange.my_did, &exchange.their_did)?;
+            exchange.state = ClaimExchangeState::Requested;
+            exchange.claim_req = Some(claim_req_json.clone());
+            self._set_claim_exchange(wallet_handle, &exchange)?;
+            Ok((connection_id, claim_req_json))
+        });
+
+        match res {
+            Ok((connection_id, claim_req_json)) => {
+                let exchange_id = exchange_id.to_string();
+                self._send_control_msg(connection_id, CLAIM_TYPE, &ClaimMsg::Request {
+                    exchange_id: exchange_id.clone(),
+                    claim_req: claim_req_json,
+                }, Box::new(move |res| {
+                    CommandExecutor::instance().send(Command::Agent(
+                        AgentCommand::ClaimRequestSentAck(wallet_handle, exchange_id.clone(), res.map(|_| ())))).unwrap();
+                }));
+            }
+            Err(err) => self.on_claim_request_sent(wallet_handle, exchange_id, Err(err))
+        }
+    }
+
+    fn on_claim_request_sent(&self, wallet_handle: i32, exchange_id: &str, res: Result<(), SovrinError>) {
+        if res.is_err() {
+            // Request can be repeated later
+            if let Ok(mut exchange) = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id) {
+                if exchange.state == ClaimExchangeState::Requested {
+                    exchange.state = ClaimExchangeState::OfferReceived;
+                    exchange.claim_req = None;
+                    if let Err(err) = self._set_claim_exchange(wallet_handle, &exchange) {
+                        error!("Can't restore state of claim exchange {}: {}", exchange_id, err);
+                    }
+                }
+            }
+        }
+
+        if let Some(cb) = self.claim_request_callbacks.borrow_mut().remove(exchange_id) {
+            return cb(res);
+        }
+        let event = match res {
+            Ok(()) => ClaimExchangeEvent::RequestSent { exchange_id: exchange_id.to_string() },
+            Err(err) => ClaimExchangeEvent::Failed { exchange_id: exchange_id.to_string(), reason: format!("Claim request can't be sent: {}", err) }
+        };
+        self._emit_claim_event(wallet_handle, event);
+    }
+
+    fn _on_claim_request_received(&self, wallet_handle: i32, exchange_id: &str, their_did: &str, claim_req_json: String) -> Result<(), SovrinError> {
+        let mut exchange = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Issuer, exchange_id)?;
+        AgentCommandExecutor::_check_claim_exchange(&exchange, their_did, ClaimExchangeState::Offered)?;
+
+        exchange.state = ClaimExchangeState::RequestReceived;
+        exchange.claim_req = Some(claim_req_json.clone());
+        self._set_claim_exchange(wallet_handle, &exchange)?;
+
+        self._emit_claim_event(wallet_handle, ClaimExchangeEvent::RequestReceived {
+            exchange_id: exchange_id.to_string(),
+            claim_req: claim_req_json,
+        });
+        Ok(())
+    }
+
+    fn claim_issue(&self, wallet_handle: i32, exchange_id: String, claim_json: String,
+                   revoc_reg_seq_no: Option<i32>, user_revoc_index: Option<i32>, cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        let claim_req_json = match self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Issuer, &exchange_id)
+            .and_then(|exchange| match (exchange.state, exchange.claim_req) {
+                (ClaimExchangeState::RequestReceived, Some(claim_req_json)) => Ok(claim_req_json),
+                (state, _) => Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                    format!("Claim exchange {} is in {:?} state", exchange_id, state))))
+            }) {
+            Ok(claim_req_json) => claim_req_json,
+            Err(err) => return cb(Err(err))
+        };
+
+        self.claim_issue_callbacks.borrow_mut().insert(exchange_id.clone(), cb);
+        CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Issuer(IssuerCommand::CreateClaim(
+            wallet_handle,
+            claim_req_json,
+            claim_json,
+            revoc_reg_seq_no,
+            user_revoc_index,
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::ClaimCreatedAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+            })))).unwrap();
+    }
+
+    fn on_claim_created(&self, wallet_handle: i32, exchange_id: &str, res: Result<(String, String), SovrinError>) {
+        let cb = match self.claim_issue_callbacks.borrow_mut().remove(exchange_id) {
+            Some(cb) => cb,
+            None => return error!("Can't handle ClaimCreatedAck cmd - callback not found for {}", exchange_id)
+        };
+
+        let res = res.and_then(|(revoc_reg_update_json, claim_json)| {
+            let mut exchange = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Issuer, exchange_id)?;
+            let connection_id = self._find_connection(wallet_handle, &exchange.my_did, &exchange.their_did)?;
+            exchange.state = ClaimExchangeState::Issued;
+            self._set_claim_exchange(wallet_handle, &exchange)?;
+            Ok((connection_id, revoc_reg_update_json, claim_json))
+        });
+
+        match res {
+            Ok((connection_id, revoc_reg_update_json, claim_json)) => {
+                self._send_control_msg(connection_id, CLAIM_TYPE, &ClaimMsg::Claim {
+                    exchange_id: exchange_id.to_string(),
+                    claim: claim_json,
+                }, Box::new(move |res| cb(res.map(|_| revoc_reg_update_json.clone()))));
+            }
+            Err(err) => cb(Err(err))
+        }
+    }
+
+    fn _on_claim_received(&self, wallet_handle: i32, exchange_id: &str, their_did: &str, claim_json: String) -> Result<(), SovrinError> {
+        let exchange = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id)?;
+        AgentCommandExecutor::_check_claim_exchange(&exchange, their_did, ClaimExchangeState::Requested)?;
+
+        let exchange_id = exchange_id.to_string();
+        CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Prover(ProverCommand::StoreClaim(
+            wallet_handle,
+            claim_json,
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::ClaimStoredAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+            })))))?;
+        Ok(())
+    }
+
+    fn on_claim_stored(&self, wallet_handle: i32, exchange_id: &str, res: Result<(), SovrinError>) {
+        let mut exchange = match self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id) {
+            Ok(exchange) => exchange,
+            Err(err) => return error!("Can't handle ClaimStoredAck cmd: {}", err)
+        };
+        if let Err(err) = res {
+            return self._fail_claim_exchange(wallet_handle, exchange, format!("Claim can't be stored: {}", err), true);
+        }
+
+        exchange.state = ClaimExchangeState::Stored;
+        if let Err(err) = self._set_claim_exchange(wallet_handle, &exchange) {
+            error!("Can't store state of claim exchange {}: {}", exchange_id, err);
+        }
+        self._emit_claim_event(wallet_handle, ClaimExchangeEvent::ClaimStored { exchange_id: exchange_id.to_string() });
+    }
+
+    fn _on_claim_problem_received(&self, wallet_handle: i32, exchange_id: &str, their_did: &str, reason: String) {
+        let exchange = self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Issuer, exchange_id)
+            .or_else(|_| self._get_claim_exchange(wallet_handle, ClaimExchangeRole::Prover, exchange_id));
+        match exchange {
+            Ok(ref exchange) if exchange.their_did != their_did =>
+                warn!("Problem of claim exchange {} received from unexpected DID {}", exchange_id, their_did),
+            Ok(exchange) => self._fail_claim_exchange(wallet_handle, exchange, format!("Claim exchange is failed by peer: {}", reason), false),
+            Err(err) => warn!("Problem of unknown claim exchange {} dropped: {}", exchange_id, err)
+        }
+    }
+
+    /// Marks exchange as failed and reports it to user and (optionally) to peer.
+    fn _fail_claim_exchange(&self, wallet_handle: i32, mut exchange: ClaimExchange, reason: String, notify_peer: bool) {
+        exchange.state = ClaimExchangeState::Failed;
+        exchange.reason = Some(reason.clone());
+        if let Err(err) = self._set_claim_exchange(wallet_handle, &exchange) {
+            error!("Can't store state of claim exchange {}: {}", exchange.id, err);
+        }
+
+        if notify_peer {
+            match self._find_connection(wallet_handle, &exchange.my_did, &exchange.their_did) {
+                Ok(connection_id) => self._send_claim_problem(connection_id, &exchange.id, &reason),
+                Err(err) => warn!("Problem of claim exchange {} can't be sent: {}", exchange.id, err)
+            }
+        }
+
+        self._emit_claim_event(wallet_handle, ClaimExchangeEvent::Failed { exchange_id: exchange.id, reason: reason });
+    }
+
+    fn _send_claim_problem(&self, connection_id: i32, exchange_id: &str, reason: &str) {
+        self._send_control_msg(connection_id, CLAIM_TYPE, &ClaimMsg::Problem {
+            exchange_id: exchange_id.to_string(),
+            reason: reason.to_string(),
+        }, Box::new(move |res| {
+            if let Err(err) = res {
+                error!("Can't send claim problem to connection {}: {}", connection_id, err);
+            }
+        }));
+    }
+
+    fn _check_claim_exchange(exchange: &ClaimExchange, their_did: &str, state: ClaimExchangeState) -> Result<(), SovrinError> {
+        if exchange.their_did != their_did {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Claim exchange {} doesn't belong to DID {}", exchange.id, their_did))));
+        }
+        if exchange.state != state {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Claim exchange {} is in {:?} state", exchange.id, exchange.state))));
+        }
+        Ok(())
+    }
+
+    fn _emit_claim_event(&self, wallet_handle: i32, event: ClaimExchangeEvent) {
+        match self.claim_subscribers.borrow().get(&wallet_handle) {
+            Some(&(_, ref event_cb)) => event_cb(event.to_json()
+                .map_err(|err| SovrinError::CommonError(
+                    CommonError::InvalidState(format!("Can't serialize ClaimExchangeEvent: {}", err.description()))))),
+            None => debug!("Claim exchange event {:?} isn't reported - no subscriber for wallet {}", event, wallet_handle)
+        }
+    }
+
+    fn claim_exchanges(&self, wallet_handle: i32) -> Result<String, SovrinError> {
+        let mut exchanges = Vec::new();
+        for (_, exchange_json) in self.wallet_service.list(wallet_handle, "claim_exchange::")? {
+            exchanges.push(ClaimExchange::from_json(&exchange_json)
+                .map_err(|_| CommonError::InvalidState(format!("Invalid claim exchange json")))?);
+        }
+
+        serde_json::to_string(&exchanges)
+            .map_err(|err| SovrinError::CommonError(
+                CommonError::InvalidState(format!("Can't serialize claim exchanges: {}", err.description()))))
+    }
+
+    fn _get_claim_exchange(&self, wallet_handle: i32, role: ClaimExchangeRole, exchange_id: &str) -> Result<ClaimExchange, SovrinError> {
+        match self.wallet_service.get(wallet_handle, &ClaimExchange::key(role, exchange_id)) {
+            Ok(exchange_json) => ClaimExchange::from_json(&exchange_json)
+                .map_err(|_| SovrinError::CommonError(CommonError::InvalidState(format!("Invalid claim exchange json")))),
+            Err(WalletError::NotFound(_)) =>
+                Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Claim exchange {} not found", exchange_id)))),
+            Err(err) => Err(SovrinError::WalletError(err))
+        }
+    }
+
+    fn _set_claim_exchange(&self, wallet_handle: i32, exchange: &ClaimExchange) -> Result<(), SovrinError> {
+        let exchange_json = exchange.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize ClaimExchange: {}", err.description())))?;
+        self.wallet_service.set(wallet_handle, &ClaimExchange::key(exchange.role, &exchange.id), &exchange_json)?;
+        Ok(())
+    }
+
+    fn proof_request(&self, connection_id: i32, proof_req_json: String, cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        let exchange = match self._create_verifier_proof_exchange(connection_id, &proof_req_json) {
+            Ok(exchange) => exchange,
+            Err(err) => return cb(Err(err))
+        };
+
+        let exchange_id = exchange.id.clone();
+        self._send_control_msg(connection_id, PROOF_TYPE, &ProofMsg::Request {
+            exchange_id: exchange.id,
+            proof_req: exchange.proof_req,
+        }, Box::new(move |res| cb(res.map(|_| exchange_id.clone()))));
+    }
+
+    /// Nonce of the proof request is replaced with fresh one, so proof created for
+    /// another request can't be replayed in this exchange.
+    fn _create_verifier_proof_exchange(&self, connection_id: i32, proof_req_json: &str) -> Result<ProofExchange, SovrinError> {
+        let (wallet_handle, my_did, their_did) = self._get_connection_identities(connection_id)?;
+        if !self.proof_subscribers.borrow().contains_key(&wallet_handle) {
+            return Err(SovrinError::CommonError(CommonError::InvalidState(
+                format!("Wallet {} isn't subscribed to proof exchanges", wallet_handle))));
+        }
+
+        let mut proof_req = ProofRequestJson::from_json(proof_req_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid proof_req_json: {}", err.description())))?;
+        proof_req.nonce = BigNumber::rand(LARGE_NONCE)?;
+        let proof_req_json = proof_req.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize ProofRequestJson: {}", err.description())))?;
+
+        let exchange = ProofExchange::new(Uuid::new_v4().to_string(), ProofExchangeRole::Verifier, ProofExchangeState::Requested,
+                                          my_did, their_did, proof_req_json);
+        self._set_proof_exchange(wallet_handle, &exchange)?;
+        Ok(exchange)
+    }
+
+    /// Handles message of proof presentation protocol received by connection.
+    /// Problem is sent back if message doesn't match state of the exchange.
+    fn on_proof_msg(&self, connection_id: i32, msg: ProofMsg) {
+        let (wallet_handle, my_did, their_did) = match self._get_connection_identities(connection_id) {
+            Ok(identities) => identities,
+            Err(err) => return error!("Proof message on connection {} dropped: {}", connection_id, err)
+        };
+
+        let (exchange_id, res) = match msg {
+            ProofMsg::Request { exchange_id, proof_req } => {
+                let res = self._on_proof_request_received(wallet_handle, &exchange_id, my_did, their_did, proof_req);
+                (exchange_id, res)
+            }
+            ProofMsg::Proof { exchange_id, proof, identifiers } => {
+                let res = self._on_proof_received(wallet_handle, &exchange_id, &their_did, proof, identifiers);
+                (exchange_id, res)
+            }
+            ProofMsg::Result { exchange_id, valid } => {
+                return self._on_proof_result_received(wallet_handle, &exchange_id, &their_did, valid);
+            }
+            ProofMsg::Problem { exchange_id, reason } => {
+                return self._on_proof_problem_received(wallet_handle, &exchange_id, &their_did, reason);
+            }
+        };
+
+        if let Err(err) = res {
+            warn!("Proof exchange {} on connection {} can't be continued: {}", exchange_id, connection_id, err);
+            self._send_proof_problem(connection_id, &exchange_id, &format!("{}", err));
+        }
+    }
+
+    fn _on_proof_request_received(&self, wallet_handle: i32, exchange_id: &str, my_did: String, their_did: String,
+                                  proof_req_json: String) -> Result<(), SovrinError> {
+        ProofRequestJson::from_json(&proof_req_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid proof request: {}", err.description())))?;
+        if self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, exchange_id).is_ok() {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Proof exchange {} already exists", exchange_id))));
+        }
+
+        let exchange = ProofExchange::new(exchange_id.to_string(), ProofExchangeRole::Prover, ProofExchangeState::RequestReceived,
+                                          my_did, their_did, proof_req_json.clone());
+        self._set_proof_exchange(wallet_handle, &exchange)?;
+
+        let exchange_id = exchange_id.to_string();
+        CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Prover(ProverCommand::GetClaimsForProofReq(
+            wallet_handle,
+            proof_req_json,
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(
+                    AgentCommand::ProofClaimsFoundAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+            })))))?;
+        Ok(())
+    }
+
+    fn on_proof_claims_found(&self, wallet_handle: i32, exchange_id: &str, res: Result<String, SovrinError>) {
+        let mut exchange = match self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, exchange_id) {
+            Ok(exchange) => exchange,
+            Err(err) => return error!("Can't handle ProofClaimsFoundAck cmd: {}", err)
+        };
+        let claims_json = match res {
+            Ok(claims_json) => claims_json,
+            Err(err) => return self._fail_proof_exchange(wallet_handle, exchange, format!("Claims for proof request can't be found: {}", err), true)
+        };
+
+        exchange.claims = Some(claims_json.clone());
+        if let Err(err) = self._set_proof_exchange(wallet_handle, &exchange) {
+            error!("Can't store state of proof exchange {}: {}", exchange_id, err);
+        }
+
+        self._emit_proof_event(wallet_handle, ProofExchangeEvent::RequestReceived {
+            exchange_id: exchange.id,
+            proof_req: exchange.proof_req,
+            claims: claims_json,
+        });
+    }
+
+    fn proof_present(&self, wallet_handle: i32, exchange_id: String, requested_claims_json: String, master_secret_name: String,
+                     cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        let res = self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, &exchange_id)
+            .and_then(|mut exchange| {
+                if exchange.state != ProofExchangeState::RequestReceived {
+                    return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                        format!("Proof exchange {} is in {:?} state", exchange_id, exchange.state))));
+                }
+                let identifiers = AgentCommandExecutor::_get_claim_identifiers(&exchange, &requested_claims_json)?;
+                exchange.identifiers = Some(identifiers);
+                self._set_proof_exchange(wallet_handle, &exchange)?;
+                Ok(exchange)
+            });
+        let exchange = match res {
+            Ok(exchange) => exchange,
+            Err(err) => return cb(Err(err))
+        };
+
+        self.proof_present_callbacks.borrow_mut().insert(exchange_id.clone(), (requested_claims_json, master_secret_name, cb));
+        let identifiers = exchange.identifiers.unwrap_or_default();
+        if let Err(err) = self._resolve_proof_ledger_records(wallet_handle, ProofExchangeRole::Prover, &exchange_id, &exchange.my_did, &identifiers) {
+            self._complete_proof_presentation(&exchange_id, Err(err));
+        }
+    }
+
+    /// Returns ledger identifiers of claims requested for the proof. Only claims
+    /// found for proof request of the exchange can be used.
+    fn _get_claim_identifiers(exchange: &ProofExchange, requested_claims_json: &str) -> Result<HashMap<String, ClaimIdentifier>, SovrinError> {
+        let requested_claims = RequestedClaimsJson::from_json(requested_claims_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid requested_claims_json: {}", err.description())))?;
+        let claims_json = exchange.claims.as_ref()
+            .ok_or(CommonError::InvalidState(format!("Claims for proof exchange {} aren't found yet", exchange.id)))?;
+        let claims = ProofClaimsJson::from_json(claims_json)
+            .map_err(|_| CommonError::InvalidState(format!("Invalid claims json of proof exchange {}", exchange.id)))?;
+
+        let mut identifiers = HashMap::new();
+        let claim_uuids = requested_claims.requested_attrs.values().map(|&(ref claim_uuid, _)| claim_uuid)
+            .chain(requested_claims.requested_predicates.values());
+        for claim_uuid in claim_uuids {
+            let claim_info = claims.attrs.values().chain(claims.predicates.values())
+                .flat_map(|claim_infos| claim_infos.iter())
+                .find(|claim_info| &claim_info.claim_uuid == claim_uuid)
+                .ok_or(CommonError::InvalidStructure(format!("Claim {} doesn't match proof request", claim_uuid)))?;
+            identifiers.insert(claim_uuid.clone(), ClaimIdentifier {
+                issuer_did: claim_info.issuer_did.clone(),
+                schema_seq_no: claim_info.schema_seq_no,
+            });
+        }
+        Ok(identifiers)
+    }
+
+    /// Requests schema and claim definition of each claim from the ledger (through the wallet cache)
+    /// with pool of the subscription. Resolution is completed by ProofLedgerReplyAck commands.
+    fn _resolve_proof_ledger_records(&self, wallet_handle: i32, role: ProofExchangeRole, exchange_id: &str, my_did: &str,
+                                     identifiers: &HashMap<String, ClaimIdentifier>) -> Result<(), SovrinError> {
+        let pool_handle = self.proof_subscribers.borrow().get(&wallet_handle).map(|&(pool_handle, _)| pool_handle)
+            .ok_or(CommonError::InvalidState(format!("Wallet {} isn't subscribed to proof exchanges", wallet_handle)))?;
+
+        let mut requests = Vec::new();
+        for (claim_uuid, identifier) in identifiers {
+            requests.push((claim_uuid.clone(), ProofLedgerRecord::Schema,
+                           self.ledger_service.build_get_txn_request(my_did, identifier.schema_seq_no)?));
+            requests.push((claim_uuid.clone(), ProofLedgerRecord::ClaimDef,
+                           self.ledger_service.build_get_claim_def_request(my_did, identifier.schema_seq_no, "CL", &identifier.issuer_did)?));
+        }
+
+        if requests.is_empty() {
+            self._on_proof_ledger_records_resolved(wallet_handle, role, exchange_id, Ok((HashMap::new(), HashMap::new())));
+            return Ok(());
+        }
+
+        self.proof_resolutions.borrow_mut().insert(ProofExchange::key(role, exchange_id), ProofResolution {
+            pending: requests.len(),
+            schemas: HashMap::new(),
+            claim_defs: HashMap::new(),
+        });
+
+        for (claim_uuid, record, request_json) in requests {
+            let exchange_id = exchange_id.to_string();
+            CommandExecutor::instance().send(Command::Ledger(LedgerCommand::SubmitRequestWithCache(
+                pool_handle,
+                wallet_handle,
+                request_json,
+                None,
+                Box::new(move |res| {
+                    CommandExecutor::instance().send(Command::Agent(AgentCommand::ProofLedgerReplyAck(
+                        wallet_handle, role, exchange_id.clone(), claim_uuid.clone(), record, res))).unwrap();
+                }))))?;
+        }
+        Ok(())
+    }
+
+    fn on_proof_ledger_reply(&self, wallet_handle: i32, role: ProofExchangeRole, exchange_id: &str, claim_uuid: String,
+                             record: ProofLedgerRecord, res: Result<String, SovrinError>) {
+        let key = ProofExchange::key(role, exchange_id);
+        let parsed = res.and_then(|reply| match record {
+            ProofLedgerRecord::Schema => self.ledger_service.parse_get_txn_schema_reply(&reply)
+                .map(|schema| (Some(schema), None)),
+            ProofLedgerRecord::ClaimDef => self.ledger_service.parse_get_claim_def_reply(&reply)
+                .map(|claim_def| (None, Some(claim_def))),
+        }.map_err(SovrinError::from));
+
+        let res = {
+            let mut resolutions = self.proof_resolutions.borrow_mut();
+            let err = {
+                let resolution = match resolutions.get_mut(&key) {
+                    Some(resolution) => resolution,
+                    None => return debug!("Ledger reply for finished resolution of {} ignored", key)
+                };
+                match parsed {
+                    Ok((schema, claim_def)) => {
+                        if let Some(schema) = schema {
+                            resolution.schemas.insert(claim_uuid.clone(), schema);
+                        }
+                        if let Some(claim_def) = claim_def {
+                            resolution.claim_defs.insert(claim_uuid, claim_def);
+                        }
+                        resolution.pending -= 1;
+                        if resolution.pending > 0 {
+                            return;
+                        }
+                        None
+                    }
+                    Err(err) => Some(err)
+                }
+            };
+            let resolution = resolutions.remove(&key).unwrap();
+            match err {
+                Some(err) => Err(err),
+                None => Ok((resolution.schemas, resolution.claim_defs))
+            }
+        };
+
+        self._on_proof_ledger_records_resolved(wallet_handle, role, exchange_id, res);
+    }
+
+    fn _on_proof_ledger_records_resolved(&self, wallet_handle: i32, role: ProofExchangeRole, exchange_id: &str,
+                                         res: Result<(HashMap<String, Schema>, HashMap<String, ClaimDefinition>), SovrinError>) {
+        match role {
+            ProofExchangeRole::Prover => self._create_proof(wallet_handle, exchange_id, res),
+            ProofExchangeRole::Verifier => self._verify_proof(wallet_handle, exchange_id, res),
+        }
+    }
+
+    fn _create_proof(&self, wallet_handle: i32, exchange_id: &str,
+                     res: Result<(HashMap<String, Schema>, HashMap<String, ClaimDefinition>), SovrinError>) {
+        let (requested_claims_json, master_secret_name) = match self.proof_present_callbacks.borrow().get(exchange_id) {
+            Some(&(ref requested_claims_json, ref master_secret_name, _)) => (requested_claims_json.clone(), master_secret_name.clone()),
+            None => return error!("Can't create proof - callback not found for {}", exchange_id)
+        };
+
+        let res = res.and_then(|(schemas, claim_defs)| {
+            let exchange = self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, exchange_id)?;
+            let (schemas_json, claim_defs_json) = AgentCommandExecutor::_ledger_records_to_json(&schemas, &claim_defs)?;
+
+            let exchange_id = exchange_id.to_string();
+            CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Prover(ProverCommand::CreateProof(
+                wallet_handle,
+                exchange.proof_req,
+                requested_claims_json,
+                schemas_json,
+                master_secret_name,
+                claim_defs_json,
+                "{}".to_string(),
+                Box::new(move |res| {
+                    CommandExecutor::instance().send(Command::Agent(
+                        AgentCommand::ProofCreatedAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+                })))))?;
+            Ok(())
+        });
+
+        if let Err(err) = res {
+            self._complete_proof_presentation(exchange_id, Err(err));
+        }
+    }
+
+    fn on_proof_created(&self, wallet_handle: i32, exchange_id: &str, res: Result<String, SovrinError>) {
+        let res = res.and_then(|proof_json| {
+            let mut exchange = self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, exchange_id)?;
+            let connection_id = self._find_connection(wallet_handle, &exchange.my_did, &exchange.their_did)?;
+            let identifiers = exchange.identifiers.clone()
+                .ok_or(CommonError::InvalidState(format!("Proof exchange {} doesn't contain claim identifiers", exchange_id)))?;
+            exchange.state = ProofExchangeState::Presented;
+            exchange.proof = Some(proof_json.clone());
+            self._set_proof_exchange(wallet_handle, &exchange)?;
+            Ok((connection_id, proof_json, identifiers))
+        });
+
+        let (connection_id, proof_json, identifiers) = match res {
+            Ok(res) => res,
+            Err(err) => return self._complete_proof_presentation(exchange_id, Err(err))
+        };
+        let cb = match self.proof_present_callbacks.borrow_mut().remove(exchange_id) {
+            Some((_, _, cb)) => cb,
+            None => return error!("Can't handle ProofCreatedAck cmd - callback not found for {}", exchange_id)
+        };
+
+        self._send_control_msg(connection_id, PROOF_TYPE, &ProofMsg::Proof {
+            exchange_id: exchange_id.to_string(),
+            proof: proof_json,
+            identifiers: identifiers,
+        }, Box::new(move |res| cb(res.map(|_| ()))));
+    }
+
+    fn _complete_proof_presentation(&self, exchange_id: &str, res: Result<(), SovrinError>) {
+        match self.proof_present_callbacks.borrow_mut().remove(exchange_id) {
+            Some((_, _, cb)) => cb(res),
+            None => error!("Can't complete proof presentation - callback not found for {}", exchange_id)
+        }
+    }
+
+    fn _on_proof_received(&self, wallet_handle: i32, exchange_id: &str, their_did: &str, proof_json: String,
+                          identifiers: HashMap<String, ClaimIdentifier>) -> Result<(), SovrinError> {
+        let mut exchange = self._get_proof_exchange(wallet_handle, ProofExchangeRole::Verifier, exchange_id)?;
+        AgentCommandExecutor::_check_proof_exchange(&exchange, their_did, ProofExchangeState::Requested)?;
+
+        let proof = ProofJson::from_json(&proof_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid proof: {}", err.description())))?;
+        if proof.proofs.len() != identifiers.len() || proof.proofs.keys().any(|claim_uuid| !identifiers.contains_key(claim_uuid)) {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Claim identifiers don't match claims of proof"))));
+        }
+
+        exchange.state = ProofExchangeState::ProofReceived;
+        exchange.proof = Some(proof_json);
+        exchange.identifiers = Some(identifiers.clone());
+        self._set_proof_exchange(wallet_handle, &exchange)?;
+
+        if let Err(err) = self._resolve_proof_ledger_records(wallet_handle, ProofExchangeRole::Verifier, exchange_id, &exchange.my_did, &identifiers) {
+            self._fail_proof_exchange(wallet_handle, exchange, format!("Proof can't be verified: {}", err), true);
+        }
+        Ok(())
+    }
+
+    /// Proof is verified against proof request stored in the wallet, so it must be created for nonce of this exchange.
+    /// Claim definitions used by prover must be the same as in the ledger.
+    fn _verify_proof(&self, wallet_handle: i32, exchange_id: &str,
+                     res: Result<(HashMap<String, Schema>, HashMap<String, ClaimDefinition>), SovrinError>) {
+        let exchange = match self._get_proof_exchange(wallet_handle, ProofExchangeRole::Verifier, exchange_id) {
+            Ok(exchange) => exchange,
+            Err(err) => return error!("Can't verify proof: {}", err)
+        };
+
+        let res = res.and_then(|(schemas, claim_defs)| {
+            let proof_json = exchange.proof.clone()
+                .ok_or(CommonError::InvalidState(format!("Proof exchange {} doesn't contain proof", exchange_id)))?;
+            let proof = ProofJson::from_json(&proof_json)
+                .map_err(|_| CommonError::InvalidState(format!("Invalid proof json of proof exchange {}", exchange_id)))?;
+            for (claim_uuid, claim_proof) in &proof.proofs {
+                let claim_def_seq_no = claim_defs.get(claim_uuid).and_then(|claim_def| claim_def.claim_def_seq_no);
+                if claim_def_seq_no != Some(claim_proof.claim_def_seq_no) {
+                    return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                        format!("Claim definition of claim {} doesn't match the ledger one", claim_uuid))));
+                }
+            }
+            let (schemas_json, claim_defs_json) = AgentCommandExecutor::_ledger_records_to_json(&schemas, &claim_defs)?;
+
+            let exchange_id = exchange_id.to_string();
+            CommandExecutor::instance().send(Command::Anoncreds(AnoncredsCommand::Verifier(VerifierCommand::VerifyProof(
+                exchange.proof_req.clone(),
+                proof_json,
+                schemas_json,
+                claim_defs_json,
+                "{}".to_string(),
+                Box::new(move |res| {
+                    CommandExecutor::instance().send(Command::Agent(
+                        AgentCommand::ProofVerifiedAck(wallet_handle, exchange_id.clone(), res))).unwrap();
+                })))))?;
+            Ok(())
+        });
+
+        if let Err(err) = res {
+            self._fail_proof_exchange(wallet_handle, exchange, format!("Proof can't be verified: {}", err), true);
+        }
+    }
+
+    fn on_proof_verified(&self, wallet_handle: i32, exchange_id: &str, res: Result<bool, SovrinError>) {
+        let mut exchange = match self._get_proof_exchange(wallet_handle, ProofExchangeRole::Verifier, exchange_id) {
+            Ok(exchange) => exchange,
+            Err(err) => return error!("Can't handle ProofVerifiedAck cmd: {}", err)
+        };
+        let valid = match res {
+            Ok(valid) => valid,
+            Err(err) => return self._fail_proof_exchange(wallet_handle, exchange, format!("Proof can't be verified: {}", err), true)
+        };
+
+        exchange.state = if valid { ProofExchangeState::Verified } else { ProofExchangeState::Rejected };
+        if let Err(err) = self._set_proof_exchange(wallet_handle, &exchange) {
+            error!("Can't store state of proof exchange {}: {}", exchange_id, err);
+        }
+
+        match self._find_connection(wallet_handle, &exchange.my_did, &exchange.their_did) {
+            Ok(connection_id) => self._send_control_msg(connection_id, PROOF_TYPE, &ProofMsg::Result {
+                exchange_id: exchange_id.to_string(),
+                valid: valid,
+            }, Box::new(move |res| {
+                if let Err(err) = res {
+                    error!("Can't send proof result to connection {}: {}", connection_id, err);
+                }
+            })),
+            Err(err) => warn!("Result of proof exchange {} can't be sent: {}", exchange_id, err)
+        }
+
+        self._emit_proof_event(wallet_handle, ProofExchangeEvent::ProofReceived {
+            exchange_id: exchange.id,
+            proof: exchange.proof.unwrap_or_default(),
+            valid: valid,
+        });
+    }
+
+    fn _on_proof_result_received(&self, wallet_handle: i32, exchange_id: &str, their_did: &str, valid: bool) {
+        let mut exchange = match self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, exchange_id)
+            .and_then(|exchange| AgentCommandExecutor::_check_proof_exchange(&exchange, their_did, ProofExchangeState::Presented)
+                .map(|_| exchange)) {
+            Ok(exchange) => exchange,
+            Err(err) => return warn!("Unexpected result of proof exchange {} dropped: {}", exchange_id, err)
+        };
+
+        exchange.state = if valid { ProofExchangeState::Verified } else { ProofExchangeState::Rejected };
+        if let Err(err) = self._set_proof_exchange(wallet_handle, &exchange) {
+            error!("Can't store state of proof exchange {}: {}", exchange_id, err);
+        }
+        self._emit_proof_event(wallet_handle, ProofExchangeEvent::ProofResult { exchange_id: exchange.id, valid: valid });
+    }
+
+    fn _on_proof_problem_received(&self, wallet_handle: i32, exchange_id: &str, their_did: &str, reason: String) {
+        let exchange = self._get_proof_exchange(wallet_handle, ProofExchangeRole::Verifier, exchange_id)
+            .or_else(|_| self._get_proof_exchange(wallet_handle, ProofExchangeRole::Prover, exchange_id));
+        match exchange {
+            Ok(ref exchange) if exchange.their_did != their_did =>
+                warn!("Problem of proof exchange {} received from unexpected DID {}", exchange_id, their_did),
+            Ok(exchange) => self._fail_proof_exchange(wallet_handle, exchange, format!("Proof exchange is failed by peer: {}", reason), false),
+            Err(err) => warn!("Problem of unknown proof exchange {} dropped: {}", exchange_id, err)
+        }
+    }
+
+    /// Marks exchange as failed and reports it to user and (optionally) to peer.
+    fn _fail_proof_exchange(&self, wallet_handle: i32, mut exchange: ProofExchange, reason: String, notify_peer: bool) {
+        exchange.state = ProofExchangeState::Failed;
+        exchange.reason = Some(reason.clone());
+        if let Err(err) = self._set_proof_exchange(wallet_handle, &exchange) {
+            error!("Can't store state of proof exchange {}: {}", exchange.id, err);
+        }
+
+        if notify_peer {
+            match self._find_connection(wallet_handle, &exchange.my_did, &exchange.their_did) {
+                Ok(connection_id) => self._send_proof_problem(connection_id, &exchange.id, &reason),
+                Err(err) => warn!("Problem of proof exchange {} can't be sent: {}", exchange.id, err)
+            }
+        }
+
+        self._emit_proof_event(wallet_handle, ProofExchangeEvent::Failed { exchange_id: exchange.id, reason: reason });
+    }
+
+    fn _send_proof_problem(&self, connection_id: i32, exchange_id: &str, reason: &str) {
+        self._send_control_msg(connection_id, PROOF_TYPE, &ProofMsg::Problem {
+            exchange_id: exchange_id.to_string(),
+            reason: reason.to_string(),
+        }, Box::new(move |res| {
+            if let Err(err) = res {
+                error!("Can't send proof problem to connection {}: {}", connection_id, err);
+            }
+        }));
+    }
+
+    fn _check_proof_exchange(exchange: &ProofExchange, their_did: &str, state: ProofExchangeState) -> Result<(), SovrinError> {
+        if exchange.their_did != their_did {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Proof exchange {} doesn't belong to DID {}", exchange.id, their_did))));
+        }
+        if exchange.state != state {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Proof exchange {} is in {:?} state", exchange.id, exchange.state))));
+        }
+        Ok(())
+    }
+
+    fn _ledger_records_to_json(schemas: &HashMap<String, Schema>, claim_defs: &HashMap<String, ClaimDefinition>) -> Result<(String, String), SovrinError> {
+        let schemas_json = serde_json::to_string(schemas)
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize schemas: {}", err.description())))?;
+        let claim_defs_json = serde_json::to_string(claim_defs)
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize claim definitions: {}", err.description())))?;
+        Ok((schemas_json, claim_defs_json))
+    }
+
+    fn _emit_proof_event(&self, wallet_handle: i32, event: ProofExchangeEvent) {
+        match self.proof_subscribers.borrow().get(&wallet_handle) {
+            Some(&(_, ref event_cb)) => event_cb(event.to_json()
+                .map_err(|err| SovrinError::CommonError(
+                    CommonError::InvalidState(format!("Can't serialize ProofExchangeEvent: {}", err.description()))))),
+            None => debug!("Proof exchange event {:?} isn't reported - no subscriber for wallet {}", event, wallet_handle)
+        }
+    }
+
+    fn proof_exchanges(&self, wallet_handle: i32) -> Result<String, SovrinError> {
+        let mut exchanges = Vec::new();
+        for (_, exchange_json) in self.wallet_service.list(wallet_handle, "proof_exchange::")? {
+            exchanges.push(ProofExchange::from_json(&exchange_json)
+                .map_err(|_| CommonError::InvalidState(format!("Invalid proof exchange json")))?);
+        }
+
+        serde_json::to_string(&exchanges)
+            .map_err(|err| SovrinError::CommonError(
+                CommonError::InvalidState(format!("Can't serialize proof exchanges: {}", err.description()))))
+    }
+
+    fn _get_proof_exchange(&self, wallet_handle: i32, role: ProofExchangeRole, exchange_id: &str) -> Result<ProofExchange, SovrinError> {
+        match self.wallet_service.get(wallet_handle, &ProofExchange::key(role, exchange_id)) {
+            Ok(exchange_json) => ProofExchange::from_json(&exchange_json)
+                .map_err(|_| SovrinError::CommonError(CommonError::InvalidState(format!("Invalid proof exchange json")))),
+            Err(WalletError::NotFound(_)) =>
+                Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Proof exchange {} not found", exchange_id)))),
+            Err(err) => Err(SovrinError::WalletError(err))
+        }
+    }
+
+    fn _set_proof_exchange(&self, wallet_handle: i32, exchange: &ProofExchange) -> Result<(), SovrinError> {
+        let exchange_json = exchange.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize ProofExchange: {}", err.description())))?;
+        self.wallet_service.set(wallet_handle, &ProofExchange::key(exchange.role, &exchange.id), &exchange_json)?;
+        Ok(())
+    }
+
+    /// Creates fresh DID served by the listener and invitation with this DID.
+    fn create_invitation(&self, listener_handle: i32, wallet_handle: i32, label: Option<String>,
+                         cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        if !self.listeners.borrow().contains_key(&listener_handle) {
+            return cb(Err(SovrinError::CommonError(
+                CommonError::InvalidStructure(format!("Listener with id {} not found", listener_handle)))));
+        }
+
+        let cmd_id = SequenceUtils::get_next_id();
+        self.invitation_requests.borrow_mut().insert(cmd_id, InvitationRequest {
+            listener_handle: listener_handle,
+            wallet_handle: wallet_handle,
+            label: label,
+            invitation: None,
+            cb: cb,
+        });
+
+        let res = CommandExecutor::instance().send(Command::Signus(SignusCommand::CreateAndStoreMyDid(
+            wallet_handle,
+            "{}".to_string(),
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(AgentCommand::InvitationDidCreatedAck(cmd_id, res))).unwrap();
+            }))));
+        if let Err(err) = res {
+            self._complete_invitation(cmd_id, Err(SovrinError::CommonError(err)));
+        }
+    }
+
+    fn on_invitation_did_created(&self, cmd_id: i32, res: Result<(String, String, String), SovrinError>) {
+        let res = res.and_then(|(did, verkey, _)| {
+            let (listener_handle, wallet_handle, label) = match self.invitation_requests.borrow().get(&cmd_id) {
+                Some(request) => (request.listener_handle, request.wallet_handle, request.label.clone()),
+                None => return Err(SovrinError::CommonError(CommonError::InvalidState(format!("Invitation request {} not found", cmd_id))))
+            };
+            let (endpoint, transport_key) = self.listeners.borrow().get(&listener_handle)
+                .map(|listener| (listener.endpoint.clone(), listener.transport_key.clone()))
+                .ok_or(CommonError::InvalidStructure(format!("Listener with id {} not found", listener_handle)))?;
+
+            self._set_invitation_record(wallet_handle, &InvitationRecord::new(did.clone(), label.clone()))?;
+            let invitation = Invitation {
+                did: did,
+                verkey: verkey,
+                endpoint: endpoint,
+                transport_key: transport_key,
+                label: label,
+            };
+            Ok((listener_handle, wallet_handle, invitation))
+        });
+
+        let (listener_handle, wallet_handle, invitation) = match res {
+            Ok(res) => res,
+            Err(err) => return self._complete_invitation(cmd_id, Err(err))
+        };
+        let did = invitation.did.clone();
+        if let Some(request) = self.invitation_requests.borrow_mut().get_mut(&cmd_id) {
+            request.invitation = Some(invitation);
+        }
+
+        self.add_or_remove_identity(listener_handle, wallet_handle, did, Box::new(move |res| {
+            CommandExecutor::instance().send(Command::Agent(AgentCommand::InvitationIdentityAck(cmd_id, res))).unwrap();
+        }), false);
+    }
+
+    fn on_invitation_identity_added(&self, cmd_id: i32, res: Result<(), SovrinError>) {
+        let res = res.and_then(|_| {
+            let invitation = self.invitation_requests.borrow().get(&cmd_id)
+                .and_then(|request| request.invitation.clone())
+                .ok_or(CommonError::InvalidState(format!("Invitation of request {} not found", cmd_id)))?;
+            invitation.to_json()
+                .map_err(|err| SovrinError::CommonError(
+                    CommonError::InvalidState(format!("Can't serialize Invitation: {}", err.description()))))
+        });
+        self._complete_invitation(cmd_id, res);
+    }
+
+    fn _complete_invitation(&self, cmd_id: i32, res: Result<String, SovrinError>) {
+        match self.invitation_requests.borrow_mut().remove(&cmd_id) {
+            Some(request) => (request.cb)(res),
+            None => error!("Can't complete invitation - request {} not found", cmd_id)
+        }
+    }
+
+    fn _is_invitation_pending(&self, wallet_handle: i32, my_did: &str) -> bool {
+        self._get_invitation_record(wallet_handle, my_did)
+            .map(|record| record.state == InvitationState::Pending)
+            .unwrap_or(false)
+    }
+
+    /// Accepts connection of unknown DID to invited DID. Invitee is authenticated by verkey sent in handshake:
+    /// DID must be built from this verkey. Invitee DID is stored, so invitation can't be used by other DIDs.
+    fn accept_invitee(&self, wallet_handle: i32, receiver_did: &str, connection: IncomingConnection) {
+        let reject_reason = self._accept_invitee(wallet_handle, receiver_did, &connection).err();
+        self.complete_handshake(&connection, receiver_did, reject_reason);
+    }
+
+    fn _accept_invitee(&self, wallet_handle: i32, receiver_did: &str, connection: &IncomingConnection) -> Result<(), String> {
+        let verkey = connection.verkey.as_ref()
+            .ok_or(format!("DID {} isn't known and its verkey isn't sent", connection.did))?;
+        AgentCommandExecutor::_check_did_built_from_verkey(&connection.did, verkey)?;
+
+        let their_did = self.signus_service.create_their_did(&TheirDidInfo::new(connection.did.clone(), None, Some(verkey.clone()), None))
+            .map_err(|err| format!("Invalid verkey of DID {}: {}", connection.did, err))?;
+        AgentCommandExecutor::_verify_incoming_connection(&self.signus_service, connection, &their_did)?;
+
+        signus::store_their_did(&self.wallet_service, wallet_handle, &their_did)
+            .and_then(|_| pairwise::store_pairwise(&self.wallet_service, wallet_handle,
+                                                   &Pairwise::new(receiver_did.to_string(), their_did.did.clone())))
+            .and_then(|_| {
+                let mut record = self._get_invitation_record(wallet_handle, receiver_did)?;
+                record.state = InvitationState::Connected;
+                record.their_did = Some(their_did.did.clone());
+                self._set_invitation_record(wallet_handle, &record)
+            })
+            .map_err(|err| format!("Can't accept invited DID {}: {}", connection.did, err))
+    }
+
+    fn _check_did_built_from_verkey(did: &str, verkey: &str) -> Result<(), String> {
+        let did_bytes = Base58::decode(did).map_err(|_| format!("Invalid DID {}", did))?;
+        let verkey_bytes = Base58::decode(verkey).map_err(|_| format!("Invalid verkey of DID {}", did))?;
+        if did_bytes.is_empty() || !verkey_bytes.starts_with(&did_bytes) {
+            return Err(format!("DID {} isn't built from its verkey", did));
+        }
+        Ok(())
+    }
+
+    /// Creates fresh DID of invitee, stores inviter DID with endpoint from invitation and connects to it.
+    fn accept_invitation(&self, wallet_handle: i32, invitation_json: String, options_json: Option<String>,
+                         cb: AgentAcceptInvitationCB, message_cb: AgentMessageCB) {
+        let res = Invitation::from_json(&invitation_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid invitation json: {}", err.description())))
+            .and_then(|invitation| {
+                let options = match options_json {
+                    Some(options_json) => AcceptInvitationOptions::from_json(&options_json)
+                        .map_err(|err| CommonError::InvalidStructure(format!("Invalid accept invitation options json: {}", err.description())))?,
+                    None => AcceptInvitationOptions::default()
+                };
+                Ok((invitation, options))
+            });
+        let (invitation, options) = match res {
+            Ok(res) => res,
+            Err(err) => return cb(Err(SovrinError::CommonError(err)))
+        };
+
+        let cmd_id = SequenceUtils::get_next_id();
+        self.accept_invitation_requests.borrow_mut().insert(cmd_id, AcceptInvitationRequest {
+            wallet_handle: wallet_handle,
+            invitation: invitation,
+            options: options,
+            my_did: None,
+            cb: cb,
+            message_cb: Some(message_cb),
+        });
+
+        let res = CommandExecutor::instance().send(Command::Signus(SignusCommand::CreateAndStoreMyDid(
+            wallet_handle,
+            "{}".to_string(),
+            Box::new(move |res| {
+                CommandExecutor::instance().send(Command::Agent(AgentCommand::AcceptInvitationDidCreatedAck(cmd_id, res))).unwrap();
+            }))));
+        if let Err(err) = res {
+            self._complete_accept_invitation(cmd_id, Err(SovrinError::CommonError(err)));
+        }
+    }
+
+    fn on_accept_invitation_did_created(&self, cmd_id: i32, res: Result<(String, String, String), SovrinError>) {
+        let res = res.and_then(|(did, _, _)| {
+            let (wallet_handle, invitation) = self.accept_invitation_requests.borrow().get(&cmd_id)
+                .map(|request| (request.wallet_handle, request.invitation.clone()))
+                .ok_or(CommonError::InvalidState(format!("Accept invitation request {} not found", cmd_id)))?;
+
+            let their_did_info = TheirDidInfo::new(invitation.did.clone(), None, Some(invitation.verkey.clone()), None);
+            let their_did = self.signus_service.create_their_did(&their_did_info)?;
+            signus::store_their_did(&self.wallet_service, wallet_handle, &their_did)?;
+            signus::store_endpoint(&self.wallet_service, wallet_handle, &invitation.did,
+                                   &Endpoint::new(invitation.endpoint.clone(), Some(invitation.transport_key.clone())))?;
+
+            let mut pairwise = Pairwise::new(did.clone(), invitation.did.clone());
+            pairwise.endpoint = Some(invitation.endpoint.clone());
+            pairwise.transport_key = Some(invitation.transport_key.clone());
+            pairwise::store_pairwise(&self.wallet_service, wallet_handle, &pairwise)?;
+
+            let (my_info, _) = self.get_connection_info_local(wallet_handle, &did, &invitation.did, None)?;
+            let info = ConnectInfo {
+                endpoint: AgentCommandExecutor::_zmq_endpoint(&invitation.endpoint),
+                server_key: invitation.transport_key,
+            };
+            Ok((did, my_info, info))
+        });
+
+        let (did, my_info, info) = match res {
+            Ok(res) => res,
+            Err(err) => return self._complete_accept_invitation(cmd_id, Err(err))
+        };
+        let message_cb = match self.accept_invitation_requests.borrow_mut().get_mut(&cmd_id) {
+            Some(request) => {
+                request.my_did = Some(did);
+                request.message_cb.take()
+            }
+            None => None
+        };
+        let message_cb = match message_cb {
+            Some(message_cb) => message_cb,
+            None => return error!("Can't connect by invitation - request {} not found", cmd_id)
+        };
+
+        self.do_connect(my_info, info, Box::new(move |res| {
+            CommandExecutor::instance().send(Command::Agent(AgentCommand::AcceptInvitationConnectAck(cmd_id, res))).unwrap();
+        }), message_cb, None);
+    }
+
+    fn on_accept_invitation_connected(&self, cmd_id: i32, res: Result<i32, SovrinError>) {
+        let connection_id = match res {
+            Ok(connection_id) => connection_id,
+            Err(err) => return self._complete_accept_invitation(cmd_id, Err(err))
+        };
+        let request = match self.accept_invitation_requests.borrow_mut().remove(&cmd_id) {
+            Some(request) => request,
+            None => return error!("Can't handle AcceptInvitationConnectAck cmd - request {} not found", cmd_id)
+        };
+
+        let my_did = request.my_did.unwrap_or_default();
+        let cb = request.cb;
+        self._send_control_msg(connection_id, INVITATION_TYPE, &InvitationMsg::Accept {
+            label: request.options.label,
+            endpoint: request.options.endpoint,
+            transport_key: request.options.transport_key,
+        }, Box::new(move |res| cb(res.map(|_| (connection_id, my_did.clone())))));
+    }
+
+    fn _complete_accept_invitation(&self, cmd_id: i32, res: Result<(i32, String), SovrinError>) {
+        match self.accept_invitation_requests.borrow_mut().remove(&cmd_id) {
+            Some(request) => (request.cb)(res),
+            None => error!("Can't complete invitation accepting - request {} not found", cmd_id)
+        }
+    }
+
+    /// Handles Accept message of invitee: stores its endpoint (if sent) and reports acceptance by listener event.
+    fn on_invitation_msg(&self, connection_id: i32, msg: InvitationMsg) {
+        let (wallet_handle, my_did, their_did) = match self._get_connection_identities(connection_id) {
+            Ok(identities) => identities,
+            Err(err) => return error!("Invitation message on connection {} dropped: {}", connection_id, err)
+        };
+
+        match msg {
+            InvitationMsg::Accept { label, endpoint, transport_key } => {
+                if let Err(err) = self._on_invitation_accepted(wallet_handle, &my_did, &their_did, label.clone(), endpoint, transport_key) {
+                    return warn!("Invitation acceptance on connection {} dropped: {}", connection_id, err);
+                }
+
+                let listener_id = self.listeners.borrow().iter()
+                    .find(|&(_, listener)| listener.connections.contains_key(&connection_id))
+                    .map(|(&listener_id, _)| listener_id);
+                match listener_id {
+                    Some(listener_id) => self.on_listener_event(listener_id, ListenerEvent::InvitationAccepted {
+                        my_did: my_did,
+                        their_did: their_did,
+                        label: label,
+                    }),
+                    None => warn!("Invitation acceptance isn't reported - listener of connection {} not found", connection_id)
+                }
+            }
+        }
+    }
+
+    fn _on_invitation_accepted(&self, wallet_handle: i32, my_did: &str, their_did: &str, label: Option<String>,
+                               endpoint: Option<String>, transport_key: Option<String>) -> Result<(), SovrinError> {
+        let mut record = self._get_invitation_record(wallet_handle, my_did)?;
+        if record.state != InvitationState::Connected || record.their_did.as_ref().map(String::as_str) != Some(their_did) {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Invitation of DID {} isn't connected to DID {}", my_did, their_did))));
+        }
+
+        if let Some(endpoint) = endpoint {
+            signus::store_endpoint(&self.wallet_service, wallet_handle, their_did, &Endpoint::new(endpoint.clone(), transport_key.clone()))?;
+
+            let mut pairwise = pairwise::get_pairwise(&self.wallet_service, wallet_handle, their_did)?;
+            pairwise.endpoint = Some(endpoint);
+            pairwise.transport_key = transport_key;
+            pairwise::store_pairwise(&self.wallet_service, wallet_handle, &pairwise)?;
+        }
+
+        record.state = InvitationState::Accepted;
+        record.their_label = label;
+        self._set_invitation_record(wallet_handle, &record)
+    }
+
+    fn _get_invitation_record(&self, wallet_handle: i32, my_did: &str) -> Result<InvitationRecord, SovrinError> {
+        match self.wallet_service.get(wallet_handle, &InvitationRecord::key(my_did)) {
+            Ok(record_json) => InvitationRecord::from_json(&record_json)
+                .map_err(|_| SovrinError::CommonError(CommonError::InvalidState(format!("Invalid invitation json")))),
+            Err(WalletError::NotFound(_)) =>
+                Err(SovrinError::CommonError(CommonError::InvalidStructure(format!("Invitation of DID {} not found", my_did)))),
+            Err(err) => Err(SovrinError::WalletError(err))
+        }
+    }
+
+    fn _set_invitation_record(&self, wallet_handle: i32, record: &InvitationRecord) -> Result<(), SovrinError> {
+        let record_json = record.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize InvitationRecord: {}", err.description())))?;
+        self.wallet_service.set(wallet_handle, &InvitationRecord::key(&record.my_did), &record_json)?;
+        Ok(())
+    }
+
+    /// Returns established connection between my and their DIDs served by the wallet.
+    fn _find_connection(&self, wallet_handle: i32, my_did: &str, their_did: &str) -> Result<i32, SovrinError> {
+        for listener in self.listeners.borrow().values() {
+            if listener.identities.get(my_did) != Some(&wallet_handle) {
+                continue;
+            }
+            let connection = listener.connections.iter()
+                .find(|&(_, &(ref sender_did, ref receiver_did))| sender_did == their_did && receiver_did == my_did);
+            if let Some((&connection_id, _)) = connection {
+                return Ok(connection_id);
+            }
+        }
+
+        let out_connections = self.out_connections.borrow();
+        self.connect_identities.borrow().iter()
+            .find(|&(connection_id, &(conn_wallet_handle, ref sender_did, ref receiver_did))|
+                out_connections.contains_key(connection_id) && conn_wallet_handle == wallet_handle
+                    && sender_did == my_did && receiver_did == their_did)
+            .map(|(&connection_id, _)| connection_id)
+            .ok_or(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Connection between {} and {} isn't established", my_did, their_did))))
+    }
+}
+
+#[derive(Clone)]
+pub struct MyConnectInfo {
+    wallet_handle: i32,
+    did: String,
+    receiver_did: String,
     secret_key: String,
     public_key: String,
+    delivery: Option<DeliveryOptions>,
+}
+
+impl fmt::Debug for MyConnectInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MyConnectInfo {{ did: {:?}, receiver_did: {:?}, secret_key: hidden, public_key: {:?}, delivery: {:?} }}",
+               self.did, self.receiver_did, self.public_key, self.delivery)
+    }
+}
+
+impl Drop for MyConnectInfo {
+    fn drop(&mut self) {
+        memzero_string(&mut self.secret_key);
+    }
 }
 
 #[derive(Debug)]
@@ -431,11 +2913,3 @@ pub struct ConnectInfo {
     server_key: String,
     endpoint: String,
 }
-
-#[derive(Deserialize)]
-struct DDO {
-    verkey: String,
-    endpoint: String,
-}
-
-impl<'a> JsonDecodable<'a> for DDO {}
@@ -8,6 +8,8 @@ use errors::sovrin::SovrinError;
 use errors::common::CommonError;
 
 use services::anoncreds::AnoncredsService;
+use services::ledger::LedgerService;
+use services::ledger::types::RevocRegEntryOperationData;
 use services::pool::PoolService;
 use services::wallet::WalletService;
 use services::anoncreds::types::{
@@ -23,6 +25,7 @@ use std::rc::Rc;
 use std::collections::HashMap;
 use utils::json::{JsonDecodable, JsonEncodable};
 use std::cell::RefCell;
+use utils::crypto::memzero::memzero_string;
 
 pub enum IssuerCommand {
     CreateAndStoreClaimDefinition(
@@ -45,26 +48,30 @@ pub enum IssuerCommand {
         Box<Fn(Result<(String, String), SovrinError>) + Send>),
     RevokeClaim(
         i32, // wallet handle
+        Option<String>, // submitter did for revoc reg entry request
         i32, // claim def seq no
         i32, // revoc reg seq no
         i32, // user revoc index
-        Box<Fn(Result<String, SovrinError>) + Send>),
+        Box<Fn(Result<(String, Option<String>), SovrinError>) + Send>),
 }
 
 pub struct IssuerCommandExecutor {
     pub anoncreds_service: Rc<AnoncredsService>,
     pub pool_service: Rc<PoolService>,
-    pub wallet_service: Rc<WalletService>
+    pub wallet_service: Rc<WalletService>,
+    pub ledger_service: Rc<LedgerService>
 }
 
 impl IssuerCommandExecutor {
     pub fn new(anoncreds_service: Rc<AnoncredsService>,
                pool_service: Rc<PoolService>,
-               wallet_service: Rc<WalletService>) -> IssuerCommandExecutor {
+               wallet_service: Rc<WalletService>,
+               ledger_service: Rc<LedgerService>) -> IssuerCommandExecutor {
         IssuerCommandExecutor {
             anoncreds_service: anoncreds_service,
             pool_service: pool_service,
             wallet_service: wallet_service,
+            ledger_service: ledger_service,
         }
     }
 
@@ -84,10 +91,11 @@ impl IssuerCommandExecutor {
                 self.create_claim(wallet_handle, &claim_req_json, &claim_json,
                                   revoc_reg_seq_no, user_revoc_index, cb);
             }
-            IssuerCommand::RevokeClaim(wallet_handle, claim_def_seq_no, revoc_reg_seq_no,
+            IssuerCommand::RevokeClaim(wallet_handle, submitter_did, claim_def_seq_no, revoc_reg_seq_no,
                                        user_revoc_index, cb) => {
                 info!(target: "issuer_command_executor", "RevokeClaim command received");
-                self.revoke_claim(wallet_handle, claim_def_seq_no, revoc_reg_seq_no, user_revoc_index, cb);
+                self.revoke_claim(wallet_handle, submitter_did.as_ref().map(String::as_str),
+                                  claim_def_seq_no, revoc_reg_seq_no, user_revoc_index, cb);
             }
         };
     }
@@ -119,14 +127,16 @@ impl IssuerCommandExecutor {
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid claim definition json: {}", err.to_string())))?;
 
-        let claim_definition_private_json = ClaimDefinitionPrivate::to_json(&claim_definition_private)
+        let mut claim_definition_private_json = ClaimDefinitionPrivate::to_json(&claim_definition_private)
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid claim definition private json: {}", err.to_string())))?;
 
         let uuid = Uuid::new_v4().to_string();
 
         self.wallet_service.set(wallet_handle, &format!("claim_definition::{}", &uuid), &claim_definition_json)?;
-        self.wallet_service.set(wallet_handle, &format!("claim_definition_private::{}", &uuid), &claim_definition_private_json)?;
+        let res = self.wallet_service.set(wallet_handle, &format!("claim_definition_private::{}", &uuid), &claim_definition_private_json);
+        memzero_string(&mut claim_definition_private_json);
+        res?;
 
         Ok((claim_definition_json, uuid))
     }
@@ -162,12 +172,14 @@ impl IssuerCommandExecutor {
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid revocation registry: {}", err.to_string())))?;
 
-        let revocation_registry_private_json = RevocationRegistryPrivate::to_json(&revocation_registry_private)
+        let mut revocation_registry_private_json = RevocationRegistryPrivate::to_json(&revocation_registry_private)
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid revocation registry private: {}", err.to_string())))?;
 
         self.wallet_service.set(wallet_handle, &format!("revocation_registry::{}", &uuid), &revocation_registry_json)?;
-        self.wallet_service.set(wallet_handle, &format!("revocation_registry_private::{}", &uuid), &revocation_registry_private_json)?;
+        let res = self.wallet_service.set(wallet_handle, &format!("revocation_registry_private::{}", &uuid), &revocation_registry_private_json);
+        memzero_string(&mut revocation_registry_private_json);
+        res?;
         // TODO: change it
         let tails_dash = serde_json::to_string(&revocation_registry_private.tails_dash)
             .map_err(map_err_trace!())
@@ -201,13 +213,15 @@ impl IssuerCommandExecutor {
 
         let claim_def_uuid = self.wallet_service.get(wallet_handle, &format!("seq_no::{}", &claim_req_json.claim_def_seq_no))?;
         let claim_def_json = self.wallet_service.get(wallet_handle, &format!("claim_definition::{}", &claim_def_uuid))?;
-        let claim_def_private_json = self.wallet_service.get(wallet_handle, &format!("claim_definition_private::{}", &claim_def_uuid))?;
+        let mut claim_def_private_json = self.wallet_service.get(wallet_handle, &format!("claim_definition_private::{}", &claim_def_uuid))?;
 
         let claim_def = ClaimDefinition::from_json(&claim_def_json)
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid claim_def_json: {}", err.to_string())))?;
 
-        let claim_def_private = ClaimDefinitionPrivate::from_json(&claim_def_private_json)
+        let claim_def_private = ClaimDefinitionPrivate::from_json(&claim_def_private_json);
+        memzero_string(&mut claim_def_private_json);
+        let claim_def_private = claim_def_private
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid claim_def_private_json: {}", err.to_string())))?;
 
@@ -221,13 +235,15 @@ impl IssuerCommandExecutor {
             Some(seq_no) => {
                 let revocation_registry_uuid = self.wallet_service.get(wallet_handle, &format!("seq_no::{}", &seq_no))?;
                 let revocation_registry_json = self.wallet_service.get(wallet_handle, &format!("revocation_registry::{}", &revocation_registry_uuid))?;
-                let revocation_registry_private_json = self.wallet_service.get(wallet_handle, &format!("revocation_registry_private::{}", &revocation_registry_uuid))?;
+                let mut revocation_registry_private_json = self.wallet_service.get(wallet_handle, &format!("revocation_registry_private::{}", &revocation_registry_uuid))?;
 
                 let revocation_registry = Some(RefCell::new(RevocationRegistry::from_json(&revocation_registry_json)
                     .map_err(map_err_trace!())
                     .map_err(|err| CommonError::InvalidState(format!("Invalid revocation_registry_json: {}", err.to_string())))?));
 
-                let revocation_registry_private = Some(RevocationRegistryPrivate::from_json(&revocation_registry_private_json)
+                let revocation_registry_private = RevocationRegistryPrivate::from_json(&revocation_registry_private_json);
+                memzero_string(&mut revocation_registry_private_json);
+                let revocation_registry_private = Some(revocation_registry_private
                     .map_err(map_err_trace!())
                     .map_err(|err| CommonError::InvalidState(format!("Invalid revocation_registry_private_json: {}", err.to_string())))?);
 
@@ -267,28 +283,37 @@ impl IssuerCommandExecutor {
 
     fn revoke_claim(&self,
                     wallet_handle: i32,
+                    submitter_did: Option<&str>,
                     claim_def_seq_no: i32,
                     revoc_reg_seq_no: i32,
                     user_revoc_index: i32,
-                    cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        let result = self._revoke_claim(wallet_handle, claim_def_seq_no, revoc_reg_seq_no, user_revoc_index);
+                    cb: Box<Fn(Result<(String, Option<String>), SovrinError>) + Send>) {
+        let result = self._revoke_claim(wallet_handle, submitter_did, claim_def_seq_no, revoc_reg_seq_no, user_revoc_index);
         cb(result)
     }
 
     fn _revoke_claim(&self,
                      wallet_handle: i32,
+                     submitter_did: Option<&str>,
                      claim_def_seq_no: i32,
                      revoc_reg_seq_no: i32,
-                     user_revoc_index: i32) -> Result<String, SovrinError> {
-        let revocation_registry_uuid = self.wallet_service.get(wallet_handle, &format!("revocation_registry_uuid::{}", &revoc_reg_seq_no))?;
+                     user_revoc_index: i32) -> Result<(String, Option<String>), SovrinError> {
+        let revocation_registry_uuid = self.wallet_service.get(wallet_handle, &format!("seq_no::{}", &revoc_reg_seq_no))?;
         let revocation_registry_json = self.wallet_service.get(wallet_handle, &format!("revocation_registry::{}", &revocation_registry_uuid))?;
-        let revocation_registry_private_json = self.wallet_service.get(wallet_handle, &format!("revocation_registry_private::{}", &revocation_registry_uuid))?;
 
         let revocation_registry = RevocationRegistry::from_json(&revocation_registry_json)
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid revocation_registry_json: {}", err.to_string())))?;
 
-        let revocation_registry_private = RevocationRegistryPrivate::from_json(&revocation_registry_private_json)
+        if revocation_registry.claim_def_seq_no != claim_def_seq_no {
+            return Err(SovrinError::CommonError(CommonError::InvalidStructure(
+                format!("Revocation registry {} doesn't belong to claim definition {}", revoc_reg_seq_no, claim_def_seq_no))));
+        }
+
+        let mut revocation_registry_private_json = self.wallet_service.get(wallet_handle, &format!("revocation_registry_private::{}", &revocation_registry_uuid))?;
+        let revocation_registry_private = RevocationRegistryPrivate::from_json(&revocation_registry_private_json);
+        memzero_string(&mut revocation_registry_private_json);
+        let revocation_registry_private = revocation_registry_private
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid revocation_registry_private_json: {}", err.to_string())))?;
 
@@ -302,8 +327,23 @@ impl IssuerCommandExecutor {
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid revocation registry: {}", err.to_string())))?;
 
-        self.wallet_service.set(wallet_handle, &format!("revocation_registry_uuid::{}", &revocation_registry_uuid), &revoc_reg_update_json)?;
+        let revoc_reg_entry_request_json = match submitter_did {
+            Some(submitter_did) => {
+                let delta = RevocRegEntryOperationData::new(revocation_registry.borrow().accumulator.acc,
+                                                            Vec::new(),
+                                                            vec![user_revoc_index]);
+                let delta_json = RevocRegEntryOperationData::to_json(&delta)
+                    .map_err(map_err_trace!())
+                    .map_err(|err| CommonError::InvalidState(format!("Invalid revocation registry delta: {}", err.to_string())))?;
+
+                Some(self.ledger_service.build_revoc_reg_entry_request(submitter_did, revoc_reg_seq_no, &delta_json)?)
+            }
+            None => None
+        };
+
+        // Persist revoked registry only after ledger request is built, so invalid submitter DID doesn't revoke claim locally
+        self.wallet_service.set(wallet_handle, &format!("revocation_registry::{}", &revocation_registry_uuid), &revoc_reg_update_json)?;
 
-        Ok(revoc_reg_update_json)
+        Ok((revoc_reg_update_json, revoc_reg_entry_request_json))
     }
 }
\ No newline at end of file
@@ -7,6 +7,7 @@ use commands::anoncreds::prover::{ProverCommand, ProverCommandExecutor};
 use commands::anoncreds::verifier::{VerifierCommand, VerifierCommandExecutor};
 
 use services::anoncreds::AnoncredsService;
+use services::ledger::LedgerService;
 use services::pool::PoolService;
 use services::wallet::WalletService;
 
@@ -27,10 +28,11 @@ pub struct AnoncredsCommandExecutor {
 impl AnoncredsCommandExecutor {
     pub fn new(anoncreds_service: Rc<AnoncredsService>,
                pool_service: Rc<PoolService>,
-               wallet_service: Rc<WalletService>) -> AnoncredsCommandExecutor {
+               wallet_service: Rc<WalletService>,
+               ledger_service: Rc<LedgerService>) -> AnoncredsCommandExecutor {
         AnoncredsCommandExecutor {
             issuer_command_cxecutor: IssuerCommandExecutor::new(
-                anoncreds_service.clone(), pool_service.clone(), wallet_service.clone()),
+                anoncreds_service.clone(), pool_service.clone(), wallet_service.clone(), ledger_service.clone()),
             prover_command_cxecutor: ProverCommandExecutor::new(
                 anoncreds_service.clone(), pool_service.clone(), wallet_service.clone()),
             verifier_command_cxecutor: VerifierCommandExecutor::new(
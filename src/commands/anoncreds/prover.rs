@@ -32,6 +32,7 @@ use std::collections::HashMap;
 use utils::crypto::pair::PointG2;
 use std::cell::RefCell;
 use utils::crypto::base58::Base58;
+use utils::crypto::memzero::memzero_string;
 
 pub enum ProverCommand {
     StoreClaimOffer(
@@ -217,7 +218,10 @@ impl ProverCommandExecutor {
 
         let master_secret = self.anoncreds_service.prover.generate_master_secret()?;
 
-        self.wallet_service.set(wallet_handle, &format!("master_secret::{}", master_secret_name), &master_secret.to_dec()?)?;
+        let mut master_secret_str = master_secret.to_dec()?;
+        let res = self.wallet_service.set(wallet_handle, &format!("master_secret::{}", master_secret_name), &master_secret_str);
+        memzero_string(&mut master_secret_str);
+        res?;
 
         Ok(())
     }
@@ -239,9 +243,11 @@ impl ProverCommandExecutor {
                                        claim_offer_json: &str,
                                        claim_def_json: &str,
                                        master_secret_name: &str) -> Result<String, SovrinError> {
-        let master_secret_str = self.wallet_service.get(wallet_handle, &format!("master_secret::{}", &master_secret_name))?;
+        let mut master_secret_str = self.wallet_service.get(wallet_handle, &format!("master_secret::{}", &master_secret_name))?;
 
-        let master_secret = BigNumber::from_dec(&master_secret_str)
+        let master_secret = BigNumber::from_dec(&master_secret_str);
+        memzero_string(&mut master_secret_str);
+        let master_secret = master_secret
             .map_err(map_err_trace!())
             .map_err(|err| CommonError::InvalidState(format!("Invalid master_secret_str: {}", err.to_string())))?;
 
@@ -513,9 +519,11 @@ impl ProverCommandExecutor {
             claims.insert(claim_uuid.clone(), claim);
         }
 
-        let ms = self.wallet_service.get(wallet_handle, &format!("master_secret::{}", master_secret_name))?;
+        let mut ms_str = self.wallet_service.get(wallet_handle, &format!("master_secret::{}", master_secret_name))?;
 
-        let ms: BigNumber = BigNumber::from_dec(&ms)?;
+        let ms = BigNumber::from_dec(&ms_str);
+        memzero_string(&mut ms_str);
+        let ms: BigNumber = ms?;
 
         let mut tails: HashMap<i32, PointG2> = HashMap::new();
         if revoc_regs.len() > 0 {
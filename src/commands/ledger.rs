@@ -1,6 +1,9 @@
+extern crate time;
+
 use errors::common::CommonError;
 use errors::pool::PoolError;
 use errors::sovrin::SovrinError;
+use errors::wallet::WalletError;
 
 use services::anoncreds::AnoncredsService;
 use services::pool::PoolService;
@@ -8,9 +11,12 @@ use services::signus::SignusService;
 use services::signus::types::MyDid;
 use services::wallet::WalletService;
 use services::ledger::LedgerService;
+use services::ledger::types::{LedgerCacheOptions, LedgerCacheRecord, GetNymResultData};
 
-use utils::json::JsonDecodable;
+use utils::json::{JsonDecodable, JsonEncodable};
+use utils::sequence::SequenceUtils;
 
+use commands::{Command, CommandExecutor};
 use super::utils::check_wallet_and_pool_handles_consistency;
 
 use std::cell::RefCell;
@@ -32,6 +38,32 @@ pub enum LedgerCommand {
         i32, // cmd_id
         Result<String, PoolError>, // result json or error
     ),
+    SubmitRequestWithCache(
+        i32, // pool handle
+        i32, // wallet handle
+        String, // request json
+        Option<String>, // cache options json
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    PurgeCache(
+        i32, // wallet handle
+        Option<String>, // cache type
+        Box<Fn(Result<(), SovrinError>) + Send>),
+    ResolveDid(
+        i32, // pool handle
+        i32, // wallet handle
+        String, // did
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ResolveDidGetNymAck(
+        i32, // pool handle
+        String, // did
+        i32, // cb_id
+        Result<String, SovrinError>, // get nym reply json or error
+    ),
+    ResolveDidGetAttribAck(
+        GetNymResultData, // nym data
+        i32, // cb_id
+        Result<String, SovrinError>, // get attrib reply json or error
+    ),
     BuildGetDdoRequest(
         String, // submitter did
         String, // target did
@@ -80,10 +112,46 @@ pub enum LedgerCommand {
         String, // signature_type
         String, // origin
         Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildRevocRegDefRequest(
+        String, // submitter did
+        i32, // xref
+        String, // data
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildGetRevocRegDefRequest(
+        String, // submitter did
+        i32, // xref
+        String, // origin
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildRevocRegEntryRequest(
+        String, // submitter did
+        i32, // xref
+        String, // data
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildGetRevocRegRequest(
+        String, // submitter did
+        i32, // xref
+        Box<Fn(Result<String, SovrinError>) + Send>),
     BuildNodeRequest(
         String, // submitter did
         String, // target_did
         String, // data
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildPoolUpgradeRequest(
+        String, // submitter did
+        String, // name
+        String, // version
+        String, // action
+        String, // sha256
+        Option<u32>, // timeout
+        Option<String>, // schedule
+        Option<String>, // justification
+        bool, // reinstall
+        bool, // force
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildPoolConfigRequest(
+        String, // submitter did
+        bool, // writes
+        bool, // force
         Box<Fn(Result<String, SovrinError>) + Send>)
 }
 
@@ -95,6 +163,7 @@ pub struct LedgerCommandExecutor {
     ledger_service: Rc<LedgerService>,
 
     send_callbacks: RefCell<HashMap<i32, Box<Fn(Result<String, SovrinError>)>>>,
+    resolve_callbacks: RefCell<HashMap<i32, Box<Fn(Result<String, SovrinError>)>>>,
 }
 
 impl LedgerCommandExecutor {
@@ -110,6 +179,7 @@ impl LedgerCommandExecutor {
             wallet_service: wallet_service,
             ledger_service: ledger_service,
             send_callbacks: RefCell::new(HashMap::new()),
+            resolve_callbacks: RefCell::new(HashMap::new()),
         }
     }
 
@@ -129,6 +199,27 @@ impl LedgerCommandExecutor {
                     .expect("Expect callback to process ack command")
                     (result.map_err(SovrinError::from));
             }
+            LedgerCommand::SubmitRequestWithCache(pool_handle, wallet_handle, request_json, options_json, cb) => {
+                info!(target: "ledger_command_executor", "SubmitRequestWithCache command received");
+                self.submit_request_with_cache(pool_handle, wallet_handle, &request_json,
+                                               options_json.as_ref().map(String::as_str), cb);
+            }
+            LedgerCommand::PurgeCache(wallet_handle, cache_type, cb) => {
+                info!(target: "ledger_command_executor", "PurgeCache command received");
+                self.purge_cache(wallet_handle, cache_type.as_ref().map(String::as_str), cb);
+            }
+            LedgerCommand::ResolveDid(pool_handle, wallet_handle, did, cb) => {
+                info!(target: "ledger_command_executor", "ResolveDid command received");
+                self.resolve_did(pool_handle, wallet_handle, &did, cb);
+            }
+            LedgerCommand::ResolveDidGetNymAck(pool_handle, did, cb_id, result) => {
+                info!(target: "ledger_command_executor", "ResolveDidGetNymAck command received");
+                self.resolve_did_get_nym_ack(pool_handle, &did, cb_id, result);
+            }
+            LedgerCommand::ResolveDidGetAttribAck(nym_data, cb_id, result) => {
+                info!(target: "ledger_command_executor", "ResolveDidGetAttribAck command received");
+                self.resolve_did_get_attrib_ack(nym_data, cb_id, result);
+            }
             LedgerCommand::BuildGetDdoRequest(submitter_did, target_did, cb) => {
                 info!(target: "ledger_command_executor", "BuildGetDdoRequest command received");
                 self.build_get_ddo_request(&submitter_did, &target_did, cb);
@@ -173,10 +264,37 @@ impl LedgerCommandExecutor {
                 info!(target: "ledger_command_executor", "BuildGetClaimDefRequest command received");
                 self.build_get_claim_def_request(&submitter_did, xref, &signature_type, &origin, cb);
             }
+            LedgerCommand::BuildRevocRegDefRequest(submitter_did, xref, data, cb) => {
+                info!(target: "ledger_command_executor", "BuildRevocRegDefRequest command received");
+                self.build_revoc_reg_def_request(&submitter_did, xref, &data, cb);
+            }
+            LedgerCommand::BuildGetRevocRegDefRequest(submitter_did, xref, origin, cb) => {
+                info!(target: "ledger_command_executor", "BuildGetRevocRegDefRequest command received");
+                self.build_get_revoc_reg_def_request(&submitter_did, xref, &origin, cb);
+            }
+            LedgerCommand::BuildRevocRegEntryRequest(submitter_did, xref, data, cb) => {
+                info!(target: "ledger_command_executor", "BuildRevocRegEntryRequest command received");
+                self.build_revoc_reg_entry_request(&submitter_did, xref, &data, cb);
+            }
+            LedgerCommand::BuildGetRevocRegRequest(submitter_did, xref, cb) => {
+                info!(target: "ledger_command_executor", "BuildGetRevocRegRequest command received");
+                self.build_get_revoc_reg_request(&submitter_did, xref, cb);
+            }
             LedgerCommand::BuildNodeRequest(submitter_did, target_did, data, cb) => {
                 info!(target: "ledger_command_executor", "BuildNodeRequest command received");
                 self.build_node_key_request(&submitter_did, &target_did, &data, cb);
             }
+            LedgerCommand::BuildPoolUpgradeRequest(submitter_did, name, version, action, sha256, timeout, schedule, justification, reinstall, force, cb) => {
+                info!(target: "ledger_command_executor", "BuildPoolUpgradeRequest command received");
+                self.build_pool_upgrade_request(&submitter_did, &name, &version, &action, &sha256, timeout,
+                                                schedule.as_ref().map(String::as_str),
+                                                justification.as_ref().map(String::as_str),
+                                                reinstall, force, cb);
+            }
+            LedgerCommand::BuildPoolConfigRequest(submitter_did, writes, force, cb) => {
+                info!(target: "ledger_command_executor", "BuildPoolConfigRequest command received");
+                self.build_pool_config_request(&submitter_did, writes, force, cb);
+            }
         };
     }
 
@@ -210,7 +328,7 @@ impl LedgerCommandExecutor {
     fn submit_request(&self,
                       handle: i32,
                       request_json: &str,
-                      cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+                      cb: Box<Fn(Result<String, SovrinError>)>) {
         let x: Result<i32, PoolError> = self.pool_service.send_tx(handle, request_json);
         match x {
             Ok(cmd_id) => { self.send_callbacks.borrow_mut().insert(cmd_id, cb); }
@@ -218,6 +336,192 @@ impl LedgerCommandExecutor {
         };
     }
 
+    fn submit_request_with_cache(&self,
+                                 pool_handle: i32,
+                                 wallet_handle: i32,
+                                 request_json: &str,
+                                 options_json: Option<&str>,
+                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
+                                                   wallet_handle, pool_handle, cb);
+
+        let (options, cache_key) = match self._get_cache_options_and_key(request_json, options_json) {
+            Ok(res) => res,
+            Err(err) => return cb(Err(err))
+        };
+
+        let cache_key = match cache_key {
+            Some(cache_key) => cache_key,
+            None => return self.submit_request(pool_handle, request_json, cb)
+        };
+
+        if options.bypass {
+            return self.submit_request(pool_handle, request_json, cb);
+        }
+
+        if !options.refresh {
+            match self._get_cached_reply(wallet_handle, &cache_key) {
+                Ok(Some(reply)) => return cb(self.ledger_service.set_reply_req_id(&reply, request_json)
+                    .map_err(SovrinError::CommonError)),
+                Ok(None) => {}
+                Err(err) => return cb(Err(err))
+            }
+        }
+
+        let wallet_service = self.wallet_service.clone();
+        let ledger_service = self.ledger_service.clone();
+        let (key, _) = cache_key;
+        self.submit_request(pool_handle, request_json, Box::new(move |result| {
+            if let Ok(ref reply) = result {
+                if ledger_service.is_cacheable_reply(reply) {
+                    let record = LedgerCacheRecord::new(reply.clone(), time::get_time().sec);
+                    let res = LedgerCacheRecord::to_json(&record)
+                        .map_err(|err| SovrinError::CommonError(CommonError::InvalidState(format!("Can't serialize cache record: {}", err.to_string()))))
+                        .and_then(|record_json| wallet_service.set(wallet_handle, &key, &record_json)
+                            .map_err(SovrinError::from));
+                    if let Err(err) = res {
+                        warn!("Can't store ledger reply in cache: {:?}", err);
+                    }
+                }
+            }
+            cb(result)
+        }));
+    }
+
+    fn _get_cache_options_and_key(&self,
+                                  request_json: &str,
+                                  options_json: Option<&str>) -> Result<(LedgerCacheOptions, Option<(String, i64)>), SovrinError> {
+        let options = match options_json {
+            Some(options_json) => LedgerCacheOptions::from_json(options_json)
+                .map_err(|err| CommonError::InvalidStructure(format!("Invalid cache options json: {}", err.to_string())))?,
+            None => LedgerCacheOptions::default()
+        };
+
+        let cache_key = self.ledger_service.get_cache_key(request_json)?
+            .map(|(key, ttl)| (key, options.ttl.unwrap_or(ttl)));
+
+        Ok((options, cache_key))
+    }
+
+    fn _get_cached_reply(&self, wallet_handle: i32, cache_key: &(String, i64)) -> Result<Option<String>, SovrinError> {
+        let &(ref key, ttl) = cache_key;
+
+        let record_json = match self.wallet_service.get(wallet_handle, key) {
+            Ok(record_json) => record_json,
+            Err(WalletError::NotFound(_)) => return Ok(None),
+            Err(err) => return Err(SovrinError::from(err))
+        };
+
+        let record = LedgerCacheRecord::from_json(&record_json)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid cache record json: {}", err.to_string())))?;
+
+        if time::get_time().sec - record.time_cached > ttl {
+            return Ok(None);
+        }
+
+        Ok(Some(record.reply))
+    }
+
+    fn purge_cache(&self,
+                   wallet_handle: i32,
+                   cache_type: Option<&str>,
+                   cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        cb(self._purge_cache(wallet_handle, cache_type))
+    }
+
+    fn _purge_cache(&self,
+                    wallet_handle: i32,
+                    cache_type: Option<&str>) -> Result<(), SovrinError> {
+        let prefix = self.ledger_service.get_cache_prefix(cache_type)?;
+
+        for (key, _) in self.wallet_service.list(wallet_handle, &prefix)? {
+            self.wallet_service.delete_record(wallet_handle, &key)?;
+        }
+
+        Ok(())
+    }
+
+    fn resolve_did(&self,
+                   pool_handle: i32,
+                   wallet_handle: i32,
+                   did: &str,
+                   cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
+                                                   wallet_handle, pool_handle, cb);
+
+        let get_nym_request = match self.ledger_service.build_get_nym_request(did, did) {
+            Ok(get_nym_request) => get_nym_request,
+            Err(err) => return cb(Err(SovrinError::CommonError(err)))
+        };
+
+        let cb_id: i32 = SequenceUtils::get_next_id();
+        self.resolve_callbacks.borrow_mut().insert(cb_id, cb);
+
+        let did = did.to_string();
+        self.submit_request(pool_handle, &get_nym_request, Box::new(move |result| {
+            CommandExecutor::instance()
+                .send(Command::Ledger(LedgerCommand::ResolveDidGetNymAck(
+                    pool_handle,
+                    did.clone(),
+                    cb_id,
+                    result
+                ))).unwrap();
+        }));
+    }
+
+    fn resolve_did_get_nym_ack(&self,
+                               pool_handle: i32,
+                               did: &str,
+                               cb_id: i32,
+                               result: Result<String, SovrinError>) {
+        let res = result
+            .and_then(|get_nym_reply| self.ledger_service.parse_get_nym_reply(&get_nym_reply)
+                .map_err(SovrinError::from))
+            .and_then(|nym_data| {
+                let get_attrib_request = self.ledger_service.build_get_attrib_request(did, did, "endpoint")?;
+                Ok((nym_data, get_attrib_request))
+            });
+
+        match res {
+            Ok((nym_data, get_attrib_request)) =>
+                self.submit_request(pool_handle, &get_attrib_request, Box::new(move |result| {
+                    CommandExecutor::instance()
+                        .send(Command::Ledger(LedgerCommand::ResolveDidGetAttribAck(
+                            nym_data.clone(),
+                            cb_id,
+                            result
+                        ))).unwrap();
+                })),
+            Err(err) => self._resolve_did_done(cb_id, Err(err))
+        }
+    }
+
+    fn resolve_did_get_attrib_ack(&self,
+                                  nym_data: GetNymResultData,
+                                  cb_id: i32,
+                                  result: Result<String, SovrinError>) {
+        let res = result
+            .and_then(|get_attrib_reply| self.ledger_service.parse_get_attrib_endpoint_reply(&get_attrib_reply)
+                .map_err(SovrinError::from))
+            .and_then(|endpoint| {
+                let ddo = self.ledger_service.build_ddo(nym_data, endpoint);
+                let ddo_json = ddo.to_json()
+                    .map_err(|err| CommonError::InvalidState(format!("Can't serialize DDO: {}", err.to_string())))?;
+                Ok(ddo_json)
+            });
+
+        self._resolve_did_done(cb_id, res)
+    }
+
+    fn _resolve_did_done(&self, cb_id: i32, result: Result<String, SovrinError>) {
+        let cb = self.resolve_callbacks.borrow_mut().remove(&cb_id);
+
+        match cb {
+            Some(cb) => cb(result),
+            None => error!("Can't process Ledger::ResolveDid for handle {} - appropriate callback not found!", cb_id)
+        }
+    }
+
     fn build_get_ddo_request(&self,
                              submitter_did: &str,
                              target_did: &str,
@@ -322,6 +626,48 @@ impl LedgerCommandExecutor {
         ).map_err(|err| SovrinError::CommonError(err)))
     }
 
+    fn build_revoc_reg_def_request(&self,
+                                   submitter_did: &str,
+                                   xref: i32,
+                                   data: &str,
+                                   cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_revoc_reg_def_request(submitter_did,
+                                                           xref,
+                                                           data
+        ).map_err(|err| SovrinError::CommonError(err)))
+    }
+
+    fn build_get_revoc_reg_def_request(&self,
+                                       submitter_did: &str,
+                                       xref: i32,
+                                       origin: &str,
+                                       cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_get_revoc_reg_def_request(submitter_did,
+                                                               xref,
+                                                               origin
+        ).map_err(|err| SovrinError::CommonError(err)))
+    }
+
+    fn build_revoc_reg_entry_request(&self,
+                                     submitter_did: &str,
+                                     xref: i32,
+                                     data: &str,
+                                     cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_revoc_reg_entry_request(submitter_did,
+                                                             xref,
+                                                             data
+        ).map_err(|err| SovrinError::CommonError(err)))
+    }
+
+    fn build_get_revoc_reg_request(&self,
+                                   submitter_did: &str,
+                                   xref: i32,
+                                   cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_get_revoc_reg_request(submitter_did,
+                                                           xref
+        ).map_err(|err| SovrinError::CommonError(err)))
+    }
+
     fn build_node_key_request(&self,
                               submitter_did: &str,
                               target_did: &str,
@@ -332,4 +678,40 @@ impl LedgerCommandExecutor {
                                                   data
         ).map_err(|err| SovrinError::CommonError(err)))
     }
+
+    fn build_pool_upgrade_request(&self,
+                                  submitter_did: &str,
+                                  name: &str,
+                                  version: &str,
+                                  action: &str,
+                                  sha256: &str,
+                                  timeout: Option<u32>,
+                                  schedule: Option<&str>,
+                                  justification: Option<&str>,
+                                  reinstall: bool,
+                                  force: bool,
+                                  cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_pool_upgrade_request(submitter_did,
+                                                          name,
+                                                          version,
+                                                          action,
+                                                          sha256,
+                                                          timeout,
+                                                          schedule,
+                                                          justification,
+                                                          reinstall,
+                                                          force
+        ).map_err(|err| SovrinError::CommonError(err)))
+    }
+
+    fn build_pool_config_request(&self,
+                                 submitter_did: &str,
+                                 writes: bool,
+                                 force: bool,
+                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_pool_config_request(submitter_did,
+                                                         writes,
+                                                         force
+        ).map_err(|err| SovrinError::CommonError(err)))
+    }
 }
\ No newline at end of file
@@ -7,6 +7,7 @@ pub mod agent;
 #[allow(unused_variables)] /* FIXME */
 pub mod anoncreds;
 pub mod ledger;
+pub mod pairwise;
 pub mod pool;
 pub mod signus;
 pub mod wallet;
@@ -14,6 +15,7 @@ pub mod wallet;
 use commands::agent::{AgentCommand, AgentCommandExecutor};
 use commands::anoncreds::{AnoncredsCommand, AnoncredsCommandExecutor};
 use commands::ledger::{LedgerCommand, LedgerCommandExecutor};
+use commands::pairwise::{PairwiseCommand, PairwiseCommandExecutor};
 use commands::pool::{PoolCommand, PoolCommandExecutor};
 use commands::signus::{SignusCommand, SignusCommandExecutor};
 use commands::wallet::{WalletCommand, WalletCommandExecutor};
@@ -38,6 +40,7 @@ pub enum Command {
     Agent(AgentCommand),
     Anoncreds(AnoncredsCommand),
     Ledger(LedgerCommand),
+    Pairwise(PairwiseCommand),
     Pool(PoolCommand),
     Signus(SignusCommand),
     Wallet(WalletCommand)
@@ -74,9 +77,10 @@ impl CommandExecutor {
                 let signus_service = Rc::new(SignusService::new());
                 let ledger_service = Rc::new(LedgerService::new());
 
-                let agent_command_executor = AgentCommandExecutor::new(agent_service.clone(), ledger_service.clone(), pool_service.clone(), wallet_service.clone());
-                let anoncreds_command_executor = AnoncredsCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), wallet_service.clone());
+                let agent_command_executor = AgentCommandExecutor::new(agent_service.clone(), ledger_service.clone(), pool_service.clone(), signus_service.clone(), wallet_service.clone());
+                let anoncreds_command_executor = AnoncredsCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), wallet_service.clone(), ledger_service.clone());
                 let ledger_command_executor = LedgerCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), signus_service.clone(), wallet_service.clone(), ledger_service.clone());
+                let pairwise_command_executor = PairwiseCommandExecutor::new(wallet_service.clone());
                 let pool_command_executor = PoolCommandExecutor::new(pool_service.clone());
                 let signus_command_executor = SignusCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), wallet_service.clone(), signus_service.clone(), ledger_service.clone());
                 let wallet_command_executor = WalletCommandExecutor::new(wallet_service.clone());
@@ -95,6 +99,10 @@ impl CommandExecutor {
                             info!(target: "command_executor", "LedgerCommand command received");
                             ledger_command_executor.execute(cmd);
                         }
+                        Ok(Command::Pairwise(cmd)) => {
+                            info!(target: "command_executor", "PairwiseCommand command received");
+                            pairwise_command_executor.execute(cmd);
+                        }
                         Ok(Command::Pool(cmd)) => {
                             info!(target: "command_executor", "PoolCommand command received");
                             pool_command_executor.execute(cmd);
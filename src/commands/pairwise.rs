@@ -0,0 +1,150 @@
+extern crate serde_json;
+
+use errors::common::CommonError;
+use errors::sovrin::SovrinError;
+use errors::wallet::WalletError;
+use services::pairwise::types::{Pairwise, PairwiseInfo};
+use services::wallet::WalletService;
+use utils::json::{JsonDecodable, JsonEncodable};
+
+use std::error::Error;
+use std::rc::Rc;
+
+pub enum PairwiseCommand {
+    CreatePairwise(
+        i32, // wallet handle
+        String, // their did
+        String, // my did
+        Option<String>, // pairwise info json
+        Box<Fn(Result<(), SovrinError>) + Send>),
+    GetPairwise(
+        i32, // wallet handle
+        String, // their did
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ListPairwise(
+        i32, // wallet handle
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    UpdatePairwise(
+        i32, // wallet handle
+        String, // their did
+        String, // pairwise info json
+        Box<Fn(Result<(), SovrinError>) + Send>),
+    DeletePairwise(
+        i32, // wallet handle
+        String, // their did
+        Box<Fn(Result<(), SovrinError>) + Send>)
+}
+
+pub struct PairwiseCommandExecutor {
+    wallet_service: Rc<WalletService>
+}
+
+impl PairwiseCommandExecutor {
+    pub fn new(wallet_service: Rc<WalletService>) -> PairwiseCommandExecutor {
+        PairwiseCommandExecutor {
+            wallet_service: wallet_service
+        }
+    }
+
+    pub fn execute(&self, command: PairwiseCommand) {
+        match command {
+            PairwiseCommand::CreatePairwise(wallet_handle, their_did, my_did, info_json, cb) => {
+                info!(target: "pairwise_command_executor", "CreatePairwise command received");
+                cb(self.create_pairwise(wallet_handle, &their_did, &my_did, info_json.as_ref().map(String::as_str)));
+            }
+            PairwiseCommand::GetPairwise(wallet_handle, their_did, cb) => {
+                info!(target: "pairwise_command_executor", "GetPairwise command received");
+                cb(self.get_pairwise(wallet_handle, &their_did));
+            }
+            PairwiseCommand::ListPairwise(wallet_handle, cb) => {
+                info!(target: "pairwise_command_executor", "ListPairwise command received");
+                cb(self.list_pairwise(wallet_handle));
+            }
+            PairwiseCommand::UpdatePairwise(wallet_handle, their_did, info_json, cb) => {
+                info!(target: "pairwise_command_executor", "UpdatePairwise command received");
+                cb(self.update_pairwise(wallet_handle, &their_did, &info_json));
+            }
+            PairwiseCommand::DeletePairwise(wallet_handle, their_did, cb) => {
+                info!(target: "pairwise_command_executor", "DeletePairwise command received");
+                cb(self.delete_pairwise(wallet_handle, &their_did));
+            }
+        };
+    }
+
+    fn create_pairwise(&self, wallet_handle: i32, their_did: &str, my_did: &str, info_json: Option<&str>) -> Result<(), SovrinError> {
+        let info = match info_json {
+            Some(info_json) => PairwiseCommandExecutor::_parse_info(info_json)?,
+            None => PairwiseInfo::default()
+        };
+
+        self.wallet_service.get(wallet_handle, &format!("my_did::{}", my_did))?;
+        self.wallet_service.get(wallet_handle, &format!("their_did::{}", their_did))?;
+
+        match self.wallet_service.get(wallet_handle, &Pairwise::key(their_did)) {
+            Ok(_) => return Err(SovrinError::CommonError(
+                CommonError::InvalidStructure(format!("Pairwise with DID {} already exists", their_did)))),
+            Err(WalletError::NotFound(_)) => {}
+            Err(err) => return Err(SovrinError::WalletError(err))
+        }
+
+        let mut pairwise = Pairwise::new(my_did.to_string(), their_did.to_string());
+        pairwise.update(info);
+        store_pairwise(&self.wallet_service, wallet_handle, &pairwise)
+    }
+
+    fn get_pairwise(&self, wallet_handle: i32, their_did: &str) -> Result<String, SovrinError> {
+        get_pairwise(&self.wallet_service, wallet_handle, their_did)?
+            .to_json()
+            .map_err(|err| SovrinError::CommonError(
+                CommonError::InvalidState(format!("Can't serialize Pairwise: {}", err.description()))))
+    }
+
+    fn list_pairwise(&self, wallet_handle: i32) -> Result<String, SovrinError> {
+        let mut pairwise_list: Vec<Pairwise> = Vec::new();
+
+        for (_, pairwise_json) in self.wallet_service.list(wallet_handle, "pairwise::")? {
+            pairwise_list.push(Pairwise::from_json(&pairwise_json)
+                .map_err(|err| CommonError::InvalidState(format!("Invalid pairwise json: {}", err.description())))?);
+        }
+
+        let pairwise_list_json = serde_json::to_string(&pairwise_list)
+            .map_err(|err|
+                CommonError::InvalidState(format!("Can't serialize list of pairwise: {}", err.description())))?;
+        Ok(pairwise_list_json)
+    }
+
+    fn update_pairwise(&self, wallet_handle: i32, their_did: &str, info_json: &str) -> Result<(), SovrinError> {
+        let info = PairwiseCommandExecutor::_parse_info(info_json)?;
+
+        let mut pairwise = get_pairwise(&self.wallet_service, wallet_handle, their_did)?;
+        pairwise.update(info);
+        store_pairwise(&self.wallet_service, wallet_handle, &pairwise)
+    }
+
+    fn delete_pairwise(&self, wallet_handle: i32, their_did: &str) -> Result<(), SovrinError> {
+        get_pairwise(&self.wallet_service, wallet_handle, their_did)?;
+        self.wallet_service.delete_record(wallet_handle, &Pairwise::key(their_did))?;
+        Ok(())
+    }
+
+    fn _parse_info(info_json: &str) -> Result<PairwiseInfo, SovrinError> {
+        PairwiseInfo::from_json(info_json)
+            .map_err(|err| SovrinError::CommonError(
+                CommonError::InvalidStructure(format!("Invalid pairwise info json: {}", err.description()))))
+    }
+}
+
+/// Returns pairwise with their DID, WalletNotFound error if it isn't stored in the wallet.
+pub fn get_pairwise(wallet_service: &WalletService, wallet_handle: i32, their_did: &str) -> Result<Pairwise, SovrinError> {
+    let pairwise_json = wallet_service.get(wallet_handle, &Pairwise::key(their_did))?;
+    Pairwise::from_json(&pairwise_json)
+        .map_err(|err| SovrinError::CommonError(
+            CommonError::InvalidState(format!("Invalid pairwise json: {}", err.description()))))
+}
+
+pub fn store_pairwise(wallet_service: &WalletService, wallet_handle: i32, pairwise: &Pairwise) -> Result<(), SovrinError> {
+    let pairwise_json = pairwise.to_json()
+        .map_err(|err| CommonError::InvalidState(format!("Can't serialize Pairwise: {}", err.description())))?;
+    wallet_service.set(wallet_handle, &Pairwise::key(&pairwise.their_did), &pairwise_json)?;
+    Ok(())
+}
@@ -1,15 +1,18 @@
+extern crate serde_json;
+
 use utils::json::{JsonDecodable, JsonEncodable};
 use errors::signus::SignusError;
 use errors::common::CommonError;
 use errors::wallet::WalletError;
 use errors::sovrin::SovrinError;
-use services::signus::types::{MyDidInfo, MyKyesInfo, MyDid, TheirDidInfo, TheirDid};
-use services::ledger::types::{Reply, GetNymResultData, GetNymReplyResult};
+use errors::ledger::LedgerError;
+use services::signus::types::{Envelope, KeyInfo, Key, MyDidInfo, MyKyesInfo, MyDid, MyDidWithMeta, TheirDidInfo, TheirDid, TheirDidWithMeta};
 use services::anoncreds::AnoncredsService;
 use services::pool::PoolService;
 use services::wallet::WalletService;
 use services::signus::SignusService;
 use services::ledger::LedgerService;
+use services::ledger::types::Endpoint;
 
 use std::error::Error;
 use std::rc::Rc;
@@ -20,6 +23,8 @@ use commands::ledger::{LedgerCommand};
 use commands::{Command, CommandExecutor};
 use std::collections::HashMap;
 use utils::sequence::SequenceUtils;
+use utils::crypto::base58::Base58;
+use utils::crypto::verkey_builder::build_full_verkey;
 
 use super::utils::check_wallet_and_pool_handles_consistency;
 
@@ -33,6 +38,27 @@ pub enum SignusCommand {
         String, // identity json
         String, // did
         Box<Fn(Result<(String, String), SovrinError>) + Send>),
+    ReplaceKeysStart(
+        i32, // wallet handle
+        String, // identity json
+        String, // did
+        Box<Fn(Result<(String, String), SovrinError>) + Send>),
+    ReplaceKeysApply(
+        i32, // wallet handle
+        String, // did
+        Box<Fn(Result<(), SovrinError>) + Send>),
+    ReplaceKeysOnLedger(
+        i32, // wallet handle
+        i32, // pool handle
+        String, // identity json
+        String, // did
+        Box<Fn(Result<(String, String), SovrinError>) + Send>),
+    ReplaceKeysOnLedgerAck(
+        i32, // wallet handle
+        String, // did
+        i32, // cb_id
+        Result<String, SovrinError> // nym reply json or error
+    ),
     StoreTheirDid(
         i32, // wallet handle
         String, // identity json
@@ -42,6 +68,16 @@ pub enum SignusCommand {
         String, // did
         String, // msg
         Box<Fn(Result<String, SovrinError>) + Send>),
+    SignDetached(
+        i32, // wallet handle
+        String, // did
+        Vec<u8>, // msg
+        Box<Fn(Result<Vec<u8>, SovrinError>) + Send>),
+    SignJws(
+        i32, // wallet handle
+        String, // did
+        Vec<u8>, // payload
+        Box<Fn(Result<String, SovrinError>) + Send>),
     //TODO divide on two commands
     VerifySignature(
         i32, // wallet handle
@@ -74,7 +110,105 @@ pub enum SignusCommand {
         String, // did
         String, // encrypted msg
         String, // nonce
-        Box<Fn(Result<String, SovrinError>) + Send>)
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    EncryptSealed(
+        i32, // wallet handle
+        i32, // pool handle
+        String, // did
+        String, // msg
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    EncryptSealedGetNymAck(
+        i32, // wallet handle
+        String, // msg
+        i32, // cb_id
+        Result<String, SovrinError> // get nym reply json or error
+    ),
+    DecryptSealed(
+        i32, // wallet handle
+        String, // my_did
+        String, // encrypted msg
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    EncryptEnvelope(
+        i32, // wallet handle
+        String, // recipient dids json
+        String, // msg
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    DecryptEnvelope(
+        i32, // wallet handle
+        String, // my_did
+        String, // envelope json
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ListMyDidsWithMeta(
+        i32, // wallet handle
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ListTheirDidsWithMeta(
+        i32, // wallet handle
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    SetDidMetadata(
+        i32, // wallet handle
+        String, // did
+        String, // metadata
+        Box<Fn(Result<(), SovrinError>) + Send>),
+    GetDidMetadata(
+        i32, // wallet handle
+        String, // did
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    CreateKey(
+        i32, // wallet handle
+        String, // key json
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    SignByKey(
+        i32, // wallet handle
+        String, // verkey
+        String, // msg
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    VerifyByKey(
+        String, // verkey
+        String, // signed message
+        Box<Fn(Result<bool, SovrinError>) + Send>),
+    VerifyDetachedByKey(
+        String, // verkey
+        Vec<u8>, // msg
+        Vec<u8>, // signature
+        Box<Fn(Result<bool, SovrinError>) + Send>),
+    EncryptByKey(
+        i32, // wallet handle
+        String, // my verkey
+        String, // their verkey
+        String, // msg
+        Box<Fn(Result<(String, String), SovrinError>) + Send>),
+    DecryptByKey(
+        i32, // wallet handle
+        String, // my verkey
+        String, // their verkey
+        String, // encrypted msg
+        String, // nonce
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    SetEndpointForDid(
+        i32, // wallet handle
+        Option<i32>, // pool handle
+        String, // did
+        String, // address
+        Option<String>, // transport key
+        Box<Fn(Result<(), SovrinError>) + Send>),
+    SetEndpointForDidAck(
+        i32, // wallet handle
+        String, // did
+        Endpoint, // endpoint
+        i32, // cb_id
+        Result<String, SovrinError> // attrib reply json or error
+    ),
+    GetEndpointForDid(
+        i32, // wallet handle
+        Option<i32>, // pool handle
+        String, // did
+        Box<Fn(Result<(String, Option<String>), SovrinError>) + Send>),
+    GetEndpointForDidGetAttribAck(
+        i32, // wallet handle
+        String, // did
+        i32, // cb_id
+        Result<String, SovrinError> // get attrib reply json or error
+    )
 }
 
 pub struct SignusCommandExecutor {
@@ -85,6 +219,10 @@ pub struct SignusCommandExecutor {
     ledger_service: Rc<LedgerService>,
     verify_callbacks: RefCell<HashMap<i32, Box<Fn(Result<bool, SovrinError>)>>>,
     encrypt_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(String, String), SovrinError>)>>>,
+    encrypt_sealed_callbacks: RefCell<HashMap<i32, Box<Fn(Result<String, SovrinError>)>>>,
+    replace_keys_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(String, String), SovrinError>)>>>,
+    set_endpoint_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(), SovrinError>)>>>,
+    get_endpoint_callbacks: RefCell<HashMap<i32, Box<Fn(Result<(String, Option<String>), SovrinError>)>>>,
 
 }
 
@@ -102,6 +240,10 @@ impl SignusCommandExecutor {
             ledger_service: ledger_service,
             verify_callbacks: RefCell::new(HashMap::new()),
             encrypt_callbacks: RefCell::new(HashMap::new()),
+            encrypt_sealed_callbacks: RefCell::new(HashMap::new()),
+            replace_keys_callbacks: RefCell::new(HashMap::new()),
+            set_endpoint_callbacks: RefCell::new(HashMap::new()),
+            get_endpoint_callbacks: RefCell::new(HashMap::new()),
         }
     }
 
@@ -115,6 +257,22 @@ impl SignusCommandExecutor {
                 info!(target: "signus_command_executor", "ReplaceKeys command received");
                 self.replace_keys(wallet_handle, &identity_json, &did, cb);
             }
+            SignusCommand::ReplaceKeysStart(wallet_handle, identity_json, did, cb) => {
+                info!(target: "signus_command_executor", "ReplaceKeysStart command received");
+                self.replace_keys_start(wallet_handle, &identity_json, &did, cb);
+            }
+            SignusCommand::ReplaceKeysApply(wallet_handle, did, cb) => {
+                info!(target: "signus_command_executor", "ReplaceKeysApply command received");
+                self.replace_keys_apply(wallet_handle, &did, cb);
+            }
+            SignusCommand::ReplaceKeysOnLedger(wallet_handle, pool_handle, identity_json, did, cb) => {
+                info!(target: "signus_command_executor", "ReplaceKeysOnLedger command received");
+                self.replace_keys_on_ledger(wallet_handle, pool_handle, &identity_json, &did, cb);
+            }
+            SignusCommand::ReplaceKeysOnLedgerAck(wallet_handle, did, cb_id, result) => {
+                info!(target: "signus_command_executor", "ReplaceKeysOnLedgerAck command received");
+                self.replace_keys_on_ledger_ack(wallet_handle, &did, cb_id, result);
+            }
             SignusCommand::StoreTheirDid(wallet_handle, identity_json, cb) => {
                 info!(target: "signus_command_executor", "StoreTheirDid command received");
                 self.store_their_did(wallet_handle, &identity_json, cb);
@@ -123,6 +281,14 @@ impl SignusCommandExecutor {
                 info!(target: "signus_command_executor", "Sign command received");
                 self.sign(wallet_handle, &did, &msg, cb);
             }
+            SignusCommand::SignDetached(wallet_handle, did, msg, cb) => {
+                info!(target: "signus_command_executor", "SignDetached command received");
+                self.sign_detached(wallet_handle, &did, &msg, cb);
+            }
+            SignusCommand::SignJws(wallet_handle, did, payload, cb) => {
+                info!(target: "signus_command_executor", "SignJws command received");
+                self.sign_jws(wallet_handle, &did, &payload, cb);
+            }
             SignusCommand::VerifySignature(wallet_handle, pool_handle, did, signed_msg, cb) => {
                 info!(target: "signus_command_executor", "VerifySignature command received");
                 self.verify_signature(wallet_handle, pool_handle, &did, &signed_msg, cb);
@@ -143,6 +309,83 @@ impl SignusCommandExecutor {
                 info!(target: "signus_command_executor", "Decrypt command received");
                 self.decrypt(wallet_handle, &my_did, &did, &encrypted_msg, &nonce, cb);
             }
+            SignusCommand::EncryptSealed(wallet_handle, pool_handle, did, msg, cb) => {
+                info!(target: "signus_command_executor", "EncryptSealed command received");
+                self.encrypt_sealed(wallet_handle, pool_handle, &did, &msg, cb);
+            }
+            SignusCommand::EncryptSealedGetNymAck(wallet_handle, msg, cb_id, result) => {
+                info!(target: "signus_command_executor", "EncryptSealedGetNymAck command received");
+                self.encrypt_sealed_get_nym_ack(wallet_handle, &msg, cb_id, result);
+            }
+            SignusCommand::DecryptSealed(wallet_handle, my_did, encrypted_msg, cb) => {
+                info!(target: "signus_command_executor", "DecryptSealed command received");
+                self.decrypt_sealed(wallet_handle, &my_did, &encrypted_msg, cb);
+            }
+            SignusCommand::EncryptEnvelope(wallet_handle, dids_json, msg, cb) => {
+                info!(target: "signus_command_executor", "EncryptEnvelope command received");
+                self.encrypt_envelope(wallet_handle, &dids_json, &msg, cb);
+            }
+            SignusCommand::DecryptEnvelope(wallet_handle, my_did, envelope_json, cb) => {
+                info!(target: "signus_command_executor", "DecryptEnvelope command received");
+                self.decrypt_envelope(wallet_handle, &my_did, &envelope_json, cb);
+            }
+            SignusCommand::ListMyDidsWithMeta(wallet_handle, cb) => {
+                info!(target: "signus_command_executor", "ListMyDidsWithMeta command received");
+                self.list_my_dids_with_meta(wallet_handle, cb);
+            }
+            SignusCommand::ListTheirDidsWithMeta(wallet_handle, cb) => {
+                info!(target: "signus_command_executor", "ListTheirDidsWithMeta command received");
+                self.list_their_dids_with_meta(wallet_handle, cb);
+            }
+            SignusCommand::SetDidMetadata(wallet_handle, did, metadata, cb) => {
+                info!(target: "signus_command_executor", "SetDidMetadata command received");
+                self.set_did_metadata(wallet_handle, &did, &metadata, cb);
+            }
+            SignusCommand::GetDidMetadata(wallet_handle, did, cb) => {
+                info!(target: "signus_command_executor", "GetDidMetadata command received");
+                self.get_did_metadata(wallet_handle, &did, cb);
+            }
+            SignusCommand::CreateKey(wallet_handle, key_json, cb) => {
+                info!(target: "signus_command_executor", "CreateKey command received");
+                self.create_key(wallet_handle, &key_json, cb);
+            }
+            SignusCommand::SignByKey(wallet_handle, verkey, msg, cb) => {
+                info!(target: "signus_command_executor", "SignByKey command received");
+                self.sign_by_key(wallet_handle, &verkey, &msg, cb);
+            }
+            SignusCommand::VerifyByKey(verkey, signed_msg, cb) => {
+                info!(target: "signus_command_executor", "VerifyByKey command received");
+                self.verify_by_key(&verkey, &signed_msg, cb);
+            }
+            SignusCommand::VerifyDetachedByKey(verkey, msg, signature, cb) => {
+                info!(target: "signus_command_executor", "VerifyDetachedByKey command received");
+                self.verify_detached_by_key(&verkey, &msg, &signature, cb);
+            }
+            SignusCommand::EncryptByKey(wallet_handle, my_verkey, their_verkey, msg, cb) => {
+                info!(target: "signus_command_executor", "EncryptByKey command received");
+                self.encrypt_by_key(wallet_handle, &my_verkey, &their_verkey, &msg, cb);
+            }
+            SignusCommand::DecryptByKey(wallet_handle, my_verkey, their_verkey, encrypted_msg, nonce, cb) => {
+                info!(target: "signus_command_executor", "DecryptByKey command received");
+                self.decrypt_by_key(wallet_handle, &my_verkey, &their_verkey, &encrypted_msg, &nonce, cb);
+            }
+            SignusCommand::SetEndpointForDid(wallet_handle, pool_handle, did, address, transport_key, cb) => {
+                info!(target: "signus_command_executor", "SetEndpointForDid command received");
+                self.set_endpoint_for_did(wallet_handle, pool_handle, &did, &address,
+                                          transport_key.as_ref().map(String::as_str), cb);
+            }
+            SignusCommand::SetEndpointForDidAck(wallet_handle, did, endpoint, cb_id, result) => {
+                info!(target: "signus_command_executor", "SetEndpointForDidAck command received");
+                self.set_endpoint_for_did_ack(wallet_handle, &did, &endpoint, cb_id, result);
+            }
+            SignusCommand::GetEndpointForDid(wallet_handle, pool_handle, did, cb) => {
+                info!(target: "signus_command_executor", "GetEndpointForDid command received");
+                self.get_endpoint_for_did(wallet_handle, pool_handle, &did, cb);
+            }
+            SignusCommand::GetEndpointForDidGetAttribAck(wallet_handle, did, cb_id, result) => {
+                info!(target: "signus_command_executor", "GetEndpointForDidGetAttribAck command received");
+                self.get_endpoint_for_did_get_attrib_ack(wallet_handle, &did, cb_id, result);
+            }
         };
     }
 
@@ -168,8 +411,11 @@ impl SignusCommandExecutor {
                 CommonError::InvalidState(
                     format!("Can't serialize MyDid: {}", err.description())))?;
 
+        let verkey = self.signus_service.my_did_public_verkey(&my_did)?;
+
+        self._store_key(wallet_handle, &self.signus_service.my_did_key(&my_did))?;
         self.wallet_service.set(wallet_handle, &format!("my_did::{}", my_did.did), &my_did_json)?;
-        Ok((my_did.did, my_did.verkey, my_did.pk))
+        Ok((my_did.did.clone(), verkey, my_did.pk.clone()))
     }
 
     fn replace_keys(&self,
@@ -191,8 +437,8 @@ impl SignusCommandExecutor {
 
         let my_did_info = MyDidInfo::new(
             Some(did.to_string()),
-            keys_info.seed,
-            keys_info.crypto_type,
+            keys_info.seed.clone(),
+            keys_info.crypto_type.clone(),
             None);
 
         let my_did = self.signus_service.create_my_did(&my_did_info)?;
@@ -203,9 +449,155 @@ impl SignusCommandExecutor {
                 CommonError::InvalidState(
                     format!("Can't serialize MyDid: {}", err.description())))?;
 
+        let verkey = self.signus_service.my_did_public_verkey(&my_did)?;
+
+        self._store_key(wallet_handle, &self.signus_service.my_did_key(&my_did))?;
         self.wallet_service.set(wallet_handle, &format!("my_did::{}", my_did.did), &my_did_json)?;
 
-        Ok((my_did.verkey, my_did.pk))
+        Ok((verkey, my_did.pk.clone()))
+    }
+
+    fn replace_keys_start(&self,
+                          wallet_handle: i32,
+                          keys_info_json: &str,
+                          did: &str,
+                          cb: Box<Fn(Result<(String, String), SovrinError>) + Send>) {
+        cb(self._replace_keys_start(wallet_handle, keys_info_json, did));
+    }
+
+    fn _replace_keys_start(&self,
+                           wallet_handle: i32,
+                           keys_info_json: &str,
+                           did: &str) -> Result<(String, String), SovrinError> {
+        self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+
+        let keys_info: MyKyesInfo = MyKyesInfo::from_json(keys_info_json)
+            .map_err(map_err_trace!())
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Invalid MyKyesInfo json: {}", err.description())))?;
+
+        let my_did_info = MyDidInfo::new(
+            Some(did.to_string()),
+            keys_info.seed.clone(),
+            keys_info.crypto_type.clone(),
+            None);
+
+        let my_did = self.signus_service.create_my_did(&my_did_info)?;
+
+        let my_did_json = my_did.to_json()
+            .map_err(map_err_trace!())
+            .map_err(|err|
+                CommonError::InvalidState(
+                    format!("Can't serialize MyDid: {}", err.description())))?;
+
+        let verkey = self.signus_service.my_did_public_verkey(&my_did)?;
+
+        self._store_key(wallet_handle, &self.signus_service.my_did_key(&my_did))?;
+        self.wallet_service.set(wallet_handle, &format!("my_temporary_did::{}", my_did.did), &my_did_json)?;
+
+        Ok((verkey, my_did.pk.clone()))
+    }
+
+    fn replace_keys_apply(&self,
+                          wallet_handle: i32,
+                          did: &str,
+                          cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        cb(self._replace_keys_apply(wallet_handle, did));
+    }
+
+    fn _replace_keys_apply(&self,
+                           wallet_handle: i32,
+                           did: &str) -> Result<(), SovrinError> {
+        let my_temporary_did_json = self.wallet_service.get(wallet_handle, &format!("my_temporary_did::{}", did))?;
+
+        self.wallet_service.set(wallet_handle, &format!("my_did::{}", did), &my_temporary_did_json)?;
+        self.wallet_service.delete_record(wallet_handle, &format!("my_temporary_did::{}", did))?;
+
+        Ok(())
+    }
+
+    fn replace_keys_on_ledger(&self,
+                              wallet_handle: i32,
+                              pool_handle: i32,
+                              keys_info_json: &str,
+                              did: &str,
+                              cb: Box<Fn(Result<(String, String), SovrinError>) + Send>) {
+        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
+                                                   wallet_handle, pool_handle, cb);
+
+        let verkey = match self._replace_keys_start(wallet_handle, keys_info_json, did) {
+            Ok((verkey, _)) => verkey,
+            Err(err) => return cb(Err(err))
+        };
+
+        let nym_request = match self.ledger_service.build_nym_request(did, did, Some(&verkey), None, None) {
+            Ok(nym_request) => nym_request,
+            Err(err) => return cb(Err(SovrinError::CommonError(err)))
+        };
+
+        let cb_id: i32 = SequenceUtils::get_next_id();
+
+        match self.replace_keys_callbacks.try_borrow_mut() {
+            Ok(mut replace_keys_callbacks) => {
+                replace_keys_callbacks.insert(cb_id, cb);
+
+                let did = did.to_string();
+                CommandExecutor::instance()
+                    .send(Command::Ledger(LedgerCommand::SignAndSubmitRequest(
+                        pool_handle,
+                        wallet_handle,
+                        did.clone(),
+                        nym_request,
+                        Box::new(move |result| {
+                            CommandExecutor::instance()
+                                .send(Command::Signus(SignusCommand::ReplaceKeysOnLedgerAck(
+                                    wallet_handle,
+                                    did.clone(),
+                                    cb_id,
+                                    result
+                                ))).unwrap();
+                        })
+                    ))).unwrap();
+            }
+            Err(err) => cb(Err(SovrinError::CommonError(CommonError::InvalidState(format!("{:?}", err)))))
+        }
+    }
+
+    fn replace_keys_on_ledger_ack(&self,
+                                  wallet_handle: i32,
+                                  did: &str,
+                                  cb_id: i32,
+                                  result: Result<String, SovrinError>) {
+        match self.replace_keys_callbacks.try_borrow_mut() {
+            Ok(mut cbs) => {
+                let cb = cbs.remove(&cb_id);
+
+                if cb.is_none() {
+                    return error!("Can't process Signus::ReplaceKeysOnLedgerAck for handle {} - appropriate callback not found!", cb_id)
+                }
+                let cb = cb.unwrap();
+
+                match result {
+                    Ok(_) => cb(self._replace_keys_on_ledger_apply(wallet_handle, did)),
+                    Err(err) => cb(Err(err))
+                }
+            }
+            Err(err) => error!("{:?}", err)
+        }
+    }
+
+    fn _replace_keys_on_ledger_apply(&self,
+                                     wallet_handle: i32,
+                                     did: &str) -> Result<(String, String), SovrinError> {
+        self._replace_keys_apply(wallet_handle, did)?;
+
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(map_err_trace!())
+            .map_err(|_| CommonError::InvalidState(format!("Invalid my did json")))?;
+
+        let verkey = self.signus_service.my_did_public_verkey(&my_did)?;
+        Ok((verkey, my_did.pk.clone()))
     }
 
     fn store_their_did(&self,
@@ -224,15 +616,7 @@ impl SignusCommandExecutor {
                 CommonError::InvalidStructure(format!("Invalid TheirDidInfo json: {}", err.description())))?;
 
         let their_did = self.signus_service.create_their_did(&their_did_info)?;
-
-        let their_did_json = their_did.to_json()
-            .map_err(map_err_trace!())
-            .map_err(|err|
-                CommonError::InvalidState(
-                    format!("Can't serialize TheirDid: {}", err.description())))?;
-
-        self.wallet_service.set(wallet_handle, &format!("their_did::{}", their_did.did), &their_did_json)?;
-        Ok(())
+        store_their_did(&self.wallet_service, wallet_handle, &their_did)
     }
 
     fn sign(&self,
@@ -256,6 +640,48 @@ impl SignusCommandExecutor {
         Ok(signed_msg)
     }
 
+    fn sign_detached(&self,
+                     wallet_handle: i32,
+                     did: &str,
+                     msg: &[u8],
+                     cb: Box<Fn(Result<Vec<u8>, SovrinError>) + Send>) {
+        cb(self._sign_detached(wallet_handle, did, msg));
+    }
+
+    fn _sign_detached(&self,
+                      wallet_handle: i32,
+                      did: &str,
+                      msg: &[u8]) -> Result<Vec<u8>, SovrinError> {
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(map_err_trace!())
+            .map_err(|_| CommonError::InvalidState((format!("Invalid my did json"))))?;
+
+        let signature = self.signus_service.sign_detached(&my_did, msg)?;
+        Ok(signature)
+    }
+
+    fn sign_jws(&self,
+                wallet_handle: i32,
+                did: &str,
+                payload: &[u8],
+                cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._sign_jws(wallet_handle, did, payload));
+    }
+
+    fn _sign_jws(&self,
+                 wallet_handle: i32,
+                 did: &str,
+                 payload: &[u8]) -> Result<String, SovrinError> {
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(map_err_trace!())
+            .map_err(|_| CommonError::InvalidState((format!("Invalid my did json"))))?;
+
+        let jws = self.signus_service.sign_jws(&my_did, payload)?;
+        Ok(jws)
+    }
+
     fn verify_signature(&self,
                         wallet_handle: i32,
                         pool_handle: i32,
@@ -344,25 +770,12 @@ impl SignusCommandExecutor {
                                      wallet_handle: i32,
                                      get_nym_response: &str,
                                      signed_msg: &str) -> Result<bool, SovrinError> {
-        let get_nym_response: Reply<GetNymReplyResult> = Reply::from_json(&get_nym_response)
-            .map_err(map_err_trace!())
-            .map_err(|_| CommonError::InvalidState(format!("Invalid their did json")))?;
+        let their_did = self._store_their_did_from_get_nym_reply(wallet_handle, get_nym_response)
+            .map_err(|err| match err {
+                SovrinError::LedgerError(_) => SovrinError::CommonError(CommonError::InvalidState(format!("Invalid their did json"))),
+                err => err
+            })?;
 
-        let gen_nym_result_data = GetNymResultData::from_json(&get_nym_response.result.data)
-            .map_err(map_err_trace!())
-            .map_err(|_| CommonError::InvalidState(format!("Invalid their did json")))?;
-
-        let their_did_info = TheirDidInfo::new(gen_nym_result_data.dest, None, gen_nym_result_data.verkey, None);
-
-        let their_did = self.signus_service.create_their_did(&their_did_info)?;
-
-        let their_did_json = their_did.to_json()
-            .map_err(map_err_trace!())
-            .map_err(|err|
-                CommonError::InvalidState(
-                    format!("Can't serialize TheirDid: {}", err.description())))?;
-
-        self.wallet_service.set(wallet_handle, &format!("their_did::{}", their_did.did), &their_did_json)?;
         self.signus_service.verify(&their_did, &signed_msg)
             .map_err(map_err_trace!())
             .map_err(|err| SovrinError::SignusError(err))
@@ -376,9 +789,12 @@ impl SignusCommandExecutor {
                msg: &str,
                cb: Box<Fn(Result<(String, String), SovrinError>) + Send>) {
         let load_public_key_from_ledger = move |cb| {
+            let get_nym_request = match self.ledger_service.build_get_nym_request(my_did, did) {
+                Ok(get_nym_request) => get_nym_request,
+                Err(err) => return cb(Err(SovrinError::CommonError(err)))
+            };
             let msg = msg.to_string();
             let my_did = my_did.to_string();
-            let get_nym_request = "".to_string(); //TODO add build_nym_request function in ledger service
             let cb_id: i32 = SequenceUtils::get_next_id();
 
             match self.encrypt_callbacks.try_borrow_mut() {
@@ -393,8 +809,8 @@ impl SignusCommandExecutor {
                                 CommandExecutor::instance()
                                     .send(Command::Signus(SignusCommand::EncryptGetNymAck(
                                         wallet_handle,
-                                        msg.clone(),
                                         my_did.clone(),
+                                        msg.clone(),
                                         cb_id,
                                         result
                                     ))).unwrap();
@@ -456,8 +872,8 @@ impl SignusCommandExecutor {
                 let cb = cb.unwrap();
 
                 match result {
-                    Ok(their_did_json) =>
-                        cb(self._encrypt_get_nym_ack(wallet_handle, my_did, &their_did_json, msg)),
+                    Ok(get_nym_response) =>
+                        cb(self._encrypt_get_nym_ack(wallet_handle, my_did, &get_nym_response, msg)),
                     Err(err) => cb(Err(err))
                 }
             }
@@ -468,22 +884,28 @@ impl SignusCommandExecutor {
     fn _encrypt_get_nym_ack(&self,
                             wallet_handle: i32,
                             my_did: &str,
-                            their_did_json: &str,
+                            get_nym_response: &str,
                             msg: &str) -> Result<(String, String), SovrinError> {
-        let my_did = MyDid::from_json(&my_did)
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", my_did))?;
+        let my_did = MyDid::from_json(&my_did_json)
             .map_err(map_err_trace!())
             .map_err(|_| CommonError::InvalidState(format!("Invalid my did json")))?;
-        let their_did = TheirDid::from_json(&their_did_json)
-            .map_err(map_err_trace!())
-            .map_err(|_| CommonError::InvalidState(format!("Invalid their did json")))?;
 
-        self.wallet_service.set(wallet_handle, &format!("their_did::{}", their_did.did), &their_did_json)?;
+        let their_did = self._store_their_did_from_get_nym_reply(wallet_handle, get_nym_response)?;
 
         self.signus_service.encrypt(&my_did, &their_did, &msg)
             .map_err(map_err_trace!())
             .map_err(|err| SovrinError::SignusError(err))
     }
 
+    fn _store_their_did_from_get_nym_reply(&self,
+                                           wallet_handle: i32,
+                                           get_nym_response: &str) -> Result<TheirDid, SovrinError> {
+        let their_did = their_did_from_get_nym_reply(&self.ledger_service, &self.signus_service, get_nym_response)?;
+        store_their_did(&self.wallet_service, wallet_handle, &their_did)?;
+        Ok(their_did)
+    }
+
     fn _encrypt(&self,
                 wallet_handle: i32,
                 my_did: &str,
@@ -533,4 +955,639 @@ impl SignusCommandExecutor {
         self.signus_service.decrypt(&my_did, &their_did, encrypted_msg, nonce)
             .map_err(|err| SovrinError::SignusError(err))
     }
-}
\ No newline at end of file
+
+    fn encrypt_sealed(&self,
+                      wallet_handle: i32,
+                      pool_handle: i32,
+                      did: &str,
+                      msg: &str,
+                      cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
+                                                   wallet_handle, pool_handle, cb);
+
+        match self.wallet_service.get_not_expired(wallet_handle, &format!("their_did::{}", did)) {
+            Ok(their_did_json) => {
+                let their_did = match TheirDid::from_json(&their_did_json) {
+                    Ok(their_did) => their_did,
+                    Err(_) => return cb(Err(SovrinError::CommonError(CommonError::InvalidState(format!("Invalid their did json")))))
+                };
+
+                if their_did.pk.is_some() {
+                    return cb(self.signus_service.encrypt_sealed(&their_did, msg).map_err(|err| SovrinError::SignusError(err)));
+                }
+            }
+            Err(WalletError::NotFound(_)) => {}
+            Err(err) => return cb(Err(SovrinError::WalletError(err)))
+        }
+
+        let get_nym_request = match self.ledger_service.build_get_nym_request(did, did) {
+            Ok(get_nym_request) => get_nym_request,
+            Err(err) => return cb(Err(SovrinError::CommonError(err)))
+        };
+
+        let cb_id: i32 = SequenceUtils::get_next_id();
+
+        match self.encrypt_sealed_callbacks.try_borrow_mut() {
+            Ok(mut encrypt_sealed_callbacks) => {
+                encrypt_sealed_callbacks.insert(cb_id, cb);
+
+                let msg = msg.to_string();
+                CommandExecutor::instance()
+                    .send(Command::Ledger(LedgerCommand::SubmitRequest(
+                        pool_handle,
+                        get_nym_request,
+                        Box::new(move |result| {
+                            CommandExecutor::instance()
+                                .send(Command::Signus(SignusCommand::EncryptSealedGetNymAck(
+                                    wallet_handle,
+                                    msg.clone(),
+                                    cb_id,
+                                    result
+                                ))).unwrap();
+                        })
+                    ))).unwrap();
+            }
+            Err(err) => cb(Err(SovrinError::CommonError(CommonError::InvalidState(format!("{:?}", err)))))
+        }
+    }
+
+    fn encrypt_sealed_get_nym_ack(&self,
+                                  wallet_handle: i32,
+                                  msg: &str,
+                                  cb_id: i32,
+                                  result: Result<String, SovrinError>) {
+        match self.encrypt_sealed_callbacks.try_borrow_mut() {
+            Ok(mut cbs) => {
+                let cb = cbs.remove(&cb_id);
+
+                if cb.is_none() {
+                    return error!("Can't process Signus::EncryptSealedGetNymAck for handle {} - appropriate callback not found!", cb_id)
+                }
+                let cb = cb.unwrap();
+
+                match result {
+                    Ok(get_nym_response) =>
+                        cb(self._encrypt_sealed_get_nym_ack(wallet_handle, &get_nym_response, msg)),
+                    Err(err) => cb(Err(err))
+                }
+            }
+            Err(err) => error!("{:?}", err)
+        }
+    }
+
+    fn _encrypt_sealed_get_nym_ack(&self,
+                                   wallet_handle: i32,
+                                   get_nym_response: &str,
+                                   msg: &str) -> Result<String, SovrinError> {
+        let their_did = self._store_their_did_from_get_nym_reply(wallet_handle, get_nym_response)?;
+
+        self.signus_service.encrypt_sealed(&their_did, &msg)
+            .map_err(map_err_trace!())
+            .map_err(|err| SovrinError::SignusError(err))
+    }
+
+    fn decrypt_sealed(&self,
+                      wallet_handle: i32,
+                      my_did: &str,
+                      encrypted_msg: &str,
+                      cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._decrypt_sealed(wallet_handle, my_did, encrypted_msg));
+    }
+
+    fn _decrypt_sealed(&self,
+                       wallet_handle: i32,
+                       my_did: &str,
+                       encrypted_msg: &str) -> Result<String, SovrinError> {
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", my_did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(map_err_trace!())
+            .map_err(|err| CommonError::InvalidState(err.to_string()))?;
+
+        self.signus_service.decrypt_sealed(&my_did, encrypted_msg)
+            .map_err(|err| SovrinError::SignusError(err))
+    }
+
+    fn encrypt_envelope(&self,
+                        wallet_handle: i32,
+                        dids_json: &str,
+                        msg: &str,
+                        cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._encrypt_envelope(wallet_handle, dids_json, msg));
+    }
+
+    fn _encrypt_envelope(&self,
+                         wallet_handle: i32,
+                         dids_json: &str,
+                         msg: &str) -> Result<String, SovrinError> {
+        let dids: Vec<String> = serde_json::from_str(dids_json)
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Invalid list of dids json: {}", err.description())))?;
+
+        let mut their_dids: Vec<TheirDid> = Vec::new();
+
+        for did in dids {
+            let their_did_json = self.wallet_service.get(wallet_handle, &format!("their_did::{}", did))?;
+            let their_did = TheirDid::from_json(&their_did_json)
+                .map_err(map_err_trace!())
+                .map_err(|err| CommonError::InvalidState(format!("Invalid their did json: {}", err.description())))?;
+
+            their_dids.push(their_did);
+        }
+
+        let envelope = self.signus_service.encrypt_envelope(&their_dids, msg)?;
+
+        let envelope_json = envelope.to_json()
+            .map_err(map_err_trace!())
+            .map_err(|err|
+                CommonError::InvalidState(format!("Can't serialize envelope: {}", err.description())))?;
+        Ok(envelope_json)
+    }
+
+    fn decrypt_envelope(&self,
+                        wallet_handle: i32,
+                        my_did: &str,
+                        envelope_json: &str,
+                        cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._decrypt_envelope(wallet_handle, my_did, envelope_json));
+    }
+
+    fn _decrypt_envelope(&self,
+                         wallet_handle: i32,
+                         my_did: &str,
+                         envelope_json: &str) -> Result<String, SovrinError> {
+        let envelope = Envelope::from_json(envelope_json)
+            .map_err(map_err_trace!())
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Invalid envelope json: {}", err.description())))?;
+
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", my_did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(map_err_trace!())
+            .map_err(|err| CommonError::InvalidState(err.to_string()))?;
+
+        self.signus_service.decrypt_envelope(&my_did, &envelope)
+            .map_err(|err| SovrinError::SignusError(err))
+    }
+
+    fn list_my_dids_with_meta(&self,
+                              wallet_handle: i32,
+                              cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._list_my_dids_with_meta(wallet_handle));
+    }
+
+    fn _list_my_dids_with_meta(&self, wallet_handle: i32) -> Result<String, SovrinError> {
+        let mut dids: Vec<MyDidWithMeta> = Vec::new();
+
+        for (_, my_did_json) in self.wallet_service.list(wallet_handle, "my_did::")? {
+            let my_did = MyDid::from_json(&my_did_json)
+                .map_err(map_err_trace!())
+                .map_err(|err| CommonError::InvalidState(format!("Invalid my did json: {}", err.description())))?;
+
+            let metadata = self._get_stored_did_metadata(wallet_handle, &my_did.did)?;
+
+            let verkey = self.signus_service.my_did_public_verkey(&my_did)?;
+            dids.push(MyDidWithMeta::new(my_did.did.clone(), verkey, metadata));
+        }
+
+        let dids_json = serde_json::to_string(&dids)
+            .map_err(|err|
+                CommonError::InvalidState(format!("Can't serialize list of dids: {}", err.description())))?;
+        Ok(dids_json)
+    }
+
+    fn list_their_dids_with_meta(&self,
+                                 wallet_handle: i32,
+                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._list_their_dids_with_meta(wallet_handle));
+    }
+
+    fn _list_their_dids_with_meta(&self, wallet_handle: i32) -> Result<String, SovrinError> {
+        let mut dids: Vec<TheirDidWithMeta> = Vec::new();
+
+        for (_, their_did_json) in self.wallet_service.list(wallet_handle, "their_did::")? {
+            let their_did = TheirDid::from_json(&their_did_json)
+                .map_err(map_err_trace!())
+                .map_err(|err| CommonError::InvalidState(format!("Invalid their did json: {}", err.description())))?;
+
+            let metadata = self._get_stored_did_metadata(wallet_handle, &their_did.did)?;
+
+            dids.push(TheirDidWithMeta::new(their_did.did, their_did.verkey, their_did.endpoint, metadata));
+        }
+
+        let dids_json = serde_json::to_string(&dids)
+            .map_err(|err|
+                CommonError::InvalidState(format!("Can't serialize list of dids: {}", err.description())))?;
+        Ok(dids_json)
+    }
+
+    fn set_did_metadata(&self,
+                        wallet_handle: i32,
+                        did: &str,
+                        metadata: &str,
+                        cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        cb(self._set_did_metadata(wallet_handle, did, metadata));
+    }
+
+    fn _set_did_metadata(&self,
+                         wallet_handle: i32,
+                         did: &str,
+                         metadata: &str) -> Result<(), SovrinError> {
+        serde_json::from_str::<serde_json::Value>(metadata)
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Metadata is invalid json: {}", err.description())))?;
+
+        match self.wallet_service.get(wallet_handle, &format!("my_did::{}", did)) {
+            Ok(_) => {}
+            Err(WalletError::NotFound(_)) => { self.wallet_service.get(wallet_handle, &format!("their_did::{}", did))?; }
+            Err(err) => return Err(SovrinError::WalletError(err))
+        }
+
+        self.wallet_service.set(wallet_handle, &format!("did_metadata::{}", did), metadata)?;
+        Ok(())
+    }
+
+    fn get_did_metadata(&self,
+                        wallet_handle: i32,
+                        did: &str,
+                        cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.wallet_service.get(wallet_handle, &format!("did_metadata::{}", did))
+            .map_err(|err| SovrinError::WalletError(err)));
+    }
+
+    fn _get_stored_did_metadata(&self, wallet_handle: i32, did: &str) -> Result<Option<String>, SovrinError> {
+        match self.wallet_service.get(wallet_handle, &format!("did_metadata::{}", did)) {
+            Ok(metadata) => Ok(Some(metadata)),
+            Err(WalletError::NotFound(_)) => Ok(None),
+            Err(err) => Err(SovrinError::WalletError(err))
+        }
+    }
+
+    fn create_key(&self,
+                  wallet_handle: i32,
+                  key_info_json: &str,
+                  cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._create_key(wallet_handle, key_info_json));
+    }
+
+    fn _create_key(&self,
+                   wallet_handle: i32,
+                   key_info_json: &str) -> Result<String, SovrinError> {
+        let key_info = KeyInfo::from_json(key_info_json)
+            .map_err(map_err_trace!())
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Invalid KeyInfo json: {}", err.description())))?;
+
+        let key = self.signus_service.create_key(&key_info)?;
+        self._store_key(wallet_handle, &key)?;
+
+        Ok(key.verkey.clone())
+    }
+
+    fn _store_key(&self, wallet_handle: i32, key: &Key) -> Result<(), SovrinError> {
+        let key_json = key.to_json()
+            .map_err(map_err_trace!())
+            .map_err(|err|
+                CommonError::InvalidState(
+                    format!("Can't serialize Key: {}", err.description())))?;
+
+        self.wallet_service.set(wallet_handle, &format!("key::{}", key.verkey), &key_json)?;
+        Ok(())
+    }
+
+    fn _get_key(&self, wallet_handle: i32, verkey: &str) -> Result<Key, SovrinError> {
+        let verkey = self._expand_verkey(wallet_handle, verkey)?;
+        let key_json = self.wallet_service.get(wallet_handle, &format!("key::{}", verkey))?;
+
+        let key = Key::from_json(&key_json)
+            .map_err(map_err_trace!())
+            .map_err(|err| CommonError::InvalidState(format!("Invalid Key json: {}", err.description())))?;
+        Ok(key)
+    }
+
+    /// Abbreviated verkey doesn't contain did bytes, so it's expanded using my and their dids stored in the wallet.
+    fn _expand_verkey(&self, wallet_handle: i32, verkey: &str) -> Result<String, SovrinError> {
+        if !verkey.starts_with("~") {
+            return Ok(verkey.to_string());
+        }
+
+        for (_, my_did_json) in self.wallet_service.list(wallet_handle, "my_did::")? {
+            let my_did = MyDid::from_json(&my_did_json)
+                .map_err(map_err_trace!())
+                .map_err(|err| CommonError::InvalidState(format!("Invalid my did json: {}", err.description())))?;
+
+            if build_full_verkey(&my_did.did, verkey)? == my_did.verkey {
+                return Ok(my_did.verkey.clone());
+            }
+        }
+
+        for (_, their_did_json) in self.wallet_service.list(wallet_handle, "their_did::")? {
+            let their_did = TheirDid::from_json(&their_did_json)
+                .map_err(map_err_trace!())
+                .map_err(|err| CommonError::InvalidState(format!("Invalid their did json: {}", err.description())))?;
+
+            let full_verkey = build_full_verkey(&their_did.did, verkey)?;
+            if their_did.verkey.as_ref() == Some(&full_verkey) {
+                return Ok(full_verkey);
+            }
+        }
+
+        Err(SovrinError::WalletError(WalletError::NotFound(format!("Did not found for abbreviated verkey: {}", verkey))))
+    }
+
+    fn sign_by_key(&self,
+                   wallet_handle: i32,
+                   verkey: &str,
+                   msg: &str,
+                   cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._sign_by_key(wallet_handle, verkey, msg));
+    }
+
+    fn _sign_by_key(&self,
+                    wallet_handle: i32,
+                    verkey: &str,
+                    msg: &str) -> Result<String, SovrinError> {
+        let key = self._get_key(wallet_handle, verkey)?;
+
+        self.signus_service.sign_by_key(&key, msg)
+            .map_err(|err| SovrinError::SignusError(err))
+    }
+
+    fn verify_by_key(&self,
+                     verkey: &str,
+                     signed_msg: &str,
+                     cb: Box<Fn(Result<bool, SovrinError>) + Send>) {
+        cb(self.signus_service.verify_by_key(verkey, signed_msg)
+            .map_err(|err| SovrinError::SignusError(err)));
+    }
+
+    fn verify_detached_by_key(&self,
+                              verkey: &str,
+                              msg: &[u8],
+                              signature: &[u8],
+                              cb: Box<Fn(Result<bool, SovrinError>) + Send>) {
+        cb(self.signus_service.verify_detached_by_key(verkey, msg, signature)
+            .map_err(|err| SovrinError::SignusError(err)));
+    }
+
+    fn encrypt_by_key(&self,
+                      wallet_handle: i32,
+                      my_verkey: &str,
+                      their_verkey: &str,
+                      msg: &str,
+                      cb: Box<Fn(Result<(String, String), SovrinError>) + Send>) {
+        cb(self._encrypt_by_key(wallet_handle, my_verkey, their_verkey, msg));
+    }
+
+    fn _encrypt_by_key(&self,
+                       wallet_handle: i32,
+                       my_verkey: &str,
+                       their_verkey: &str,
+                       msg: &str) -> Result<(String, String), SovrinError> {
+        let my_key = self._get_key(wallet_handle, my_verkey)?;
+        let their_verkey = self._expand_verkey(wallet_handle, their_verkey)?;
+
+        self.signus_service.encrypt_by_key(&my_key, &their_verkey, msg)
+            .map_err(|err| SovrinError::SignusError(err))
+    }
+
+    fn decrypt_by_key(&self,
+                      wallet_handle: i32,
+                      my_verkey: &str,
+                      their_verkey: &str,
+                      encrypted_msg: &str,
+                      nonce: &str,
+                      cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._decrypt_by_key(wallet_handle, my_verkey, their_verkey, encrypted_msg, nonce));
+    }
+
+    fn _decrypt_by_key(&self,
+                       wallet_handle: i32,
+                       my_verkey: &str,
+                       their_verkey: &str,
+                       encrypted_msg: &str,
+                       nonce: &str) -> Result<String, SovrinError> {
+        let my_key = self._get_key(wallet_handle, my_verkey)?;
+        let their_verkey = self._expand_verkey(wallet_handle, their_verkey)?;
+
+        self.signus_service.decrypt_by_key(&my_key, &their_verkey, encrypted_msg, nonce)
+            .map_err(|err| SovrinError::SignusError(err))
+    }
+
+    fn set_endpoint_for_did(&self,
+                            wallet_handle: i32,
+                            pool_handle: Option<i32>,
+                            did: &str,
+                            address: &str,
+                            transport_key: Option<&str>,
+                            cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        let endpoint = Endpoint::new(address.to_string(), transport_key.map(String::from));
+
+        let pool_handle = match pool_handle {
+            Some(pool_handle) => pool_handle,
+            None => return cb(store_endpoint(&self.wallet_service, wallet_handle, did, &endpoint))
+        };
+
+        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
+                                                   wallet_handle, pool_handle, cb);
+
+        let attrib_request = match self.ledger_service.build_endpoint_attrib_request(did, did, &endpoint) {
+            Ok(attrib_request) => attrib_request,
+            Err(err) => return cb(Err(SovrinError::CommonError(err)))
+        };
+
+        let cb_id: i32 = SequenceUtils::get_next_id();
+
+        match self.set_endpoint_callbacks.try_borrow_mut() {
+            Ok(mut set_endpoint_callbacks) => {
+                set_endpoint_callbacks.insert(cb_id, cb);
+
+                let did = did.to_string();
+                CommandExecutor::instance()
+                    .send(Command::Ledger(LedgerCommand::SignAndSubmitRequest(
+                        pool_handle,
+                        wallet_handle,
+                        did.clone(),
+                        attrib_request,
+                        Box::new(move |result| {
+                            CommandExecutor::instance()
+                                .send(Command::Signus(SignusCommand::SetEndpointForDidAck(
+                                    wallet_handle,
+                                    did.clone(),
+                                    endpoint.clone(),
+                                    cb_id,
+                                    result
+                                ))).unwrap();
+                        })
+                    ))).unwrap();
+            }
+            Err(err) => cb(Err(SovrinError::CommonError(CommonError::InvalidState(format!("{:?}", err)))))
+        }
+    }
+
+    fn set_endpoint_for_did_ack(&self,
+                                wallet_handle: i32,
+                                did: &str,
+                                endpoint: &Endpoint,
+                                cb_id: i32,
+                                result: Result<String, SovrinError>) {
+        match self.set_endpoint_callbacks.try_borrow_mut() {
+            Ok(mut cbs) => {
+                let cb = cbs.remove(&cb_id);
+
+                if cb.is_none() {
+                    return error!("Can't process Signus::SetEndpointForDidAck for handle {} - appropriate callback not found!", cb_id)
+                }
+                let cb = cb.unwrap();
+
+                match result {
+                    Ok(_) => cb(store_endpoint(&self.wallet_service, wallet_handle, did, endpoint)),
+                    Err(err) => cb(Err(err))
+                }
+            }
+            Err(err) => error!("{:?}", err)
+        }
+    }
+
+    fn get_endpoint_for_did(&self,
+                            wallet_handle: i32,
+                            pool_handle: Option<i32>,
+                            did: &str,
+                            cb: Box<Fn(Result<(String, Option<String>), SovrinError>) + Send>) {
+        match get_stored_endpoint(&self.wallet_service, wallet_handle, did) {
+            Ok(Some(endpoint)) => return cb(Ok((endpoint.ha, endpoint.verkey))),
+            Ok(None) => {}
+            Err(err) => return cb(Err(err))
+        }
+
+        let pool_handle = match pool_handle {
+            Some(pool_handle) => pool_handle,
+            None => return cb(Err(SovrinError::WalletError(WalletError::NotFound(format!("Endpoint not found for did: {}", did)))))
+        };
+
+        check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
+                                                   wallet_handle, pool_handle, cb);
+
+        let get_attrib_request = match self.ledger_service.build_get_attrib_request(did, did, "endpoint") {
+            Ok(get_attrib_request) => get_attrib_request,
+            Err(err) => return cb(Err(SovrinError::CommonError(err)))
+        };
+
+        let cb_id: i32 = SequenceUtils::get_next_id();
+
+        match self.get_endpoint_callbacks.try_borrow_mut() {
+            Ok(mut get_endpoint_callbacks) => {
+                get_endpoint_callbacks.insert(cb_id, cb);
+
+                let did = did.to_string();
+                CommandExecutor::instance()
+                    .send(Command::Ledger(LedgerCommand::SubmitRequest(
+                        pool_handle,
+                        get_attrib_request,
+                        Box::new(move |result| {
+                            CommandExecutor::instance()
+                                .send(Command::Signus(SignusCommand::GetEndpointForDidGetAttribAck(
+                                    wallet_handle,
+                                    did.clone(),
+                                    cb_id,
+                                    result
+                                ))).unwrap();
+                        })
+                    ))).unwrap();
+            }
+            Err(err) => cb(Err(SovrinError::CommonError(CommonError::InvalidState(format!("{:?}", err)))))
+        }
+    }
+
+    fn get_endpoint_for_did_get_attrib_ack(&self,
+                                           wallet_handle: i32,
+                                           did: &str,
+                                           cb_id: i32,
+                                           result: Result<String, SovrinError>) {
+        match self.get_endpoint_callbacks.try_borrow_mut() {
+            Ok(mut cbs) => {
+                let cb = cbs.remove(&cb_id);
+
+                if cb.is_none() {
+                    return error!("Can't process Signus::GetEndpointForDidGetAttribAck for handle {} - appropriate callback not found!", cb_id)
+                }
+                let cb = cb.unwrap();
+
+                match result {
+                    Ok(get_attrib_reply) =>
+                        cb(self._get_endpoint_for_did_get_attrib_ack(wallet_handle, did, &get_attrib_reply)),
+                    Err(err) => cb(Err(err))
+                }
+            }
+            Err(err) => error!("{:?}", err)
+        }
+    }
+
+    fn _get_endpoint_for_did_get_attrib_ack(&self,
+                                            wallet_handle: i32,
+                                            did: &str,
+                                            get_attrib_reply: &str) -> Result<(String, Option<String>), SovrinError> {
+        let endpoint = self.ledger_service.parse_get_attrib_endpoint_reply(get_attrib_reply)
+            .map_err(map_err_trace!())?;
+
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None => return Err(SovrinError::LedgerError(LedgerError::NotFound(format!("Endpoint not found for did: {}", did))))
+        };
+
+        store_endpoint(&self.wallet_service, wallet_handle, did, &endpoint)?;
+        Ok((endpoint.ha, endpoint.verkey))
+    }
+}
+
+/// Stores endpoint of DID in the wallet. It is used as a local shortcut instead of resolving the DID on the ledger.
+pub fn store_endpoint(wallet_service: &WalletService, wallet_handle: i32, did: &str, endpoint: &Endpoint) -> Result<(), SovrinError> {
+    Base58::decode(did)?;
+
+    let endpoint_json = endpoint.to_json()
+        .map_err(map_err_trace!())
+        .map_err(|err|
+            CommonError::InvalidState(
+                format!("Can't serialize Endpoint: {}", err.description())))?;
+
+    wallet_service.set(wallet_handle, &format!("endpoint::{}", did), &endpoint_json)?;
+    Ok(())
+}
+
+/// Returns not expired endpoint of DID stored in the wallet or None if it isn't stored.
+pub fn get_stored_endpoint(wallet_service: &WalletService, wallet_handle: i32, did: &str) -> Result<Option<Endpoint>, SovrinError> {
+    let endpoint_json = match wallet_service.get_not_expired(wallet_handle, &format!("endpoint::{}", did)) {
+        Ok(endpoint_json) => endpoint_json,
+        Err(WalletError::NotFound(_)) => return Ok(None),
+        Err(err) => return Err(SovrinError::WalletError(err))
+    };
+
+    let endpoint = Endpoint::from_json(&endpoint_json)
+        .map_err(map_err_trace!())
+        .map_err(|err|
+            CommonError::InvalidState(
+                format!("Invalid Endpoint json: {}", err.description())))?;
+    Ok(Some(endpoint))
+}
+
+/// Builds their DID from GET_NYM reply. Abbreviated verkey of the reply is expanded.
+pub fn their_did_from_get_nym_reply(ledger_service: &LedgerService, signus_service: &SignusService,
+                                    get_nym_response: &str) -> Result<TheirDid, SovrinError> {
+    let get_nym_result_data = ledger_service.parse_get_nym_reply(get_nym_response)
+        .map_err(map_err_trace!())?;
+
+    let their_did_info = TheirDidInfo::new(get_nym_result_data.dest, None, get_nym_result_data.verkey, None);
+
+    let their_did = signus_service.create_their_did(&their_did_info)?;
+    Ok(their_did)
+}
+
+pub fn store_their_did(wallet_service: &WalletService, wallet_handle: i32, their_did: &TheirDid) -> Result<(), SovrinError> {
+    let their_did_json = their_did.to_json()
+        .map_err(map_err_trace!())
+        .map_err(|err|
+            CommonError::InvalidState(
+                format!("Can't serialize TheirDid: {}", err.description())))?;
+
+    wallet_service.set(wallet_handle, &format!("their_did::{}", their_did.did), &their_did_json)?;
+    Ok(())
+}
@@ -11,6 +11,7 @@ use errors::ToErrorCode;
 #[derive(Debug)]
 pub enum LedgerError {
     NoConsensus(String),
+    NotFound(String),
     CommonError(CommonError)
 }
 
@@ -18,6 +19,7 @@ impl fmt::Display for LedgerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             LedgerError::NoConsensus(ref description) => write!(f, "No consensus: {}", description),
+            LedgerError::NotFound(ref description) => write!(f, "Not found: {}", description),
             LedgerError::CommonError(ref err) => err.fmt(f)
         }
     }
@@ -27,6 +29,7 @@ impl error::Error for LedgerError {
     fn description(&self) -> &str {
         match *self {
             LedgerError::NoConsensus(ref description) => description,
+            LedgerError::NotFound(ref description) => description,
             LedgerError::CommonError(ref err) => err.description()
         }
     }
@@ -34,6 +37,7 @@ impl error::Error for LedgerError {
     fn cause(&self) -> Option<&error::Error> {
         match *self {
             LedgerError::NoConsensus(ref description) => None,
+            LedgerError::NotFound(ref description) => None,
             LedgerError::CommonError(ref err) => Some(err)
         }
     }
@@ -49,6 +53,7 @@ impl ToErrorCode for LedgerError {
     fn to_error_code(&self) -> ErrorCode {
         match *self {
             LedgerError::NoConsensus(ref description) => ErrorCode::LedgerNoConsensusError,
+            LedgerError::NotFound(ref description) => ErrorCode::LedgerNotFoundError,
             LedgerError::CommonError(ref err) => err.to_error_code()
         }
     }
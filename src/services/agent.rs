@@ -1,31 +1,52 @@
 #![warn(unused_variables)]
 extern crate rust_base58;
 extern crate serde_json;
+extern crate time;
+extern crate uuid;
 extern crate zmq;
 
 use self::rust_base58::FromBase58;
+use self::uuid::Uuid;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::{io, thread};
+use std::time::{Duration, Instant};
+use std::{fmt, io, thread};
 
 use commands::{Command, CommandExecutor};
 use commands::agent::AgentCommand;
 use errors::common::CommonError;
+use utils::crypto::base58::Base58;
+use utils::crypto::base64::Base64;
+use utils::crypto::memzero::{memzero, memzero_string};
+use utils::crypto::xsalsa20::XSalsa20;
 use utils::json::{JsonDecodable, JsonEncodable};
 use utils::sequence::SequenceUtils;
 
 struct RemoteAgent {
     socket: zmq::Socket,
     addr: String,
+    did: String,
+    receiver_did: String,
     public_key: Vec<u8>,
     secret_key: Vec<u8>,
     server_key: Vec<u8>,
     conn_handle: i32,
+    connected: bool,
+    reconnecting: bool,
+    delivery: Option<Delivery>,
 }
 
 struct AgentListener {
     connections: Vec<(i32 /* connection_handle*/, String /* identity */)>,
+    deliveries: HashMap<i32 /* connection_handle*/, Delivery>,
+    challenges: HashMap<String /* identity */, Challenge>,
     listener_handle: i32,
+    did: String,
+    identities: HashSet<String>,
+    policy: ListenerPolicy,
     socket: zmq::Socket,
+    zap_socket: zmq::Socket,
 }
 
 struct AgentWorker {
@@ -72,15 +93,20 @@ impl AgentService {
         AgentService { agent: Agent::new() }
     }
 
-    pub fn connect(&self, sender_did: &str, my_sk: &str, my_pk: &str, endpoint: &str, server_key: &str) -> Result<i32, CommonError> {
+    /// Starts outgoing connection. If delivery options are set messages of the connection
+    /// are delivered reliably: they are resent until peer acknowledges them.
+    pub fn connect(&self, sender_did: &str, my_sk: &str, my_pk: &str, receiver_did: &str, endpoint: &str, server_key: &str,
+                   delivery: Option<DeliveryOptions>) -> Result<i32, CommonError> {
         let conn_handle = SequenceUtils::get_next_id();
         let connect_cmd: AgentWorkerCommand = AgentWorkerCommand::Connect(ConnectCmd {
             did: sender_did.to_string(),
+            receiver_did: receiver_did.to_string(),
             secret_key: my_sk.to_string(),
             public_key: my_pk.to_string(),
             endpoint: endpoint.to_string(),
             server_key: server_key.to_string(),
             conn_handle: conn_handle,
+            delivery: delivery,
         });
         self.agent.cmd_socket.send_str(connect_cmd.to_json()
                                            .map_err(|err|
@@ -89,13 +115,19 @@ impl AgentService {
         Ok(conn_handle)
     }
 
-    pub fn listen(&self, endpoint: &str, pk: &str, sk: &str) -> Result<i32, CommonError> {
+    /// Starts listener with transport keys of the DID that serves all the identities.
+    /// Incoming connection without receiver DID is addressed to the transport DID.
+    pub fn listen(&self, endpoint: &str, did: &str, pk: &str, sk: &str, identities: &[String],
+                  policy: ListenerPolicy) -> Result<i32, CommonError> {
         let listen_handle = SequenceUtils::get_next_id();
         let listen_cmd = AgentWorkerCommand::Listen(ListenCmd {
             listen_handle: listen_handle,
             endpoint: endpoint.to_string(),
+            did: did.to_string(),
             pk: pk.to_string(),
-            sk: sk.to_string()
+            sk: sk.to_string(),
+            identities: identities.to_vec(),
+            policy: policy,
         });
         self.agent.cmd_socket.send_str(listen_cmd.to_json()
                                            .map_err(|err|
@@ -104,12 +136,61 @@ impl AgentService {
         Ok(listen_handle)
     }
 
-    pub fn send(&self, conn_id: i32, msg: Option<&str>) -> Result<i32, CommonError> {
+    /// Answers listener challenge on outgoing connection with DID, its verkey and signature of the challenge nonce.
+    pub fn answer_challenge(&self, conn_handle: i32, did: &str, verkey: &str, signature: &str) -> Result<(), CommonError> {
+        let answer_cmd = AgentWorkerCommand::AnswerChallenge(AnswerChallengeCmd {
+            conn_handle: conn_handle,
+            did: did.to_string(),
+            verkey: verkey.to_string(),
+            signature: signature.to_string(),
+        });
+        self.agent.cmd_socket.send_str(answer_cmd.to_json()
+                                           .map_err(|err|
+                                               CommonError::InvalidState(format!("Can't serialize AgentWorkerCommand::AnswerChallenge {}", err.description())))?
+                                           .as_str(), zmq::DONTWAIT)?;
+        Ok(())
+    }
+
+    /// Finishes handshake of incoming connection: accepts it if reject_reason is None or rejects with the reason.
+    pub fn complete_handshake(&self, listener_handle: i32, identity: &str, client_key: &str, sender_did: &str, receiver_did: &str,
+                              reliable: bool, reject_reason: Option<&str>) -> Result<(), CommonError> {
+        let complete_cmd = AgentWorkerCommand::CompleteHandshake(CompleteHandshakeCmd {
+            listener_handle: listener_handle,
+            identity: identity.to_string(),
+            client_key: client_key.to_string(),
+            sender_did: sender_did.to_string(),
+            receiver_did: receiver_did.to_string(),
+            reliable: reliable,
+            reject_reason: reject_reason.map(str::to_string),
+        });
+        self.agent.cmd_socket.send_str(complete_cmd.to_json()
+                                           .map_err(|err|
+                                               CommonError::InvalidState(format!("Can't serialize AgentWorkerCommand::CompleteHandshake {}", err.description())))?
+                                           .as_str(), zmq::DONTWAIT)?;
+        Ok(())
+    }
+
+    pub fn add_or_remove_identity(&self, listener_handle: i32, did: &str, remove: bool) -> Result<i32, CommonError> {
+        let cmd_id = SequenceUtils::get_next_id();
+        let identity_cmd = AgentWorkerCommand::Identity(IdentityCmd {
+            cmd_id: cmd_id,
+            listener_handle: listener_handle,
+            did: did.to_string(),
+            remove: remove,
+        });
+        self.agent.cmd_socket.send_str(identity_cmd.to_json()
+                                           .map_err(|err|
+                                               CommonError::InvalidState(format!("Can't serialize AgentWorkerCommand::Identity {}", err.description())))?
+                                           .as_str(), zmq::DONTWAIT)?;
+        Ok(cmd_id)
+    }
+
+    pub fn send(&self, conn_id: i32, envelope: &AgentEnvelope) -> Result<i32, CommonError> {
         let send_handle = SequenceUtils::get_next_id();
         let send_cmd = AgentWorkerCommand::Send(SendCmd {
             cmd_id: send_handle,
             conn_handle: conn_id,
-            msg: msg.map(str::to_string),
+            msg: envelope.to_data()?,
         });
         self.agent.cmd_socket.send_str(send_cmd.to_json()
                                            .map_err(|err|
@@ -138,81 +219,154 @@ impl AgentWorker {
     fn run(&mut self) {
         'agent_pool_loop: loop {
             trace!("agent worker poll loop >>");
-            let cmds = self.poll().unwrap();
+            let cmds = self.poll(self.poll_timeout()).unwrap();
             for cmd in cmds {
                 debug!("AgentWorker::run received cmd {:?}", cmd);
                 match cmd {
                     AgentWorkerCommand::Connect(cmd) => self.connect(&cmd).unwrap(),
                     AgentWorkerCommand::Close(cmd) => self.close_connection_or_listener(cmd.cmd_id, cmd.handle, cmd.close_listener).unwrap(),
-                    AgentWorkerCommand::Listen(cmd) => self.start_listen(cmd.listen_handle, cmd.endpoint, cmd.pk, cmd.sk).unwrap(),
+                    AgentWorkerCommand::Listen(cmd) => self.start_listen(&cmd).unwrap(),
+                    AgentWorkerCommand::Identity(cmd) => self.add_or_remove_identity(&cmd).unwrap(),
+                    AgentWorkerCommand::AnswerChallenge(cmd) => self.answer_challenge(&cmd),
+                    AgentWorkerCommand::CompleteHandshake(cmd) => self.complete_handshake(&cmd),
                     AgentWorkerCommand::Response(resp) => self.agent_connections[resp.agent_ind].handle_response(resp.msg),
-                    AgentWorkerCommand::Request(req) => self.agent_listeners[req.listener_ind].handle_request(req.identity, req.msg).unwrap(),
+                    AgentWorkerCommand::Request(req) => {
+                        if let Err(err) = self.agent_listeners[req.listener_ind].handle_request(req.identity, req.client_key, req.msg) {
+                            error!("AgentWorker::run can't handle request to listener {}", err);
+                        }
+                    }
+                    AgentWorkerCommand::ZapRequest(req) => {
+                        if let Err(err) = self.agent_listeners[req.listener_ind].handle_zap_request() {
+                            error!("AgentWorker::run can't handle ZAP request to listener {}", err);
+                        }
+                    }
                     AgentWorkerCommand::Send(cmd) => self.send(cmd.cmd_id, cmd.conn_handle, cmd.msg).unwrap(),
                     AgentWorkerCommand::Exit => break 'agent_pool_loop,
                 }
             }
+            self.resend_expired();
             trace!("agent worker poll loop <<");
         }
         trace!("agent poll finished");
     }
 
     fn connect(&mut self, cmd: &ConnectCmd) -> Result<(), CommonError> {
-        let ra = RemoteAgent::new(cmd.public_key.as_str(), cmd.secret_key.as_str(),
-                                  cmd.server_key.as_str(), cmd.endpoint.as_str(),
-                                  cmd.conn_handle)
+        let ra = RemoteAgent::new(cmd.did.as_str(), cmd.public_key.as_str(), cmd.secret_key.as_str(),
+                                  cmd.receiver_did.as_str(), cmd.server_key.as_str(), cmd.endpoint.as_str(),
+                                  cmd.conn_handle, cmd.delivery.clone())
             .map_err(map_err_trace!("RemoteAgent::new failed"))?;
         ra.connect().map_err(map_err_trace!("RemoteAgent::connect failed"))?;
         self.agent_connections.push(ra);
         Ok(())
     }
 
-    fn start_listen(&mut self, handle: i32, endpoint: String, pk: String, sk: String) -> Result<(), CommonError> {
-        let res = self.try_start_listen(handle, endpoint, pk, sk);
-        let cmd = AgentCommand::ListenAck(handle, res.map(|()| (handle)));
+    fn answer_challenge(&mut self, cmd: &AnswerChallengeCmd) {
+        let res = match self.agent_connections.iter().find(|ac| ac.conn_handle == cmd.conn_handle) {
+            Some(remote_agent) => remote_agent.send_handshake_msg(&HandshakeMsg::Did {
+                did: cmd.did.clone(),
+                signature: cmd.signature.clone(),
+                verkey: Some(cmd.verkey.clone()),
+            }),
+            None => Err(CommonError::InvalidStructure(format!("Connection with id {} not founded", cmd.conn_handle)))
+        };
+        if let Err(err) = res {
+            let cmd = AgentCommand::ConnectAck(cmd.conn_handle, Err(err));
+            if let Err(err) = CommandExecutor::instance().send(Command::Agent(cmd)) {
+                error!("AgentWorker::answer_challenge can't send ConnectAck {}", err);
+            }
+        }
+    }
+
+    fn complete_handshake(&mut self, cmd: &CompleteHandshakeCmd) {
+        let res = match self.agent_listeners.iter_mut().find(|al| al.listener_handle == cmd.listener_handle) {
+            Some(agent_listener) => match cmd.reject_reason {
+                Some(ref reason) => agent_listener.reject(&cmd.identity, Some(&cmd.sender_did), Some(&cmd.client_key), reason),
+                None => agent_listener.accept(&cmd.identity, &cmd.sender_did, &cmd.receiver_did, cmd.reliable)
+            },
+            None => Err(CommonError::InvalidStructure(format!("Listener with id {} not founded", cmd.listener_handle)))
+        };
+        if let Err(err) = res {
+            warn!("AgentWorker::complete_handshake can't complete handshake with {}: {}", cmd.identity, err);
+        }
+    }
+
+    fn start_listen(&mut self, cmd: &ListenCmd) -> Result<(), CommonError> {
+        let res = self.try_start_listen(cmd);
+        let cmd = AgentCommand::ListenAck(cmd.listen_handle, res.map(|()| (cmd.listen_handle)));
         CommandExecutor::instance().send(Command::Agent(cmd))
     }
 
-    fn send(&mut self, cmd_id: i32, conn_handle: i32, msg: Option<String>)
+    fn add_or_remove_identity(&mut self, cmd: &IdentityCmd) -> Result<(), CommonError> {
+        let res = match self.agent_listeners.iter_mut().find(|al| al.listener_handle == cmd.listener_handle) {
+            Some(agent_listener) => {
+                if cmd.remove {
+                    agent_listener.identities.remove(&cmd.did);
+                } else {
+                    agent_listener.identities.insert(cmd.did.clone());
+                }
+                Ok(())
+            }
+            None => Err(CommonError::InvalidStructure(format!("Listener with id {} not founded", cmd.listener_handle)))
+        };
+        CommandExecutor::instance().send(Command::Agent(AgentCommand::IdentityAck(cmd.cmd_id, res)))
+    }
+
+    fn send(&mut self, cmd_id: i32, conn_handle: i32, msg: String)
             -> Result<(), CommonError> {
         let res = self.try_send(conn_handle, msg);
         let cmd = AgentCommand::SendAck(cmd_id, res);
         CommandExecutor::instance().send(Command::Agent(cmd))
     }
 
-    fn try_send(&mut self, handle: i32, msg: Option<String>) -> Result<(), CommonError> {
-        let msg = msg.unwrap_or(String::new());
+    fn try_send(&mut self, handle: i32, msg: String) -> Result<i32, CommonError> {
+        let remote_agent_ind: Option<usize> =
+            self.agent_connections.iter().position(|ac| ac.conn_handle == handle);
+        let listener_ind: Option<usize> =
+            self.agent_listeners.iter().position(|al| al.connections.iter().any(|&(conn_id, _)| conn_id == handle));
 
-        let remote_agent: Option<&RemoteAgent> =
-            self.agent_connections.iter().find(|ac| ac.conn_handle == handle);
-        let listener_with_identity: Option<(&AgentListener, &String)> =
-            self.find_listener_by_conn_handle(handle);
-
-        if remote_agent.is_some() && listener_with_identity.is_some() {
+        if remote_agent_ind.is_some() && listener_ind.is_some() {
             return Err(CommonError::InvalidState("duplication connections".to_string())) //TODO
         }
-        if let Some(remote_agent) = remote_agent {
-            return remote_agent.socket.send(msg.as_bytes(), zmq::DONTWAIT).map_err(From::from)
+        if let Some(ind) = remote_agent_ind {
+            return self.agent_connections[ind].send_msg(msg)
         }
-        if let Some(li) = listener_with_identity {
-            let agent_listener: &AgentListener = li.0;
-            let identity: &String = li.1;
-            return agent_listener.socket
-                .send_str(identity.as_str(), zmq::DONTWAIT | zmq::SNDMORE)
-                .and_then(|()|
-                    agent_listener.socket.send(msg.as_bytes(), zmq::DONTWAIT))
-                .map_err(From::from)
+        if let Some(ind) = listener_ind {
+            return self.agent_listeners[ind].send_msg(handle, msg)
         }
         /* if remote_agent.is_none() && listener_with_identity.is_none() */
         Err(CommonError::InvalidStructure(format!("Connection with id {} not founded", handle)))
     }
 
-    fn find_listener_by_conn_handle(&self, handle: i32) -> Option<(&AgentListener, &String)> {
-        for listener in &self.agent_listeners {
-            if let Some(&(_, ref identity)) = listener.connections.iter().find(|&&(conn_id, _)| conn_id == handle) {
-                return Some((listener, identity));
+    /// Resends messages of reliable connections that weren't acknowledged in time.
+    fn resend_expired(&mut self) {
+        let now = Instant::now();
+        for remote_agent in &mut self.agent_connections {
+            remote_agent.resend_expired(now);
+        }
+        for agent_listener in &mut self.agent_listeners {
+            agent_listener.resend_expired(now);
+        }
+    }
+
+    /// Returns poll timeout in ms till the nearest resend of not acknowledged message or -1 if there is nothing to resend.
+    fn poll_timeout(&self) -> i64 {
+        let deadline = self.agent_connections.iter()
+            .filter_map(|ra| ra.delivery.as_ref().and_then(Delivery::next_deadline))
+            .chain(self.agent_listeners.iter()
+                .flat_map(|al| al.deliveries.values().filter_map(Delivery::next_deadline)))
+            .min();
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if deadline <= now {
+                    0
+                } else {
+                    let timeout = deadline - now;
+                    (timeout.as_secs() * 1000 + timeout.subsec_nanos() as u64 / 1_000_000 + 1) as i64
+                }
             }
+            None => -1
         }
-        return None;
     }
 
     fn close_connection_or_listener(&mut self, cmd_handle: i32, handle: i32, close_listener: bool) -> Result<(), CommonError> {
@@ -229,7 +383,10 @@ impl AgentWorker {
         /* TODO check duplicates */
         for i in 0..self.agent_connections.len() {
             if self.agent_connections[i].conn_handle == conn_handle {
-                self.agent_connections.remove(i);
+                let mut remote_agent = self.agent_connections.remove(i);
+                if let Some(ref mut delivery) = remote_agent.delivery {
+                    delivery.fail_all(conn_handle, "Connection closed");
+                }
                 return Ok(())
             }
         }
@@ -237,6 +394,9 @@ impl AgentWorker {
             for i in 0..agent_listener.connections.len() {
                 if agent_listener.connections[i].0 == conn_handle {
                     agent_listener.connections.remove(i);
+                    if let Some(mut delivery) = agent_listener.deliveries.remove(&conn_handle) {
+                        delivery.fail_all(conn_handle, "Connection closed");
+                    }
                     return Ok(())
                 }
             }
@@ -254,14 +414,14 @@ impl AgentWorker {
         return Err(CommonError::InvalidStructure(format!("Can't close agent listener {} - not found", listener_handle)))
     }
 
-    fn try_start_listen(&mut self, handle: i32, endpoint: String, pk: String, sk: String) -> Result<(), CommonError> {
-        let listener = AgentListener::new(handle, endpoint.clone(), pk, sk).map_err(map_err_trace!("AgentListener::new"))?;
+    fn try_start_listen(&mut self, cmd: &ListenCmd) -> Result<(), CommonError> {
+        let listener = AgentListener::new(cmd).map_err(map_err_trace!("AgentListener::new"))?;
         self.agent_listeners.push(listener);
-        info!("Agent listener started at {}", endpoint);
+        info!("Agent listener started at {}", cmd.endpoint);
         Ok(())
     }
 
-    fn poll(&self) -> Result<Vec<AgentWorkerCommand>, CommonError> {
+    fn poll(&self, timeout: i64) -> Result<Vec<AgentWorkerCommand>, CommonError> {
         let mut result = Vec::new();
         let mut poll_items: Vec<zmq::PollItem> = Vec::new();
         poll_items.push(self.cmd_socket.as_poll_item(zmq::POLLIN));
@@ -277,7 +437,11 @@ impl AgentWorker {
             poll_items.push(agent_listener.socket.as_poll_item(zmq::POLLIN));
         }
 
-        zmq::poll(poll_items.as_mut_slice(), -1).map_err(map_err_trace!("agent poll failed"))?;
+        for agent_listener in &self.agent_listeners {
+            poll_items.push(agent_listener.zap_socket.as_poll_item(zmq::POLLIN));
+        }
+
+        zmq::poll(poll_items.as_mut_slice(), timeout).map_err(map_err_trace!("agent poll failed"))?;
 
         if poll_items[0].is_readable() {
             let msg = self.cmd_socket.recv_string(zmq::DONTWAIT)?.map_err(|inv_bytes|
@@ -301,12 +465,16 @@ impl AgentWorker {
         for i in 0..agent_listeners_cnt {
             if poll_items[1 + agent_connections_cnt + i].is_readable() {
                 let identity = self.agent_listeners[i].socket.recv_string(zmq::DONTWAIT)?;
-                let msg = self.agent_listeners[i].socket.recv_string(zmq::DONTWAIT)?;
-                if let (Ok(identity), Ok(msg)) = (identity, msg) {
+                let mut msg = self.agent_listeners[i].socket.recv_msg(zmq::DONTWAIT)?;
+                // User-Id is set by ZAP handler to the client CurveZMQ public key
+                let client_key = msg.gets("User-Id").map(str::to_string);
+                let msg = msg.as_str().map(str::to_string);
+                if let (Ok(identity), Some(msg)) = (identity, msg) {
                     trace!("Input on agent listener socket {}: identity {} msg {}", i, identity, msg);
                     result.push(AgentWorkerCommand::Request(Request {
                         listener_ind: i,
                         identity: identity,
+                        client_key: client_key,
                         msg: msg,
                     }))
                 } else {
@@ -314,107 +482,1270 @@ impl AgentWorker {
                 }
             }
         }
+        for i in 0..agent_listeners_cnt {
+            if poll_items[1 + agent_connections_cnt + agent_listeners_cnt + i].is_readable() {
+                result.push(AgentWorkerCommand::ZapRequest(ZapRequest {
+                    listener_ind: i,
+                }))
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl Drop for RemoteAgent {
+    fn drop(&mut self) {
+        memzero(&mut self.secret_key);
+    }
+}
+
+impl RemoteAgent {
+    fn new(did: &str, pub_key: &str, sec_key: &str, receiver_did: &str, ver_key: &str, addr: &str, conn_handle: i32,
+           delivery: Option<DeliveryOptions>) -> Result<RemoteAgent, CommonError> {
+        Ok(RemoteAgent {
+            socket: zmq::Context::new().socket(zmq::SocketType::DEALER)?,
+            did: did.to_string(),
+            receiver_did: receiver_did.to_string(),
+            public_key: pub_key.from_base58()
+                .map_err(|err| CommonError::InvalidStructure(format!("invalid pub_key {}", err)))?,
+            secret_key: sec_key.from_base58()
+                .map_err(|err| CommonError::InvalidStructure(format!("invalid sec_key {}", err)))?,
+            server_key: ver_key.from_base58()
+                .map_err(|err| CommonError::InvalidStructure(format!("invalid server_key {}", err)))?,
+            addr: addr.to_string(),
+            conn_handle: conn_handle,
+            connected: false,
+            reconnecting: false,
+            delivery: delivery.map(Delivery::new),
+        })
+    }
+
+    fn connect(&self) -> Result<(), CommonError> {
+        impl From<zmq::EncodeError> for CommonError {
+            fn from(err: zmq::EncodeError) -> CommonError {
+                CommonError::InvalidState(format!("Invalid data stored RemoteAgent detected while connect {:?}", err))
+            }
+        }
+        self.socket.set_identity(zmq::z85_encode(self.public_key.as_slice())?.as_bytes())
+            .map_err(map_err_trace!())?;
+        self.socket.set_curve_secretkey(zmq::z85_encode(self.secret_key.as_slice())?.as_str())
+            .map_err(map_err_trace!())?;
+        self.socket.set_curve_publickey(zmq::z85_encode(self.public_key.as_slice())?.as_str())
+            .map_err(map_err_trace!())?;
+        self.socket.set_curve_serverkey(zmq::z85_encode(self.server_key.as_slice())?.as_str())
+            .map_err(map_err_trace!())?;
+        self.socket.set_linger(0).map_err(map_err_trace!())?; //TODO set correct timeout
+        if let Some(ref delivery) = self.delivery {
+            // ZeroMQ doubles reconnect interval after each failed attempt up to this limit
+            self.socket.set_reconnect_ivl_max(delivery.options.max_interval as i32).map_err(map_err_trace!())?;
+        }
+        self.socket.connect(self.addr.as_str())
+            .map_err(map_err_trace!("RemoteAgent::connect self.socket.connect failed"))?;
+        self.send_hello().map_err(map_err_trace!())?;
+        Ok(())
+    }
+
+    fn send_hello(&self) -> Result<(), CommonError> {
+        self.send_handshake_msg(&HandshakeMsg::Hello {
+            did: Some(self.receiver_did.clone()),
+            reliable: self.delivery.is_some(),
+        })
+    }
+
+    /// Sends serialized envelope of user message and returns its id, id is 0 for not reliable connection.
+    /// Message of reliable connection that can't be sent now will be resent later.
+    fn send_msg(&mut self, msg: String) -> Result<i32, CommonError> {
+        match self.delivery {
+            Some(ref mut delivery) => {
+                let (id, data) = delivery.push(msg)?;
+                if self.connected {
+                    if let Err(err) = self.socket.send_str(&data, zmq::DONTWAIT) {
+                        warn!("RemoteAgent::send_msg can't send message {} now: {}", id, err);
+                    }
+                }
+                Ok(id)
+            }
+            None => {
+                self.socket.send(msg.as_bytes(), zmq::DONTWAIT)?;
+                Ok(0)
+            }
+        }
+    }
+
+    /// Repeats DID handshake after peer lost the connection, e.g. on listener restart.
+    /// Not acknowledged messages are resent in new delivery session after handshake is completed.
+    fn reconnect(&mut self) {
+        info!("Connection {} is reset by peer, reconnecting", self.conn_handle);
+        self.connected = false;
+        self.reconnecting = true;
+        if let Err(err) = self.send_hello() {
+            error!("RemoteAgent::reconnect can't send Hello {}", err);
+        }
+    }
+
+    fn resend_expired(&mut self, now: Instant) {
+        if let Some(ref mut delivery) = self.delivery {
+            for data in delivery.expired(self.conn_handle, now) {
+                // Messages are resent only after reconnect is completed
+                if self.connected {
+                    if let Err(err) = self.socket.send_str(&data, zmq::DONTWAIT) {
+                        warn!("RemoteAgent::resend_expired can't resend message: {}", err);
+                    }
+                }
+            }
+        }
+    }
+
+    fn send_handshake_msg(&self, msg: &HandshakeMsg) -> Result<(), CommonError> {
+        let data = AgentEnvelope::control_data(HANDSHAKE_TYPE, None, msg)?;
+        self.socket.send_str(data.as_str(), zmq::DONTWAIT).map_err(From::from)
+    }
+
+    fn handle_response(&mut self, msg: String) {
+        let envelope = match AgentEnvelope::parse(&msg) {
+            Ok(envelope) => envelope,
+            Err(err) => return error!("RemoteAgent::handle_response dropped invalid message on connection {}: {}", self.conn_handle, err)
+        };
+
+        let msg_type = envelope.msg_type.clone();
+        match msg_type.as_str() {
+            HANDSHAKE_TYPE => self.handle_handshake_msg(&envelope),
+            DELIVERY_TYPE => self.handle_delivery_msg(&envelope),
+            PING_TYPE => {
+                if let Err(err) = AgentEnvelope::pong_data(&envelope)
+                    .and_then(|pong| self.socket.send_str(&pong, zmq::DONTWAIT).map_err(From::from)) {
+                    error!("RemoteAgent::handle_response can't send pong {}", err);
+                }
+            }
+            PONG_TYPE => debug!("Pong for {:?} received on connection {}", envelope.thread_id, self.conn_handle),
+            _ if self.connected => {
+                let cmd = AgentCommand::MessageReceived(self.conn_handle, Ok((self.conn_handle, envelope)));
+                if let Err(err) = CommandExecutor::instance().send(Command::Agent(cmd)) {
+                    error!("RemoteAgent::handle_response got incoming msg, but can't send to user {}", err);
+                };
+            }
+            _ => warn!("RemoteAgent::handle_response dropped message {} received before handshake on connection {}", envelope.id, self.conn_handle)
+        }
+    }
+
+    fn handle_delivery_msg(&mut self, envelope: &AgentEnvelope) {
+        let conn_handle = self.conn_handle;
+        let delivery = match self.delivery {
+            Some(ref mut delivery) => delivery,
+            None => return warn!("RemoteAgent::handle_delivery_msg dropped delivery message on not reliable connection {}", conn_handle)
+        };
+        match envelope.parse_body().and_then(|delivery_msg| delivery.handle(conn_handle, delivery_msg)) {
+            Ok(Some(ack)) => {
+                if let Err(err) = self.socket.send_str(&ack, zmq::DONTWAIT) {
+                    error!("RemoteAgent::handle_delivery_msg can't send ack {}", err);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => error!("RemoteAgent::handle_delivery_msg can't handle delivery message {}", err)
+        }
+    }
+
+    fn handle_handshake_msg(&mut self, envelope: &AgentEnvelope) {
+        let msg: Result<HandshakeMsg, CommonError> = envelope.parse_body();
+        let cmd: AgentCommand = if self.connected {
+            match msg {
+                Ok(HandshakeMsg::Reset) if self.delivery.is_some() => return self.reconnect(),
+                msg => return warn!("RemoteAgent::handle_handshake_msg dropped {:?} on established connection {}", msg, self.conn_handle)
+            }
+        } else {
+            match msg {
+                Ok(HandshakeMsg::Challenge { nonce }) => AgentCommand::ConnectChallenge(self.conn_handle, self.did.clone(), nonce),
+                Ok(HandshakeMsg::Accepted) if self.reconnecting => {
+                    info!("Connection {} is reconnected", self.conn_handle);
+                    self.connected = true;
+                    self.reconnecting = false;
+                    // Listener starts delivery state of reconnected peer from scratch
+                    if let Some(ref mut delivery) = self.delivery {
+                        if let Err(err) = delivery.reset_session() {
+                            error!("RemoteAgent::handle_handshake_msg can't reset delivery session {}", err);
+                        }
+                    }
+                    return self.resend_all();
+                }
+                Ok(HandshakeMsg::Accepted) => {
+                    self.connected = true;
+                    AgentCommand::ConnectAck(self.conn_handle, Ok(self.conn_handle))
+                }
+                Ok(HandshakeMsg::Rejected { reason }) if self.reconnecting => {
+                    self.reconnecting = false;
+                    if let Some(ref mut delivery) = self.delivery {
+                        delivery.fail_all(self.conn_handle, &format!("Reconnect rejected: {}", reason));
+                    }
+                    return;
+                }
+                // Peer resets each message that was sent before reconnect
+                Ok(HandshakeMsg::Reset) if self.reconnecting => return,
+                Ok(HandshakeMsg::Rejected { reason }) =>
+                    AgentCommand::ConnectAck(self.conn_handle, Err(CommonError::InvalidState(format!("Connection rejected: {}", reason)))),
+                msg => AgentCommand::ConnectAck(self.conn_handle, Err(CommonError::InvalidStructure(format!("Unexpected handshake message: {:?}", msg))))
+            }
+        };
+        if let Err(err) = CommandExecutor::instance().send(Command::Agent(cmd)) {
+            error!("RemoteAgent::handle_handshake_msg can't send handshake result {}", err);
+        };
+    }
+
+    fn resend_all(&mut self) {
+        if let Some(ref mut delivery) = self.delivery {
+            for data in delivery.reschedule_all(Instant::now()) {
+                if let Err(err) = self.socket.send_str(&data, zmq::DONTWAIT) {
+                    warn!("RemoteAgent::resend_all can't resend message: {}", err);
+                }
+            }
+        }
+    }
+}
+
+impl AgentListener {
+    fn new(cmd: &ListenCmd) -> Result<AgentListener, zmq::Error> {
+        let ctx = zmq::Context::new();
+        // ZAP handler must be bound in the same context before listener socket starts accepting connections
+        let zap_sock = ctx.socket(zmq::SocketType::REP)?;
+        zap_sock.bind("inproc://zeromq.zap.01")?;
+        let sock = ctx.socket(zmq::SocketType::ROUTER)?;
+        //TODO use forked zmq and set cb instead of raw keys
+        sock.set_curve_publickey(zmq::z85_encode(cmd.pk.from_base58().unwrap().as_slice()).unwrap().as_str())?;
+        sock.set_curve_secretkey(zmq::z85_encode(cmd.sk.from_base58().unwrap().as_slice()).unwrap().as_str())?;
+        //TODO /cb instead keys
+        sock.set_curve_server(true)?;
+        sock.bind(cmd.endpoint.as_str())?;
+        Ok(AgentListener {
+            connections: Vec::new(),
+            deliveries: HashMap::new(),
+            challenges: HashMap::new(),
+            listener_handle: cmd.listen_handle,
+            did: cmd.did.clone(),
+            identities: cmd.identities.iter().cloned().collect(),
+            policy: cmd.policy.clone(),
+            socket: sock,
+            zap_socket: zap_sock,
+        })
+    }
+
+    /// Handles ZeroMQ authentication request (RFC 27). CurveZMQ handshake itself proves that client
+    /// owns the key, so CURVE client is passed if listener policy allows its key and the key is reported
+    /// as User-Id. Binding of the key to the client DID is checked later in DID handshake.
+    fn handle_zap_request(&self) -> Result<(), CommonError> {
+        let frames = self.zap_socket.recv_multipart(zmq::DONTWAIT)?;
+        // version, request id, domain, address, identity, mechanism, client key
+        if frames.len() < 7 {
+            return Err(CommonError::InvalidStructure(format!("Invalid ZAP request with {} frames", frames.len())));
+        }
+
+        let res = if frames[5].as_slice() == "CURVE".as_bytes() {
+            let client_key = Base58::encode(&frames[6]);
+            self.policy.check(&client_key)
+                .map(|()| client_key.clone())
+                .map_err(|reason| {
+                    self.on_rejected(self.policy.did_for_key(&client_key), Some(&client_key), &reason);
+                    reason
+                })
+        } else {
+            Err("Only CURVE mechanism is supported".to_string())
+        };
+
+        let (status_code, status_text, user_id) = match res {
+            Ok(client_key) => ("200", "OK".to_string(), client_key),
+            Err(reason) => ("400", reason, String::new())
+        };
+
+        self.zap_socket.send_multipart(&["1.0".as_bytes(), frames[1].as_slice(), status_code.as_bytes(),
+                                         status_text.as_bytes(), user_id.as_bytes(), "".as_bytes()], zmq::DONTWAIT)
+            .map_err(From::from)
+    }
+
+    fn handle_request(&mut self, identity: String, client_key: Option<String>, msg: String) -> Result<(), CommonError> {
+        let envelope = match AgentEnvelope::parse(&msg) {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                warn!("Invalid message to agent listener from {}: {}", identity, err);
+                if self.connections.iter().any(|&(_, ref id)| identity.eq(id.as_str())) {
+                    return Ok(());
+                }
+                return self.reject(&identity, None, client_key.as_ref().map(String::as_str), &format!("{}", err));
+            }
+        };
+
+        if envelope.msg_type == PING_TYPE {
+            let pong = AgentEnvelope::pong_data(&envelope)?;
+            return self.socket.send_multipart(&[identity.as_bytes(), pong.as_bytes()], zmq::DONTWAIT).map_err(From::from);
+        }
+
+        if let Some(&(conn_handle, _)) = self.connections.iter().find(|&&(_, ref id)| identity.eq(id.as_str())) {
+            let msg_type = envelope.msg_type.clone();
+            return match msg_type.as_str() {
+                DELIVERY_TYPE => match self.deliveries.get_mut(&conn_handle) {
+                    Some(delivery) => match envelope.parse_body().and_then(|msg| delivery.handle(conn_handle, msg)) {
+                        Ok(Some(ack)) => self.socket.send_multipart(&[identity.as_bytes(), ack.as_bytes()], zmq::DONTWAIT).map_err(From::from),
+                        Ok(None) => Ok(()),
+                        Err(err) => {
+                            warn!("Delivery message {} on connection {} dropped: {}", envelope.id, conn_handle, err);
+                            Ok(())
+                        }
+                    },
+                    None => {
+                        warn!("Delivery message on not reliable connection {} dropped", conn_handle);
+                        Ok(())
+                    }
+                },
+                HANDSHAKE_TYPE | PONG_TYPE => {
+                    debug!("Control message {} on connection {} ignored", msg_type, conn_handle);
+                    Ok(())
+                }
+                _ => CommandExecutor::instance().send(Command::Agent(AgentCommand::MessageReceived(
+                    conn_handle, Ok((conn_handle, envelope)))))
+            };
+        }
+
+        if envelope.msg_type == DELIVERY_TYPE {
+            // Peer with reliable connection (e.g. established before listener restart) must repeat handshake
+            info!("Delivery message from unknown connection to agent listener from {}, reset it", identity);
+            return self.send_handshake_msg(&identity, &HandshakeMsg::Reset);
+        }
+
+        if envelope.msg_type != HANDSHAKE_TYPE {
+            info!("Message {} from unknown connection to agent listener from {}", envelope.id, identity);
+            return self.reject(&identity, None, client_key.as_ref().map(String::as_str), "Connection isn't established");
+        }
+
+        match (envelope.parse_body(), client_key) {
+            (Ok(HandshakeMsg::Hello { did, reliable }), Some(client_key)) => {
+                info!("New connection to agent listener from {} to {:?}", identity, did);
+                let receiver_did = did.unwrap_or(self.did.clone());
+                if !self.identities.contains(&receiver_did) {
+                    return self.reject(&identity, None, Some(&client_key),
+                                       &format!("Identity {} isn't served by listener", receiver_did));
+                }
+                let nonce = Base58::encode(&XSalsa20::new().gen_nonce());
+                self.challenges.insert(identity.clone(), Challenge {
+                    client_key: client_key,
+                    receiver_did: receiver_did,
+                    nonce: nonce.clone(),
+                    reliable: reliable,
+                });
+                self.send_handshake_msg(&identity, &HandshakeMsg::Challenge { nonce: nonce })
+            }
+            (Ok(HandshakeMsg::Did { did, signature, verkey }), client_key) => {
+                match self.challenges.remove(&identity) {
+                    Some(challenge) => {
+                        let cmd = AgentCommand::CheckIncomingConnection(IncomingConnection {
+                            listener_handle: self.listener_handle,
+                            identity: identity,
+                            did: did,
+                            verkey: verkey,
+                            receiver_did: challenge.receiver_did,
+                            client_key: challenge.client_key,
+                            nonce: challenge.nonce,
+                            signature: signature,
+                            reliable: challenge.reliable,
+                        });
+                        CommandExecutor::instance().send(Command::Agent(cmd))
+                    }
+                    None => self.reject(&identity, Some(&did), client_key.as_ref().map(String::as_str), "Challenge wasn't requested")
+                }
+            }
+            (msg, client_key) => {
+                info!("Unexpected handshake message {:?} to agent listener from {}", msg, identity);
+                self.reject(&identity, None, client_key.as_ref().map(String::as_str), "Unexpected handshake message")
+            }
+        }
+    }
+
+    fn accept(&mut self, identity: &str, sender_did: &str, receiver_did: &str, reliable: bool) -> Result<(), CommonError> {
+        info!("Connection to agent listener from {} authenticated as {}", identity, sender_did);
+        let conn_handle = SequenceUtils::get_next_id();
+        self.connections.push((conn_handle, identity.to_string()));
+        if reliable {
+            self.deliveries.insert(conn_handle, Delivery::new(DeliveryOptions::default()));
+        }
+        let cmd = AgentCommand::ListenerOnConnect(self.listener_handle,
+                                                  Ok((self.listener_handle, conn_handle,
+                                                      sender_did.to_string(), receiver_did.to_string())));
+        CommandExecutor::instance().send(Command::Agent(cmd))?;
+        self.send_handshake_msg(identity, &HandshakeMsg::Accepted)
+    }
+
+    fn reject(&self, identity: &str, did: Option<&str>, client_key: Option<&str>, reason: &str) -> Result<(), CommonError> {
+        info!("Connection to agent listener from {} rejected: {}", identity, reason);
+        self.on_rejected(did, client_key, reason);
+        self.send_handshake_msg(identity, &HandshakeMsg::Rejected { reason: reason.to_string() })
+    }
+
+    fn on_rejected(&self, did: Option<&str>, client_key: Option<&str>, reason: &str) {
+        let event = ListenerEvent::ConnectionRejected {
+            did: did.map(str::to_string),
+            client_key: client_key.map(str::to_string),
+            reason: reason.to_string(),
+        };
+        let cmd = AgentCommand::ListenerOnEvent(self.listener_handle, event);
+        if let Err(err) = CommandExecutor::instance().send(Command::Agent(cmd)) {
+            error!("AgentListener::on_rejected can't send ListenerOnEvent {}", err);
+        }
+    }
+
+    fn send_handshake_msg(&self, identity: &str, msg: &HandshakeMsg) -> Result<(), CommonError> {
+        let data = AgentEnvelope::control_data(HANDSHAKE_TYPE, None, msg)?;
+        self.socket.send_multipart(&[identity.as_bytes(), data.as_bytes()], zmq::DONTWAIT).map_err(From::from)
+    }
+
+    /// Sends serialized envelope of user message to incoming connection and returns its id, id is 0 for not reliable connection.
+    fn send_msg(&mut self, conn_handle: i32, msg: String) -> Result<i32, CommonError> {
+        let identity = self.connections.iter()
+            .find(|&&(conn_id, _)| conn_id == conn_handle)
+            .map(|&(_, ref identity)| identity.clone())
+            .ok_or(CommonError::InvalidStructure(format!("Connection with id {} not founded", conn_handle)))?;
+        match self.deliveries.get_mut(&conn_handle) {
+            Some(delivery) => {
+                let (id, data) = delivery.push(msg)?;
+                // Message is dropped by ROUTER socket if peer is disconnected and will be resent later
+                if let Err(err) = self.socket.send_multipart(&[identity.as_bytes(), data.as_bytes()], zmq::DONTWAIT) {
+                    warn!("AgentListener::send_msg can't send message {} now: {}", id, err);
+                }
+                Ok(id)
+            }
+            None => {
+                self.socket.send_str(identity.as_str(), zmq::DONTWAIT | zmq::SNDMORE)
+                    .and_then(|()| self.socket.send(msg.as_bytes(), zmq::DONTWAIT))?;
+                Ok(0)
+            }
+        }
+    }
+
+    fn resend_expired(&mut self, now: Instant) {
+        for &(conn_handle, ref identity) in &self.connections {
+            if let Some(delivery) = self.deliveries.get_mut(&conn_handle) {
+                for data in delivery.expired(conn_handle, now) {
+                    if let Err(err) = self.socket.send_multipart(&[identity.as_bytes(), data.as_bytes()], zmq::DONTWAIT) {
+                        warn!("AgentListener::resend_expired can't resend message: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Version of agent envelope format. Envelopes of other versions are dropped.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// Type of messages sent by sovrin_agent_send.
+pub const USER_MESSAGE_TYPE: &'static str = "message";
+
+/// Types with this prefix are reserved for control messages and can't be used by user messages.
+pub const CONTROL_TYPE_PREFIX: &'static str = "sovrin/";
+
+pub const MAILBOX_TYPE: &'static str = "sovrin/mailbox";
+
+pub const CLAIM_TYPE: &'static str = "sovrin/claim";
+
+pub const PROOF_TYPE: &'static str = "sovrin/proof";
+
+pub const INVITATION_TYPE: &'static str = "sovrin/invitation";
+
+const HANDSHAKE_TYPE: &'static str = "sovrin/handshake";
+
+const DELIVERY_TYPE: &'static str = "sovrin/delivery";
+
+const PING_TYPE: &'static str = "sovrin/ping";
+
+const PONG_TYPE: &'static str = "sovrin/pong";
+
+/// Envelope of each message sent over agent connection, both user and control ones.
+/// Body is binary-safe: it is encoded as base64url without padding.
+/// Reply to message or message of some conversation refers to it by thread id.
+/// Signature is made by sender DID verkey over all other fields of envelope (see signing_input).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgentEnvelope {
+    pub version: u32,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    pub thread_id: Option<String>,
+    pub timestamp: i64,
+    pub signature: Option<String>,
+    pub body: String,
+}
+
+impl AgentEnvelope {
+    pub fn new(msg_type: &str, thread_id: Option<&str>, body: &[u8]) -> AgentEnvelope {
+        AgentEnvelope {
+            version: ENVELOPE_VERSION,
+            id: Uuid::new_v4().to_string(),
+            msg_type: msg_type.to_string(),
+            thread_id: thread_id.map(str::to_string),
+            timestamp: time::get_time().sec,
+            signature: None,
+            body: Base64::encode_url_safe(body),
+        }
+    }
+
+    /// Parses envelope received from peer.
+    pub fn parse(data: &str) -> Result<AgentEnvelope, CommonError> {
+        let envelope = AgentEnvelope::from_json(data)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid agent envelope: {}", err.description())))?;
+        if envelope.version != ENVELOPE_VERSION {
+            return Err(CommonError::InvalidStructure(format!("Unsupported agent envelope version: {}", envelope.version)));
+        }
+        Ok(envelope)
+    }
+
+    pub fn is_control_type(msg_type: &str) -> bool {
+        msg_type.starts_with(CONTROL_TYPE_PREFIX)
+    }
+
+    pub fn body(&self) -> Result<Vec<u8>, CommonError> {
+        Base64::decode_url_safe(&self.body)
+    }
+
+    /// Parses body of control message.
+    pub fn parse_body<T>(&self) -> Result<T, CommonError> where T: for<'a> JsonDecodable<'a> {
+        let body = String::from_utf8(self.body()?)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid {} message: {}", self.msg_type, err.description())))?;
+        T::from_json(&body)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid {} message: {}", self.msg_type, err.description())))
+    }
+
+    pub fn signing_input(&self) -> String {
+        format!("{}.{}.{}.{}.{}.{}", self.version, self.id, self.msg_type,
+                self.thread_id.as_ref().map(String::as_str).unwrap_or(""), self.timestamp, self.body)
+    }
+
+    pub fn to_data(&self) -> Result<String, CommonError> {
+        self.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize AgentEnvelope {}", err.description())))
+    }
+
+    /// Returns serialized envelope of control message.
+    fn control_data<T: JsonEncodable>(msg_type: &str, thread_id: Option<&str>, msg: &T) -> Result<String, CommonError> {
+        let msg = msg.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize {} message {}", msg_type, err.description())))?;
+        AgentEnvelope::new(msg_type, thread_id, msg.as_bytes()).to_data()
+    }
+
+    fn pong_data(ping: &AgentEnvelope) -> Result<String, CommonError> {
+        AgentEnvelope::new(PONG_TYPE, Some(&ping.id), &[]).to_data()
+    }
+}
+
+impl JsonEncodable for AgentEnvelope {}
+
+impl<'a> JsonDecodable<'a> for AgentEnvelope {}
+
+/// Challenge sent by listener to incoming connection.
+#[derive(Debug)]
+struct Challenge {
+    client_key: String,
+    receiver_did: String,
+    nonce: String,
+    reliable: bool,
+}
+
+/// Messages of DID handshake (sent in envelopes of sovrin/handshake type) performed over established CurveZMQ connection:
+/// client sends Hello with receiver DID, listener answers with Challenge, client sends Did with signature
+/// of the challenge nonce by DID verkey and listener answers with Accepted or Rejected.
+/// Verkey is sent in Did too, so listener can authenticate DID it invited without the ledger.
+/// Listener answers with Reset on delivery message of reliable connection it doesn't know.
+#[serde(tag = "type")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum HandshakeMsg {
+    Hello {
+        did: Option<String>,
+        #[serde(default)]
+        reliable: bool,
+    },
+    Challenge { nonce: String },
+    Did {
+        did: String,
+        signature: String,
+        #[serde(default)]
+        verkey: Option<String>,
+    },
+    Accepted,
+    Rejected { reason: String },
+    Reset,
+}
+
+impl JsonEncodable for HandshakeMsg {}
+
+impl<'a> JsonDecodable<'a> for HandshakeMsg {}
+
+/// Messages of reliable connection (sent in envelopes of sovrin/delivery type): each user message
+/// envelope is wrapped with id unique in the connection and is acknowledged by peer.
+#[serde(tag = "type")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum DeliveryMsg {
+    Message { id: i32, msg: String },
+    Ack { id: i32 },
+}
+
+impl JsonEncodable for DeliveryMsg {}
+
+impl<'a> JsonDecodable<'a> for DeliveryMsg {}
+
+/// Connection options passed by user.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ConnectOptions {
+    pub reliable: Option<bool>,
+    pub ack_timeout: Option<u64>,
+    pub max_interval: Option<u64>,
+    pub max_attempts: Option<u32>,
+}
+
+impl ConnectOptions {
+    pub fn delivery_options(&self) -> Option<DeliveryOptions> {
+        if !self.reliable.unwrap_or(false) {
+            return None;
+        }
+        let default = DeliveryOptions::default();
+        Some(DeliveryOptions {
+            ack_timeout: self.ack_timeout.unwrap_or(default.ack_timeout),
+            max_interval: self.max_interval.unwrap_or(default.max_interval),
+            max_attempts: self.max_attempts.unwrap_or(default.max_attempts),
+        })
+    }
+}
+
+impl JsonEncodable for ConnectOptions {}
+
+impl<'a> JsonDecodable<'a> for ConnectOptions {}
+
+/// Options of reliable delivery: message is resent if it isn't acknowledged in ack_timeout ms,
+/// timeout is doubled after each attempt up to max_interval ms. Message isn't delivered
+/// if it isn't acknowledged after max_attempts attempts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeliveryOptions {
+    pub ack_timeout: u64,
+    pub max_interval: u64,
+    pub max_attempts: u32,
+}
+
+impl Default for DeliveryOptions {
+    fn default() -> DeliveryOptions {
+        DeliveryOptions {
+            ack_timeout: 3000,
+            max_interval: 30000,
+            max_attempts: 10,
+        }
+    }
+}
+
+/// Reliable delivery state of connection. Outgoing messages are kept until peer acknowledges them,
+/// ids of incoming messages are tracked to drop duplicates of resent messages.
+/// Messages are sent with sequence number of delivery session, it starts from 1 again after reconnect
+/// while id returned to user stays the same.
+struct Delivery {
+    options: DeliveryOptions,
+    next_id: i32,
+    next_seq: i32,
+    pending: Vec<PendingMsg>,
+    received_up_to: i32,
+    received_ahead: HashSet<i32>,
+}
+
+struct PendingMsg {
+    id: i32,
+    seq: i32,
+    msg: String,
+    data: String,
+    attempts: u32,
+    deadline: Instant,
+}
+
+impl Delivery {
+    fn new(options: DeliveryOptions) -> Delivery {
+        Delivery {
+            options: options,
+            next_id: 1,
+            next_seq: 1,
+            pending: Vec::new(),
+            received_up_to: 0,
+            received_ahead: HashSet::new(),
+        }
+    }
+
+    /// Registers outgoing message, returns its id and serialized delivery message to send.
+    fn push(&mut self, msg: String) -> Result<(i32, String), CommonError> {
+        let (id, seq) = (self.next_id, self.next_seq);
+        let data = Delivery::_message_data(seq, &msg)?;
+        let deadline = Instant::now() + Delivery::_timeout(&self.options, 1);
+        self.next_id += 1;
+        self.next_seq += 1;
+        self.pending.push(PendingMsg {
+            id: id,
+            seq: seq,
+            msg: msg,
+            data: data.clone(),
+            attempts: 1,
+            deadline: deadline,
+        });
+        Ok((id, data))
+    }
+
+    /// Handles delivery message from peer. Returns serialized ack to send back for incoming message.
+    fn handle(&mut self, conn_handle: i32, msg: DeliveryMsg) -> Result<Option<String>, CommonError> {
+        match msg {
+            DeliveryMsg::Message { id, msg } => {
+                if self.on_message(id) {
+                    let res = AgentEnvelope::parse(&msg).map(|envelope| (conn_handle, envelope));
+                    CommandExecutor::instance().send(Command::Agent(
+                        AgentCommand::MessageReceived(conn_handle, res)))?;
+                } else {
+                    debug!("Duplicate of message {} received on connection {}", id, conn_handle);
+                }
+                // Duplicate is acknowledged too as previous ack could be lost
+                let ack = AgentEnvelope::control_data(DELIVERY_TYPE, None, &DeliveryMsg::Ack { id: id })?;
+                Ok(Some(ack))
+            }
+            DeliveryMsg::Ack { id: seq } => {
+                if let Some(id) = self.on_ack(seq) {
+                    CommandExecutor::instance().send(Command::Agent(
+                        AgentCommand::DeliveryStatus(conn_handle, id, Ok(()))))?;
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns true if message wasn't received before.
+    fn on_message(&mut self, id: i32) -> bool {
+        if id <= self.received_up_to || !self.received_ahead.insert(id) {
+            return false;
+        }
+        while self.received_ahead.remove(&(self.received_up_to + 1)) {
+            self.received_up_to += 1;
+        }
+        true
+    }
+
+    /// Returns id of acknowledged message if it was pending.
+    fn on_ack(&mut self, seq: i32) -> Option<i32> {
+        let pos = self.pending.iter().position(|pending| pending.seq == seq);
+        pos.map(|pos| self.pending.remove(pos).id)
+    }
+
+    /// Starts new delivery session when handshake is completed again and peer's delivery state is new:
+    /// received sequence numbers are forgotten and pending messages are renumbered from 1 in the same order.
+    fn reset_session(&mut self) -> Result<(), CommonError> {
+        self.received_up_to = 0;
+        self.received_ahead.clear();
+        self.next_seq = 1;
+        for pending in &mut self.pending {
+            pending.seq = self.next_seq;
+            pending.data = Delivery::_message_data(pending.seq, &pending.msg)?;
+            self.next_seq += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns messages to resend. Messages that ran out of attempts are dropped and reported as failed.
+    fn expired(&mut self, conn_handle: i32, now: Instant) -> Vec<String> {
+        let mut resend = Vec::new();
+        let mut failed = Vec::new();
+        for pending in &mut self.pending {
+            if pending.deadline > now {
+                continue;
+            }
+            if pending.attempts >= self.options.max_attempts {
+                failed.push(pending.id);
+            } else {
+                pending.attempts += 1;
+                pending.deadline = now + Delivery::_timeout(&self.options, pending.attempts);
+                resend.push(pending.data.clone());
+            }
+        }
+        self.pending.retain(|pending| !failed.contains(&pending.id));
+        for id in failed {
+            Delivery::_report_failed(conn_handle, id, &format!("Message isn't acknowledged after {} attempts", self.options.max_attempts));
+        }
+        resend
+    }
+
+    /// Returns all pending messages to resend now, e.g. after reconnect.
+    fn reschedule_all(&mut self, now: Instant) -> Vec<String> {
+        let mut resend = Vec::new();
+        for pending in &mut self.pending {
+            pending.deadline = now + Delivery::_timeout(&self.options, pending.attempts);
+            resend.push(pending.data.clone());
+        }
+        resend
+    }
+
+    /// Drops all pending messages and reports them as failed.
+    fn fail_all(&mut self, conn_handle: i32, reason: &str) {
+        for pending in self.pending.drain(..) {
+            Delivery::_report_failed(conn_handle, pending.id, reason);
+        }
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending.iter().map(|pending| pending.deadline).min()
+    }
+
+    fn _message_data(seq: i32, msg: &str) -> Result<String, CommonError> {
+        AgentEnvelope::control_data(DELIVERY_TYPE, None, &DeliveryMsg::Message { id: seq, msg: msg.to_string() })
+    }
+
+    fn _timeout(options: &DeliveryOptions, attempts: u32) -> Duration {
+        let timeout = options.ack_timeout.saturating_mul(1 << cmp::min(attempts - 1, 16));
+        Duration::from_millis(cmp::max(cmp::min(timeout, options.max_interval), options.ack_timeout))
+    }
+
+    fn _report_failed(conn_handle: i32, id: i32, reason: &str) {
+        let err = CommonError::IOError(io::Error::new(io::ErrorKind::TimedOut, reason.to_string()));
+        if let Err(err) = CommandExecutor::instance().send(Command::Agent(AgentCommand::DeliveryStatus(conn_handle, id, Err(err)))) {
+            error!("Delivery can't report failed message {}", err);
+        }
+    }
+}
+
+/// Messages of store-and-forward mailbox (sent in envelopes of sovrin/mailbox type) over established connection to relay agent:
+/// Forward asks relay to store message encrypted for hosted DID, Pull asks relay to return
+/// all stored messages of connection DID. Relay answers with Stored, Messages or Failed
+/// with id of the request. Pulled messages are removed by relay only after puller sends Ack
+/// with their ids, Ack isn't answered.
+#[serde(tag = "type")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum MailboxMsg {
+    Forward { id: i32, did: String, message: String },
+    Pull { id: i32 },
+    Ack { ids: Vec<i32> },
+    Stored { id: i32 },
+    Messages { id: i32, did: String, messages: Vec<MailboxMessage> },
+    Failed { id: i32, reason: String },
+}
+
+impl JsonEncodable for MailboxMsg {}
+
+impl<'a> JsonDecodable<'a> for MailboxMsg {}
+
+/// Mailbox of DID hosted by relay, stored in relay wallet.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MailboxInfo {
+    pub did: String,
+    pub next_id: i32,
+}
+
+impl MailboxInfo {
+    pub fn new(did: String) -> MailboxInfo {
+        MailboxInfo {
+            did: did,
+            next_id: 1,
+        }
+    }
+}
+
+impl JsonEncodable for MailboxInfo {}
+
+impl<'a> JsonDecodable<'a> for MailboxInfo {}
+
+/// Message stored in mailbox. Message is encrypted for hosted DID, so relay can't read it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MailboxMessage {
+    pub id: i32,
+    pub sender_did: String,
+    pub message: String,
+}
+
+impl JsonEncodable for MailboxMessage {}
+
+impl<'a> JsonDecodable<'a> for MailboxMessage {}
+
+/// Messages of claim issuance protocol (sent in envelopes of sovrin/claim type):
+/// issuer sends Offer, prover answers with Request, issuer completes the exchange with Claim.
+/// Problem is sent by either side if the exchange can't be continued and is never answered.
+#[serde(tag = "type")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ClaimMsg {
+    Offer { exchange_id: String, claim_offer: String, claim_def: String },
+    Request { exchange_id: String, claim_req: String },
+    Claim { exchange_id: String, claim: String },
+    Problem { exchange_id: String, reason: String },
+}
+
+impl JsonEncodable for ClaimMsg {}
+
+impl<'a> JsonDecodable<'a> for ClaimMsg {}
+
+#[serde(rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ClaimExchangeRole {
+    Issuer,
+    Prover,
+}
+
+/// Issuer exchange goes through offered, request_received and issued states,
+/// prover exchange goes through offer_received, requested and stored states.
+#[serde(rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ClaimExchangeState {
+    Offered,
+    OfferReceived,
+    Requested,
+    RequestReceived,
+    Issued,
+    Stored,
+    Failed,
+}
+
+/// State of claim exchange stored in wallet of each side, so exchange can be continued after restart.
+/// Connection of exchange is found by DIDs as connection handles don't survive restart.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClaimExchange {
+    pub id: String,
+    pub role: ClaimExchangeRole,
+    pub state: ClaimExchangeState,
+    pub my_did: String,
+    pub their_did: String,
+    pub claim_offer: String,
+    pub claim_def: Option<String>,
+    pub claim_req: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl ClaimExchange {
+    pub fn new(id: String, role: ClaimExchangeRole, state: ClaimExchangeState, my_did: String, their_did: String,
+               claim_offer: String, claim_def: Option<String>) -> ClaimExchange {
+        ClaimExchange {
+            id: id,
+            role: role,
+            state: state,
+            my_did: my_did,
+            their_did: their_did,
+            claim_offer: claim_offer,
+            claim_def: claim_def,
+            claim_req: None,
+            reason: None,
+        }
+    }
+
+    /// Wallet key of exchange. Role is part of the key as issuer and prover can share the wallet.
+    pub fn key(role: ClaimExchangeRole, id: &str) -> String {
+        match role {
+            ClaimExchangeRole::Issuer => format!("claim_exchange::issuer::{}", id),
+            ClaimExchangeRole::Prover => format!("claim_exchange::prover::{}", id),
+        }
+    }
+}
+
+impl JsonEncodable for ClaimExchange {}
+
+impl<'a> JsonDecodable<'a> for ClaimExchange {}
+
+/// Options of claim exchanges of a wallet. If master secret name is set, prover creates
+/// claim request for each received offer automatically.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ClaimExchangeOptions {
+    pub master_secret_name: Option<String>,
+}
+
+impl JsonEncodable for ClaimExchangeOptions {}
+
+impl<'a> JsonDecodable<'a> for ClaimExchangeOptions {}
+
+/// Events reported to claim exchange event callback.
+#[serde(tag = "type", rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ClaimExchangeEvent {
+    OfferReceived { exchange_id: String, claim_offer: String },
+    RequestSent { exchange_id: String },
+    RequestReceived { exchange_id: String, claim_req: String },
+    ClaimStored { exchange_id: String },
+    Failed { exchange_id: String, reason: String },
+}
+
+impl JsonEncodable for ClaimExchangeEvent {}
+
+impl<'a> JsonDecodable<'a> for ClaimExchangeEvent {}
+
+/// Messages of proof presentation protocol: verifier sends Request with fresh nonce,
+/// prover answers with Proof and verifier reports result of verification with Result.
+/// Proof carries ledger identifiers of used claims, so verifier resolves schemas and claim definitions itself.
+#[serde(tag = "type")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ProofMsg {
+    Request { exchange_id: String, proof_req: String },
+    Proof { exchange_id: String, proof: String, identifiers: HashMap<String, ClaimIdentifier> },
+    Result { exchange_id: String, valid: bool },
+    Problem { exchange_id: String, reason: String },
+}
+
+impl JsonEncodable for ProofMsg {}
+
+impl<'a> JsonDecodable<'a> for ProofMsg {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClaimIdentifier {
+    pub issuer_did: String,
+    pub schema_seq_no: i32,
+}
+
+#[serde(rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ProofExchangeRole {
+    Verifier,
+    Prover,
+}
+
+/// Verifier exchange goes through requested, proof_received and verified (or rejected) states,
+/// prover exchange goes through request_received, presented and verified (or rejected) states.
+#[serde(rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ProofExchangeState {
+    Requested,
+    RequestReceived,
+    Presented,
+    ProofReceived,
+    Verified,
+    Rejected,
+    Failed,
+}
+
+/// State of proof exchange stored in wallet of each side. Proof request of verifier exchange
+/// contains the nonce the proof must be created for.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProofExchange {
+    pub id: String,
+    pub role: ProofExchangeRole,
+    pub state: ProofExchangeState,
+    pub my_did: String,
+    pub their_did: String,
+    pub proof_req: String,
+    pub claims: Option<String>,
+    pub proof: Option<String>,
+    pub identifiers: Option<HashMap<String, ClaimIdentifier>>,
+    pub reason: Option<String>,
+}
+
+impl ProofExchange {
+    pub fn new(id: String, role: ProofExchangeRole, state: ProofExchangeState, my_did: String, their_did: String,
+               proof_req: String) -> ProofExchange {
+        ProofExchange {
+            id: id,
+            role: role,
+            state: state,
+            my_did: my_did,
+            their_did: their_did,
+            proof_req: proof_req,
+            claims: None,
+            proof: None,
+            identifiers: None,
+            reason: None,
+        }
+    }
+
+    pub fn key(role: ProofExchangeRole, id: &str) -> String {
+        match role {
+            ProofExchangeRole::Verifier => format!("proof_exchange::verifier::{}", id),
+            ProofExchangeRole::Prover => format!("proof_exchange::prover::{}", id),
+        }
+    }
+}
+
+impl JsonEncodable for ProofExchange {}
+
+impl<'a> JsonDecodable<'a> for ProofExchange {}
+
+/// Events reported to proof exchange event callback.
+#[serde(tag = "type", rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ProofExchangeEvent {
+    RequestReceived { exchange_id: String, proof_req: String, claims: String },
+    ProofReceived { exchange_id: String, proof: String, valid: bool },
+    ProofResult { exchange_id: String, valid: bool },
+    Failed { exchange_id: String, reason: String },
+}
+
+impl JsonEncodable for ProofExchangeEvent {}
+
+impl<'a> JsonDecodable<'a> for ProofExchangeEvent {}
+
+/// Invitation to pairwise relationship passed to invitee out of band: fresh DID of inviter
+/// with its verkey and endpoint of the listener serving this DID.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Invitation {
+    pub did: String,
+    pub verkey: String,
+    pub endpoint: String,
+    pub transport_key: String,
+    pub label: Option<String>,
+}
+
+impl JsonEncodable for Invitation {}
+
+impl<'a> JsonDecodable<'a> for Invitation {}
+
+/// Options of invitation accepting passed by invitee. Endpoint (with transport key) is
+/// stored by inviter, so it can connect to invitee later.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AcceptInvitationOptions {
+    pub label: Option<String>,
+    pub endpoint: Option<String>,
+    pub transport_key: Option<String>,
+}
+
+impl JsonEncodable for AcceptInvitationOptions {}
+
+impl<'a> JsonDecodable<'a> for AcceptInvitationOptions {}
+
+/// Message sent by invitee over connection to invited DID, each invitation is accepted only once.
+#[serde(tag = "type")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum InvitationMsg {
+    Accept { label: Option<String>, endpoint: Option<String>, transport_key: Option<String> },
+}
+
+impl JsonEncodable for InvitationMsg {}
+
+impl<'a> JsonDecodable<'a> for InvitationMsg {}
+
+/// Invitation goes through pending, connected (invitee DID is authenticated and stored)
+/// and accepted (Accept message is received) states.
+#[serde(rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum InvitationState {
+    Pending,
+    Connected,
+    Accepted,
+}
+
+/// State of invitation stored in inviter wallet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InvitationRecord {
+    pub my_did: String,
+    pub label: Option<String>,
+    pub state: InvitationState,
+    pub their_did: Option<String>,
+    pub their_label: Option<String>,
+}
+
+impl InvitationRecord {
+    pub fn new(my_did: String, label: Option<String>) -> InvitationRecord {
+        InvitationRecord {
+            my_did: my_did,
+            label: label,
+            state: InvitationState::Pending,
+            their_did: None,
+            their_label: None,
+        }
+    }
 
-        Ok(result)
+    pub fn key(my_did: &str) -> String {
+        format!("invitation::{}", my_did)
     }
 }
 
-impl RemoteAgent {
-    fn new(pub_key: &str, sec_key: &str, ver_key: &str, addr: &str, conn_handle: i32) -> Result<RemoteAgent, CommonError> {
-        Ok(RemoteAgent {
-            socket: zmq::Context::new().socket(zmq::SocketType::DEALER)?,
-            public_key: pub_key.from_base58()
-                .map_err(|err| CommonError::InvalidStructure(format!("invalid pub_key {}", err)))?,
-            secret_key: sec_key.from_base58()
-                .map_err(|err| CommonError::InvalidStructure(format!("invalid sec_key {}", err)))?,
-            server_key: ver_key.from_base58()
-                .map_err(|err| CommonError::InvalidStructure(format!("invalid server_key {}", err)))?,
-            addr: addr.to_string(),
-            conn_handle: conn_handle,
-        })
-    }
+impl JsonEncodable for InvitationRecord {}
 
-    fn connect(&self) -> Result<(), CommonError> {
-        impl From<zmq::EncodeError> for CommonError {
-            fn from(err: zmq::EncodeError) -> CommonError {
-                CommonError::InvalidState(format!("Invalid data stored RemoteAgent detected while connect {:?}", err))
+impl<'a> JsonDecodable<'a> for InvitationRecord {}
+
+/// Listener policy passed by user:
+/// any - accept any DID, known - accept only DIDs stored in wallet as their DIDs,
+/// list - accept DIDs from allow list (if set) that aren't in deny list.
+#[serde(tag = "type", rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ListenerPolicyInfo {
+    Any,
+    Known,
+    List { allow: Option<Vec<String>>, deny: Option<Vec<String>> },
+}
+
+impl ListenerPolicyInfo {
+    pub fn check_did(&self, did: &str) -> Result<(), String> {
+        match *self {
+            ListenerPolicyInfo::List { ref allow, ref deny } => {
+                if deny.as_ref().map(|deny| deny.iter().any(|d| d == did)).unwrap_or(false) {
+                    return Err(format!("DID {} is denied by listener policy", did));
+                }
+                if allow.as_ref().map(|allow| !allow.iter().any(|d| d == did)).unwrap_or(false) {
+                    return Err(format!("DID {} isn't allowed by listener policy", did));
+                }
+                Ok(())
             }
+            _ => Ok(())
         }
-        self.socket.set_identity(zmq::z85_encode(self.public_key.as_slice())?.as_bytes())
-            .map_err(map_err_trace!())?;
-        self.socket.set_curve_secretkey(zmq::z85_encode(self.secret_key.as_slice())?.as_str())
-            .map_err(map_err_trace!())?;
-        self.socket.set_curve_publickey(zmq::z85_encode(self.public_key.as_slice())?.as_str())
-            .map_err(map_err_trace!())?;
-        self.socket.set_curve_serverkey(zmq::z85_encode(self.server_key.as_slice())?.as_str())
-            .map_err(map_err_trace!())?;
-        self.socket.set_linger(0).map_err(map_err_trace!())?; //TODO set correct timeout
-        self.socket.connect(self.addr.as_str())
-            .map_err(map_err_trace!("RemoteAgent::connect self.socket.connect failed"))?;
-        self.socket.send_str("DID", zmq::DONTWAIT).map_err(map_err_trace!())?;
-        Ok(())
     }
+}
 
-    fn handle_response(&self, msg: String) {
-        // TODO check state
-        let cmd: AgentCommand = if msg.eq("DID_ACK") {
-            AgentCommand::ConnectAck(self.conn_handle, Ok(self.conn_handle))
-        } else {
-            AgentCommand::MessageReceived(self.conn_handle, Ok((self.conn_handle, msg)))
-        };
-        if let Err(err) = CommandExecutor::instance().send(Command::Agent(cmd)) {
-            error!("RemoteAgent::handle_response got incoming msg, but can't send to user {}", err);
-        };
-    }
+impl JsonEncodable for ListenerPolicyInfo {}
+
+impl<'a> JsonDecodable<'a> for ListenerPolicyInfo {}
+
+/// Policy of accepting incoming connections by listener. It is checked by ZAP handler
+/// against client transport key before any message from the client is received.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListenerPolicy {
+    pub allowed: Option<HashMap<String /* transport key */, String /* did */>>,
+    pub denied: HashMap<String /* transport key */, String /* did */>,
 }
 
-impl AgentListener {
-    fn new(handle: i32, endpoint: String, pk: String, sk: String) -> Result<AgentListener, zmq::Error> {
-        let sock = zmq::Context::new().socket(zmq::SocketType::ROUTER)?;
-        //TODO use forked zmq and set cb instead of raw keys
-        sock.set_curve_publickey(zmq::z85_encode(pk.from_base58().unwrap().as_slice()).unwrap().as_str())?;
-        sock.set_curve_secretkey(zmq::z85_encode(sk.from_base58().unwrap().as_slice()).unwrap().as_str())?;
-        //TODO /cb instead keys
-        sock.set_curve_server(true)?;
-        sock.bind(endpoint.as_str())?;
-        Ok(AgentListener {
-            connections: Vec::new(),
-            listener_handle: handle,
-            socket: sock,
-        })
+impl ListenerPolicy {
+    pub fn allow_any() -> ListenerPolicy {
+        ListenerPolicy {
+            allowed: None,
+            denied: HashMap::new(),
+        }
     }
 
-    fn handle_request(&mut self, identity: String, msg: String) -> Result<(), CommonError> {
-        if let Some(&(conn_handle, _)) = self.connections.iter().find(|&&(_, ref id)| identity.eq(id.as_str())) {
-            return CommandExecutor::instance().send(Command::Agent(AgentCommand::MessageReceived(
-                conn_handle, Ok((conn_handle, msg)))));
+    fn check(&self, client_key: &str) -> Result<(), String> {
+        if let Some(did) = self.denied.get(client_key) {
+            return Err(format!("DID {} is denied by listener policy", did));
         }
-
-        if msg.eq("DID") {
-            info!("New connection to agent listener from {} with msg {}", identity, msg);
-            let conn_handle = SequenceUtils::get_next_id();
-            self.connections.push((conn_handle, identity.clone()));
-            let cmd = AgentCommand::ListenerOnConnect(self.listener_handle,
-                                                      Ok((self.listener_handle, conn_handle,
-                                                          identity.clone(), msg.clone())));
-            CommandExecutor::instance().send(Command::Agent(cmd))?;
-            self.socket.send_multipart(&[identity.as_bytes(), "DID_ACK".as_bytes()], zmq::DONTWAIT)?;
-        } else {
-            info!("Message {} from unknown connection to agent listener from {}", msg, identity);
-            // TODO may be notify: ListenOnConnect(self.listener_handle, Err(incorrect connection))
-            self.socket.send_multipart(&[identity.as_bytes(), "NOT_CONNECTED".as_bytes()], zmq::DONTWAIT)?;
+        match self.allowed {
+            Some(ref allowed) if !allowed.contains_key(client_key) =>
+                Err(format!("Transport key {} isn't allowed by listener policy", client_key)),
+            _ => Ok(())
         }
-        Ok(())
+    }
+
+    fn did_for_key(&self, client_key: &str) -> Option<&str> {
+        self.denied.get(client_key)
+            .or(self.allowed.as_ref().and_then(|allowed| allowed.get(client_key)))
+            .map(String::as_str)
     }
 }
 
+/// Events reported to listener event callback.
+#[serde(tag = "type", rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum ListenerEvent {
+    ConnectionRejected { did: Option<String>, client_key: Option<String>, reason: String },
+    InvitationAccepted { my_did: String, their_did: String, label: Option<String> },
+}
+
+impl JsonEncodable for ListenerEvent {}
+
+impl<'a> JsonDecodable<'a> for ListenerEvent {}
+
+/// Incoming connection that passed challenge and waits for DID verification.
+#[derive(Debug, Clone)]
+pub struct IncomingConnection {
+    pub listener_handle: i32,
+    pub identity: String,
+    pub did: String,
+    pub verkey: Option<String>,
+    pub receiver_did: String,
+    pub client_key: String,
+    pub nonce: String,
+    pub signature: String,
+    pub reliable: bool,
+}
+
 #[serde(tag = "cmd")]
 #[derive(Serialize, Deserialize, Debug)]
 enum AgentWorkerCommand {
     Connect(ConnectCmd),
     Listen(ListenCmd),
+    Identity(IdentityCmd),
     Response(Response),
     Request(Request),
+    ZapRequest(ZapRequest),
+    AnswerChallenge(AnswerChallengeCmd),
+    CompleteHandshake(CompleteHandshakeCmd),
     Send(SendCmd),
     Close(CloseCmd),
     Exit,
@@ -424,29 +1755,62 @@ impl JsonEncodable for AgentWorkerCommand {}
 
 impl<'a> JsonDecodable<'a> for AgentWorkerCommand {}
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize)]
 struct ConnectCmd {
     endpoint: String,
     did: String,
+    receiver_did: String,
     secret_key: String,
     public_key: String,
     server_key: String,
     conn_handle: i32,
+    delivery: Option<DeliveryOptions>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl fmt::Debug for ConnectCmd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ConnectCmd {{ endpoint: {:?}, did: {:?}, receiver_did: {:?}, secret_key: hidden, public_key: {:?}, server_key: {:?}, conn_handle: {}, delivery: {:?} }}",
+               self.endpoint, self.did, self.receiver_did, self.public_key, self.server_key, self.conn_handle, self.delivery)
+    }
+}
+
+impl Drop for ConnectCmd {
+    fn drop(&mut self) {
+        memzero_string(&mut self.secret_key);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 struct ListenCmd {
     listen_handle: i32,
     endpoint: String,
+    did: String,
     pk: String,
     sk: String,
+    identities: Vec<String>,
+    policy: ListenerPolicy,
+}
+
+impl fmt::Debug for ListenCmd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ListenCmd {{ listen_handle: {}, endpoint: {:?}, did: {:?}, pk: {:?}, sk: hidden, identities: {:?}, policy: {:?} }}",
+               self.listen_handle, self.endpoint, self.did, self.pk, self.identities, self.policy)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct IdentityCmd {
+    cmd_id: i32,
+    listener_handle: i32,
+    did: String,
+    remove: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct SendCmd {
     cmd_id: i32,
     conn_handle: i32,
-    msg: Option<String>,
+    msg: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -459,9 +1823,34 @@ struct Response {
 struct Request {
     listener_ind: usize,
     identity: String,
+    client_key: Option<String>,
     msg: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct ZapRequest {
+    listener_ind: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct AnswerChallengeCmd {
+    conn_handle: i32,
+    did: String,
+    verkey: String,
+    signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CompleteHandshakeCmd {
+    listener_handle: i32,
+    identity: String,
+    client_key: String,
+    sender_did: String,
+    receiver_did: String,
+    reliable: bool,
+    reject_reason: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct CloseCmd {
     cmd_id: i32,
@@ -489,6 +1878,16 @@ mod tests {
 
     use utils::timeout::TimeoutUtils;
 
+    fn _control_data<T: JsonEncodable>(msg_type: &str, msg: &T) -> String {
+        AgentEnvelope::control_data(msg_type, None, msg).unwrap()
+    }
+
+    fn _parse_control_msg<T>(msg_type: &str, data: &str) -> T where T: for<'a> JsonDecodable<'a> {
+        let envelope = AgentEnvelope::parse(data).unwrap();
+        assert_eq!(envelope.msg_type, msg_type);
+        envelope.parse_body().unwrap()
+    }
+
     #[test]
     fn agent_can_be_dropped() {
         let (sender, receiver) = channel();
@@ -502,6 +1901,32 @@ mod tests {
         receiver.recv_timeout(TimeoutUtils::short_timeout()).expect("drop not finished");
     }
 
+    #[test]
+    fn agent_worker_command_debug_hides_secret_keys() {
+        let connect_cmd = AgentWorkerCommand::Connect(ConnectCmd {
+            server_key: "serv".to_string(),
+            public_key: "pk".to_string(),
+            secret_key: "secret".to_string(),
+            endpoint: "ep".to_string(),
+            did: "sd".to_string(),
+            receiver_did: "rd".to_string(),
+            conn_handle: 1,
+            delivery: None,
+        });
+        let listen_cmd = AgentWorkerCommand::Listen(ListenCmd {
+            listen_handle: 1,
+            endpoint: "endpoint".to_string(),
+            did: "did".to_string(),
+            pk: "pk".to_string(),
+            sk: "secret".to_string(),
+            identities: vec!["did".to_string()],
+            policy: ListenerPolicy::allow_any(),
+        });
+
+        assert!(!format!("{:?}", connect_cmd).contains("secret"));
+        assert!(!format!("{:?}", listen_cmd).contains("secret"));
+    }
+
     mod agent_service {
         use super::*;
 
@@ -519,14 +1944,16 @@ mod tests {
             let agent_service = AgentService {
                 agent: agent,
             };
-            let conn_handle = agent_service.connect("sd", "sk", "pk", "ep", "serv").unwrap();
+            let conn_handle = agent_service.connect("sd", "sk", "pk", "rd", "ep", "serv", None).unwrap();
             let expected_cmd = ConnectCmd {
                 server_key: "serv".to_string(),
                 public_key: "pk".to_string(),
                 secret_key: "sk".to_string(),
                 endpoint: "ep".to_string(),
                 did: "sd".to_string(),
+                receiver_did: "rd".to_string(),
                 conn_handle: conn_handle,
+                delivery: None,
             };
             let str = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
             assert_eq!(str, AgentWorkerCommand::Connect(expected_cmd).to_json().unwrap());
@@ -545,12 +1972,15 @@ mod tests {
             let agent_service = AgentService {
                 agent: agent,
             };
-            let conn_handle = agent_service.listen("endpoint", "pk", "sk").unwrap();
+            let conn_handle = agent_service.listen("endpoint", "did", "pk", "sk", &["did".to_string()], ListenerPolicy::allow_any()).unwrap();
             let expected_cmd = ListenCmd {
                 listen_handle: conn_handle,
                 endpoint: "endpoint".to_string(),
+                did: "did".to_string(),
                 pk: "pk".to_string(),
                 sk: "sk".to_string(),
+                identities: vec!["did".to_string()],
+                policy: ListenerPolicy::allow_any(),
             };
             let str = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
             assert_eq!(str, AgentWorkerCommand::Listen(expected_cmd).to_json().unwrap());
@@ -570,12 +2000,12 @@ mod tests {
                 agent: agent,
             };
             let conn_handle = SequenceUtils::get_next_id();
-            let msg = Some("test_msg");
-            let cmd_id = agent_service.send(conn_handle, msg).unwrap();
+            let envelope = AgentEnvelope::new(USER_MESSAGE_TYPE, None, "test_msg".as_bytes());
+            let cmd_id = agent_service.send(conn_handle, &envelope).unwrap();
             let expected_cmd = SendCmd {
                 cmd_id: cmd_id,
                 conn_handle: conn_handle,
-                msg: msg.map(str::to_string),
+                msg: envelope.to_data().unwrap(),
             };
             let str = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
             assert_eq!(str, AgentWorkerCommand::Send(expected_cmd).to_json().unwrap());
@@ -634,15 +2064,18 @@ mod tests {
                 public_key: zmq::z85_decode(send_key_pair.public_key.as_str()).unwrap().to_base58(),
                 secret_key: zmq::z85_decode(send_key_pair.secret_key.as_str()).unwrap().to_base58(),
                 did: "".to_string(),
+                receiver_did: "rd".to_string(),
                 server_key: zmq::z85_decode(recv_key_pair.public_key.as_str()).unwrap().to_base58(),
                 conn_handle: 0,
+                delivery: None,
             };
 
             agent_worker.connect(&cmd).unwrap();
 
             assert_eq!(agent_worker.agent_connections.len(), 1);
             recv_soc.recv_string(0).unwrap().unwrap(); //ignore identity
-            assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "DID");
+            assert_eq!(_parse_control_msg::<HandshakeMsg>(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()),
+                       HandshakeMsg::Hello { did: Some("rd".to_string()), reliable: false });
         }
 
         #[test]
@@ -655,7 +2088,7 @@ mod tests {
             };
             send_soc.send_str(r#"{"cmd": "Exit"}"#, zmq::DONTWAIT).unwrap();
 
-            let cmds = agent_worker.poll().unwrap();
+            let cmds = agent_worker.poll(-1).unwrap();
 
             assert_eq!(cmds.len(), 1);
             assert_match!(AgentWorkerCommand::Exit, cmds[0]);
@@ -668,6 +2101,11 @@ mod tests {
                 agent_connections: vec!(RemoteAgent {
                     socket: recv_soc,
                     addr: String::new(),
+                    did: String::new(),
+                    receiver_did: String::new(),
+                    connected: false,
+                    reconnecting: false,
+                    delivery: None,
                     public_key: Vec::new(),
                     secret_key: Vec::new(),
                     server_key: Vec::new(),
@@ -678,7 +2116,7 @@ mod tests {
             };
             send_soc.send_str("msg", zmq::DONTWAIT).unwrap();
 
-            let mut cmds = agent_worker.poll().unwrap();
+            let mut cmds = agent_worker.poll(-1).unwrap();
 
             assert_eq!(cmds.len(), 1);
             let cmd = cmds.remove(0);
@@ -708,13 +2146,19 @@ mod tests {
                     connections: Vec::new(),
                     listener_handle: 0,
                     socket: recv_soc,
+                    challenges: HashMap::new(),
+                    did: "did".to_string(),
+                    identities: vec!["did".to_string()].into_iter().collect(),
+                    deliveries: HashMap::new(),
+                    policy: ListenerPolicy::allow_any(),
+                    zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
                 }),
                 agent_connections: Vec::new(),
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
             };
             send_soc.send_str("msg", zmq::DONTWAIT).unwrap();
 
-            let mut cmds = agent_worker.poll().unwrap();
+            let mut cmds = agent_worker.poll(-1).unwrap();
 
             assert_eq!(cmds.len(), 1);
             let cmd = cmds.remove(0);
@@ -751,6 +2195,12 @@ mod tests {
                     socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
                     connections: vec![(conn_handle, "test_identity".to_string())],
                     listener_handle: SequenceUtils::get_next_id(),
+                    challenges: HashMap::new(),
+                    did: "did".to_string(),
+                    identities: vec!["did".to_string()].into_iter().collect(),
+                    deliveries: HashMap::new(),
+                    policy: ListenerPolicy::allow_any(),
+                    zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
                 }],
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
             };
@@ -769,6 +2219,11 @@ mod tests {
                     secret_key: Vec::new(),
                     server_key: Vec::new(),
                     addr: String::new(),
+                    did: String::new(),
+                    receiver_did: String::new(),
+                    connected: false,
+                    reconnecting: false,
+                    delivery: None,
                 }],
                 agent_listeners: Vec::new(),
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
@@ -786,6 +2241,12 @@ mod tests {
                     socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
                     connections: Vec::new(),
                     listener_handle: listener_handle,
+                    challenges: HashMap::new(),
+                    did: "did".to_string(),
+                    identities: vec!["did".to_string()].into_iter().collect(),
+                    deliveries: HashMap::new(),
+                    policy: ListenerPolicy::allow_any(),
+                    zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
                 }],
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
             };
@@ -806,7 +2267,16 @@ mod tests {
             let pk = zmq::z85_decode(server_keys.public_key.as_str()).unwrap().to_base58();
             let sk = zmq::z85_decode(server_keys.secret_key.as_str()).unwrap().to_base58();
             let endpoint = "tcp://0.0.0.0:9700".to_string();
-            agent_worker.try_start_listen(0, endpoint.clone(), pk.clone(), sk.clone()).unwrap();
+            let cmd = ListenCmd {
+                listen_handle: 0,
+                endpoint: endpoint.clone(),
+                did: "did".to_string(),
+                pk: pk.clone(),
+                sk: sk.clone(),
+                identities: vec!["did".to_string()],
+                policy: ListenerPolicy::allow_any(),
+            };
+            agent_worker.try_start_listen(&cmd).unwrap();
             assert_eq!(agent_worker.agent_listeners.len(), 1);
 
             let msg = "msg";
@@ -817,10 +2287,13 @@ mod tests {
             sock.set_curve_serverkey(server_keys.public_key.as_str()).unwrap();
             sock.connect(endpoint.as_str()).unwrap();
             sock.send_str(msg, 0).unwrap();
+            agent_worker.agent_listeners[0].zap_socket.poll(zmq::POLLIN, 1000).unwrap();
+            agent_worker.agent_listeners[0].handle_zap_request().unwrap();
             agent_worker.agent_listeners[0].socket.poll(zmq::POLLIN, 1000).unwrap();
             agent_worker.agent_listeners[0].socket.recv_bytes(zmq::DONTWAIT).unwrap(); //ignore identity
-            let act_msg = agent_worker.agent_listeners[0].socket.recv_string(zmq::DONTWAIT).unwrap().unwrap();
-            assert_eq!(act_msg, msg);
+            let mut act_msg = agent_worker.agent_listeners[0].socket.recv_msg(zmq::DONTWAIT).unwrap();
+            assert_eq!(act_msg.gets("User-Id").unwrap(), zmq::z85_decode(kp.public_key.as_str()).unwrap().to_base58());
+            assert_eq!(act_msg.as_str().unwrap(), msg);
         }
 
         #[test]
@@ -832,7 +2305,7 @@ mod tests {
             };
             let conn_handle = SequenceUtils::get_next_id();
 
-            let res = agent_worker.try_send(conn_handle, None);
+            let res = agent_worker.try_send(conn_handle, "test_str".to_string());
 
             assert_match!(Err(CommonError::InvalidStructure(_)), res);
         }
@@ -848,16 +2321,27 @@ mod tests {
                     secret_key: Vec::new(),
                     server_key: Vec::new(),
                     addr: String::new(),
+                    did: String::new(),
+                    receiver_did: String::new(),
+                    connected: false,
+                    reconnecting: false,
+                    delivery: None,
                 }],
                 agent_listeners: vec![AgentListener {
                     socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
                     connections: vec![(conn_handle, String::new())],
                     listener_handle: SequenceUtils::get_next_id(),
+                    challenges: HashMap::new(),
+                    did: "did".to_string(),
+                    identities: vec!["did".to_string()].into_iter().collect(),
+                    deliveries: HashMap::new(),
+                    policy: ListenerPolicy::allow_any(),
+                    zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
                 }],
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
             };
 
-            let res = agent_worker.try_send(conn_handle, None);
+            let res = agent_worker.try_send(conn_handle, "test_str".to_string());
 
             assert_match!(Err(CommonError::InvalidState(_)), res);
         }
@@ -872,11 +2356,17 @@ mod tests {
                     socket: send_soc,
                     connections: vec![(conn_handle, "test_identity".to_string())],
                     listener_handle: SequenceUtils::get_next_id(),
+                    challenges: HashMap::new(),
+                    did: "did".to_string(),
+                    identities: vec!["did".to_string()].into_iter().collect(),
+                    deliveries: HashMap::new(),
+                    policy: ListenerPolicy::allow_any(),
+                    zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
                 }],
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
             };
 
-            agent_worker.try_send(conn_handle, Some("test_str".to_string())).unwrap();
+            agent_worker.try_send(conn_handle, "test_str".to_string()).unwrap();
             assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "test_identity");
             assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "test_str");
         }
@@ -893,14 +2383,50 @@ mod tests {
                     secret_key: Vec::new(),
                     server_key: Vec::new(),
                     addr: String::new(),
+                    did: String::new(),
+                    receiver_did: String::new(),
+                    connected: false,
+                    reconnecting: false,
+                    delivery: None,
                 }],
                 agent_listeners: Vec::new(),
                 cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
             };
 
-            agent_worker.try_send(conn_handle, Some("test_str".to_string())).unwrap();
+            agent_worker.try_send(conn_handle, "test_str".to_string()).unwrap();
             assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "test_str");
         }
+
+        #[test]
+        fn agent_worker_try_send_works_for_reliable_connection() {
+            let (send_soc, recv_soc) = _create_zmq_socket_pair("aw_send_reliable", true).unwrap();
+            let conn_handle = SequenceUtils::get_next_id();
+            let mut agent_worker = AgentWorker {
+                agent_connections: vec![RemoteAgent {
+                    conn_handle: conn_handle,
+                    socket: send_soc,
+                    public_key: Vec::new(),
+                    secret_key: Vec::new(),
+                    server_key: Vec::new(),
+                    addr: String::new(),
+                    did: String::new(),
+                    receiver_did: String::new(),
+                    connected: true,
+                    reconnecting: false,
+                    delivery: Some(Delivery::new(DeliveryOptions::default())),
+                }],
+                agent_listeners: Vec::new(),
+                cmd_socket: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
+            };
+            assert_eq!(agent_worker.poll_timeout(), -1);
+
+            let msg_id = agent_worker.try_send(conn_handle, "test_str".to_string()).unwrap();
+
+            assert_eq!(msg_id, 1);
+            assert_eq!(_parse_control_msg::<DeliveryMsg>(DELIVERY_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()),
+                       DeliveryMsg::Message { id: 1, msg: "test_str".to_string() });
+            assert!(agent_worker.poll_timeout() > 0);
+        }
     }
 
     #[test]
@@ -914,13 +2440,420 @@ mod tests {
         let agent = RemoteAgent {
             socket: send_soc,
             addr: addr,
+            did: String::new(),
+            receiver_did: String::new(),
+            connected: false,
+            reconnecting: false,
+            delivery: None,
             server_key: zmq::z85_decode(send_key_pair.public_key.as_str()).unwrap(),
             secret_key: zmq::z85_decode(recv_key_pair.secret_key.as_str()).unwrap(),
             public_key: zmq::z85_decode(recv_key_pair.public_key.as_str()).unwrap(),
             conn_handle: 0,
         };
         agent.connect().unwrap();
-        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "DID");
+        assert_eq!(_parse_control_msg::<HandshakeMsg>(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()),
+                   HandshakeMsg::Hello { did: Some(String::new()), reliable: false });
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_hello() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_hello", true).unwrap();
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: Vec::new(),
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            deliveries: HashMap::new(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+
+        listener.handle_request("identity".to_string(), Some("client_key".to_string()),
+                                _control_data(HANDSHAKE_TYPE, &HandshakeMsg::Hello { did: None, reliable: false })).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let msg: HandshakeMsg = _parse_control_msg(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap());
+        let challenge = listener.challenges.get("identity").unwrap();
+        assert_eq!(challenge.client_key, "client_key");
+        assert_eq!(challenge.receiver_did, "did");
+        assert_eq!(msg, HandshakeMsg::Challenge { nonce: challenge.nonce.clone() });
+        assert!(listener.connections.is_empty());
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_hello_to_not_served_identity() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_hello_not_served", true).unwrap();
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: Vec::new(),
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            deliveries: HashMap::new(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let msg = HandshakeMsg::Hello { did: Some("other_did".to_string()), reliable: false };
+
+        listener.handle_request("identity".to_string(), Some("client_key".to_string()), _control_data(HANDSHAKE_TYPE, &msg)).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let msg: HandshakeMsg = _parse_control_msg(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap());
+        assert_match!(HandshakeMsg::Rejected { .. }, msg);
+        assert!(listener.challenges.is_empty());
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_did_without_challenge() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_no_challenge", true).unwrap();
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: Vec::new(),
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            deliveries: HashMap::new(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let msg = HandshakeMsg::Did { did: "did".to_string(), signature: "signature".to_string(), verkey: None };
+
+        listener.handle_request("identity".to_string(), Some("client_key".to_string()), _control_data(HANDSHAKE_TYPE, &msg)).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let msg: HandshakeMsg = _parse_control_msg(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap());
+        assert_match!(HandshakeMsg::Rejected { .. }, msg);
+        assert!(listener.connections.is_empty());
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_delivery_msg() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_delivery_msg", true).unwrap();
+        let conn_handle = SequenceUtils::get_next_id();
+        let mut deliveries = HashMap::new();
+        deliveries.insert(conn_handle, Delivery::new(DeliveryOptions::default()));
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: vec![(conn_handle, "identity".to_string())],
+            deliveries: deliveries,
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let envelope = AgentEnvelope::new(USER_MESSAGE_TYPE, None, "msg".as_bytes());
+        let msg = DeliveryMsg::Message { id: 1, msg: envelope.to_data().unwrap() };
+
+        listener.handle_request("identity".to_string(), None, _control_data(DELIVERY_TYPE, &msg)).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let msg: DeliveryMsg = _parse_control_msg(DELIVERY_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap());
+        assert_eq!(msg, DeliveryMsg::Ack { id: 1 });
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_invalid_delivery_msg() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_invalid_delivery_msg", true).unwrap();
+        let conn_handle = SequenceUtils::get_next_id();
+        let mut deliveries = HashMap::new();
+        deliveries.insert(conn_handle, Delivery::new(DeliveryOptions::default()));
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: vec![(conn_handle, "identity".to_string())],
+            deliveries: deliveries,
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let envelope = AgentEnvelope::new(DELIVERY_TYPE, None, r#"{"type":"Message","id":"not_number"}"#.as_bytes());
+
+        listener.handle_request("identity".to_string(), None, envelope.to_data().unwrap()).unwrap();
+
+        assert!(recv_soc.recv_string(zmq::DONTWAIT).is_err());
+        assert_eq!(listener.connections, vec![(conn_handle, "identity".to_string())]);
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_delivery_msg_from_unknown_connection() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_delivery_msg_unknown", true).unwrap();
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: Vec::new(),
+            deliveries: HashMap::new(),
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let msg = DeliveryMsg::Message { id: 1, msg: "msg".to_string() };
+
+        listener.handle_request("identity".to_string(), Some("client_key".to_string()), _control_data(DELIVERY_TYPE, &msg)).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let msg: HandshakeMsg = _parse_control_msg(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap());
+        assert_eq!(msg, HandshakeMsg::Reset);
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_ping() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_ping", true).unwrap();
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: Vec::new(),
+            deliveries: HashMap::new(),
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let ping = AgentEnvelope::new(PING_TYPE, None, &[]);
+
+        listener.handle_request("identity".to_string(), Some("client_key".to_string()), ping.to_data().unwrap()).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let pong = AgentEnvelope::parse(&recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()).unwrap();
+        assert_eq!(pong.msg_type, PONG_TYPE);
+        assert_eq!(pong.thread_id, Some(ping.id));
+    }
+
+    #[test]
+    fn agent_listener_handle_request_works_for_user_msg_from_unknown_connection() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("test_listener_user_msg_unknown", true).unwrap();
+        let mut listener = AgentListener {
+            socket: send_soc,
+            zap_socket: zmq::Context::new().socket(zmq::SocketType::REP).unwrap(),
+            connections: Vec::new(),
+            deliveries: HashMap::new(),
+            challenges: HashMap::new(),
+            did: "did".to_string(),
+            identities: vec!["did".to_string()].into_iter().collect(),
+            policy: ListenerPolicy::allow_any(),
+            listener_handle: SequenceUtils::get_next_id(),
+        };
+        let envelope = AgentEnvelope::new(USER_MESSAGE_TYPE, None, "msg".as_bytes());
+
+        listener.handle_request("identity".to_string(), Some("client_key".to_string()), envelope.to_data().unwrap()).unwrap();
+
+        assert_eq!(recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap(), "identity");
+        let msg: HandshakeMsg = _parse_control_msg(HANDSHAKE_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap());
+        assert_match!(HandshakeMsg::Rejected { .. }, msg);
+    }
+
+    #[test]
+    fn delivery_works_for_ack_and_duplicates() {
+        let mut delivery = Delivery::new(DeliveryOptions::default());
+
+        let (id, data) = delivery.push("msg".to_string()).unwrap();
+        assert_eq!(id, 1);
+        assert_eq!(_parse_control_msg::<DeliveryMsg>(DELIVERY_TYPE, &data), DeliveryMsg::Message { id: 1, msg: "msg".to_string() });
+        assert!(delivery.next_deadline().is_some());
+        assert_eq!(delivery.on_ack(1), Some(1));
+        assert_eq!(delivery.on_ack(1), None);
+        assert!(delivery.next_deadline().is_none());
+
+        assert!(delivery.on_message(2));
+        assert!(!delivery.on_message(2));
+        assert!(delivery.on_message(1));
+        assert!(!delivery.on_message(1));
+        assert_eq!(delivery.received_up_to, 2);
+        assert!(delivery.received_ahead.is_empty());
+    }
+
+    #[test]
+    fn remote_agent_reconnect_works_for_sending_in_both_directions() {
+        let (send_soc, recv_soc) = _create_zmq_socket_pair("ra_reconnect_delivery", true).unwrap();
+        let mut delivery = Delivery::new(DeliveryOptions::default());
+        // Session before listener restart: message 1 is acknowledged, message 2 is lost, 3 messages are received
+        delivery.push("acked".to_string()).unwrap();
+        assert_eq!(delivery.on_ack(1), Some(1));
+        delivery.push("lost".to_string()).unwrap();
+        for id in 1..4 {
+            assert!(delivery.on_message(id));
+        }
+        let mut agent = RemoteAgent {
+            conn_handle: SequenceUtils::get_next_id(),
+            socket: send_soc,
+            public_key: Vec::new(),
+            secret_key: Vec::new(),
+            server_key: Vec::new(),
+            addr: String::new(),
+            did: String::new(),
+            receiver_did: String::new(),
+            connected: false,
+            reconnecting: true,
+            delivery: Some(delivery),
+        };
+        let mut listener_delivery = Delivery::new(DeliveryOptions::default());
+
+        let accepted = AgentEnvelope::parse(&AgentEnvelope::control_data(HANDSHAKE_TYPE, None, &HandshakeMsg::Accepted).unwrap()).unwrap();
+        agent.handle_handshake_msg(&accepted);
+        assert!(agent.connected);
+
+        assert_eq!(_parse_control_msg::<DeliveryMsg>(DELIVERY_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()),
+                   DeliveryMsg::Message { id: 1, msg: "lost".to_string() });
+        assert_eq!(agent.send_msg("new".to_string()).unwrap(), 3);
+        assert_eq!(_parse_control_msg::<DeliveryMsg>(DELIVERY_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()),
+                   DeliveryMsg::Message { id: 2, msg: "new".to_string() });
+        assert!(listener_delivery.on_message(1));
+        assert!(listener_delivery.on_message(2));
+        assert!(listener_delivery.received_ahead.is_empty());
+
+        let (_, data) = listener_delivery.push(AgentEnvelope::new(USER_MESSAGE_TYPE, None, "reply".as_bytes()).to_data().unwrap()).unwrap();
+        agent.handle_delivery_msg(&AgentEnvelope::parse(&data).unwrap());
+        assert_eq!(_parse_control_msg::<DeliveryMsg>(DELIVERY_TYPE, &recv_soc.recv_string(zmq::DONTWAIT).unwrap().unwrap()),
+                   DeliveryMsg::Ack { id: 1 });
+        let delivery = agent.delivery.as_mut().unwrap();
+        assert_eq!(delivery.received_up_to, 1);
+        assert!(delivery.received_ahead.is_empty());
+        assert_eq!(delivery.on_ack(1), Some(2));
+        assert_eq!(delivery.on_ack(2), Some(3));
+    }
+
+    #[test]
+    fn delivery_expired_works() {
+        let mut delivery = Delivery::new(DeliveryOptions {
+            ack_timeout: 10,
+            max_interval: 15,
+            max_attempts: 2,
+        });
+        delivery.push("msg".to_string()).unwrap();
+        let now = Instant::now();
+
+        assert!(delivery.expired(0, now).is_empty());
+        assert_eq!(delivery.expired(0, now + Duration::from_millis(20)).len(), 1);
+        assert_eq!(delivery.pending[0].attempts, 2);
+        assert_eq!(delivery.pending[0].deadline, now + Duration::from_millis(35));
+        assert!(delivery.expired(0, now + Duration::from_millis(40)).is_empty());
+        assert!(delivery.pending.is_empty());
+    }
+
+    #[test]
+    fn connect_options_delivery_options_works() {
+        assert_eq!(ConnectOptions::from_json("{}").unwrap().delivery_options(), None);
+        assert_eq!(ConnectOptions::from_json(r#"{"reliable":true,"max_attempts":3}"#).unwrap().delivery_options(),
+                   Some(DeliveryOptions { max_attempts: 3, ..DeliveryOptions::default() }));
+    }
+
+    #[test]
+    fn handshake_msg_to_json_works() {
+        let msg = HandshakeMsg::Challenge { nonce: "nonce".to_string() };
+        assert_eq!(msg.to_json().unwrap(), r#"{"type":"Challenge","nonce":"nonce"}"#);
+        assert_eq!(HandshakeMsg::from_json(r#"{"type":"Accepted"}"#).unwrap(), HandshakeMsg::Accepted);
+        assert_eq!(HandshakeMsg::from_json(r#"{"type":"Hello","did":null}"#).unwrap(), HandshakeMsg::Hello { did: None, reliable: false });
+    }
+
+    #[test]
+    fn listener_policy_check_works() {
+        let mut allowed = HashMap::new();
+        allowed.insert("allowed_key".to_string(), "allowed_did".to_string());
+        allowed.insert("denied_key".to_string(), "denied_did".to_string());
+        let mut denied = HashMap::new();
+        denied.insert("denied_key".to_string(), "denied_did".to_string());
+        let policy = ListenerPolicy { allowed: Some(allowed), denied: denied };
+
+        assert!(policy.check("allowed_key").is_ok());
+        assert!(policy.check("denied_key").is_err());
+        assert!(policy.check("unknown_key").is_err());
+        assert_eq!(policy.did_for_key("denied_key"), Some("denied_did"));
+        assert_eq!(policy.did_for_key("unknown_key"), None);
+        assert!(ListenerPolicy::allow_any().check("unknown_key").is_ok());
+    }
+
+    #[test]
+    fn listener_policy_info_check_did_works() {
+        let policy = ListenerPolicyInfo::from_json(r#"{"type":"list","allow":["did1","did2"],"deny":["did2"]}"#).unwrap();
+
+        assert!(policy.check_did("did1").is_ok());
+        assert!(policy.check_did("did2").is_err());
+        assert!(policy.check_did("did3").is_err());
+        assert!(ListenerPolicyInfo::from_json(r#"{"type":"any"}"#).unwrap().check_did("did3").is_ok());
+    }
+
+    #[test]
+    fn listener_event_to_json_works() {
+        let event = ListenerEvent::ConnectionRejected {
+            did: None,
+            client_key: Some("key".to_string()),
+            reason: "reason".to_string(),
+        };
+        assert_eq!(event.to_json().unwrap(), r#"{"type":"connection_rejected","did":null,"client_key":"key","reason":"reason"}"#);
+    }
+
+    #[test]
+    fn agent_envelope_works() {
+        let envelope = AgentEnvelope::new("type", Some("thread"), &[0, 1, 255]);
+        assert_eq!(envelope.version, ENVELOPE_VERSION);
+        assert!(!envelope.id.is_empty());
+
+        let parsed = AgentEnvelope::parse(&envelope.to_data().unwrap()).unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!(parsed.body().unwrap(), vec![0, 1, 255]);
+        assert_eq!(parsed.signing_input(), format!("1.{}.type.thread.{}.AAH_", envelope.id, envelope.timestamp));
+    }
+
+    #[test]
+    fn agent_envelope_parse_works_for_invalid_data() {
+        let mut envelope = AgentEnvelope::new(USER_MESSAGE_TYPE, None, "msg".as_bytes());
+        envelope.version = ENVELOPE_VERSION + 1;
+
+        assert_match!(Err(CommonError::InvalidStructure(_)), AgentEnvelope::parse(&envelope.to_data().unwrap()));
+        assert_match!(Err(CommonError::InvalidStructure(_)), AgentEnvelope::parse("msg"));
+    }
+
+    #[test]
+    fn agent_envelope_is_control_type_works() {
+        assert!(AgentEnvelope::is_control_type(HANDSHAKE_TYPE));
+        assert!(AgentEnvelope::is_control_type(MAILBOX_TYPE));
+        assert!(!AgentEnvelope::is_control_type(USER_MESSAGE_TYPE));
+    }
+
+    #[test]
+    fn mailbox_msg_to_json_works() {
+        let msg = MailboxMsg::Forward { id: 1, did: "did".to_string(), message: "message".to_string() };
+        assert_eq!(msg.to_json().unwrap(), r#"{"type":"Forward","id":1,"did":"did","message":"message"}"#);
+        assert_eq!(MailboxMsg::from_json(r#"{"type":"Pull","id":2}"#).unwrap(), MailboxMsg::Pull { id: 2 });
+        assert_eq!(MailboxMsg::Ack { ids: vec![1, 2] }.to_json().unwrap(), r#"{"type":"Ack","ids":[1,2]}"#);
+        assert!(MailboxMsg::from_json("user message").is_err());
+    }
+
+    #[test]
+    fn claim_msg_to_json_works() {
+        let msg = ClaimMsg::Request { exchange_id: "id".to_string(), claim_req: "req".to_string() };
+        assert_eq!(msg.to_json().unwrap(), r#"{"type":"Request","exchange_id":"id","claim_req":"req"}"#);
+        let event = ClaimExchangeEvent::ClaimStored { exchange_id: "id".to_string() };
+        assert_eq!(event.to_json().unwrap(), r#"{"type":"claim_stored","exchange_id":"id"}"#);
+        assert_eq!(ClaimExchangeOptions::from_json("{}").unwrap().master_secret_name, None);
+    }
+
+    #[test]
+    fn proof_msg_to_json_works() {
+        let mut identifiers = HashMap::new();
+        identifiers.insert("claim_uuid".to_string(), ClaimIdentifier { issuer_did: "did".to_string(), schema_seq_no: 1 });
+        let msg = ProofMsg::Proof { exchange_id: "id".to_string(), proof: "proof".to_string(), identifiers: identifiers };
+        assert_eq!(msg.to_json().unwrap(),
+                   r#"{"type":"Proof","exchange_id":"id","proof":"proof","identifiers":{"claim_uuid":{"issuer_did":"did","schema_seq_no":1}}}"#);
+        assert_eq!(ProofMsg::from_json(&msg.to_json().unwrap()).unwrap(), msg);
+        let event = ProofExchangeEvent::ProofResult { exchange_id: "id".to_string(), valid: true };
+        assert_eq!(event.to_json().unwrap(), r#"{"type":"proof_result","exchange_id":"id","valid":true}"#);
+    }
+
+    #[test]
+    fn handshake_msg_did_from_json_works_without_verkey() {
+        let msg = HandshakeMsg::from_json(r#"{"type":"Did","did":"did","signature":"signature"}"#).unwrap();
+        assert_eq!(msg, HandshakeMsg::Did { did: "did".to_string(), signature: "signature".to_string(), verkey: None });
     }
 
     #[test]
@@ -4,6 +4,7 @@ use errors::common::CommonError;
 use services::anoncreds::helpers::{AppendByteArray, clone_bignum_map};
 use std::collections::{HashMap, HashSet};
 use std::cell::RefCell;
+use std::fmt;
 use utils::json::{JsonEncodable, JsonDecodable};
 
 pub enum ByteOrder {
@@ -61,7 +62,7 @@ impl JsonEncodable for AccumulatorPublicKey {}
 
 impl<'a> JsonDecodable<'a> for AccumulatorPublicKey {}
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AccumulatorSecretKey {
     pub gamma: GroupOrderElement
 }
@@ -78,6 +79,12 @@ impl JsonEncodable for AccumulatorSecretKey {}
 
 impl<'a> JsonDecodable<'a> for AccumulatorSecretKey {}
 
+impl fmt::Debug for AccumulatorSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AccumulatorSecretKey {{ gamma: hidden }}")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AggregatedProof {
     pub c_hash: BigNumber,
@@ -1033,7 +1040,7 @@ impl JsonEncodable for RevocationPublicKey {}
 
 impl<'a> JsonDecodable<'a> for RevocationPublicKey {}
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct RevocationSecretKey {
     pub x: GroupOrderElement,
     pub sk: GroupOrderElement
@@ -1052,6 +1059,12 @@ impl JsonEncodable for RevocationSecretKey {}
 
 impl<'a> JsonDecodable<'a> for RevocationSecretKey {}
 
+impl fmt::Debug for RevocationSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RevocationSecretKey {{ x: hidden, sk: hidden }}")
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RevocationClaimInitData {
     pub u: PointG1,
@@ -1152,7 +1165,7 @@ impl JsonEncodable for Schema {}
 
 impl<'a> JsonDecodable<'a> for Schema {}
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Serialize, Deserialize, PartialEq)]
 pub struct SecretKey {
     pub p: BigNumber,
     pub q: BigNumber
@@ -1171,6 +1184,12 @@ impl JsonEncodable for SecretKey {}
 
 impl<'a> JsonDecodable<'a> for SecretKey {}
 
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretKey {{ p: hidden, q: hidden }}")
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Witness {
     pub sigma_i: PointG2,
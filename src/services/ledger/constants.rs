@@ -1,5 +1,6 @@
 pub const NODE: &'static str = "0";
 pub const NYM: &'static str = "1";
+pub const GET_TXN: &'static str = "3";
 pub const ATTRIB: &'static str = "100";
 pub const SCHEMA: &'static str = "101";
 pub const CLAIM_DEF: &'static str = "102";
@@ -7,7 +8,18 @@ pub const GET_ATTR: &'static str = "104";
 pub const GET_NYM: &'static str = "105";
 pub const GET_SCHEMA: &'static str = "107";
 pub const GET_CLAIM_DEF: &'static str = "108";
+pub const POOL_UPGRADE: &'static str = "109";
+pub const POOL_CONFIG: &'static str = "111";
+pub const REVOC_REG_DEF: &'static str = "113";
+pub const REVOC_REG_ENTRY: &'static str = "114";
+pub const GET_REVOC_REG_DEF: &'static str = "115";
+pub const GET_REVOC_REG: &'static str = "116";
 pub const GET_DDO: &'static str = "120";//TODO change number
 
+pub const GET_NYM_CACHE_TTL: i64 = 300;
+pub const GET_SCHEMA_CACHE_TTL: i64 = 86400;
+pub const GET_CLAIM_DEF_CACHE_TTL: i64 = 86400;
+pub const GET_TXN_CACHE_TTL: i64 = 86400;
+
 pub const STEWARD: isize = 2;
 pub const TRUSTEE: isize = 0;
\ No newline at end of file
@@ -1,4 +1,5 @@
 extern crate time;
+extern crate serde_json;
 
 pub mod merkletree;
 pub mod types;
@@ -8,6 +9,7 @@ use self::types::{
     AttribOperation,
     GetAttribOperation,
     GetNymOperation,
+    GetTxnOperation,
     GetSchemaOperationData,
     GetSchemaOperation,
     NymOperation,
@@ -20,16 +22,53 @@ use self::types::{
     GetDdoOperation,
     NodeOperation,
     NodeOperationData,
+    PoolUpgradeOperation,
+    PoolConfigOperation,
+    RevocRegDefOperation,
+    GetRevocRegDefOperation,
+    RevocRegEntryOperation,
+    RevocRegEntryOperationData,
+    GetRevocRegOperation,
+    Reply,
+    GetNymReplyResult,
+    GetNymResultData,
+    GetAttribReplyResult,
+    Endpoint,
+    AttribEndpointData,
+    Ddo,
+    DdoEndpoint,
     Role
 };
+use services::anoncreds::types::{ClaimDefinition, RevocationRegistry, Schema};
+use services::ledger::constants::{
+    GET_NYM,
+    GET_TXN,
+    SCHEMA,
+    GET_SCHEMA,
+    GET_CLAIM_DEF,
+    GET_NYM_CACHE_TTL,
+    GET_TXN_CACHE_TTL,
+    GET_SCHEMA_CACHE_TTL,
+    GET_CLAIM_DEF_CACHE_TTL,
+    STEWARD,
+    TRUSTEE
+};
 use errors::common::CommonError;
+use errors::ledger::LedgerError;
 use utils::json::{JsonEncodable, JsonDecodable};
 use utils::crypto::base58::Base58;
+use utils::crypto::verkey_builder::build_full_verkey;
+
+use self::serde_json::Value;
+
+use std::collections::HashMap;
 
 trait LedgerSerializer {
     fn serialize(&self) -> String;
 }
 
+const LEDGER_CACHE_PREFIX: &'static str = "ledger_cache::";
+
 pub struct LedgerService {}
 
 impl LedgerService {
@@ -81,6 +120,19 @@ impl LedgerService {
         Ok(request_json)
     }
 
+    pub fn build_get_txn_request(&self, identifier: &str, seq_no: i32) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+
+        let req_id = LedgerService::get_req_id();
+        let operation = GetTxnOperation::new(seq_no);
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid get_txn request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
     pub fn build_get_ddo_request(&self, identifier: &str, dest: &str) -> Result<String, CommonError> {
         Base58::decode(&identifier)?;
         Base58::decode(&dest)?;
@@ -116,6 +168,12 @@ impl LedgerService {
         Ok(request_json)
     }
 
+    pub fn build_endpoint_attrib_request(&self, identifier: &str, dest: &str, endpoint: &Endpoint) -> Result<String, CommonError> {
+        let raw = AttribEndpointData::new(endpoint.clone()).to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Invalid endpoint attrib json: {}", err.to_string())))?;
+        self.build_attrib_request(identifier, dest, None, Some(&raw), None)
+    }
+
     pub fn build_get_attrib_request(&self, identifier: &str, dest: &str, raw: &str) -> Result<String, CommonError> {
         Base58::decode(&identifier)?;
         Base58::decode(&dest)?;
@@ -194,6 +252,63 @@ impl LedgerService {
         Ok(request_json)
     }
 
+    pub fn build_revoc_reg_def_request(&self, identifier: &str, _ref: i32, data: &str) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+
+        let req_id = LedgerService::get_req_id();
+        RevocationRegistry::from_json(&data)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid data json: {}", err.to_string())))?;
+        let operation = RevocRegDefOperation::new(_ref, data.to_string());
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid revoc_reg_def request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
+    pub fn build_get_revoc_reg_def_request(&self, identifier: &str, _ref: i32, origin: &str) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+        Base58::decode(&origin)?;
+
+        let req_id = LedgerService::get_req_id();
+        let operation = GetRevocRegDefOperation::new(_ref, origin.to_string());
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid get_revoc_reg_def request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
+    pub fn build_revoc_reg_entry_request(&self, identifier: &str, _ref: i32, data: &str) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+
+        let req_id = LedgerService::get_req_id();
+        RevocRegEntryOperationData::from_json(&data)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid data json: {}", err.to_string())))?;
+        let operation = RevocRegEntryOperation::new(_ref, data.to_string());
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid revoc_reg_entry request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
+    pub fn build_get_revoc_reg_request(&self, identifier: &str, _ref: i32) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+
+        let req_id = LedgerService::get_req_id();
+        let operation = GetRevocRegOperation::new(_ref);
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid get_revoc_reg request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
     pub fn build_node_request(&self, identifier: &str, dest: &str, data: &str) -> Result<String, CommonError> {
         Base58::decode(&identifier)?;
         Base58::decode(&dest)?;
@@ -201,6 +316,19 @@ impl LedgerService {
         let req_id = LedgerService::get_req_id();
         let data = NodeOperationData::from_json(&data)
             .map_err(|err| CommonError::InvalidStructure(format!("Invalid data json: {}", err.to_string())))?;
+
+        // Address fields may be omitted only all together, e.g. when a node is promoted or demoted
+        // by setting its services. In that case services must be present.
+        let address_fields = [data.node_ip.is_some(), data.node_port.is_some(),
+            data.client_ip.is_some(), data.client_port.is_some()];
+        let has_address = address_fields.iter().all(|f| *f);
+        if !has_address && address_fields.iter().any(|f| *f) {
+            return Err(CommonError::InvalidStructure(format!("Invalid data json: node_ip, node_port, client_ip and client_port must be specified together")));
+        }
+        if !has_address && data.services.is_none() {
+            return Err(CommonError::InvalidStructure(format!("Invalid data json: either node address or services must be specified")));
+        }
+
         let operation = NodeOperation::new(dest.to_string(), data);
         let request = Request::new(req_id,
                                    identifier.to_string(),
@@ -210,6 +338,233 @@ impl LedgerService {
         Ok(request_json)
     }
 
+    pub fn build_pool_upgrade_request(&self, identifier: &str, name: &str, version: &str, action: &str,
+                                      sha256: &str, timeout: Option<u32>, schedule: Option<&str>,
+                                      justification: Option<&str>, reinstall: bool, force: bool) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+
+        if action != "start" && action != "cancel" {
+            return Err(CommonError::InvalidStructure(format!("Invalid action: {}", action)));
+        }
+
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_digit(16)) {
+            return Err(CommonError::InvalidStructure(format!("Invalid sha256: {}", sha256)));
+        }
+
+        let schedule = match schedule {
+            Some(schedule) => Some(serde_json::from_str::<HashMap<String, String>>(schedule)
+                .map_err(|err| CommonError::InvalidStructure(format!("Invalid schedule json: {}", err.to_string())))?),
+            None => None
+        };
+
+        if action == "start" && schedule.is_none() {
+            return Err(CommonError::InvalidStructure(format!("Schedule is required for start action")));
+        }
+
+        let req_id = LedgerService::get_req_id();
+        let operation = PoolUpgradeOperation::new(name.to_string(),
+                                                  version.to_string(),
+                                                  action.to_string(),
+                                                  sha256.to_string(),
+                                                  timeout,
+                                                  schedule,
+                                                  justification.as_ref().map(|s| s.to_string()),
+                                                  reinstall,
+                                                  force);
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid pool_upgrade request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
+    pub fn build_pool_config_request(&self, identifier: &str, writes: bool, force: bool) -> Result<String, CommonError> {
+        Base58::decode(&identifier)?;
+
+        let req_id = LedgerService::get_req_id();
+        let operation = PoolConfigOperation::new(writes, force);
+        let request = Request::new(req_id,
+                                   identifier.to_string(),
+                                   operation);
+        let request_json = Request::to_json(&request)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid pool_config request json: {}", err.to_string())))?;
+        Ok(request_json)
+    }
+
+    /// Returns the wallet key and default ttl (in seconds) used to cache reply of the given request
+    /// or None if requests of this type are not cached.
+    pub fn get_cache_key(&self, request_json: &str) -> Result<Option<(String, i64)>, CommonError> {
+        let request: Value = serde_json::from_str(request_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid request json: {}", err.to_string())))?;
+        let operation = &request["operation"];
+
+        let key = match operation["type"].as_str() {
+            Some(GET_NYM) => {
+                let dest = LedgerService::_get_str_field(operation, "dest")?;
+                Some((format!("{}nym::{}", LEDGER_CACHE_PREFIX, dest), GET_NYM_CACHE_TTL))
+            }
+            Some(GET_SCHEMA) => {
+                let dest = LedgerService::_get_str_field(operation, "dest")?;
+                let name = LedgerService::_get_str_field(&operation["data"], "name")?;
+                let version = LedgerService::_get_str_field(&operation["data"], "version")?;
+                Some((format!("{}schema::{}::{}::{}", LEDGER_CACHE_PREFIX, dest, name, version), GET_SCHEMA_CACHE_TTL))
+            }
+            Some(GET_CLAIM_DEF) => {
+                let origin = LedgerService::_get_str_field(operation, "origin")?;
+                let signature_type = LedgerService::_get_str_field(operation, "signature_type")?;
+                let _ref = operation["ref"].as_i64()
+                    .ok_or(CommonError::InvalidStructure(format!("Invalid request json: ref not found")))?;
+                Some((format!("{}claim_def::{}::{}::{}", LEDGER_CACHE_PREFIX, origin, _ref, signature_type), GET_CLAIM_DEF_CACHE_TTL))
+            }
+            Some(GET_TXN) => {
+                let seq_no = operation["data"].as_i64()
+                    .ok_or(CommonError::InvalidStructure(format!("Invalid request json: data not found")))?;
+                Some((format!("{}txn::{}", LEDGER_CACHE_PREFIX, seq_no), GET_TXN_CACHE_TTL))
+            }
+            _ => None
+        };
+
+        Ok(key)
+    }
+
+    /// Returns the wallet key prefix of cached replies of the given type ("nym", "schema", "claim_def" or "txn")
+    /// or of all cached replies if type is not set.
+    pub fn get_cache_prefix(&self, cache_type: Option<&str>) -> Result<String, CommonError> {
+        match cache_type {
+            Some(cache_type @ "nym") |
+            Some(cache_type @ "schema") |
+            Some(cache_type @ "claim_def") |
+            Some(cache_type @ "txn") => Ok(format!("{}{}::", LEDGER_CACHE_PREFIX, cache_type)),
+            Some(cache_type) => Err(CommonError::InvalidStructure(format!("Invalid cache type: {}", cache_type))),
+            None => Ok(LEDGER_CACHE_PREFIX.to_string())
+        }
+    }
+
+    /// Only successful replies that contain data are cached,
+    /// so records that don't exist yet are re-requested next time.
+    pub fn is_cacheable_reply(&self, reply_json: &str) -> bool {
+        match serde_json::from_str::<Value>(reply_json) {
+            Ok(reply) => reply["op"] == "REPLY" && !reply["result"]["data"].is_null(),
+            Err(_) => false
+        }
+    }
+
+    /// Replaces reqId of cached reply with reqId of the request it's returned for.
+    pub fn set_reply_req_id(&self, reply_json: &str, request_json: &str) -> Result<String, CommonError> {
+        let request: Value = serde_json::from_str(request_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid request json: {}", err.to_string())))?;
+        let mut reply: Value = serde_json::from_str(reply_json)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid cached reply json: {}", err.to_string())))?;
+
+        if let Some(result) = reply.get_mut("result").and_then(Value::as_object_mut) {
+            result.insert("reqId".to_string(), request["reqId"].clone());
+        }
+
+        serde_json::to_string(&reply)
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize cached reply: {}", err.to_string())))
+    }
+
+    /// Returns NYM data from GET_NYM reply. Abbreviated verkey is expanded to the full one.
+    pub fn parse_get_nym_reply(&self, reply_json: &str) -> Result<GetNymResultData, LedgerError> {
+        let reply: Reply<GetNymReplyResult> = Reply::from_json(reply_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid get_nym reply json: {}", err.to_string())))?;
+
+        let data = match reply.result.data {
+            Some(data) => data,
+            None => return Err(LedgerError::NotFound(format!("NYM not found for did: {}", reply.result.dest)))
+        };
+
+        let data = GetNymResultData::from_json(&data)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid get_nym reply data: {}", err.to_string())))?;
+
+        let verkey = match data.verkey {
+            Some(ref verkey) => Some(build_full_verkey(&data.dest, verkey)?),
+            None => None
+        };
+
+        Ok(GetNymResultData { verkey: verkey, ..data })
+    }
+
+    /// Returns schema from GET_TXN reply. Fails if the transaction isn't SCHEMA one.
+    pub fn parse_get_txn_schema_reply(&self, reply_json: &str) -> Result<Schema, LedgerError> {
+        let reply: Value = serde_json::from_str(reply_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid get_txn reply json: {}", err.to_string())))?;
+
+        let mut txn = match LedgerService::_parse_json_field(&reply["result"], "data")? {
+            Some(txn) => txn,
+            None => return Err(LedgerError::NotFound(format!("Transaction not found for seq no: {}", reply["result"]["data"])))
+        };
+        if txn["type"].as_str() != Some(SCHEMA) {
+            return Err(LedgerError::CommonError(CommonError::InvalidStructure(format!("Transaction {} isn't schema", txn["seqNo"]))));
+        }
+        txn["data"] = LedgerService::_parse_json_field(&txn, "data")?.unwrap_or(Value::Null);
+
+        serde_json::from_value(txn)
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidStructure(format!("Invalid schema transaction: {}", err.to_string()))))
+    }
+
+    /// Returns claim definition (with seqNo) from GET_CLAIM_DEF reply.
+    pub fn parse_get_claim_def_reply(&self, reply_json: &str) -> Result<ClaimDefinition, LedgerError> {
+        let reply: Value = serde_json::from_str(reply_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid get_claim_def reply json: {}", err.to_string())))?;
+
+        let mut result = reply["result"].clone();
+        result["data"] = match LedgerService::_parse_json_field(&result, "data")? {
+            Some(data) => data,
+            None => return Err(LedgerError::NotFound(format!("Claim definition not found for schema {} and issuer {}", result["ref"], result["origin"])))
+        };
+
+        serde_json::from_value(result)
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidStructure(format!("Invalid get_claim_def reply: {}", err.to_string()))))
+    }
+
+    /// Returns endpoint from GET_ATTRIB reply for "endpoint" raw attribute
+    /// or None if this attribute isn't set for the did.
+    pub fn parse_get_attrib_endpoint_reply(&self, reply_json: &str) -> Result<Option<Endpoint>, LedgerError> {
+        let reply: Reply<GetAttribReplyResult> = Reply::from_json(reply_json)
+            .map_err(|err| CommonError::InvalidStructure(format!("Invalid get_attrib reply json: {}", err.to_string())))?;
+
+        match reply.result.data {
+            Some(ref data) => {
+                let data = AttribEndpointData::from_json(data)
+                    .map_err(|err| CommonError::InvalidStructure(format!("Invalid endpoint attrib: {}", err.to_string())))?;
+                Ok(Some(data.endpoint))
+            }
+            None => Ok(None)
+        }
+    }
+
+    /// Unknown NYM roles are returned as null role of DDO.
+    pub fn build_ddo(&self, nym_data: GetNymResultData, endpoint: Option<Endpoint>) -> Ddo {
+        let role = nym_data.role.and_then(|role|
+            match role.parse::<isize>() {
+                Ok(TRUSTEE) => Some("TRUSTEE".to_string()),
+                Ok(STEWARD) => Some("STEWARD".to_string()),
+                _ => None
+            });
+
+        let endpoint = endpoint.map(|endpoint| DdoEndpoint::new(endpoint.ha, endpoint.verkey));
+
+        Ddo::new(nym_data.dest, nym_data.verkey, role, endpoint)
+    }
+
+    fn _get_str_field<'a>(value: &'a Value, field: &str) -> Result<&'a str, CommonError> {
+        value[field].as_str()
+            .ok_or(CommonError::InvalidStructure(format!("Invalid request json: {} not found", field)))
+    }
+
+    /// Ledger returns some fields as JSON encoded strings, so both forms are accepted.
+    fn _parse_json_field(value: &Value, field: &str) -> Result<Option<Value>, CommonError> {
+        match value[field] {
+            Value::Null => Ok(None),
+            Value::String(ref json) => serde_json::from_str(json)
+                .map(Some)
+                .map_err(|err| CommonError::InvalidStructure(format!("Invalid {} json: {}", field, err.to_string()))),
+            ref value => Ok(Some(value.clone()))
+        }
+    }
+
     fn get_req_id() -> u64 {
         time::get_time().sec as u64 * (1e9 as u64) + time::get_time().nsec as u64
     }
@@ -218,6 +573,7 @@ impl LedgerService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use services::anoncreds::prover::mocks as prover_mocks;
 
     #[test]
     fn build_nym_request_works_for_only_required_fields() {
@@ -302,6 +658,21 @@ mod tests {
         assert!(attrib_request.contains(expected_result));
     }
 
+    #[test]
+    fn build_endpoint_attrib_request_works() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let dest = "dest";
+        let endpoint = Endpoint::new("127.0.0.1:5555".to_string(), Some("verkey".to_string()));
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"100","dest":"dest","raw":"{\"endpoint\":{\"ha\":\"127.0.0.1:5555\",\"verkey\":\"verkey\"}}"}"#;
+
+        let attrib_request = ledger_service.build_endpoint_attrib_request(identifier, dest, &endpoint);
+        assert!(attrib_request.is_ok());
+        let attrib_request = attrib_request.unwrap();
+        assert!(attrib_request.contains(expected_result));
+    }
+
     #[test]
     fn build_get_attrib_request_works() {
         let ledger_service = LedgerService::new();
@@ -381,6 +752,83 @@ mod tests {
         assert!(get_claim_def_request.contains(expected_result));
     }
 
+    #[test]
+    fn build_revoc_reg_def_request_works() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let revocation_registry = prover_mocks::get_revocation_registry();
+        let data = RevocationRegistry::to_json(&revocation_registry).unwrap();
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"113","ref":1,"data":"#;
+
+        let revoc_reg_def_request = ledger_service.build_revoc_reg_def_request(identifier, 1, &data);
+        assert!(revoc_reg_def_request.is_ok());
+        let revoc_reg_def_request = revoc_reg_def_request.unwrap();
+        assert!(revoc_reg_def_request.contains(expected_result));
+    }
+
+    #[test]
+    fn build_revoc_reg_def_request_works_for_wrong_data() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let data = r#"{"claim_def_seq_no":1}"#;
+
+        let revoc_reg_def_request = ledger_service.build_revoc_reg_def_request(identifier, 1, data);
+        assert!(revoc_reg_def_request.is_err());
+    }
+
+    #[test]
+    fn build_get_revoc_reg_def_request_works() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let origin = "origin";
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"115","ref":1,"origin":"origin"}"#;
+
+        let get_revoc_reg_def_request = ledger_service.build_get_revoc_reg_def_request(identifier, 1, origin);
+        assert!(get_revoc_reg_def_request.is_ok());
+        let get_revoc_reg_def_request = get_revoc_reg_def_request.unwrap();
+        assert!(get_revoc_reg_def_request.contains(expected_result));
+    }
+
+    #[test]
+    fn build_revoc_reg_entry_request_works() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let data = RevocRegEntryOperationData::new(prover_mocks::get_accumulator().acc, vec![], vec![1]);
+        let data = RevocRegEntryOperationData::to_json(&data).unwrap();
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"114","ref":2,"data":"#;
+
+        let revoc_reg_entry_request = ledger_service.build_revoc_reg_entry_request(identifier, 2, &data);
+        assert!(revoc_reg_entry_request.is_ok());
+        let revoc_reg_entry_request = revoc_reg_entry_request.unwrap();
+        assert!(revoc_reg_entry_request.contains(expected_result));
+    }
+
+    #[test]
+    fn build_revoc_reg_entry_request_works_for_wrong_data() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let data = r#"{"issued":[], "revoked":[1]}"#;
+
+        let revoc_reg_entry_request = ledger_service.build_revoc_reg_entry_request(identifier, 2, data);
+        assert!(revoc_reg_entry_request.is_err());
+    }
+
+    #[test]
+    fn build_get_revoc_reg_request_works() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"116","ref":2}"#;
+
+        let get_revoc_reg_request = ledger_service.build_get_revoc_reg_request(identifier, 2);
+        assert!(get_revoc_reg_request.is_ok());
+        let get_revoc_reg_request = get_revoc_reg_request.unwrap();
+        assert!(get_revoc_reg_request.contains(expected_result));
+    }
+
     #[test]
     fn build_node_request_works() {
         let ledger_service = LedgerService::new();
@@ -406,4 +854,319 @@ mod tests {
         let node_request = ledger_service.build_node_request(identifier, dest, data);
         assert!(node_request.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn build_node_request_works_for_demote() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let dest = "dest";
+        let data = r#"{"alias":"some", "services": []}"#;
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"0","dest":"dest","data":{"alias":"some","services":[]}}"#;
+
+        let node_request = ledger_service.build_node_request(identifier, dest, data);
+        assert!(node_request.is_ok());
+        let node_request = node_request.unwrap();
+        assert!(node_request.contains(expected_result));
+    }
+
+    #[test]
+    fn build_node_request_works_for_promote() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let dest = "dest";
+        let data = r#"{"alias":"some", "services": ["VALIDATOR"]}"#;
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"0","dest":"dest","data":{"alias":"some","services":["VALIDATOR"]}}"#;
+
+        let node_request = ledger_service.build_node_request(identifier, dest, data);
+        assert!(node_request.is_ok());
+        let node_request = node_request.unwrap();
+        assert!(node_request.contains(expected_result));
+    }
+
+    #[test]
+    fn build_node_request_works_for_partial_address() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let dest = "dest";
+        let data = r#"{"node_ip":"ip", "node_port": 1, "alias":"some", "services": ["VALIDATOR"]}"#;
+
+        let node_request = ledger_service.build_node_request(identifier, dest, data);
+        assert!(node_request.is_err());
+    }
+
+    #[test]
+    fn build_node_request_works_for_unknown_service() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let dest = "dest";
+        let data = r#"{"alias":"some", "services": ["SOME"]}"#;
+
+        let node_request = ledger_service.build_node_request(identifier, dest, data);
+        assert!(node_request.is_err());
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_start_action() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+        let schedule = r#"{"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv":"2017-12-25T10:25:58.271857+00:00"}"#;
+
+        let expected_result = format!(r#""identifier":"identifier","operation":{{"type":"109","name":"upgrade","version":"1.0.1","action":"start","sha256":"{}","schedule":{},"justification":"some","reinstall":false,"force":false}}"#,
+                                      sha256, schedule);
+
+        let pool_upgrade_request = ledger_service.build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "start", sha256,
+                                                                             None, Some(schedule), Some("some"), false, false);
+        assert!(pool_upgrade_request.is_ok());
+        let pool_upgrade_request = pool_upgrade_request.unwrap();
+        assert!(pool_upgrade_request.contains(&expected_result));
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_cancel_action() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+        let expected_result = format!(r#""identifier":"identifier","operation":{{"type":"109","name":"upgrade","version":"1.0.1","action":"cancel","sha256":"{}","reinstall":false,"force":false}}"#,
+                                      sha256);
+
+        let pool_upgrade_request = ledger_service.build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "cancel", sha256,
+                                                                             None, None, None, false, false);
+        assert!(pool_upgrade_request.is_ok());
+        let pool_upgrade_request = pool_upgrade_request.unwrap();
+        assert!(pool_upgrade_request.contains(&expected_result));
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_invalid_action() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+        let pool_upgrade_request = ledger_service.build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "stop", sha256,
+                                                                             None, None, None, false, false);
+        assert!(pool_upgrade_request.is_err());
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_start_action_without_schedule() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+        let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+        let pool_upgrade_request = ledger_service.build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "start", sha256,
+                                                                             None, None, None, false, false);
+        assert!(pool_upgrade_request.is_err());
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_invalid_sha256() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+
+        let pool_upgrade_request = ledger_service.build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "cancel", "sha256",
+                                                                             None, None, None, false, false);
+        assert!(pool_upgrade_request.is_err());
+    }
+
+    #[test]
+    fn build_pool_config_request_works() {
+        let ledger_service = LedgerService::new();
+        let identifier = "identifier";
+
+        let expected_result = r#""identifier":"identifier","operation":{"type":"111","writes":false,"force":true}"#;
+
+        let pool_config_request = ledger_service.build_pool_config_request(identifier, false, true);
+        assert!(pool_config_request.is_ok());
+        let pool_config_request = pool_config_request.unwrap();
+        assert!(pool_config_request.contains(expected_result));
+    }
+
+    #[test]
+    fn get_cache_key_works_for_get_nym() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_get_nym_request("identifier", "dest").unwrap();
+
+        let cache_key = ledger_service.get_cache_key(&request).unwrap();
+        assert_eq!(Some(("ledger_cache::nym::dest".to_string(), GET_NYM_CACHE_TTL)), cache_key);
+    }
+
+    #[test]
+    fn get_cache_key_works_for_get_schema() {
+        let ledger_service = LedgerService::new();
+        let data = r#"{"name":"name","version":"1.0"}"#;
+        let request = ledger_service.build_get_schema_request("identifier", "dest", data).unwrap();
+
+        let cache_key = ledger_service.get_cache_key(&request).unwrap();
+        assert_eq!(Some(("ledger_cache::schema::dest::name::1.0".to_string(), GET_SCHEMA_CACHE_TTL)), cache_key);
+    }
+
+    #[test]
+    fn get_cache_key_works_for_get_claim_def() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_get_claim_def_request("identifier", 1, "CL", "origin").unwrap();
+
+        let cache_key = ledger_service.get_cache_key(&request).unwrap();
+        assert_eq!(Some(("ledger_cache::claim_def::origin::1::CL".to_string(), GET_CLAIM_DEF_CACHE_TTL)), cache_key);
+    }
+
+    #[test]
+    fn get_cache_key_works_for_get_txn() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_get_txn_request("identifier", 1).unwrap();
+
+        let cache_key = ledger_service.get_cache_key(&request).unwrap();
+        assert_eq!(Some(("ledger_cache::txn::1".to_string(), GET_TXN_CACHE_TTL)), cache_key);
+    }
+
+    #[test]
+    fn get_cache_key_works_for_not_cached_type() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_get_attrib_request("identifier", "dest", "raw").unwrap();
+
+        let cache_key = ledger_service.get_cache_key(&request).unwrap();
+        assert_eq!(None, cache_key);
+    }
+
+    #[test]
+    fn get_cache_prefix_works() {
+        let ledger_service = LedgerService::new();
+
+        assert_eq!("ledger_cache::", ledger_service.get_cache_prefix(None).unwrap());
+        assert_eq!("ledger_cache::schema::", ledger_service.get_cache_prefix(Some("schema")).unwrap());
+        assert!(ledger_service.get_cache_prefix(Some("attrib")).is_err());
+    }
+
+    #[test]
+    fn is_cacheable_reply_works() {
+        let ledger_service = LedgerService::new();
+
+        assert!(ledger_service.is_cacheable_reply(r#"{"op":"REPLY","result":{"data":"{\"dest\":\"dest\"}"}}"#));
+        assert!(!ledger_service.is_cacheable_reply(r#"{"op":"REPLY","result":{"data":null}}"#));
+        assert!(!ledger_service.is_cacheable_reply(r#"{"op":"REQNACK","reason":"some"}"#));
+    }
+
+    #[test]
+    fn set_reply_req_id_works() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"reqId":1,"type":"105","data":null}}"#;
+        let request = r#"{"reqId":2,"identifier":"8wZcEriaNLNKtteJvx7f8i","operation":{"type":"105","dest":"8wZcEriaNLNKtteJvx7f8i"}}"#;
+
+        let reply: Value = serde_json::from_str(&ledger_service.set_reply_req_id(reply, request).unwrap()).unwrap();
+        assert_eq!(2, reply["result"]["reqId"].as_i64().unwrap());
+        assert_eq!("105", reply["result"]["type"].as_str().unwrap());
+    }
+
+    #[test]
+    fn parse_get_nym_reply_works() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"105","dest":"8wZcEriaNLNKtteJvx7f8i","data":"{\"dest\":\"8wZcEriaNLNKtteJvx7f8i\",\"identifier\":\"V4SGRU86Z58d6TV7PBUe6f\",\"role\":\"2\",\"verkey\":\"GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL\"}"}}"#;
+
+        let nym_data = ledger_service.parse_get_nym_reply(reply).unwrap();
+        assert_eq!("8wZcEriaNLNKtteJvx7f8i", nym_data.dest);
+        assert_eq!(Some("2".to_string()), nym_data.role);
+        assert_eq!(Some("GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL".to_string()), nym_data.verkey);
+    }
+
+    #[test]
+    fn parse_get_nym_reply_works_for_abbreviated_verkey() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"105","dest":"8wZcEriaNLNKtteJvx7f8i","data":"{\"dest\":\"8wZcEriaNLNKtteJvx7f8i\",\"identifier\":\"V4SGRU86Z58d6TV7PBUe6f\",\"role\":null,\"verkey\":\"~NcYxiDXkpYi6ov5FcYDi1e\"}"}}"#;
+
+        let nym_data = ledger_service.parse_get_nym_reply(reply).unwrap();
+        assert_eq!(Some("5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp".to_string()), nym_data.verkey);
+    }
+
+    #[test]
+    fn parse_get_nym_reply_works_for_unknown_did() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"105","dest":"8wZcEriaNLNKtteJvx7f8i","data":null}}"#;
+
+        let res = ledger_service.parse_get_nym_reply(reply);
+        assert_match!(Err(LedgerError::NotFound(_)), res);
+    }
+
+    #[test]
+    fn parse_get_txn_schema_reply_works() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"3","data":"{\"identifier\":\"8wZcEriaNLNKtteJvx7f8i\",\"seqNo\":12,\"type\":\"101\",\"data\":\"{\\\"name\\\":\\\"gvt\\\",\\\"version\\\":\\\"1.0\\\",\\\"keys\\\":[\\\"name\\\",\\\"age\\\"]}\"}"}}"#;
+
+        let schema = ledger_service.parse_get_txn_schema_reply(reply).unwrap();
+        assert_eq!(12, schema.seq_no);
+        assert_eq!("gvt", schema.data.name);
+        assert_eq!(2, schema.data.keys.len());
+    }
+
+    #[test]
+    fn parse_get_txn_schema_reply_works_for_other_txn_type() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"3","data":{"identifier":"8wZcEriaNLNKtteJvx7f8i","seqNo":12,"type":"1","dest":"8wZcEriaNLNKtteJvx7f8i"}}}"#;
+
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), ledger_service.parse_get_txn_schema_reply(reply));
+    }
+
+    #[test]
+    fn parse_get_txn_schema_reply_works_for_unknown_seq_no() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"3","data":null}}"#;
+
+        assert_match!(Err(LedgerError::NotFound(_)), ledger_service.parse_get_txn_schema_reply(reply));
+    }
+
+    #[test]
+    fn parse_get_claim_def_reply_works_for_unknown_claim_def() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"108","ref":1,"signature_type":"CL","origin":"8wZcEriaNLNKtteJvx7f8i","data":null}}"#;
+
+        assert_match!(Err(LedgerError::NotFound(_)), ledger_service.parse_get_claim_def_reply(reply));
+    }
+
+    #[test]
+    fn parse_get_attrib_endpoint_reply_works() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"104","dest":"8wZcEriaNLNKtteJvx7f8i","raw":"endpoint","data":"{\"endpoint\":{\"ha\":\"127.0.0.1:5555\",\"verkey\":\"GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL\"}}"}}"#;
+
+        let endpoint = ledger_service.parse_get_attrib_endpoint_reply(reply).unwrap();
+        assert_eq!(Some(Endpoint::new("127.0.0.1:5555".to_string(), Some("GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL".to_string()))), endpoint);
+    }
+
+    #[test]
+    fn parse_get_attrib_endpoint_reply_works_for_missed_attrib() {
+        let ledger_service = LedgerService::new();
+        let reply = r#"{"op":"REPLY","result":{"identifier":"8wZcEriaNLNKtteJvx7f8i","reqId":1,"type":"104","dest":"8wZcEriaNLNKtteJvx7f8i","raw":"endpoint","data":null}}"#;
+
+        assert_eq!(None, ledger_service.parse_get_attrib_endpoint_reply(reply).unwrap());
+    }
+
+    #[test]
+    fn build_ddo_works() {
+        let ledger_service = LedgerService::new();
+        let nym_data = GetNymResultData {
+            identifier: None,
+            dest: "8wZcEriaNLNKtteJvx7f8i".to_string(),
+            role: Some("0".to_string()),
+            verkey: Some("5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp".to_string())
+        };
+        let endpoint = Endpoint::new("127.0.0.1:5555".to_string(), None);
+
+        let ddo = ledger_service.build_ddo(nym_data, Some(endpoint)).to_json().unwrap();
+        assert_eq!(r#"{"did":"8wZcEriaNLNKtteJvx7f8i","verkey":"5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp","role":"TRUSTEE","endpoint":{"address":"127.0.0.1:5555","transport_key":null}}"#, ddo);
+    }
+
+    #[test]
+    fn build_ddo_works_for_unknown_role() {
+        let ledger_service = LedgerService::new();
+        let nym_data = GetNymResultData {
+            identifier: None,
+            dest: "8wZcEriaNLNKtteJvx7f8i".to_string(),
+            role: Some("101".to_string()),
+            verkey: None
+        };
+
+        let ddo = ledger_service.build_ddo(nym_data, None);
+        assert_eq!(None, ddo.role);
+    }
+}
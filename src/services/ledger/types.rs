@@ -1,8 +1,11 @@
 use services::anoncreds::types::{PublicKey, RevocationPublicKey};
+use utils::crypto::pair::PointG2;
+use std::collections::HashMap;
 use utils::json::{JsonEncodable, JsonDecodable};
 use services::ledger::constants::{
     NODE,
     NYM,
+    GET_TXN,
     ATTRIB,
     SCHEMA,
     GET_ATTR,
@@ -11,6 +14,12 @@ use services::ledger::constants::{
     GET_SCHEMA,
     CLAIM_DEF,
     GET_CLAIM_DEF,
+    POOL_UPGRADE,
+    POOL_CONFIG,
+    REVOC_REG_DEF,
+    REVOC_REG_ENTRY,
+    GET_REVOC_REG_DEF,
+    GET_REVOC_REG,
     STEWARD,
     TRUSTEE
 };
@@ -90,6 +99,24 @@ impl GetNymOperation {
 
 impl JsonEncodable for GetNymOperation {}
 
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetTxnOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub data: i32
+}
+
+impl GetTxnOperation {
+    pub fn new(data: i32) -> GetTxnOperation {
+        GetTxnOperation {
+            _type: GET_TXN.to_string(),
+            data: data
+        }
+    }
+}
+
+impl JsonEncodable for GetTxnOperation {}
+
 #[derive(Serialize, PartialEq, Debug)]
 pub struct AttribOperation {
     #[serde(rename = "type")]
@@ -295,6 +322,109 @@ impl GetClaimDefOperation {
 
 impl JsonEncodable for GetClaimDefOperation {}
 
+#[derive(Serialize, PartialEq, Debug)]
+pub struct RevocRegDefOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "ref")]
+    pub _ref: i32,
+    pub data: String
+}
+
+impl RevocRegDefOperation {
+    pub fn new(_ref: i32, data: String) -> RevocRegDefOperation {
+        RevocRegDefOperation {
+            _type: REVOC_REG_DEF.to_string(),
+            _ref: _ref,
+            data: data
+        }
+    }
+}
+
+impl JsonEncodable for RevocRegDefOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetRevocRegDefOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "ref")]
+    pub _ref: i32,
+    pub origin: String
+}
+
+impl GetRevocRegDefOperation {
+    pub fn new(_ref: i32, origin: String) -> GetRevocRegDefOperation {
+        GetRevocRegDefOperation {
+            _type: GET_REVOC_REG_DEF.to_string(),
+            _ref: _ref,
+            origin: origin
+        }
+    }
+}
+
+impl JsonEncodable for GetRevocRegDefOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct RevocRegEntryOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "ref")]
+    pub _ref: i32,
+    pub data: String
+}
+
+impl RevocRegEntryOperation {
+    pub fn new(_ref: i32, data: String) -> RevocRegEntryOperation {
+        RevocRegEntryOperation {
+            _type: REVOC_REG_ENTRY.to_string(),
+            _ref: _ref,
+            data: data
+        }
+    }
+}
+
+impl JsonEncodable for RevocRegEntryOperation {}
+
+#[derive(Serialize, PartialEq, Debug, Deserialize)]
+pub struct RevocRegEntryOperationData {
+    pub accum: PointG2,
+    pub issued: Vec<i32>,
+    pub revoked: Vec<i32>
+}
+
+impl RevocRegEntryOperationData {
+    pub fn new(accum: PointG2, issued: Vec<i32>, revoked: Vec<i32>) -> RevocRegEntryOperationData {
+        RevocRegEntryOperationData {
+            accum: accum,
+            issued: issued,
+            revoked: revoked
+        }
+    }
+}
+
+impl JsonEncodable for RevocRegEntryOperationData {}
+
+impl<'a> JsonDecodable<'a> for RevocRegEntryOperationData {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetRevocRegOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "ref")]
+    pub _ref: i32
+}
+
+impl GetRevocRegOperation {
+    pub fn new(_ref: i32) -> GetRevocRegOperation {
+        GetRevocRegOperation {
+            _type: GET_REVOC_REG.to_string(),
+            _ref: _ref
+        }
+    }
+}
+
+impl JsonEncodable for GetRevocRegOperation {}
+
 #[derive(Serialize, PartialEq, Debug)]
 pub struct NodeOperation {
     #[serde(rename = "type")]
@@ -323,16 +453,22 @@ pub enum Services {
 
 #[derive(Serialize, PartialEq, Debug, Deserialize)]
 pub struct NodeOperationData {
-    pub node_ip: String,
-    pub node_port: i32,
-    pub client_ip: String,
-    pub client_port: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_port: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_port: Option<i32>,
     pub alias: String,
-    pub services: Vec<Services>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<Services>>
 }
 
 impl NodeOperationData {
-    pub fn new(node_ip: String, node_port: i32, client_ip: String, client_port: i32, alias: String, services: Vec<Services>) -> NodeOperationData {
+    pub fn new(node_ip: Option<String>, node_port: Option<i32>, client_ip: Option<String>, client_port: Option<i32>,
+               alias: String, services: Option<Vec<Services>>) -> NodeOperationData {
         NodeOperationData {
             node_ip: node_ip,
             node_port: node_port,
@@ -348,6 +484,65 @@ impl JsonEncodable for NodeOperationData {}
 
 impl<'a> JsonDecodable<'a> for NodeOperationData {}
 
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PoolUpgradeOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub name: String,
+    pub version: String,
+    pub action: String,
+    pub sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+    pub reinstall: bool,
+    pub force: bool
+}
+
+impl PoolUpgradeOperation {
+    pub fn new(name: String, version: String, action: String, sha256: String, timeout: Option<u32>,
+               schedule: Option<HashMap<String, String>>, justification: Option<String>,
+               reinstall: bool, force: bool) -> PoolUpgradeOperation {
+        PoolUpgradeOperation {
+            _type: POOL_UPGRADE.to_string(),
+            name: name,
+            version: version,
+            action: action,
+            sha256: sha256,
+            timeout: timeout,
+            schedule: schedule,
+            justification: justification,
+            reinstall: reinstall,
+            force: force
+        }
+    }
+}
+
+impl JsonEncodable for PoolUpgradeOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PoolConfigOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub writes: bool,
+    pub force: bool
+}
+
+impl PoolConfigOperation {
+    pub fn new(writes: bool, force: bool) -> PoolConfigOperation {
+        PoolConfigOperation {
+            _type: POOL_CONFIG.to_string(),
+            writes: writes,
+            force: force
+        }
+    }
+}
+
+impl JsonEncodable for PoolConfigOperation {}
+
 #[derive(Serialize, PartialEq, Debug)]
 pub struct GetDdoOperation {
     #[serde(rename = "type")]
@@ -381,13 +576,13 @@ pub struct GetNymReplyResult {
     pub req_id: u64,
     #[serde(rename = "type")]
     pub _type: String,
-    pub data: String,
+    pub data: Option<String>,
     pub dest: String
 }
 
 impl<'a> JsonDecodable<'a> for GetNymReplyResult {}
 
-#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[derive(Deserialize, Eq, PartialEq, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GetNymResultData {
     pub identifier: Option<String>,
@@ -396,4 +591,133 @@ pub struct GetNymResultData {
     pub verkey: Option<String>
 }
 
-impl<'a> JsonDecodable<'a> for GetNymResultData {}
\ No newline at end of file
+impl<'a> JsonDecodable<'a> for GetNymResultData {}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAttribReplyResult {
+    pub identifier: String,
+    pub req_id: u64,
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub data: Option<String>,
+    pub dest: String,
+    pub raw: String
+}
+
+impl<'a> JsonDecodable<'a> for GetAttribReplyResult {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Endpoint {
+    pub ha: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verkey: Option<String>
+}
+
+impl Endpoint {
+    pub fn new(ha: String, verkey: Option<String>) -> Endpoint {
+        Endpoint {
+            ha: ha,
+            verkey: verkey
+        }
+    }
+}
+
+impl JsonEncodable for Endpoint {}
+
+impl<'a> JsonDecodable<'a> for Endpoint {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct AttribEndpointData {
+    pub endpoint: Endpoint
+}
+
+impl AttribEndpointData {
+    pub fn new(endpoint: Endpoint) -> AttribEndpointData {
+        AttribEndpointData {
+            endpoint: endpoint
+        }
+    }
+}
+
+impl JsonEncodable for AttribEndpointData {}
+
+impl<'a> JsonDecodable<'a> for AttribEndpointData {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct DdoEndpoint {
+    pub address: String,
+    pub transport_key: Option<String>
+}
+
+impl DdoEndpoint {
+    pub fn new(address: String, transport_key: Option<String>) -> DdoEndpoint {
+        DdoEndpoint {
+            address: address,
+            transport_key: transport_key
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Ddo {
+    pub did: String,
+    pub verkey: Option<String>,
+    pub role: Option<String>,
+    pub endpoint: Option<DdoEndpoint>
+}
+
+impl Ddo {
+    pub fn new(did: String, verkey: Option<String>, role: Option<String>, endpoint: Option<DdoEndpoint>) -> Ddo {
+        Ddo {
+            did: did,
+            verkey: verkey,
+            role: role,
+            endpoint: endpoint
+        }
+    }
+}
+
+impl JsonEncodable for Ddo {}
+
+impl<'a> JsonDecodable<'a> for Ddo {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct LedgerCacheOptions {
+    #[serde(default)]
+    pub refresh: bool,
+    #[serde(default)]
+    pub bypass: bool,
+    pub ttl: Option<i64>
+}
+
+impl Default for LedgerCacheOptions {
+    fn default() -> LedgerCacheOptions {
+        LedgerCacheOptions {
+            refresh: false,
+            bypass: false,
+            ttl: None
+        }
+    }
+}
+
+impl<'a> JsonDecodable<'a> for LedgerCacheOptions {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct LedgerCacheRecord {
+    pub reply: String,
+    pub time_cached: i64
+}
+
+impl LedgerCacheRecord {
+    pub fn new(reply: String, time_cached: i64) -> LedgerCacheRecord {
+        LedgerCacheRecord {
+            reply: reply,
+            time_cached: time_cached
+        }
+    }
+}
+
+impl JsonEncodable for LedgerCacheRecord {}
+
+impl<'a> JsonDecodable<'a> for LedgerCacheRecord {}
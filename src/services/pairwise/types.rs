@@ -0,0 +1,67 @@
+use utils::json::{JsonEncodable, JsonDecodable};
+
+/// Last known state of agent connection between DIDs of pairwise.
+#[serde(rename_all = "snake_case")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PairwiseState {
+    Created,
+    Connected,
+    Disconnected,
+}
+
+/// Relationship between my and their DIDs stored in the wallet. It is identified by their DID.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pairwise {
+    pub my_did: String,
+    pub their_did: String,
+    pub endpoint: Option<String>,
+    pub transport_key: Option<String>,
+    pub metadata: Option<String>,
+    pub state: PairwiseState,
+}
+
+impl Pairwise {
+    pub fn new(my_did: String, their_did: String) -> Pairwise {
+        Pairwise {
+            my_did: my_did,
+            their_did: their_did,
+            endpoint: None,
+            transport_key: None,
+            metadata: None,
+            state: PairwiseState::Created,
+        }
+    }
+
+    pub fn key(their_did: &str) -> String {
+        format!("pairwise::{}", their_did)
+    }
+
+    /// Replaces fields that are set in info.
+    pub fn update(&mut self, info: PairwiseInfo) {
+        if info.endpoint.is_some() {
+            self.endpoint = info.endpoint;
+        }
+        if info.transport_key.is_some() {
+            self.transport_key = info.transport_key;
+        }
+        if info.metadata.is_some() {
+            self.metadata = info.metadata;
+        }
+    }
+}
+
+impl JsonEncodable for Pairwise {}
+
+impl<'a> JsonDecodable<'a> for Pairwise {}
+
+/// Pairwise fields passed by user on creating and updating.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PairwiseInfo {
+    pub endpoint: Option<String>,
+    pub transport_key: Option<String>,
+    pub metadata: Option<String>,
+}
+
+impl JsonEncodable for PairwiseInfo {}
+
+impl<'a> JsonDecodable<'a> for PairwiseInfo {}
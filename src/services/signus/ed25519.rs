@@ -12,8 +12,8 @@ impl ED25519Signus {
 }
 
 impl CryptoType for ED25519Signus {
-    fn encrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Vec<u8> {
-        ED25519::encrypt(private_key, public_key, doc, nonce)
+    fn encrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Ok(ED25519::encrypt(private_key, public_key, doc, nonce))
     }
 
     fn decrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError> {
@@ -24,6 +24,14 @@ impl CryptoType for ED25519Signus {
         ED25519::gen_nonce()
     }
 
+    fn encrypt_sealed(&self, public_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        ED25519::encrypt_sealed(public_key, doc)
+    }
+
+    fn decrypt_sealed(&self, public_key: &[u8], private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        ED25519::decrypt_sealed(public_key, private_key, doc)
+    }
+
     fn create_key_pair_for_signature(&self, seed: Option<&[u8]>) -> Result<(Vec<u8>, Vec<u8>), CommonError> {
         ED25519::create_key_pair_for_signature(seed)
     }
@@ -1,4 +1,6 @@
 mod ed25519;
+#[cfg(feature = "secp256k1_rust")]
+mod secp256k1;
 pub mod types;
 
 extern crate serde_json;
@@ -6,14 +8,24 @@ extern crate serde_json;
 use self::serde_json::Value;
 
 use self::ed25519::ED25519Signus;
+#[cfg(feature = "secp256k1_rust")]
+use self::secp256k1::Secp256K1Signus;
 use self::types::{
+    Envelope,
+    EnvelopeRecipient,
+    KeyInfo,
+    Key,
     MyDidInfo,
     MyDid,
     TheirDidInfo,
     TheirDid
 };
 use utils::crypto::base58::Base58;
+use utils::crypto::base64::Base64;
+use utils::crypto::memzero::SecretBytes;
 use utils::crypto::signature_serializer::serialize_signature;
+use utils::crypto::verkey_builder::{build_abbreviated_verkey, build_full_verkey};
+use utils::crypto::xsalsa20::{KEYBYTES, NONCEBYTES, XSalsa20};
 
 use errors::common::CommonError;
 use errors::signus::SignusError;
@@ -23,11 +35,16 @@ use std::error::Error;
 use std::str;
 
 const DEFAULT_CRYPTO_TYPE: &'static str = "ed25519";
+const JWS_ALG: &'static str = "EdDSA";
+#[cfg(feature = "secp256k1_rust")]
+const SECP256K1_CRYPTO_TYPE: &'static str = "secp256k1";
 
 trait CryptoType {
-    fn encrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Vec<u8>;
+    fn encrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError>;
     fn decrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError>;
     fn gen_nonce(&self) -> Vec<u8>;
+    fn encrypt_sealed(&self, public_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError>;
+    fn decrypt_sealed(&self, public_key: &[u8], private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError>;
     fn create_key_pair_for_signature(&self, seed: Option<&[u8]>) -> Result<(Vec<u8>, Vec<u8>), CommonError>;
     fn sign(&self, private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError>;
     fn verify(&self, public_key: &[u8], doc: &[u8], signature: &[u8]) -> Result<bool, CommonError>;
@@ -43,28 +60,37 @@ impl SignusService {
     pub fn new() -> SignusService {
         let mut crypto_types: HashMap<&str, Box<CryptoType>> = HashMap::new();
         crypto_types.insert(DEFAULT_CRYPTO_TYPE, Box::new(ED25519Signus::new()));
+        #[cfg(feature = "secp256k1_rust")]
+        crypto_types.insert(SECP256K1_CRYPTO_TYPE, Box::new(Secp256K1Signus::new()));
 
         SignusService {
             crypto_types: crypto_types
         }
     }
 
-    pub fn create_my_did(&self, my_did_info: &MyDidInfo) -> Result<MyDid, SignusError> {
-        let xtype = my_did_info.crypto_type.clone().unwrap_or(DEFAULT_CRYPTO_TYPE.to_string());
+    pub fn create_key(&self, key_info: &KeyInfo) -> Result<Key, SignusError> {
+        let crypto_type = key_info.crypto_type.clone().unwrap_or(DEFAULT_CRYPTO_TYPE.to_string());
 
-        if !self.crypto_types.contains_key(&xtype.as_str()) {
-            return Err(
-                SignusError::UnknownCryptoError(
-                    format!("MyDidInfo info contains unknown crypto: {}", xtype)));
-        }
+        let signus = self._get_crypto_type(&crypto_type)?;
+
+        let seed = key_info.seed.as_ref().map(String::as_bytes);
+        let (verkey, signkey) = signus.create_key_pair_for_signature(seed)?;
 
-        let signus = self.crypto_types.get(&xtype.as_str()).unwrap();
+        Ok(Key::new(SignusService::verkey_with_crypto_type(&Base58::encode(&verkey), &crypto_type),
+                    Base58::encode(&signkey)))
+    }
+
+    pub fn create_my_did(&self, my_did_info: &MyDidInfo) -> Result<MyDid, SignusError> {
+        let key = self.create_key(&KeyInfo::new(my_did_info.seed.clone(), my_did_info.crypto_type.clone()))?;
 
-        let seed = my_did_info.seed.as_ref().map(String::as_bytes);
-        let (ver_key, sign_key) = signus.create_key_pair_for_signature(seed)?;
+        let (verkey, crypto_type) = SignusService::split_verkey(&key.verkey);
+        let signus = self._get_crypto_type(crypto_type)?;
+
+        let ver_key = Base58::decode(verkey)?;
+        let sign_key = SecretBytes::new(Base58::decode(&key.signkey)?);
 
         let public_key = signus.verkey_to_public_key(&ver_key)?;
-        let secret_key = signus.signkey_to_private_key(&sign_key)?;
+        let secret_key = SecretBytes::new(signus.signkey_to_private_key(&sign_key)?);
 
         let did = match my_did_info.did {
             Some(ref did) => Base58::decode(did)?,
@@ -73,33 +99,45 @@ impl SignusService {
         };
 
         let my_did = MyDid::new(Base58::encode(&did),
-                                xtype.clone(),
+                                crypto_type.to_string(),
                                 Base58::encode(&public_key),
                                 Base58::encode(&secret_key),
-                                Base58::encode(&ver_key),
-                                Base58::encode(&sign_key));
+                                verkey.to_string(),
+                                key.signkey.clone());
 
         Ok(my_did)
     }
 
+    /// Returns verkey of my did in the form that should be published:
+    /// abbreviated ("~" + last 16 bytes) if the did is built from the default crypto type verkey,
+    /// full verkey otherwise.
+    pub fn my_did_public_verkey(&self, my_did: &MyDid) -> Result<String, SignusError> {
+        if my_did.crypto_type != DEFAULT_CRYPTO_TYPE {
+            return Ok(my_did.verkey.clone());
+        }
+
+        Ok(build_abbreviated_verkey(&my_did.did, &my_did.verkey)?)
+    }
+
+    pub fn my_did_key(&self, my_did: &MyDid) -> Key {
+        Key::new(SignusService::verkey_with_crypto_type(&my_did.verkey, &my_did.crypto_type),
+                 my_did.signkey.clone())
+    }
+
     pub fn create_their_did(&self, their_did_info: &TheirDidInfo) -> Result<TheirDid, SignusError> {
         let xtype = their_did_info.crypto_type.clone().unwrap_or(DEFAULT_CRYPTO_TYPE.to_string());
 
-        if !self.crypto_types.contains_key(&xtype.as_str()) {
-            return Err(
-                SignusError::UnknownCryptoError(
-                    format!("TheirDidInfo info contains unknown crypto: {}", xtype)));
-        }
-
-        let signus = self.crypto_types.get(&xtype.as_str()).unwrap();
+        let signus = self._get_crypto_type(&xtype)?;
 
         // Check did is correct Base58
         Base58::decode(&their_did_info.did)?;
 
         let (verkey, pk) = match their_did_info.verkey {
-            Some(ref verkey) => (
-                Some(verkey.clone()),
-                Some(Base58::encode(&signus.verkey_to_public_key(&Base58::decode(verkey)?)?))),
+            Some(ref verkey) => {
+                let verkey = build_full_verkey(&their_did_info.did, verkey)?;
+                let pk = Base58::encode(&signus.verkey_to_public_key(&Base58::decode(&verkey)?)?);
+                (Some(verkey), Some(pk))
+            }
             None => (None, None)
         };
 
@@ -112,15 +150,14 @@ impl SignusService {
     }
 
     pub fn sign(&self, my_did: &MyDid, doc: &str) -> Result<String, SignusError> {
-        if !self.crypto_types.contains_key(&my_did.crypto_type.as_str()) {
-            return Err(
-                SignusError::UnknownCryptoError(
-                    format!("Trying to sign message with unknown crypto: {}", my_did.crypto_type)));
-        }
+        self.sign_by_key(&self.my_did_key(my_did), doc)
+    }
 
-        let signus = self.crypto_types.get(&my_did.crypto_type.as_str()).unwrap();
+    pub fn sign_by_key(&self, key: &Key, doc: &str) -> Result<String, SignusError> {
+        let (_, crypto_type) = SignusService::split_verkey(&key.verkey);
+        let signus = self._get_crypto_type(crypto_type)?;
 
-        let sign_key = Base58::decode(&my_did.signkey)?;
+        let sign_key = SecretBytes::new(Base58::decode(&key.signkey)?);
         let mut msg: Value = serde_json::from_str(doc)
             .map_err(|err|
                 SignusError::CommonError(
@@ -143,17 +180,32 @@ impl SignusService {
     }
 
     pub fn verify(&self, their_did: &TheirDid, signed_msg: &str) -> Result<bool, SignusError> {
-        if !self.crypto_types.contains_key(their_did.crypto_type.as_str()) {
-            return Err(SignusError::UnknownCryptoError(format!("Trying to verify message with unknown crypto: {}", their_did.crypto_type)));
-        }
-
-        let signus = self.crypto_types.get(their_did.crypto_type.as_str()).unwrap();
-
         let verkey = match their_did.verkey {
-            Some(ref verkey) => Base58::decode(&verkey)?,
+            Some(ref verkey) => SignusService::verkey_with_crypto_type(verkey, &their_did.crypto_type),
             None => return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("TheirDid doesn't contain verkey: {}", their_did.did))))
         };
 
+        self.verify_by_key(&verkey, signed_msg)
+    }
+
+    /// Verifies either signed json object or compact JWS.
+    pub fn verify_by_key(&self, verkey: &str, signed_msg: &str) -> Result<bool, SignusError> {
+        if signed_msg.trim_left().starts_with("{") {
+            self._verify_json_by_key(verkey, signed_msg)
+        } else if signed_msg.split('.').count() == 3 {
+            self.verify_jws_by_key(verkey, signed_msg)
+        } else {
+            Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("Signed message is neither json object nor compact JWS"))))
+        }
+    }
+
+    fn _verify_json_by_key(&self, verkey: &str, signed_msg: &str) -> Result<bool, SignusError> {
+        let (verkey, crypto_type) = SignusService::split_verkey(verkey);
+        let signus = self._get_crypto_type(crypto_type)?;
+
+        let verkey = Base58::decode(&verkey)?;
+
         let signed_msg: Value = serde_json::from_str(signed_msg)
             .map_err(|err|
                 SignusError::CommonError(
@@ -179,25 +231,112 @@ impl SignusService {
         }
     }
 
-    pub fn encrypt(&self, my_did: &MyDid, their_did: &TheirDid, doc: &str) -> Result<(String, String), SignusError> {
-        if !self.crypto_types.contains_key(&my_did.crypto_type.as_str()) {
-            return Err(SignusError::UnknownCryptoError(format!("Trying to encrypt message with unknown crypto: {}", my_did.crypto_type)));
+    pub fn sign_detached(&self, my_did: &MyDid, msg: &[u8]) -> Result<Vec<u8>, SignusError> {
+        self.sign_detached_by_key(&self.my_did_key(my_did), msg)
+    }
+
+    /// Signs arbitrary bytes and returns just the signature.
+    pub fn sign_detached_by_key(&self, key: &Key, msg: &[u8]) -> Result<Vec<u8>, SignusError> {
+        let (_, crypto_type) = SignusService::split_verkey(&key.verkey);
+        let signus = self._get_crypto_type(crypto_type)?;
+
+        let sign_key = SecretBytes::new(Base58::decode(&key.signkey)?);
+        Ok(signus.sign(&sign_key, msg)?)
+    }
+
+    pub fn verify_detached(&self, their_did: &TheirDid, msg: &[u8], signature: &[u8]) -> Result<bool, SignusError> {
+        let verkey = match their_did.verkey {
+            Some(ref verkey) => SignusService::verkey_with_crypto_type(verkey, &their_did.crypto_type),
+            None => return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("TheirDid doesn't contain verkey: {}", their_did.did))))
+        };
+
+        self.verify_detached_by_key(&verkey, msg, signature)
+    }
+
+    pub fn verify_detached_by_key(&self, verkey: &str, msg: &[u8], signature: &[u8]) -> Result<bool, SignusError> {
+        let (verkey, crypto_type) = SignusService::split_verkey(verkey);
+        let signus = self._get_crypto_type(crypto_type)?;
+
+        let verkey = Base58::decode(&verkey)?;
+        Ok(signus.verify(&verkey, msg, signature)?)
+    }
+
+    pub fn sign_jws(&self, my_did: &MyDid, payload: &[u8]) -> Result<String, SignusError> {
+        self.sign_jws_by_key(&self.my_did_key(my_did), payload)
+    }
+
+    /// Returns compact JWS ("header.payload.signature", base64url without padding) signed with EdDSA.
+    /// Only ed25519 keys can be used.
+    pub fn sign_jws_by_key(&self, key: &Key, payload: &[u8]) -> Result<String, SignusError> {
+        let (verkey, crypto_type) = SignusService::split_verkey(&key.verkey);
+
+        if crypto_type != DEFAULT_CRYPTO_TYPE {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("JWS can't be signed by {} key", crypto_type))));
         }
 
-        let signus = self.crypto_types.get(&my_did.crypto_type.as_str()).unwrap();
+        let header = format!(r#"{{"alg":"{}","kid":"{}"}}"#, JWS_ALG, verkey);
+
+        let signing_input = format!("{}.{}", Base64::encode_url_safe(header.as_bytes()), Base64::encode_url_safe(payload));
+        let signature = self.sign_detached_by_key(key, signing_input.as_bytes())?;
 
-        if their_did.pk.is_none() {
-            return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("TheirDid doesn't contain pk: {}", their_did.did))));
+        Ok(format!("{}.{}", signing_input, Base64::encode_url_safe(&signature)))
+    }
+
+    pub fn verify_jws_by_key(&self, verkey: &str, jws: &str) -> Result<bool, SignusError> {
+        let parts: Vec<&str> = jws.split('.').collect();
+
+        if parts.len() != 3 {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("JWS must contain header, payload and signature parts"))));
         }
 
-        let public_key = their_did.pk.clone().unwrap();
+        let header = Base64::decode_url_safe(parts[0])?;
+        let header: Value = serde_json::from_slice(&header)
+            .map_err(|err|
+                SignusError::CommonError(
+                    CommonError::InvalidStructure(format!("JWS header is invalid json: {}", err.description()))))?;
 
-        let nonce = signus.gen_nonce();
+        if header["alg"] != JWS_ALG {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("Unsupported JWS alg: {}", header["alg"]))));
+        }
+
+        let (_, crypto_type) = SignusService::split_verkey(verkey);
+
+        if crypto_type != DEFAULT_CRYPTO_TYPE {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("JWS can't be verified by {} key", crypto_type))));
+        }
+
+        Base64::decode_url_safe(parts[1])?;
+        let signature = Base64::decode_url_safe(parts[2])?;
+
+        self.verify_detached_by_key(verkey, format!("{}.{}", parts[0], parts[1]).as_bytes(), &signature)
+    }
+
+    pub fn encrypt(&self, my_did: &MyDid, their_did: &TheirDid, doc: &str) -> Result<(String, String), SignusError> {
+        if my_did.crypto_type != their_did.crypto_type {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(
+                    format!("Can't encrypt message from {} did {} to {} did {}",
+                            my_did.crypto_type, my_did.did, their_did.crypto_type, their_did.did))));
+        }
 
-        let secret_key = Base58::decode(&my_did.sk)?;
-        let public_key = Base58::decode(&public_key)?;
+        let their_verkey = match their_did.verkey {
+            Some(ref verkey) => SignusService::verkey_with_crypto_type(verkey, &their_did.crypto_type),
+            None => return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("TheirDid doesn't contain pk: {}", their_did.did))))
+        };
+
+        self.encrypt_by_key(&self.my_did_key(my_did), &their_verkey, doc)
+    }
+
+    pub fn encrypt_by_key(&self, my_key: &Key, their_verkey: &str, doc: &str) -> Result<(String, String), SignusError> {
+        let (signus, secret_key, public_key) = self._box_keys(my_key, their_verkey)?;
+
+        let nonce = signus.gen_nonce();
 
-        let encrypted_doc = signus.encrypt(&secret_key, &public_key, &doc.as_bytes(), &nonce);
+        let encrypted_doc = signus.encrypt(&secret_key, &public_key, &doc.as_bytes(), &nonce)?;
         let encrypted_doc = Base58::encode(&encrypted_doc);
         let nonce = Base58::encode(&nonce);
 
@@ -205,21 +344,24 @@ impl SignusService {
     }
 
     pub fn decrypt(&self, my_did: &MyDid, their_did: &TheirDid, doc: &str, nonce: &str) -> Result<String, SignusError> {
-        if !self.crypto_types.contains_key(&my_did.crypto_type.as_str()) {
-            return Err(SignusError::UnknownCryptoError(format!("MyDid crypto is unknown: {}, {}", my_did.did, my_did.crypto_type)));
+        if my_did.crypto_type != their_did.crypto_type {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(
+                    format!("Can't decrypt message from {} did {} with {} did {}",
+                            their_did.crypto_type, their_did.did, my_did.crypto_type, my_did.did))));
         }
 
-        let signus = self.crypto_types.get(&my_did.crypto_type.as_str()).unwrap();
+        let their_verkey = match their_did.verkey {
+            Some(ref verkey) => SignusService::verkey_with_crypto_type(verkey, &their_did.crypto_type),
+            None => return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("No pk in TheirDid: {}", their_did.did))))
+        };
 
-        if their_did.pk.is_none() {
-            return Err(SignusError::CommonError(
-                CommonError::InvalidStructure(format!("No pk in TheirDid: {}", their_did.did))));
-        }
+        self.decrypt_by_key(&self.my_did_key(my_did), &their_verkey, doc, nonce)
+    }
 
-        let public_key = their_did.pk.clone().unwrap();
+    pub fn decrypt_by_key(&self, my_key: &Key, their_verkey: &str, doc: &str, nonce: &str) -> Result<String, SignusError> {
+        let (signus, secret_key, public_key) = self._box_keys(my_key, their_verkey)?;
 
-        let secret_key = Base58::decode(&my_did.sk)?;
-        let public_key = Base58::decode(&public_key)?;
         let nonce = Base58::decode(&nonce)?;
         let doc = Base58::decode(&doc)?;
 
@@ -227,15 +369,147 @@ impl SignusService {
 
         let decrypted_doc = str::from_utf8(&decrypted_doc)
             .map_err(|err|
-                CommonError::InvalidStructure(format!("Decrypted message is invalid string: {}", their_did.did)))?;
+                CommonError::InvalidStructure(format!("Decrypted message is invalid string: {}", their_verkey)))?;
         Ok(decrypted_doc.to_string())
     }
+
+    pub fn encrypt_sealed(&self, their_did: &TheirDid, doc: &str) -> Result<String, SignusError> {
+        let signus = self._get_crypto_type(&their_did.crypto_type)?;
+
+        let public_key = match their_did.pk {
+            Some(ref pk) => Base58::decode(pk)?,
+            None => return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("TheirDid doesn't contain pk: {}", their_did.did))))
+        };
+
+        let encrypted_doc = signus.encrypt_sealed(&public_key, &doc.as_bytes())?;
+        Ok(Base58::encode(&encrypted_doc))
+    }
+
+    pub fn decrypt_sealed(&self, my_did: &MyDid, doc: &str) -> Result<String, SignusError> {
+        let signus = self._get_crypto_type(&my_did.crypto_type)?;
+
+        let public_key = Base58::decode(&my_did.pk)?;
+        let secret_key = SecretBytes::new(Base58::decode(&my_did.sk)?);
+        let doc = Base58::decode(&doc)?;
+
+        let decrypted_doc = signus.decrypt_sealed(&public_key, &secret_key, &doc)?;
+
+        let decrypted_doc = str::from_utf8(&decrypted_doc)
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Decrypted message is invalid string: {}", my_did.did)))?;
+        Ok(decrypted_doc.to_string())
+    }
+
+    /// Encrypts message once by a random content key and wraps this key
+    /// for every recipient by sealed box with recipient's public key.
+    pub fn encrypt_envelope(&self, their_dids: &[TheirDid], doc: &str) -> Result<Envelope, SignusError> {
+        if their_dids.is_empty() {
+            return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("Envelope must have at least one recipient"))));
+        }
+
+        let xsalsa20 = XSalsa20::new();
+
+        let content_key = SecretBytes::new(xsalsa20.create_key());
+        let nonce = xsalsa20.gen_nonce();
+        let ciphertext = xsalsa20.encrypt(&content_key, &nonce, doc.as_bytes());
+
+        let mut recipients: Vec<EnvelopeRecipient> = Vec::new();
+
+        for their_did in their_dids {
+            let signus = self._get_crypto_type(&their_did.crypto_type)?;
+
+            let public_key = match their_did.pk {
+                Some(ref pk) => Base58::decode(pk)?,
+                None => return Err(SignusError::CommonError(CommonError::InvalidStructure(format!("TheirDid doesn't contain pk: {}", their_did.did))))
+            };
+
+            let encrypted_key = signus.encrypt_sealed(&public_key, &content_key)?;
+            recipients.push(EnvelopeRecipient::new(their_did.did.clone(), Base58::encode(&encrypted_key)));
+        }
+
+        Ok(Envelope::new(Base58::encode(&ciphertext), Base58::encode(&nonce), recipients))
+    }
+
+    pub fn decrypt_envelope(&self, my_did: &MyDid, envelope: &Envelope) -> Result<String, SignusError> {
+        let recipient = envelope.recipients.iter()
+            .find(|recipient| recipient.did == my_did.did)
+            .ok_or(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("Envelope doesn't contain entry for did: {}", my_did.did))))?;
+
+        let signus = self._get_crypto_type(&my_did.crypto_type)?;
+
+        let public_key = Base58::decode(&my_did.pk)?;
+        let secret_key = SecretBytes::new(Base58::decode(&my_did.sk)?);
+
+        let content_key = SecretBytes::new(signus.decrypt_sealed(&public_key, &secret_key, &Base58::decode(&recipient.encrypted_key)?)?);
+        let nonce = Base58::decode(&envelope.nonce)?;
+
+        // Nonce and content key are chosen by sender, so their lengths are checked before use
+        if nonce.len() != NONCEBYTES {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("Envelope nonce must be {} bytes", NONCEBYTES))));
+        }
+
+        if content_key.len() != KEYBYTES {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(format!("Envelope content key must be {} bytes", KEYBYTES))));
+        }
+
+        let xsalsa20 = XSalsa20::new();
+        let doc = xsalsa20.decrypt(&content_key,
+                                   &nonce,
+                                   &Base58::decode(&envelope.ciphertext)?)?;
+
+        let doc = str::from_utf8(&doc)
+            .map_err(|err|
+                CommonError::InvalidStructure(format!("Decrypted message is invalid string: {}", err.description())))?;
+        Ok(doc.to_string())
+    }
+
+    /// Verkey can be followed by ":<crypto_type>" suffix. Verkey without suffix uses default crypto type.
+    pub fn split_verkey(verkey: &str) -> (&str, &str) {
+        match verkey.find(':') {
+            Some(pos) => (&verkey[..pos], &verkey[pos + 1..]),
+            None => (verkey, DEFAULT_CRYPTO_TYPE)
+        }
+    }
+
+    pub fn verkey_with_crypto_type(verkey: &str, crypto_type: &str) -> String {
+        if crypto_type == DEFAULT_CRYPTO_TYPE {
+            verkey.to_string()
+        } else {
+            format!("{}:{}", verkey, crypto_type)
+        }
+    }
+
+    fn _get_crypto_type(&self, crypto_type: &str) -> Result<&Box<CryptoType>, SignusError> {
+        self.crypto_types.get(crypto_type)
+            .ok_or(SignusError::UnknownCryptoError(format!("Unknown crypto type: {}", crypto_type)))
+    }
+
+    fn _box_keys(&self, my_key: &Key, their_verkey: &str) -> Result<(&Box<CryptoType>, SecretBytes, Vec<u8>), SignusError> {
+        let (_, my_crypto_type) = SignusService::split_verkey(&my_key.verkey);
+        let (their_verkey, their_crypto_type) = SignusService::split_verkey(their_verkey);
+
+        if my_crypto_type != their_crypto_type {
+            return Err(SignusError::CommonError(
+                CommonError::InvalidStructure(
+                    format!("Can't use {} key together with {} key", my_crypto_type, their_crypto_type))));
+        }
+
+        let signus = self._get_crypto_type(my_crypto_type)?;
+
+        let secret_key = SecretBytes::new(signus.signkey_to_private_key(&SecretBytes::new(Base58::decode(&my_key.signkey)?))?);
+        let public_key = signus.verkey_to_public_key(&Base58::decode(their_verkey)?)?;
+
+        Ok((signus, secret_key, public_key))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use services::signus::types::MyDidInfo;
+    use services::signus::types::{MyDidInfo, TheirDidInfo};
 
     #[test]
     fn create_my_did_with_empty_input_works() {
@@ -256,7 +530,40 @@ mod tests {
         let res = service.create_my_did(&did_info);
         assert!(res.is_ok());
 
-        assert_eq!(did.unwrap(), did_info.did.unwrap());
+        assert_eq!(did.unwrap(), did_info.did.clone().unwrap());
+    }
+
+    #[test]
+    fn my_did_public_verkey_works_for_abbreviated_verkey() {
+        let service = SignusService::new();
+
+        let seed = Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string());
+        let did_info = MyDidInfo::new(None, seed, None, None);
+
+        let my_did = service.create_my_did(&did_info).unwrap();
+        assert_eq!("~B7k8kTMqRfiyWoQYf6fTn4", service.my_did_public_verkey(&my_did).unwrap());
+    }
+
+    #[test]
+    fn my_did_public_verkey_works_for_cid() {
+        let service = SignusService::new();
+
+        let seed = Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string());
+        let did_info = MyDidInfo::new(None, seed, None, Some(true));
+
+        let my_did = service.create_my_did(&did_info).unwrap();
+        assert_eq!("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW", service.my_did_public_verkey(&my_did).unwrap());
+    }
+
+    #[test]
+    fn create_their_did_works_for_abbreviated_verkey() {
+        let service = SignusService::new();
+
+        let their_did_info = TheirDidInfo::new("NcYxiDXkpYi6ov5FcYDi1e".to_string(), None,
+                                               Some("~B7k8kTMqRfiyWoQYf6fTn4".to_string()), None);
+
+        let their_did = service.create_their_did(&their_did_info).unwrap();
+        assert_eq!(Some("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW".to_string()), their_did.verkey);
     }
 
     #[test]
@@ -366,7 +673,7 @@ mod tests {
             crypto_type: DEFAULT_CRYPTO_TYPE.to_string(),
             pk: None,
             endpoint: None,
-            verkey: Some(my_did.verkey)
+            verkey: Some(my_did.verkey.clone())
         };
 
         let res = service.verify(&their_did, &signature);
@@ -455,11 +762,11 @@ mod tests {
         let my_did_for_encrypt = my_did.clone();
 
         let their_did_for_decrypt = TheirDid {
-            did: my_did.did,
+            did: my_did.did.clone(),
             crypto_type: DEFAULT_CRYPTO_TYPE.to_string(),
-            pk: Some(my_did.pk),
+            pk: Some(my_did.pk.clone()),
             endpoint: None,
-            verkey: Some(my_did.verkey)
+            verkey: Some(my_did.verkey.clone())
         };
 
 
@@ -487,4 +794,334 @@ mod tests {
 
         assert_eq!(msg.to_string(), decrypted_message);
     }
+
+    #[test]
+    fn create_key_works() {
+        let service = SignusService::new();
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+        assert_eq!(32, Base58::decode(&key.verkey).unwrap().len());
+    }
+
+    #[test]
+    fn create_key_works_for_seed() {
+        let service = SignusService::new();
+
+        let seed = Some("00000000000000000000000000000My1".to_string());
+        let key = service.create_key(&KeyInfo::new(seed.clone(), None)).unwrap();
+        let my_did = service.create_my_did(&MyDidInfo::new(None, seed, None, None)).unwrap();
+
+        assert_eq!(my_did.verkey, key.verkey);
+        assert_eq!(my_did.signkey, key.signkey);
+    }
+
+    #[test]
+    fn create_key_works_for_unknown_crypto_type() {
+        let service = SignusService::new();
+
+        let res = service.create_key(&KeyInfo::new(None, Some("type".to_string())));
+        assert_match!(Err(SignusError::UnknownCryptoError(_)), res);
+    }
+
+    #[test]
+    fn sign_verify_by_key_works() {
+        let service = SignusService::new();
+
+        let message = r#"{"reqId":1495034346617224651}"#;
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let signed_message = service.sign_by_key(&key, message).unwrap();
+        assert!(service.verify_by_key(&key.verkey, &signed_message).unwrap());
+    }
+
+    #[test]
+    fn encrypt_decrypt_by_key_works() {
+        let service = SignusService::new();
+
+        let msg = "some message";
+
+        let my_key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+        let their_key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let (encrypted_message, nonce) = service.encrypt_by_key(&my_key, &their_key.verkey, msg).unwrap();
+
+        let decrypted_message = service.decrypt_by_key(&their_key, &my_key.verkey, &encrypted_message, &nonce).unwrap();
+        assert_eq!(msg.to_string(), decrypted_message);
+    }
+
+    #[test]
+    fn sign_verify_detached_works() {
+        let service = SignusService::new();
+
+        let msg = vec![0, 159, 146, 150, 255];
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let signature = service.sign_detached_by_key(&key, &msg).unwrap();
+        assert_eq!(64, signature.len());
+        assert!(service.verify_detached_by_key(&key.verkey, &msg, &signature).unwrap());
+        assert!(!service.verify_detached_by_key(&key.verkey, &[1, 2, 3], &signature).unwrap());
+    }
+
+    #[test]
+    fn sign_verify_jws_works() {
+        let service = SignusService::new();
+
+        let payload = r#"["not", "an", "object"]"#;
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let jws = service.sign_jws_by_key(&key, payload.as_bytes()).unwrap();
+        assert_eq!(3, jws.split('.').count());
+        assert!(service.verify_by_key(&key.verkey, &jws).unwrap());
+    }
+
+    #[test]
+    fn verify_jws_works_for_other_payload() {
+        let service = SignusService::new();
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let jws = service.sign_jws_by_key(&key, b"payload").unwrap();
+        let other_jws = service.sign_jws_by_key(&key, b"other payload").unwrap();
+
+        let parts: Vec<&str> = jws.split('.').collect();
+        let other_parts: Vec<&str> = other_jws.split('.').collect();
+        let forged_jws = format!("{}.{}.{}", parts[0], other_parts[1], parts[2]);
+
+        assert!(!service.verify_by_key(&key.verkey, &forged_jws).unwrap());
+    }
+
+    #[test]
+    fn verify_jws_works_for_unsupported_alg() {
+        let service = SignusService::new();
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let jws = service.sign_jws_by_key(&key, b"payload").unwrap();
+        let parts: Vec<&str> = jws.split('.').collect();
+        let header = Base64::encode_url_safe(br#"{"alg":"HS256"}"#);
+
+        let res = service.verify_by_key(&key.verkey, &format!("{}.{}.{}", header, parts[1], parts[2]));
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn verify_by_key_works_for_unknown_format() {
+        let service = SignusService::new();
+
+        let key = service.create_key(&KeyInfo::new(None, None)).unwrap();
+
+        let res = service.verify_by_key(&key.verkey, "some text");
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn encrypt_decrypt_envelope_works() {
+        let service = SignusService::new();
+
+        let msg = "some message";
+
+        let first_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+        let second_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+
+        let their_dids: Vec<TheirDid> = [&first_did, &second_did].iter()
+            .map(|my_did| {
+                let their_did_info = TheirDidInfo::new(my_did.did.clone(), None, Some(my_did.verkey.clone()), None);
+                service.create_their_did(&their_did_info).unwrap()
+            })
+            .collect();
+
+        let envelope = service.encrypt_envelope(&their_dids, msg).unwrap();
+        assert_eq!(2, envelope.recipients.len());
+
+        assert_eq!(msg, service.decrypt_envelope(&first_did, &envelope).unwrap());
+        assert_eq!(msg, service.decrypt_envelope(&second_did, &envelope).unwrap());
+    }
+
+    #[test]
+    fn decrypt_envelope_works_for_not_recipient() {
+        let service = SignusService::new();
+
+        let recipient_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+        let other_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+
+        let their_did_info = TheirDidInfo::new(recipient_did.did.clone(), None, Some(recipient_did.verkey.clone()), None);
+        let their_did = service.create_their_did(&their_did_info).unwrap();
+
+        let envelope = service.encrypt_envelope(&[their_did], "some message").unwrap();
+
+        let res = service.decrypt_envelope(&other_did, &envelope);
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn decrypt_envelope_works_for_truncated_nonce() {
+        let service = SignusService::new();
+
+        let recipient_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+
+        let their_did_info = TheirDidInfo::new(recipient_did.did.clone(), None, Some(recipient_did.verkey.clone()), None);
+        let their_did = service.create_their_did(&their_did_info).unwrap();
+
+        let mut envelope = service.encrypt_envelope(&[their_did], "some message").unwrap();
+        let nonce = Base58::decode(&envelope.nonce).unwrap();
+        envelope.nonce = Base58::encode(&nonce[..NONCEBYTES - 1]);
+
+        let res = service.decrypt_envelope(&recipient_did, &envelope);
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn decrypt_envelope_works_for_short_sealed_key() {
+        let service = SignusService::new();
+
+        let recipient_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+
+        let their_did_info = TheirDidInfo::new(recipient_did.did.clone(), None, Some(recipient_did.verkey.clone()), None);
+        let their_did = service.create_their_did(&their_did_info).unwrap();
+
+        let mut envelope = service.encrypt_envelope(&[their_did], "some message").unwrap();
+
+        let signus = service._get_crypto_type(DEFAULT_CRYPTO_TYPE).unwrap();
+        let short_key = signus.encrypt_sealed(&Base58::decode(&recipient_did.pk).unwrap(), &[1; KEYBYTES / 2]).unwrap();
+        envelope.recipients[0].encrypted_key = Base58::encode(&short_key);
+
+        let res = service.decrypt_envelope(&recipient_did, &envelope);
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn encrypt_envelope_works_for_no_recipients() {
+        let service = SignusService::new();
+
+        let res = service.encrypt_envelope(&[], "some message");
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn split_verkey_works() {
+        assert_eq!(("GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL", DEFAULT_CRYPTO_TYPE),
+                   SignusService::split_verkey("GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL"));
+        assert_eq!(("GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL", "secp256k1"),
+                   SignusService::split_verkey("GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL:secp256k1"));
+    }
+
+    #[test]
+    fn encrypt_decrypt_sealed_works() {
+        let service = SignusService::new();
+
+        let msg = "some message";
+
+        let my_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+
+        let their_did = TheirDid {
+            did: my_did.did.clone(),
+            crypto_type: DEFAULT_CRYPTO_TYPE.to_string(),
+            pk: Some(my_did.pk.clone()),
+            endpoint: None,
+            verkey: Some(my_did.verkey.clone())
+        };
+
+        let encrypted_message = service.encrypt_sealed(&their_did, msg).unwrap();
+
+        let decrypted_message = service.decrypt_sealed(&my_did, &encrypted_message).unwrap();
+        assert_eq!(msg.to_string(), decrypted_message);
+    }
+
+    #[test]
+    fn encrypt_sealed_works_for_their_did_without_pk() {
+        let service = SignusService::new();
+
+        let their_did = TheirDid {
+            did: "sw2SA2jCbsiq2kfns".to_string(),
+            crypto_type: DEFAULT_CRYPTO_TYPE.to_string(),
+            pk: None,
+            endpoint: None,
+            verkey: None
+        };
+
+        let res = service.encrypt_sealed(&their_did, "some message");
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    #[cfg(feature = "secp256k1_rust")]
+    fn sign_verify_works_for_secp256k1() {
+        let service = SignusService::new();
+
+        let did_info = MyDidInfo::new(None, None, Some(SECP256K1_CRYPTO_TYPE.to_string()), None);
+
+        let message = r#"{"reqId":1495034346617224651}"#;
+
+        let my_did = service.create_my_did(&did_info).unwrap();
+        assert_eq!(SECP256K1_CRYPTO_TYPE, my_did.crypto_type);
+
+        let signature = service.sign(&my_did, message).unwrap();
+
+        let their_did = TheirDid {
+            did: my_did.did.clone(),
+            crypto_type: SECP256K1_CRYPTO_TYPE.to_string(),
+            pk: None,
+            endpoint: None,
+            verkey: Some(my_did.verkey.clone())
+        };
+
+        assert!(service.verify(&their_did, &signature).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "secp256k1_rust")]
+    fn encrypt_decrypt_works_for_secp256k1() {
+        let service = SignusService::new();
+
+        let msg = "some message";
+
+        let did_info = MyDidInfo::new(None, None, Some(SECP256K1_CRYPTO_TYPE.to_string()), None);
+
+        let my_did = service.create_my_did(&did_info).unwrap();
+        let their_did = service.create_my_did(&did_info).unwrap();
+
+        let their_did_for_encrypt = TheirDid {
+            did: their_did.did.clone(),
+            crypto_type: SECP256K1_CRYPTO_TYPE.to_string(),
+            pk: Some(their_did.pk.clone()),
+            endpoint: None,
+            verkey: Some(their_did.verkey.clone())
+        };
+
+        let their_did_for_decrypt = TheirDid {
+            did: my_did.did.clone(),
+            crypto_type: SECP256K1_CRYPTO_TYPE.to_string(),
+            pk: Some(my_did.pk.clone()),
+            endpoint: None,
+            verkey: Some(my_did.verkey.clone())
+        };
+
+        let (encrypted_message, nonce) = service.encrypt(&my_did, &their_did_for_encrypt, msg).unwrap();
+
+        let decrypted_message = service.decrypt(&their_did, &their_did_for_decrypt, &encrypted_message, &nonce).unwrap();
+        assert_eq!(msg.to_string(), decrypted_message);
+    }
+
+    #[test]
+    #[cfg(feature = "secp256k1_rust")]
+    fn encrypt_works_for_different_crypto_types() {
+        let service = SignusService::new();
+
+        let my_did = service.create_my_did(&MyDidInfo::new(None, None, None, None)).unwrap();
+        let their_did = service.create_my_did(&MyDidInfo::new(None, None, Some(SECP256K1_CRYPTO_TYPE.to_string()), None)).unwrap();
+
+        let their_did = TheirDid {
+            did: their_did.did,
+            crypto_type: SECP256K1_CRYPTO_TYPE.to_string(),
+            pk: Some(their_did.pk),
+            endpoint: None,
+            verkey: Some(their_did.verkey)
+        };
+
+        let res = service.encrypt(&my_did, &their_did, "some message");
+        assert_match!(Err(SignusError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use super::CryptoType;
+use utils::crypto::secp256k1::Secp256K1;
+use errors::common::CommonError;
+
+
+pub struct Secp256K1Signus {}
+
+impl Secp256K1Signus {
+    pub fn new() -> Secp256K1Signus {
+        Secp256K1Signus {}
+    }
+}
+
+impl CryptoType for Secp256K1Signus {
+    fn encrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Secp256K1::encrypt(private_key, public_key, doc, nonce)
+    }
+
+    fn decrypt(&self, private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Secp256K1::decrypt(private_key, public_key, doc, nonce)
+    }
+
+    fn gen_nonce(&self) -> Vec<u8> {
+        Secp256K1::gen_nonce()
+    }
+
+    fn encrypt_sealed(&self, public_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Secp256K1::encrypt_sealed(public_key, doc)
+    }
+
+    fn decrypt_sealed(&self, _public_key: &[u8], private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Secp256K1::decrypt_sealed(private_key, doc)
+    }
+
+    fn create_key_pair_for_signature(&self, seed: Option<&[u8]>) -> Result<(Vec<u8>, Vec<u8>), CommonError> {
+        Secp256K1::create_key_pair_for_signature(seed)
+    }
+
+    fn sign(&self, private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Secp256K1::sign(private_key, doc)
+    }
+
+    fn verify(&self, public_key: &[u8], doc: &[u8], signature: &[u8]) -> Result<bool, CommonError> {
+        Secp256K1::verify(public_key, doc, signature)
+    }
+
+    fn verkey_to_public_key(&self, vk: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Ok(vk.to_vec())
+    }
+
+    fn signkey_to_private_key(&self, sk: &[u8]) -> Result<Vec<u8>, CommonError> {
+        Ok(sk.to_vec())
+    }
+}
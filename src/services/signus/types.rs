@@ -1,4 +1,5 @@
 use utils::json::{JsonEncodable, JsonDecodable};
+use utils::crypto::memzero::{memzero_string, memzero_opt_string};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct MyDidInfo {
@@ -24,6 +25,12 @@ impl JsonEncodable for MyDidInfo {}
 
 impl<'a> JsonDecodable<'a> for MyDidInfo {}
 
+impl Drop for MyDidInfo {
+    fn drop(&mut self) {
+        memzero_opt_string(&mut self.seed);
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct MyKyesInfo {
     pub seed: Option<String>,
@@ -43,6 +50,62 @@ impl JsonEncodable for MyKyesInfo {}
 
 impl<'a> JsonDecodable<'a> for MyKyesInfo {}
 
+impl Drop for MyKyesInfo {
+    fn drop(&mut self) {
+        memzero_opt_string(&mut self.seed);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeyInfo {
+    pub seed: Option<String>,
+    pub crypto_type: Option<String>
+}
+
+impl KeyInfo {
+    pub fn new(seed: Option<String>, crypto_type: Option<String>) -> KeyInfo {
+        KeyInfo {
+            seed: seed,
+            crypto_type: crypto_type
+        }
+    }
+}
+
+impl JsonEncodable for KeyInfo {}
+
+impl<'a> JsonDecodable<'a> for KeyInfo {}
+
+impl Drop for KeyInfo {
+    fn drop(&mut self) {
+        memzero_opt_string(&mut self.seed);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Key {
+    pub verkey: String,
+    pub signkey: String
+}
+
+impl Key {
+    pub fn new(verkey: String, signkey: String) -> Key {
+        Key {
+            verkey: verkey,
+            signkey: signkey
+        }
+    }
+}
+
+impl JsonEncodable for Key {}
+
+impl<'a> JsonDecodable<'a> for Key {}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        memzero_string(&mut self.signkey);
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct MyDid {
     pub did: String,
@@ -70,6 +133,13 @@ impl JsonEncodable for MyDid {}
 
 impl<'a> JsonDecodable<'a> for MyDid {}
 
+impl Drop for MyDid {
+    fn drop(&mut self) {
+        memzero_string(&mut self.sk);
+        memzero_string(&mut self.signkey);
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TheirDidInfo {
     pub did: String,
@@ -116,4 +186,76 @@ impl TheirDid {
 
 impl JsonEncodable for TheirDid {}
 
-impl<'a> JsonDecodable<'a> for TheirDid {}
\ No newline at end of file
+impl<'a> JsonDecodable<'a> for TheirDid {}
+
+#[derive(Serialize, Deserialize)]
+pub struct MyDidWithMeta {
+    pub did: String,
+    pub verkey: String,
+    pub metadata: Option<String>
+}
+
+impl MyDidWithMeta {
+    pub fn new(did: String, verkey: String, metadata: Option<String>) -> MyDidWithMeta {
+        MyDidWithMeta {
+            did: did,
+            verkey: verkey,
+            metadata: metadata
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TheirDidWithMeta {
+    pub did: String,
+    pub verkey: Option<String>,
+    pub endpoint: Option<String>,
+    pub metadata: Option<String>
+}
+
+impl TheirDidWithMeta {
+    pub fn new(did: String, verkey: Option<String>, endpoint: Option<String>, metadata: Option<String>) -> TheirDidWithMeta {
+        TheirDidWithMeta {
+            did: did,
+            verkey: verkey,
+            endpoint: endpoint,
+            metadata: metadata
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EnvelopeRecipient {
+    pub did: String,
+    pub encrypted_key: String
+}
+
+impl EnvelopeRecipient {
+    pub fn new(did: String, encrypted_key: String) -> EnvelopeRecipient {
+        EnvelopeRecipient {
+            did: did,
+            encrypted_key: encrypted_key
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Envelope {
+    pub ciphertext: String,
+    pub nonce: String,
+    pub recipients: Vec<EnvelopeRecipient>
+}
+
+impl Envelope {
+    pub fn new(ciphertext: String, nonce: String, recipients: Vec<EnvelopeRecipient>) -> Envelope {
+        Envelope {
+            ciphertext: ciphertext,
+            nonce: nonce,
+            recipients: recipients
+        }
+    }
+}
+
+impl JsonEncodable for Envelope {}
+
+impl<'a> JsonDecodable<'a> for Envelope {}
@@ -123,6 +123,17 @@ impl Wallet for DefaultWallet {
         return Ok(record.value)
     }
 
+    fn delete_record(&self, key: &str) -> Result<(), WalletError> {
+        let deleted = _open_connection(self.name.as_str())?
+            .execute("DELETE FROM wallet WHERE key = ?1", &[&key.to_string()])?;
+
+        if deleted == 0 {
+            return Err(WalletError::NotFound(key.to_string()))
+        }
+
+        Ok(())
+    }
+
     fn get_pool_name(&self) -> String {
         self.pool_name.clone()
     }
@@ -304,6 +315,23 @@ mod tests {
         TestUtils::cleanup_sovrin_home();
     }
 
+    #[test]
+    fn wallet_delete_record_works() {
+        TestUtils::cleanup_sovrin_home();
+
+        let wallet_type = DefaultWalletType::new();
+        wallet_type.create("wallet1", None, None).unwrap();
+        let wallet = wallet_type.open("wallet1", "pool1", None, None, None).unwrap();
+
+        wallet.set("key1", "value1").unwrap();
+        wallet.delete_record("key1").unwrap();
+
+        let value = wallet.get("key1");
+        assert_match!(Err(WalletError::NotFound(_)), value);
+
+        TestUtils::cleanup_sovrin_home();
+    }
+
     #[test]
     fn wallet_set_get_works_for_update() {
         TestUtils::cleanup_sovrin_home();
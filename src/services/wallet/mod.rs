@@ -19,6 +19,7 @@ pub trait Wallet {
     fn get(&self, key: &str) -> Result<String, WalletError>;
     fn list(&self, key_prefix: &str) -> Result<Vec<(String, String)>, WalletError>;
     fn get_not_expired(&self, key: &str) -> Result<String, WalletError>;
+    fn delete_record(&self, key: &str) -> Result<(), WalletError>;
     fn get_pool_name(&self) -> String;
 }
 
@@ -219,6 +220,13 @@ impl WalletService {
         }
     }
 
+    pub fn delete_record(&self, handle: i32, key: &str) -> Result<(), WalletError> {
+        match self.wallets.borrow().get(&handle) {
+            Some(wallet) => wallet.delete_record(key),
+            None => Err(WalletError::InvalidHandle(handle.to_string()))
+        }
+    }
+
     pub fn get_pool_name(&self, handle: i32) -> Result<String, WalletError> {
         match self.wallets.borrow().get(&handle) {
             Some(wallet) => Ok(wallet.get_pool_name()),
@@ -443,6 +451,37 @@ mod tests {
         TestUtils::cleanup_sovrin_home();
     }
 
+    #[test]
+    fn delete_record_works() {
+        TestUtils::cleanup_sovrin_home();
+
+        let wallet_service = WalletService::new();
+        wallet_service.create("pool1", None, "wallet1", None, None).unwrap();
+        let wallet_handle = wallet_service.open("wallet1", None, None).unwrap();
+
+        wallet_service.set(wallet_handle, "key1", "value1").unwrap();
+        wallet_service.delete_record(wallet_handle, "key1").unwrap();
+
+        let res = wallet_service.get(wallet_handle, "key1");
+        assert_match!(Err(WalletError::NotFound(_)), res);
+
+        TestUtils::cleanup_sovrin_home();
+    }
+
+    #[test]
+    fn delete_record_works_for_unknown() {
+        TestUtils::cleanup_sovrin_home();
+
+        let wallet_service = WalletService::new();
+        wallet_service.create("pool1", None, "wallet1", None, None).unwrap();
+        let wallet_handle = wallet_service.open("wallet1", None, None).unwrap();
+
+        let res = wallet_service.delete_record(wallet_handle, "key1");
+        assert_match!(Err(WalletError::NotFound(_)), res);
+
+        TestUtils::cleanup_sovrin_home();
+    }
+
     #[test]
     fn get_pool_name_works() {
         TestUtils::cleanup_sovrin_home();
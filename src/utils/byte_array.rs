@@ -0,0 +1,13 @@
+macro_rules! check_useful_c_byte_array {
+    ($ptr:ident, $len:expr, $err:expr) => {
+        if $ptr.is_null() && $len > 0 {
+            return $err
+        }
+
+        let $ptr = if $len > 0 {
+            unsafe { ::std::slice::from_raw_parts($ptr, $len as usize) }.to_vec()
+        } else {
+            Vec::new()
+        };
+    }
+}
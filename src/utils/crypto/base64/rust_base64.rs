@@ -0,0 +1,41 @@
+extern crate base64;
+
+use errors::common::CommonError;
+
+pub struct Base64 {}
+
+impl Base64 {
+    pub fn encode_url_safe(doc: &[u8]) -> String {
+        base64::encode_config(doc, base64::URL_SAFE_NO_PAD)
+    }
+
+    pub fn decode_url_safe(doc: &str) -> Result<Vec<u8>, CommonError> {
+        base64::decode_config(doc, base64::URL_SAFE_NO_PAD)
+            .map_err(|err| CommonError::InvalidStructure(format!("{}", err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_url_safe_works() {
+        let result = Base64::encode_url_safe(&[251, 255, 1]);
+        assert_eq!("-_8B", &result, "Got unexpected data");
+    }
+
+    #[test]
+    fn decode_url_safe_works() {
+        let result = Base64::decode_url_safe("-_8B");
+
+        assert!(result.is_ok(), "Got error");
+        assert_eq!(&[251, 255, 1], &result.unwrap()[..], "Get unexpected data");
+    }
+
+    #[test]
+    fn decode_url_safe_works_for_invalid_data() {
+        let result = Base64::decode_url_safe("-_8B+");
+        assert!(result.is_err());
+    }
+}
@@ -374,6 +374,14 @@ impl PartialEq for BigNumber {
     }
 }
 
+impl Drop for BigNumber {
+    /// BigNumber holds secret values like master secret or claim definition private keys,
+    /// so memory is cleared before it is freed.
+    fn drop(&mut self) {
+        self.openssl_bn.clear();
+    }
+}
+
 impl BytesView for BigNumber {
     fn to_bytes(&self) -> Result<Vec<u8>, CommonError> {
         Ok(self.to_bytes()?)
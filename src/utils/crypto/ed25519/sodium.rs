@@ -2,6 +2,7 @@ extern crate sodiumoxide;
 extern crate libc;
 
 use errors::common::CommonError;
+use utils::crypto::memzero::memzero;
 
 use self::libc::c_int;
 use self::sodiumoxide::crypto::box_;
@@ -19,8 +20,21 @@ extern {
     pub fn crypto_sign_ed25519_sk_to_curve25519(
         curve25519_sk: *mut [u8; 32],
         ed25519_sk: *const [u8; 64]) -> c_int;
+    pub fn crypto_box_seal(
+        c: *mut u8,
+        m: *const u8,
+        mlen: u64,
+        pk: *const [u8; 32]) -> c_int;
+    pub fn crypto_box_seal_open(
+        m: *mut u8,
+        c: *const u8,
+        clen: u64,
+        pk: *const [u8; 32],
+        sk: *const [u8; 32]) -> c_int;
 }
 
+const SEALBYTES: usize = 48;
+
 pub struct ED25519 {}
 
 impl ED25519 {
@@ -47,6 +61,46 @@ impl ED25519 {
         box_::gen_nonce()[..].to_vec()
     }
 
+    pub fn encrypt_sealed(public_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        if public_key.len() != 32 {
+            return Err(CommonError::InvalidStructure(format!("Invalid public key")))
+        }
+
+        let public_key: [u8; 32] = ED25519::_clone_into_array(public_key);
+        let mut encrypted_doc: Vec<u8> = vec![0; doc.len() + SEALBYTES];
+        let res = unsafe {
+            crypto_box_seal(encrypted_doc.as_mut_ptr(), doc.as_ptr(), doc.len() as u64, &public_key)
+        };
+
+        if res != 0 {
+            return Err(CommonError::InvalidStructure("Unable to encrypt data".to_string()))
+        }
+        Ok(encrypted_doc)
+    }
+
+    pub fn decrypt_sealed(public_key: &[u8], private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        if public_key.len() != 32 || private_key.len() != 32 {
+            return Err(CommonError::InvalidStructure(format!("Invalid key")))
+        }
+
+        if doc.len() < SEALBYTES {
+            return Err(CommonError::InvalidStructure("Unable to decrypt data".to_string()))
+        }
+
+        let public_key: [u8; 32] = ED25519::_clone_into_array(public_key);
+        let mut private_key: [u8; 32] = ED25519::_clone_into_array(private_key);
+        let mut decrypted_doc: Vec<u8> = vec![0; doc.len() - SEALBYTES];
+        let res = unsafe {
+            crypto_box_seal_open(decrypted_doc.as_mut_ptr(), doc.as_ptr(), doc.len() as u64, &public_key, &private_key)
+        };
+        memzero(&mut private_key);
+
+        if res != 0 {
+            return Err(CommonError::InvalidStructure("Unable to decrypt data".to_string()))
+        }
+        Ok(decrypted_doc)
+    }
+
     pub fn create_key_pair_for_signature(seed: Option<&[u8]>) -> Result<(Vec<u8>, Vec<u8>), CommonError> {
         if seed.is_some() && seed.unwrap().len() != 32 {
             return Err(CommonError::InvalidStructure(format!("Invalid seed")))
@@ -72,10 +126,13 @@ impl ED25519 {
         let mut pr_key: [u8; 64] = [0; 64];
         pr_key.clone_from_slice(private_key);
 
-        Ok(sign::sign_detached(
+        let signature = sign::sign_detached(
             doc,
             &sign::SecretKey(pr_key)
-        )[..].to_vec())
+        )[..].to_vec();
+        memzero(&mut pr_key);
+
+        Ok(signature)
     }
 
     pub fn verify(public_key: &[u8], doc: &[u8], sign: &[u8]) -> Result<bool, CommonError> {
@@ -108,7 +165,10 @@ impl ED25519 {
         unsafe {
             crypto_sign_ed25519_sk_to_curve25519(&mut to, &from);
         }
-        Ok(to.iter().cloned().collect())
+        let res = to.iter().cloned().collect();
+        memzero(&mut from);
+        memzero(&mut to);
+        Ok(res)
     }
 
     pub fn vk_to_curve25519(pk: &[u8]) -> Result<Vec<u8>, CommonError> {
@@ -166,6 +226,41 @@ mod tests {
         assert_eq!(text, alice_decrypted_text.unwrap());
     }
 
+    #[test]
+    fn encrypt_decrypt_sealed_works() {
+        let text = randombytes::randombytes(16);
+        let seed = randombytes::randombytes(32);
+
+        let (ver_key, sign_key) = ED25519::create_key_pair_for_signature(Some(&seed)).unwrap();
+        let pk = ED25519::vk_to_curve25519(&ver_key).unwrap();
+        let sk = ED25519::sk_to_curve25519(&sign_key).unwrap();
+
+        let encrypted_text = ED25519::encrypt_sealed(&pk, &text).unwrap();
+        let decrypted_text = ED25519::decrypt_sealed(&pk, &sk, &encrypted_text).unwrap();
+        assert_eq!(text, decrypted_text);
+    }
+
+    #[test]
+    fn encrypt_sealed_works_for_low_order_key() {
+        let text = randombytes::randombytes(16);
+        assert!(ED25519::encrypt_sealed(&[0; 32], &text).is_err());
+    }
+
+    #[test]
+    fn decrypt_sealed_works_for_other_key() {
+        let text = randombytes::randombytes(16);
+
+        let (ver_key, _) = ED25519::create_key_pair_for_signature(None).unwrap();
+        let pk = ED25519::vk_to_curve25519(&ver_key).unwrap();
+
+        let (other_ver_key, other_sign_key) = ED25519::create_key_pair_for_signature(None).unwrap();
+        let other_pk = ED25519::vk_to_curve25519(&other_ver_key).unwrap();
+        let other_sk = ED25519::sk_to_curve25519(&other_sign_key).unwrap();
+
+        let encrypted_text = ED25519::encrypt_sealed(&pk, &text).unwrap();
+        assert!(ED25519::decrypt_sealed(&other_pk, &other_sk, &encrypted_text).is_err());
+    }
+
     #[test]
     fn signin_verify_works() {
         let seed = randombytes::randombytes(32);
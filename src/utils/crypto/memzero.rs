@@ -0,0 +1,78 @@
+use std::fmt;
+use std::ops::Deref;
+use std::ptr;
+
+/// Overwrites memory with zeroes. Volatile writes are used,
+/// so the compiler can't skip zeroing of memory that is never read again.
+pub fn memzero(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+}
+
+/// Zero bytes are valid utf-8, so the string stays valid after zeroing.
+pub fn memzero_string(string: &mut String) {
+    memzero(unsafe { string.as_bytes_mut() });
+}
+
+pub fn memzero_opt_string(string: &mut Option<String>) {
+    if let Some(ref mut string) = *string {
+        memzero_string(string);
+    }
+}
+
+/// Buffer for secret key material. Memory is zeroed on drop
+/// and content is never printed by Debug.
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn new(bytes: Vec<u8>) -> SecretBytes {
+        SecretBytes(bytes)
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        memzero(&mut self.0);
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretBytes(hidden)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memzero_works() {
+        let mut bytes = vec![1, 2, 3];
+        memzero(&mut bytes);
+        assert_eq!(vec![0, 0, 0], bytes);
+    }
+
+    #[test]
+    fn memzero_string_works() {
+        let mut string = "secret".to_string();
+        memzero_string(&mut string);
+        assert_eq!("\0\0\0\0\0\0", string);
+    }
+
+    #[test]
+    fn secret_bytes_debug_works() {
+        let secret = SecretBytes::new(vec![1, 2, 3]);
+        assert_eq!("SecretBytes(hidden)", format!("{:?}", secret));
+        assert_eq!(&[1, 2, 3], &secret[..]);
+    }
+}
@@ -6,10 +6,18 @@ pub mod bn;
 #[path = "ed25519/sodium.rs"]
 pub mod ed25519;
 
+#[cfg(feature = "secp256k1_rust")]
+#[path = "secp256k1/rust_secp256k1.rs"]
+pub mod secp256k1;
+
 #[cfg(feature = "base58_rust_base58")]
 #[path = "base58/rust_base58.rs"]
 pub mod base58;
 
+#[cfg(feature = "base64_rust_base64")]
+#[path = "base64/rust_base64.rs"]
+pub mod base64;
+
 #[cfg(feature = "pair_milagro")]
 #[path = "pair/milagro.rs"]
 pub mod pair;
@@ -26,4 +34,8 @@ pub mod xsalsa20;
 #[path = "hash/openssl.rs"]
 pub mod hash;
 
-pub mod signature_serializer;
\ No newline at end of file
+pub mod memzero;
+
+pub mod signature_serializer;
+
+pub mod verkey_builder;
\ No newline at end of file
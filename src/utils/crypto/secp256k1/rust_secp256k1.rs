@@ -0,0 +1,179 @@
+extern crate secp256k1;
+extern crate rand;
+
+use errors::common::CommonError;
+use utils::crypto::hash::Hash;
+use utils::crypto::xsalsa20::XSalsa20;
+
+use self::secp256k1::{Secp256k1, Message};
+use self::secp256k1::ecdh::SharedSecret;
+use self::secp256k1::key::{PublicKey, SecretKey};
+use self::secp256k1::Signature;
+use self::rand::{OsRng, Rng};
+
+const PUBLIC_KEY_LEN: usize = 33;
+const SEALED_HEADER_LEN: usize = PUBLIC_KEY_LEN + 24;
+
+pub struct Secp256K1 {}
+
+impl Secp256K1 {
+    pub fn encrypt(private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError> {
+        let key = Secp256K1::_shared_key(private_key, public_key)?;
+        Ok(XSalsa20::new().encrypt(&key, nonce, doc))
+    }
+
+    pub fn decrypt(private_key: &[u8], public_key: &[u8], doc: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CommonError> {
+        let key = Secp256K1::_shared_key(private_key, public_key)?;
+        XSalsa20::new().decrypt(&key, nonce, doc)
+    }
+
+    pub fn gen_nonce() -> Vec<u8> {
+        XSalsa20::new().gen_nonce()
+    }
+
+    /// Sealed message is ephemeral public key (33 bytes) followed by nonce (24 bytes)
+    /// and the message encrypted with ECDH shared key of ephemeral and recipient keys.
+    pub fn encrypt_sealed(public_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        let (ephemeral_public_key, ephemeral_private_key) = Secp256K1::create_key_pair_for_signature(None)?;
+        let nonce = Secp256K1::gen_nonce();
+
+        let mut encrypted_doc = Secp256K1::encrypt(&ephemeral_private_key, public_key, doc, &nonce)?;
+
+        let mut result = ephemeral_public_key;
+        result.extend_from_slice(&nonce);
+        result.append(&mut encrypted_doc);
+        Ok(result)
+    }
+
+    pub fn decrypt_sealed(private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        if doc.len() < SEALED_HEADER_LEN {
+            return Err(CommonError::InvalidStructure("Unable to decrypt data".to_string()))
+        }
+
+        let (ephemeral_public_key, rest) = doc.split_at(PUBLIC_KEY_LEN);
+        let (nonce, encrypted_doc) = rest.split_at(SEALED_HEADER_LEN - PUBLIC_KEY_LEN);
+
+        Secp256K1::decrypt(private_key, ephemeral_public_key, encrypted_doc, nonce)
+    }
+
+    pub fn create_key_pair_for_signature(seed: Option<&[u8]>) -> Result<(Vec<u8>, Vec<u8>), CommonError> {
+        if seed.is_some() && seed.unwrap().len() != 32 {
+            return Err(CommonError::InvalidStructure(format!("Invalid seed")))
+        }
+
+        let seed = match seed {
+            Some(seed) => seed.to_vec(),
+            None => {
+                let mut seed = vec![0u8; 32];
+                OsRng::new()
+                    .map_err(|err| CommonError::InvalidState(format!("Can't create random generator: {}", err)))?
+                    .fill_bytes(&mut seed);
+                seed
+            }
+        };
+
+        let secp = Secp256k1::new();
+
+        let secret_key = SecretKey::from_slice(&secp, &seed)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid seed")))?;
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid seed")))?;
+
+        Ok((public_key.serialize_vec(&secp, true).to_vec(), seed))
+    }
+
+    pub fn sign(private_key: &[u8], doc: &[u8]) -> Result<Vec<u8>, CommonError> {
+        let secp = Secp256k1::new();
+
+        let secret_key = SecretKey::from_slice(&secp, private_key)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid sign key")))?;
+        let message = Secp256K1::_message(doc)?;
+
+        let signature = secp.sign(&message, &secret_key)
+            .map_err(|_| CommonError::InvalidStructure(format!("Unable to sign message")))?;
+
+        Ok(signature.serialize_compact(&secp).to_vec())
+    }
+
+    pub fn verify(public_key: &[u8], doc: &[u8], sign: &[u8]) -> Result<bool, CommonError> {
+        let secp = Secp256k1::new();
+
+        let public_key = PublicKey::from_slice(&secp, public_key)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid verkey")))?;
+        let signature = Signature::from_compact(&secp, sign)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid signature")))?;
+        let message = Secp256K1::_message(doc)?;
+
+        Ok(secp.verify(&message, &signature, &public_key).is_ok())
+    }
+
+    fn _message(doc: &[u8]) -> Result<Message, CommonError> {
+        let mut ctx = Hash::new_context()?;
+        ctx.update(doc)?;
+        let digest = ctx.finish2()?;
+
+        Message::from_slice(&digest)
+            .map_err(|_| CommonError::InvalidState(format!("Invalid message digest")))
+    }
+
+    fn _shared_key(private_key: &[u8], public_key: &[u8]) -> Result<Vec<u8>, CommonError> {
+        let secp = Secp256k1::new();
+
+        let secret_key = SecretKey::from_slice(&secp, private_key)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid private key")))?;
+        let public_key = PublicKey::from_slice(&secp, public_key)
+            .map_err(|_| CommonError::InvalidStructure(format!("Invalid public key")))?;
+
+        Ok(SharedSecret::new(&secp, &public_key, &secret_key)[..].to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_works() {
+        let text = "some message".as_bytes();
+        let nonce = Secp256K1::gen_nonce();
+
+        let (alice_ver_key, alice_sign_key) = Secp256K1::create_key_pair_for_signature(None).unwrap();
+        let (bob_ver_key, bob_sign_key) = Secp256K1::create_key_pair_for_signature(None).unwrap();
+
+        let encrypted_text = Secp256K1::encrypt(&alice_sign_key, &bob_ver_key, &text, &nonce).unwrap();
+        let decrypted_text = Secp256K1::decrypt(&bob_sign_key, &alice_ver_key, &encrypted_text, &nonce).unwrap();
+        assert_eq!(text.to_vec(), decrypted_text);
+    }
+
+    #[test]
+    fn encrypt_decrypt_sealed_works() {
+        let text = "some message".as_bytes();
+
+        let (ver_key, sign_key) = Secp256K1::create_key_pair_for_signature(None).unwrap();
+
+        let encrypted_text = Secp256K1::encrypt_sealed(&ver_key, &text).unwrap();
+        let decrypted_text = Secp256K1::decrypt_sealed(&sign_key, &encrypted_text).unwrap();
+        assert_eq!(text.to_vec(), decrypted_text);
+    }
+
+    #[test]
+    fn sign_verify_works() {
+        let text = "some message".as_bytes();
+
+        let (ver_key, sign_key) = Secp256K1::create_key_pair_for_signature(None).unwrap();
+        let signature = Secp256K1::sign(&sign_key, &text).unwrap();
+
+        assert_eq!(33, ver_key.len());
+        assert!(Secp256K1::verify(&ver_key, &text, &signature).unwrap());
+    }
+
+    #[test]
+    fn create_key_pair_for_signature_works_for_seed() {
+        let seed = "00000000000000000000000000000My1".as_bytes();
+
+        let (ver_key, _) = Secp256K1::create_key_pair_for_signature(Some(seed)).unwrap();
+        let (ver_key_2, _) = Secp256K1::create_key_pair_for_signature(Some(seed)).unwrap();
+
+        assert_eq!(ver_key, ver_key_2);
+    }
+}
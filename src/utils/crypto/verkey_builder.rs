@@ -0,0 +1,99 @@
+use errors::common::CommonError;
+use utils::crypto::base58::Base58;
+
+const ABBREVIATED_PART_LEN: usize = 16;
+
+/// Abbreviated verkey is "~" followed by the last 16 bytes of full verkey.
+/// First 16 bytes of such verkey are the bytes of did itself.
+pub fn build_full_verkey(did: &str, verkey: &str) -> Result<String, CommonError> {
+    if verkey.starts_with("~") {
+        let mut result = Base58::decode(did)?;
+        let mut end_verkey = Base58::decode(&verkey[1..])?;
+        if result.len() != ABBREVIATED_PART_LEN || end_verkey.len() != ABBREVIATED_PART_LEN {
+            return Err(CommonError::InvalidStructure(format!("Invalid abbreviated verkey {} for did {}", verkey, did)));
+        }
+        result.append(&mut end_verkey);
+        Ok(Base58::encode(&result))
+    } else {
+        Ok(verkey.to_string())
+    }
+}
+
+/// Returns abbreviated verkey if did is built from the first 16 bytes of verkey.
+/// Otherwise verkey is returned as is.
+pub fn build_abbreviated_verkey(did: &str, verkey: &str) -> Result<String, CommonError> {
+    let did = Base58::decode(did)?;
+    let verkey = Base58::decode(verkey)?;
+
+    if did.len() < verkey.len() && verkey.starts_with(&did) {
+        Ok(format!("~{}", Base58::encode(&verkey[did.len()..])))
+    } else {
+        Ok(Base58::encode(&verkey))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_full_verkey_works_for_full_verkey() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+        let verkey = "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL";
+
+        let full_verkey = build_full_verkey(did, verkey).unwrap();
+        assert_eq!(verkey, full_verkey);
+    }
+
+    #[test]
+    fn build_full_verkey_works_for_abbreviated_verkey() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+        let verkey = "~NcYxiDXkpYi6ov5FcYDi1e";
+
+        let full_verkey = build_full_verkey(did, verkey).unwrap();
+        assert_eq!("5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp", full_verkey);
+    }
+
+    #[test]
+    fn build_full_verkey_works_for_invalid_abbreviated_verkey() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+        let verkey = "~NcYxiDXkpYi6ov5FcYDi1e0";
+
+        assert!(build_full_verkey(did, verkey).is_err());
+    }
+
+    #[test]
+    fn build_full_verkey_works_for_abbreviated_verkey_of_invalid_length() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+
+        assert!(build_full_verkey(did, "~NcYxiDXkpYi6ov5F").is_err());
+        assert!(build_full_verkey("8wZcEriaNLNKtte", "~NcYxiDXkpYi6ov5FcYDi1e").is_err());
+    }
+
+    #[test]
+    fn build_abbreviated_verkey_works_for_derived_did() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+        let verkey = "5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp";
+
+        let abbr_verkey = build_abbreviated_verkey(did, verkey).unwrap();
+        assert_eq!("~NcYxiDXkpYi6ov5FcYDi1e", abbr_verkey);
+    }
+
+    #[test]
+    fn build_abbreviated_verkey_works_for_not_derived_did() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+        let verkey = "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL";
+
+        let abbr_verkey = build_abbreviated_verkey(did, verkey).unwrap();
+        assert_eq!(verkey, abbr_verkey);
+    }
+
+    #[test]
+    fn build_abbreviated_verkey_works_for_round_trip() {
+        let did = "8wZcEriaNLNKtteJvx7f8i";
+        let verkey = "5L2HBnzbu6Auh2pkDRbFt5f4prvgE2LzknkuYLsKkacp";
+
+        let abbr_verkey = build_abbreviated_verkey(did, verkey).unwrap();
+        assert_eq!(verkey, build_full_verkey(did, &abbr_verkey).unwrap());
+    }
+}
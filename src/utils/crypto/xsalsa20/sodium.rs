@@ -5,6 +5,9 @@ use errors::common::CommonError;
 use self::sodiumoxide::crypto::secretbox;
 use std::convert::AsMut;
 
+pub const KEYBYTES: usize = secretbox::KEYBYTES;
+pub const NONCEBYTES: usize = secretbox::NONCEBYTES;
+
 pub struct XSalsa20 {}
 
 impl XSalsa20 {
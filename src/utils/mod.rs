@@ -5,6 +5,9 @@ pub mod json;
 #[macro_use]
 pub mod cstring;
 
+#[macro_use]
+pub mod byte_array;
+
 #[macro_use]
 pub mod ccallback;
 
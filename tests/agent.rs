@@ -7,6 +7,7 @@ extern crate log;
 extern crate rust_base58;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
 extern crate zmq;
 
 use std::sync::mpsc::channel;
@@ -18,6 +19,8 @@ use sovrin::api::ErrorCode;
 mod utils;
 
 use utils::agent::AgentUtils;
+use utils::anoncreds::AnoncredsUtils;
+use utils::pairwise::PairwiseUtils;
 use utils::signus::SignusUtils;
 use utils::test::TestUtils;
 use utils::timeout::TimeoutUtils;
@@ -26,6 +29,13 @@ use utils::wallet::WalletUtils;
 mod high_cases {
     use super::*;
 
+    fn _wait_event(receiver: &::std::sync::mpsc::Receiver<String>, event_type: &str) -> serde_json::Value {
+        let event_json = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+        let event: serde_json::Value = serde_json::from_str(&event_json).unwrap();
+        assert_eq!(event["type"].as_str().unwrap(), event_type);
+        event
+    }
+
     #[test]
     fn sovrin_agent_listen_works_with_sovrin_agent_connect() {
         TestUtils::cleanup_storage();
@@ -33,7 +43,7 @@ mod high_cases {
         let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
         let endpoint = "tcp://127.0.0.1:9701";
 
-        AgentUtils::listen(wallet_handle, endpoint, None, None).unwrap();
+        AgentUtils::listen(0, wallet_handle, endpoint, None, None).unwrap();
 
         SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
 
@@ -71,9 +81,17 @@ mod high_cases {
                 let identity = socket.recv_string(zmq::DONTWAIT).unwrap().unwrap();
                 let msg = socket.recv_string(zmq::DONTWAIT).unwrap().unwrap();
                 info!("Fake agent socket - recv - from {}, msg {}", identity, msg);
-                if msg.eq("DID") {
-                    info!("Fake agent socket send ACK");
-                    socket.send_multipart(&[identity.as_bytes(), "DID_ACK".as_bytes()], zmq::DONTWAIT).unwrap();
+                if msg.contains(r#""type":"Hello""#) {
+                    info!("Fake agent socket send challenge");
+                    socket.send_multipart(&[identity.as_bytes(), r#"{"type":"Challenge","nonce":"nonce"}"#.as_bytes()], zmq::DONTWAIT).unwrap();
+                }
+                socket.poll(zmq::POLLIN, -1).unwrap();
+                let identity = socket.recv_string(zmq::DONTWAIT).unwrap().unwrap();
+                let msg = socket.recv_string(zmq::DONTWAIT).unwrap().unwrap();
+                info!("Fake agent socket - recv - from {}, msg {}", identity, msg);
+                if msg.contains(r#""type":"Did""#) {
+                    info!("Fake agent socket send accept");
+                    socket.send_multipart(&[identity.as_bytes(), r#"{"type":"Accepted"}"#.as_bytes()], zmq::DONTWAIT).unwrap();
                 }
             });
             //FIXME /temporary code
@@ -82,6 +100,34 @@ mod high_cases {
 
             TestUtils::cleanup_storage();
         }
+
+        #[test]
+        fn sovrin_agent_connect_works_for_pairwise() {
+            TestUtils::cleanup_storage();
+            let endpoint = "tcp://127.0.0.1:9730";
+
+            let srv_wallet_handle = WalletUtils::create_and_open_wallet("pool30", "wallet30_srv", "default").unwrap();
+            let cli_wallet_handle = WalletUtils::create_and_open_wallet("pool30", "wallet30_cli", "default").unwrap();
+            let (srv_did, srv_ver_key, srv_pub_key) = SignusUtils::create_and_store_my_did(srv_wallet_handle, None).unwrap();
+            let (cli_did, cli_ver_key, cli_pub_key) = SignusUtils::create_and_store_my_did(cli_wallet_handle, None).unwrap();
+            SignusUtils::store_their_did_from_parts(srv_wallet_handle, cli_did.as_str(), cli_pub_key.as_str(), cli_ver_key.as_str(), endpoint).unwrap();
+            // Endpoint of server is known only from pairwise
+            SignusUtils::store_their_did(cli_wallet_handle, &format!(r#"{{"did":"{}","verkey":"{}"}}"#, srv_did, srv_ver_key)).unwrap();
+            PairwiseUtils::create_pairwise(cli_wallet_handle, &srv_did, &cli_did,
+                                           Some(&format!(r#"{{"endpoint":"{}","transport_key":"{}"}}"#, endpoint, srv_pub_key))).unwrap();
+
+            AgentUtils::listen(0, srv_wallet_handle, endpoint, None, None).unwrap();
+            let conn_handle = AgentUtils::connect_pairwise(0, cli_wallet_handle, &srv_did, None).unwrap();
+
+            let pairwise: serde_json::Value = serde_json::from_str(&PairwiseUtils::get_pairwise(cli_wallet_handle, &srv_did).unwrap()).unwrap();
+            assert_eq!(pairwise["state"].as_str().unwrap(), "connected");
+
+            AgentUtils::close_connection(conn_handle).unwrap();
+            let pairwise: serde_json::Value = serde_json::from_str(&PairwiseUtils::get_pairwise(cli_wallet_handle, &srv_did).unwrap()).unwrap();
+            assert_eq!(pairwise["state"].as_str().unwrap(), "disconnected");
+
+            TestUtils::cleanup_storage();
+        }
     }
 
     mod sovrin_agent_listen {
@@ -98,7 +144,95 @@ mod high_cases {
             let endpoint = "tcp://127.0.0.1:9703";
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
 
-            AgentUtils::listen(wallet_handle, endpoint, None, None).unwrap();
+            AgentUtils::listen(0, wallet_handle, endpoint, None, None).unwrap();
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_listen_works_for_known_policy() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool11", "wallet11", "default").unwrap();
+            let (did, ver_key, pub_key) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9711";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+
+            AgentUtils::listen_with_options(0, wallet_handle, endpoint, None, Some(r#"{"type":"known"}"#), None, None, None).unwrap();
+
+            AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_listen_works_for_multiple_identities() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool15", "wallet15", "default").unwrap();
+            let (did, ver_key, pub_key) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let (second_did, _, _) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9715";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::set_endpoint_for_did(wallet_handle, -1, second_did.as_str(), endpoint, Some(pub_key.as_str())).unwrap();
+
+            let identities = format!(r#"["{}","{}"]"#, did, second_did);
+            AgentUtils::listen_with_options(0, wallet_handle, endpoint, Some(&identities), None, None, None, None).unwrap();
+
+            AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
+            AgentUtils::connect(0, wallet_handle, did.as_str(), second_did.as_str(), None).unwrap();
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_add_identity {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_add_identity_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool16", "wallet16", "default").unwrap();
+            let (did, ver_key, pub_key) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let (second_did, _, _) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9716";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::set_endpoint_for_did(wallet_handle, -1, second_did.as_str(), endpoint, Some(pub_key.as_str())).unwrap();
+
+            let identities = format!(r#"["{}"]"#, did);
+            let listener_handle = AgentUtils::listen_with_options(0, wallet_handle, endpoint, Some(&identities), None, None, None, None).unwrap();
+
+            AgentUtils::add_identity(listener_handle, wallet_handle, second_did.as_str()).unwrap();
+
+            AgentUtils::connect(0, wallet_handle, did.as_str(), second_did.as_str(), None).unwrap();
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_remove_identity {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_remove_identity_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool17", "wallet17", "default").unwrap();
+            let (did, ver_key, pub_key) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let (second_did, _, _) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9717";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::set_endpoint_for_did(wallet_handle, -1, second_did.as_str(), endpoint, Some(pub_key.as_str())).unwrap();
+
+            let identities = format!(r#"["{}","{}"]"#, did, second_did);
+            let listener_handle = AgentUtils::listen_with_options(0, wallet_handle, endpoint, Some(&identities), None, None, None, None).unwrap();
+
+            AgentUtils::remove_identity(listener_handle, wallet_handle, second_did.as_str()).unwrap();
+
+            assert_eq!(AgentUtils::connect(0, wallet_handle, did.as_str(), second_did.as_str(), None).unwrap_err(),
+                       ErrorCode::CommonInvalidState);
+            AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
 
             TestUtils::cleanup_storage();
         }
@@ -118,16 +252,16 @@ mod high_cases {
             let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
             let endpoint = "tcp://127.0.0.1:9704";
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
-            AgentUtils::listen(wallet_handle, endpoint,
+            AgentUtils::listen(0, wallet_handle, endpoint,
                                Some(Box::new(move |_, conn_handle| {
                                    wait_conn_send.send(conn_handle).unwrap();
                                })),
                                Some(Box::new(move |_, msg| {
-                                   wait_msg_from_cli_send.send(msg).unwrap();
+                                   wait_msg_from_cli_send.send(AgentUtils::message_body(&msg)).unwrap();
                                }))).unwrap();
             let cli_to_srv_connect_id = AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(),
                                                             Some(Box::new(move |_, msg| {
-                                                                wait_msg_from_srv_send.send(msg).unwrap();
+                                                                wait_msg_from_srv_send.send(AgentUtils::message_body(&msg)).unwrap();
                                                             }))).unwrap();
             let srv_to_cli_connect_id = wait_conn_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
             let client_msg = "msg_from_client";
@@ -140,6 +274,350 @@ mod high_cases {
 
             TestUtils::cleanup_storage();
         }
+
+        #[test]
+        fn sovrin_agent_send_works_for_reliable_connection() {
+            TestUtils::cleanup_storage();
+
+            let (wait_conn_send, wait_conn_recv) = channel();
+            let (wait_msg_from_srv_send, wait_msg_from_srv_recv) = channel();
+            let (wait_msg_from_cli_send, wait_msg_from_cli_recv) = channel();
+            let (wait_delivery_send, wait_delivery_recv) = channel();
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool20", "wallet20", "default").unwrap();
+            let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9720";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            AgentUtils::listen(0, wallet_handle, endpoint,
+                               Some(Box::new(move |_, conn_handle| {
+                                   wait_conn_send.send(conn_handle).unwrap();
+                               })),
+                               Some(Box::new(move |_, msg| {
+                                   wait_msg_from_cli_send.send(AgentUtils::message_body(&msg)).unwrap();
+                               }))).unwrap();
+            let cli_to_srv_connect_id = AgentUtils::connect_with_options(0, wallet_handle, did.as_str(), did.as_str(),
+                                                                         Some(r#"{"reliable":true}"#),
+                                                                         Some(Box::new(move |_, msg| {
+                                                                             wait_msg_from_srv_send.send(AgentUtils::message_body(&msg)).unwrap();
+                                                                         })),
+                                                                         Some(Box::new(move |_, err, msg_id| {
+                                                                             wait_delivery_send.send((err, msg_id)).unwrap();
+                                                                         }))).unwrap();
+            let srv_to_cli_connect_id = wait_conn_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+            let client_msg = "msg_from_client";
+            let server_msg = "msg_from_server";
+
+            let msg_id = AgentUtils::send(cli_to_srv_connect_id, client_msg).unwrap();
+            assert_eq!(msg_id, 1);
+            assert_eq!(wait_msg_from_cli_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap(), client_msg);
+            assert_eq!(wait_delivery_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap(), (ErrorCode::Success, msg_id));
+
+            assert_eq!(AgentUtils::send(srv_to_cli_connect_id, server_msg).unwrap(), 1);
+            assert_eq!(wait_msg_from_srv_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap(), server_msg);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_send_typed_works_for_signed_binary_message() {
+            TestUtils::cleanup_storage();
+
+            let (wait_msg_from_cli_send, wait_msg_from_cli_recv) = channel();
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool24", "wallet24", "default").unwrap();
+            let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9724";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            AgentUtils::listen(0, wallet_handle, endpoint, None,
+                               Some(Box::new(move |_, msg| {
+                                   wait_msg_from_cli_send.send(msg).unwrap();
+                               }))).unwrap();
+            let conn_handle = AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
+            let body = [0u8, 1, 2, 255];
+
+            AgentUtils::send_typed(conn_handle, "test/binary", Some("thread_id"), &body, true).unwrap();
+
+            let envelope_json = wait_msg_from_cli_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+            let envelope: serde_json::Value = serde_json::from_str(&envelope_json).unwrap();
+            assert_eq!(envelope["version"].as_u64().unwrap(), 1);
+            assert_eq!(envelope["type"].as_str().unwrap(), "test/binary");
+            assert_eq!(envelope["thread_id"].as_str().unwrap(), "thread_id");
+            assert!(envelope["signature"].is_string());
+            assert_eq!(AgentUtils::envelope_body(&envelope_json), body.to_vec());
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_mailbox {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_mailbox_works() {
+            TestUtils::cleanup_storage();
+
+            let endpoint = "tcp://127.0.0.1:9722";
+            let relay_wallet_handle = WalletUtils::create_and_open_wallet("pool22", "wallet22_relay", "default").unwrap();
+            let sender_wallet_handle = WalletUtils::create_and_open_wallet("pool22", "wallet22_sender", "default").unwrap();
+            let receiver_wallet_handle = WalletUtils::create_and_open_wallet("pool22", "wallet22_receiver", "default").unwrap();
+
+            let (relay_did, relay_ver_key, relay_pub_key) = SignusUtils::create_and_store_my_did(relay_wallet_handle, None).unwrap();
+            let (sender_did, sender_ver_key, sender_pub_key) = SignusUtils::create_and_store_my_did(sender_wallet_handle, None).unwrap();
+            let (receiver_did, receiver_ver_key, receiver_pub_key) = SignusUtils::create_and_store_my_did(receiver_wallet_handle, None).unwrap();
+
+            SignusUtils::store_their_did_from_parts(relay_wallet_handle, sender_did.as_str(), sender_pub_key.as_str(), sender_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(relay_wallet_handle, receiver_did.as_str(), receiver_pub_key.as_str(), receiver_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(sender_wallet_handle, relay_did.as_str(), relay_pub_key.as_str(), relay_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(sender_wallet_handle, receiver_did.as_str(), receiver_pub_key.as_str(), receiver_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(receiver_wallet_handle, relay_did.as_str(), relay_pub_key.as_str(), relay_ver_key.as_str(), endpoint).unwrap();
+
+            AgentUtils::listen(0, relay_wallet_handle, endpoint, None, None).unwrap();
+            AgentUtils::mailbox_host(relay_wallet_handle, receiver_did.as_str()).unwrap();
+
+            let sender_conn_handle = AgentUtils::connect(0, sender_wallet_handle, sender_did.as_str(), relay_did.as_str(), None).unwrap();
+            AgentUtils::mailbox_forward(sender_conn_handle, sender_wallet_handle, receiver_did.as_str(), "msg_to_offline_receiver").unwrap();
+            AgentUtils::close_connection(sender_conn_handle).unwrap();
+
+            let receiver_conn_handle = AgentUtils::connect(0, receiver_wallet_handle, receiver_did.as_str(), relay_did.as_str(), None).unwrap();
+            let messages_json = AgentUtils::mailbox_pull(receiver_conn_handle, receiver_wallet_handle).unwrap();
+            let messages: serde_json::Value = serde_json::from_str(&messages_json).unwrap();
+            let messages = messages.as_array().unwrap();
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0]["sender_did"].as_str().unwrap(), sender_did);
+            assert_eq!(messages[0]["message"].as_str().unwrap(), "msg_to_offline_receiver");
+
+            assert_eq!(AgentUtils::mailbox_pull(receiver_conn_handle, receiver_wallet_handle).unwrap(), "[]");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_claim {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_claim_issuance_works() {
+            TestUtils::cleanup_storage();
+
+            let endpoint = "tcp://127.0.0.1:9726";
+            let master_secret_name = "master_secret_name";
+            let issuer_wallet_handle = WalletUtils::create_and_open_wallet("pool26", "wallet26_issuer", "default").unwrap();
+            let prover_wallet_handle = WalletUtils::create_and_open_wallet("pool26", "wallet26_prover", "default").unwrap();
+
+            let (issuer_did, issuer_ver_key, issuer_pub_key) = SignusUtils::create_and_store_my_did(issuer_wallet_handle, None).unwrap();
+            let (prover_did, prover_ver_key, prover_pub_key) = SignusUtils::create_and_store_my_did(prover_wallet_handle, None).unwrap();
+            SignusUtils::store_their_did_from_parts(issuer_wallet_handle, prover_did.as_str(), prover_pub_key.as_str(), prover_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(prover_wallet_handle, issuer_did.as_str(), issuer_pub_key.as_str(), issuer_ver_key.as_str(), endpoint).unwrap();
+
+            let claim_def_json = AnoncredsUtils::create_claim_definition_and_set_link(issuer_wallet_handle, &AnoncredsUtils::get_gvt_schema_json(1), 1).unwrap();
+            AnoncredsUtils::prover_create_master_secret(prover_wallet_handle, master_secret_name).unwrap();
+
+            let (issuer_event_sender, issuer_event_receiver) = channel();
+            AgentUtils::claim_subscribe(issuer_wallet_handle, None, Box::new(move |event_json| {
+                issuer_event_sender.send(event_json).unwrap();
+            })).unwrap();
+            let (prover_event_sender, prover_event_receiver) = channel();
+            AgentUtils::claim_subscribe(prover_wallet_handle, Some(&format!(r#"{{"master_secret_name":"{}"}}"#, master_secret_name)),
+                                        Box::new(move |event_json| {
+                                            prover_event_sender.send(event_json).unwrap();
+                                        })).unwrap();
+
+            let (wait_conn_send, wait_conn_recv) = channel();
+            AgentUtils::listen(0, issuer_wallet_handle, endpoint,
+                               Some(Box::new(move |_, conn_handle| {
+                                   wait_conn_send.send(conn_handle).unwrap();
+                               })), None).unwrap();
+            AgentUtils::connect(0, prover_wallet_handle, prover_did.as_str(), issuer_did.as_str(), None).unwrap();
+            let issuer_conn_handle = wait_conn_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+            let exchange_id = AgentUtils::claim_offer(issuer_conn_handle, &AnoncredsUtils::get_claim_offer(&issuer_did, 1, 1), &claim_def_json).unwrap();
+
+            let event = _wait_event(&prover_event_receiver, "offer_received");
+            assert_eq!(event["exchange_id"].as_str().unwrap(), exchange_id);
+            _wait_event(&prover_event_receiver, "request_sent");
+            _wait_event(&issuer_event_receiver, "request_received");
+
+            AgentUtils::claim_issue(issuer_wallet_handle, &exchange_id, &AnoncredsUtils::get_gvt_claim_json()).unwrap();
+            _wait_event(&prover_event_receiver, "claim_stored");
+
+            let claims: serde_json::Value = serde_json::from_str(&AnoncredsUtils::prover_get_claims(prover_wallet_handle, "{}").unwrap()).unwrap();
+            assert_eq!(claims.as_array().unwrap().len(), 1);
+
+            let exchanges: serde_json::Value = serde_json::from_str(&AgentUtils::claim_exchanges(issuer_wallet_handle).unwrap()).unwrap();
+            assert_eq!(exchanges[0]["state"].as_str().unwrap(), "issued");
+            let exchanges: serde_json::Value = serde_json::from_str(&AgentUtils::claim_exchanges(prover_wallet_handle).unwrap()).unwrap();
+            assert_eq!(exchanges[0]["state"].as_str().unwrap(), "stored");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_proof {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_agent_proof_presentation_works() {
+            use utils::ledger::LedgerUtils;
+            use utils::pool::PoolUtils;
+
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_agent_proof_presentation_works";
+            let endpoint = "tcp://127.0.0.1:9728";
+            let master_secret_name = "master_secret_name";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let verifier_wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet28_verifier", "default").unwrap();
+            let prover_wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet28_prover", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(verifier_wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (verifier_did, verifier_ver_key, verifier_pub_key) = SignusUtils::create_and_store_my_did(verifier_wallet_handle, None).unwrap();
+            let (prover_did, prover_ver_key, prover_pub_key) = SignusUtils::create_and_store_my_did(prover_wallet_handle, None).unwrap();
+            SignusUtils::store_their_did_from_parts(verifier_wallet_handle, prover_did.as_str(), prover_pub_key.as_str(), prover_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(prover_wallet_handle, verifier_did.as_str(), verifier_pub_key.as_str(), verifier_ver_key.as_str(), endpoint).unwrap();
+
+            // Verifier publishes schema and claim definition and issues claim to prover
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &verifier_did, Some(&verifier_ver_key), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, verifier_wallet_handle, &trustee_did, &nym_request).unwrap();
+
+            let schema_request = LedgerUtils::build_schema_request(&verifier_did, r#"{"name":"gvt","version":"1.0","keys":["age","sex","height","name"]}"#).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, verifier_wallet_handle, &verifier_did, &schema_request).unwrap();
+            let get_schema_request = LedgerUtils::build_get_schema_request(&verifier_did, &verifier_did, r#"{"name":"gvt","version":"1.0"}"#).unwrap();
+            let get_schema_response: serde_json::Value = serde_json::from_str(&PoolUtils::send_request(pool_handle, &get_schema_request).unwrap()).unwrap();
+            let schema_seq_no = get_schema_response["result"]["seqNo"].as_i64().unwrap() as i32;
+
+            let (claim_def_json, claim_def_uuid) = AnoncredsUtils::issuer_create_claim_definition(verifier_wallet_handle,
+                                                                                                  &AnoncredsUtils::get_gvt_schema_json(schema_seq_no),
+                                                                                                  None, false).unwrap();
+            let claim_def: serde_json::Value = serde_json::from_str(&claim_def_json).unwrap();
+            let claim_def_request = LedgerUtils::build_claim_def_txn(&verifier_did, schema_seq_no, "CL", &claim_def["data"].to_string()).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, verifier_wallet_handle, &verifier_did, &claim_def_request).unwrap();
+            let get_claim_def_request = LedgerUtils::build_get_claim_def_txn(&verifier_did, schema_seq_no, "CL", &verifier_did).unwrap();
+            let get_claim_def_response: serde_json::Value = serde_json::from_str(&PoolUtils::send_request(pool_handle, &get_claim_def_request).unwrap()).unwrap();
+            let claim_def_seq_no = get_claim_def_response["result"]["seqNo"].as_i64().unwrap() as i32;
+            WalletUtils::wallet_set_seq_no_for_value(verifier_wallet_handle, &claim_def_uuid, claim_def_seq_no).unwrap();
+            let claim_def_json = claim_def_json.replace(r#""seqNo":null"#, &format!(r#""seqNo":{}"#, claim_def_seq_no));
+
+            let claim_offer_json = AnoncredsUtils::get_claim_offer(&verifier_did, claim_def_seq_no, schema_seq_no);
+            AnoncredsUtils::prover_create_master_secret(prover_wallet_handle, master_secret_name).unwrap();
+            AnoncredsUtils::prover_store_claim_offer(prover_wallet_handle, &claim_offer_json).unwrap();
+            let claim_req_json = AnoncredsUtils::prover_create_and_store_claim_req(prover_wallet_handle, &prover_did, &claim_offer_json,
+                                                                                   &claim_def_json, master_secret_name).unwrap();
+            let (_, claim_json) = AnoncredsUtils::issuer_create_claim(verifier_wallet_handle, &claim_req_json, &AnoncredsUtils::get_gvt_claim_json()).unwrap();
+            AnoncredsUtils::prover_store_claim(prover_wallet_handle, &claim_json).unwrap();
+
+            // Proof exchange
+            let (verifier_event_sender, verifier_event_receiver) = channel();
+            AgentUtils::proof_subscribe(pool_handle, verifier_wallet_handle, Box::new(move |event_json| {
+                verifier_event_sender.send(event_json).unwrap();
+            })).unwrap();
+            let (prover_event_sender, prover_event_receiver) = channel();
+            AgentUtils::proof_subscribe(pool_handle, prover_wallet_handle, Box::new(move |event_json| {
+                prover_event_sender.send(event_json).unwrap();
+            })).unwrap();
+
+            let (wait_conn_send, wait_conn_recv) = channel();
+            AgentUtils::listen(0, verifier_wallet_handle, endpoint,
+                               Some(Box::new(move |_, conn_handle| {
+                                   wait_conn_send.send(conn_handle).unwrap();
+                               })), None).unwrap();
+            AgentUtils::connect(0, prover_wallet_handle, prover_did.as_str(), verifier_did.as_str(), None).unwrap();
+            let verifier_conn_handle = wait_conn_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+            let proof_req_json = format!(r#"{{"nonce":"123432421212",
+                                              "requested_attrs":{{"attr1_uuid":{{"schema_seq_no":{}, "name":"name"}}}},
+                                              "requested_predicates":{{"predicate1_uuid":{{"attr_name":"age","p_type":"GE","value":18}}}}
+                                            }}"#, schema_seq_no);
+            let exchange_id = AgentUtils::proof_request(verifier_conn_handle, &proof_req_json).unwrap();
+
+            let event = _wait_event(&prover_event_receiver, "request_received");
+            assert_eq!(event["exchange_id"].as_str().unwrap(), exchange_id);
+            let proof_req: serde_json::Value = serde_json::from_str(event["proof_req"].as_str().unwrap()).unwrap();
+            assert_ne!(proof_req["nonce"].as_str().unwrap(), "123432421212");
+            let claims: serde_json::Value = serde_json::from_str(event["claims"].as_str().unwrap()).unwrap();
+            let claim_uuid = claims["attrs"]["attr1_uuid"][0]["claim_uuid"].as_str().unwrap();
+
+            let requested_claims_json = format!(r#"{{
+                                                  "self_attested_attributes":{{}},
+                                                  "requested_attrs":{{"attr1_uuid":["{}",true]}},
+                                                  "requested_predicates":{{"predicate1_uuid":"{}"}}
+                                                }}"#, claim_uuid, claim_uuid);
+            AgentUtils::proof_present(prover_wallet_handle, &exchange_id, &requested_claims_json, master_secret_name).unwrap();
+
+            let event = _wait_event(&verifier_event_receiver, "proof_received");
+            assert_eq!(event["exchange_id"].as_str().unwrap(), exchange_id);
+            assert!(event["valid"].as_bool().unwrap());
+            let event = _wait_event(&prover_event_receiver, "proof_result");
+            assert!(event["valid"].as_bool().unwrap());
+
+            let exchanges: serde_json::Value = serde_json::from_str(&AgentUtils::proof_exchanges(verifier_wallet_handle).unwrap()).unwrap();
+            assert_eq!(exchanges[0]["state"].as_str().unwrap(), "verified");
+            let exchanges: serde_json::Value = serde_json::from_str(&AgentUtils::proof_exchanges(prover_wallet_handle).unwrap()).unwrap();
+            assert_eq!(exchanges[0]["state"].as_str().unwrap(), "verified");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_invitation {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_invitation_works() {
+            TestUtils::cleanup_storage();
+            let endpoint = "tcp://127.0.0.1:9729";
+
+            let inviter_wallet_handle = WalletUtils::create_and_open_wallet("pool29", "wallet29_inviter", "default").unwrap();
+            let invitee_wallet_handle = WalletUtils::create_and_open_wallet("pool29", "wallet29_invitee", "default").unwrap();
+            SignusUtils::create_and_store_my_did(inviter_wallet_handle, None).unwrap();
+
+            let (wait_conn_send, wait_conn_recv) = channel();
+            let (inviter_msg_send, inviter_msg_recv) = channel();
+            let (event_send, event_recv) = channel();
+            let listener_handle = AgentUtils::listen_with_options(0, inviter_wallet_handle, endpoint, None, None,
+                                                                  Some(Box::new(move |_, conn_handle| {
+                                                                      wait_conn_send.send(conn_handle).unwrap();
+                                                                  })),
+                                                                  Some(Box::new(move |_, msg| {
+                                                                      inviter_msg_send.send(msg).unwrap();
+                                                                  })),
+                                                                  Some(Box::new(move |_, event_json| {
+                                                                      event_send.send(event_json).unwrap();
+                                                                  }))).unwrap();
+
+            let invitation_json = AgentUtils::create_invitation(listener_handle, inviter_wallet_handle, Some("inviter")).unwrap();
+            let invitation: serde_json::Value = serde_json::from_str(&invitation_json).unwrap();
+            let inviter_did = invitation["did"].as_str().unwrap().to_string();
+            assert_eq!(invitation["endpoint"].as_str().unwrap(), endpoint);
+            assert_eq!(invitation["label"].as_str().unwrap(), "inviter");
+
+            let (invitee_msg_send, invitee_msg_recv) = channel();
+            let (invitee_conn_handle, invitee_did) = AgentUtils::accept_invitation(invitee_wallet_handle, &invitation_json,
+                                                                                   Some(r#"{"label":"invitee"}"#),
+                                                                                   Some(Box::new(move |_, msg| {
+                                                                                       invitee_msg_send.send(msg).unwrap();
+                                                                                   }))).unwrap();
+            let inviter_conn_handle = wait_conn_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+            let event = _wait_event(&event_recv, "invitation_accepted");
+            assert_eq!(event["my_did"].as_str().unwrap(), inviter_did);
+            assert_eq!(event["their_did"].as_str().unwrap(), invitee_did);
+            assert_eq!(event["label"].as_str().unwrap(), "invitee");
+
+            let pairwise: serde_json::Value = serde_json::from_str(&PairwiseUtils::get_pairwise(inviter_wallet_handle, &invitee_did).unwrap()).unwrap();
+            assert_eq!(pairwise["my_did"].as_str().unwrap(), inviter_did);
+            let pairwise: serde_json::Value = serde_json::from_str(&PairwiseUtils::get_pairwise(invitee_wallet_handle, &inviter_did).unwrap()).unwrap();
+            assert_eq!(pairwise["my_did"].as_str().unwrap(), invitee_did);
+            assert_eq!(pairwise["state"].as_str().unwrap(), "connected");
+
+            AgentUtils::send(invitee_conn_handle, "msg_from_invitee").unwrap();
+            let msg = inviter_msg_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+            assert_eq!(AgentUtils::message_body(&msg), "msg_from_invitee");
+
+            AgentUtils::send(inviter_conn_handle, "msg_from_inviter").unwrap();
+            let msg = invitee_msg_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+            assert_eq!(AgentUtils::message_body(&msg), "msg_from_inviter");
+
+            TestUtils::cleanup_storage();
+        }
     }
 
     mod sovrin_agent_close_connection {
@@ -152,7 +630,7 @@ mod high_cases {
             let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
             let endpoint = "tcp://127.0.0.1:9705";
 
-            let _ = AgentUtils::listen(wallet_handle, endpoint, None, None).unwrap();
+            let _ = AgentUtils::listen(0, wallet_handle, endpoint, None, None).unwrap();
 
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
 
@@ -173,7 +651,7 @@ mod high_cases {
             let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
             let endpoint = "tcp://127.0.0.1:9706";
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
-            AgentUtils::listen(wallet_handle, endpoint,
+            AgentUtils::listen(0, wallet_handle, endpoint,
                                Some(Box::new(move |_, conn_handle| {
                                    wait_conn_send.send(conn_handle).unwrap();
                                })),
@@ -202,7 +680,7 @@ mod high_cases {
             let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
             let endpoint = "tcp://127.0.0.1:9708";
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
-            let listener_handle = AgentUtils::listen(wallet_handle, endpoint,
+            let listener_handle = AgentUtils::listen(0, wallet_handle, endpoint,
                                                      Some(Box::new(move |_, conn_handle| {
                                                          wait_conn_send.send(conn_handle).unwrap();
                                                      })),
@@ -223,6 +701,322 @@ mod high_cases {
 mod medium_cases {
     use super::*;
 
+    mod sovrin_agent_listen {
+        use super::*;
+        use rust_base58::{FromBase58, ToBase58};
+
+        #[test]
+        fn sovrin_agent_listen_works_for_known_policy_and_unknown_sender() {
+            TestUtils::cleanup_storage();
+
+            let (wait_event_send, wait_event_recv) = channel();
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool12", "wallet12", "default").unwrap();
+            let (_, _, pub_key) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9712";
+
+            AgentUtils::listen_with_options(0, wallet_handle, endpoint, None, Some(r#"{"type":"known"}"#),
+                                            Some(Box::new(|_, _| panic!("Unknown sender connected"))),
+                                            None,
+                                            Some(Box::new(move |_, event_json| {
+                                                wait_event_send.send(event_json).unwrap();
+                                            }))).unwrap();
+
+            let keys = zmq::CurveKeyPair::new().unwrap();
+            let socket = zmq::Context::new().socket(zmq::SocketType::DEALER).unwrap();
+            socket.set_curve_publickey(keys.public_key.as_str()).unwrap();
+            socket.set_curve_secretkey(keys.secret_key.as_str()).unwrap();
+            socket.set_curve_serverkey(zmq::z85_encode(pub_key.from_base58().unwrap().as_slice()).unwrap().as_str()).unwrap();
+            socket.set_linger(0).unwrap();
+            socket.connect(endpoint).unwrap();
+            socket.send_str(r#"{"type":"Hello"}"#, zmq::DONTWAIT).unwrap();
+
+            let event_json = wait_event_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+            assert!(event_json.contains(r#""type":"connection_rejected""#));
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_listen_works_for_known_policy_and_sender_stored_after_listen() {
+            TestUtils::cleanup_storage();
+
+            let (wait_event_send, wait_event_recv) = channel();
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool31", "wallet31", "default").unwrap();
+            let (_, _, pub_key) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let (sender_did, sender_ver_key, _) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9731";
+
+            AgentUtils::listen_with_options(0, wallet_handle, endpoint, None, Some(r#"{"type":"known"}"#),
+                                            Some(Box::new(|_, _| panic!("Sender stored after listen connected"))),
+                                            None,
+                                            Some(Box::new(move |_, event_json| {
+                                                wait_event_send.send(event_json).unwrap();
+                                            }))).unwrap();
+
+            let keys = zmq::CurveKeyPair::new().unwrap();
+            let sender_pk = zmq::z85_decode(keys.public_key.as_str()).unwrap().to_base58();
+            SignusUtils::store_their_did_from_parts(wallet_handle, sender_did.as_str(), sender_pk.as_str(), sender_ver_key.as_str(), endpoint).unwrap();
+
+            let socket = zmq::Context::new().socket(zmq::SocketType::DEALER).unwrap();
+            socket.set_curve_publickey(keys.public_key.as_str()).unwrap();
+            socket.set_curve_secretkey(keys.secret_key.as_str()).unwrap();
+            socket.set_curve_serverkey(zmq::z85_encode(pub_key.from_base58().unwrap().as_slice()).unwrap().as_str()).unwrap();
+            socket.set_linger(0).unwrap();
+            socket.connect(endpoint).unwrap();
+            socket.send_str(r#"{"type":"Hello"}"#, zmq::DONTWAIT).unwrap();
+
+            // Transport keys of known DIDs are taken at listen start
+            let event_json = wait_event_recv.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+            assert!(event_json.contains(r#""type":"connection_rejected""#));
+            assert!(event_json.contains(&format!(r#""client_key":"{}""#, sender_pk)));
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_listen_works_for_invalid_policy() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool13", "wallet13", "default").unwrap();
+            SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9713";
+
+            let res = AgentUtils::listen_with_options(0, wallet_handle, endpoint, None, Some(r#"{"type":"unknown"}"#), None, None, None);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_listen_works_for_allow_list_with_did_not_in_wallet() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool14", "wallet14", "default").unwrap();
+            SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9714";
+
+            let res = AgentUtils::listen_with_options(0, wallet_handle, endpoint, None,
+                                                      Some(r#"{"type":"list","allow":["8wZcEriaNLNKtteJvx7f8i"]}"#), None, None, None);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_connect {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_connect_works_for_sender_did_verkey_mismatch() {
+            TestUtils::cleanup_storage();
+
+            let endpoint = "tcp://127.0.0.1:9710";
+            let srv_seed = "sovrin_agent_connect_works_for_s";
+
+            let srv_wallet_handle = WalletUtils::create_and_open_wallet("pool10", "wallet10_srv", "default").unwrap();
+            let cli_wallet_handle = WalletUtils::create_and_open_wallet("pool10", "wallet10_cli", "default").unwrap();
+
+            let (srv_did, srv_ver_key, srv_pub_key) = SignusUtils::create_and_store_my_did(srv_wallet_handle, Some(srv_seed.to_string())).unwrap();
+            SignusUtils::create_and_store_my_did(cli_wallet_handle, Some(srv_seed.to_string())).unwrap();
+            let (cli_did, _, cli_pub_key) = SignusUtils::create_and_store_my_did(cli_wallet_handle, None).unwrap();
+            let (_, other_ver_key, _) = SignusUtils::create_and_store_my_did(cli_wallet_handle, None).unwrap();
+
+            SignusUtils::store_their_did_from_parts(cli_wallet_handle, srv_did.as_str(), srv_pub_key.as_str(), srv_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(srv_wallet_handle, cli_did.as_str(), cli_pub_key.as_str(), other_ver_key.as_str(), endpoint).unwrap();
+
+            AgentUtils::listen(0, srv_wallet_handle, endpoint, None, None).unwrap();
+
+            assert_eq!(AgentUtils::connect(0, cli_wallet_handle, cli_did.as_str(), srv_did.as_str(), None).unwrap_err(),
+                       ErrorCode::CommonInvalidState);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_connect_works_for_invalid_options() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool21", "wallet21", "default").unwrap();
+            let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9721";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+
+            assert_eq!(AgentUtils::connect_with_options(0, wallet_handle, did.as_str(), did.as_str(),
+                                                        Some(r#"{"reliable":"yes"}"#), None, None).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_add_identity {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_add_identity_works_for_did_not_in_wallet() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool18", "wallet18", "default").unwrap();
+            SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9718";
+            let listener_handle = AgentUtils::listen(0, wallet_handle, endpoint, None, None).unwrap();
+
+            assert_eq!(AgentUtils::add_identity(listener_handle, wallet_handle, "unknown_did").unwrap_err(),
+                       ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_add_identity_works_for_incorrect_listener_handle() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool19", "wallet19", "default").unwrap();
+            let (did, _, _) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+
+            assert_eq!(AgentUtils::add_identity(wallet_handle, wallet_handle, did.as_str()).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_send {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_send_typed_works_for_reserved_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool25", "wallet25", "default").unwrap();
+            let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9725";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            AgentUtils::listen(0, wallet_handle, endpoint, None, None).unwrap();
+            let conn_handle = AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
+
+            assert_eq!(AgentUtils::send_typed(conn_handle, "sovrin/handshake", None, "msg".as_bytes(), false).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_mailbox {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_mailbox_forward_works_for_not_hosted_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool23", "wallet23", "default").unwrap();
+            let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+            let endpoint = "tcp://127.0.0.1:9723";
+            SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
+            AgentUtils::listen(0, wallet_handle, endpoint, None, None).unwrap();
+            let conn_handle = AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
+
+            assert_eq!(AgentUtils::mailbox_forward(conn_handle, wallet_handle, did.as_str(), "msg").unwrap_err(),
+                       ErrorCode::CommonInvalidState);
+            assert_eq!(AgentUtils::mailbox_pull(conn_handle, wallet_handle).unwrap_err(),
+                       ErrorCode::CommonInvalidState);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_agent_mailbox_pull_works_for_failed_pull() {
+            TestUtils::cleanup_storage();
+
+            let endpoint = "tcp://127.0.0.1:9732";
+            let relay_wallet_handle = WalletUtils::create_and_open_wallet("pool32", "wallet32_relay", "default").unwrap();
+            let receiver_wallet_handle = WalletUtils::create_and_open_wallet("pool32", "wallet32_receiver", "default").unwrap();
+            let other_wallet_handle = WalletUtils::create_and_open_wallet("pool32", "wallet32_other", "default").unwrap();
+
+            let (relay_did, relay_ver_key, relay_pub_key) = SignusUtils::create_and_store_my_did(relay_wallet_handle, None).unwrap();
+            let (receiver_did, receiver_ver_key, receiver_pub_key) = SignusUtils::create_and_store_my_did(receiver_wallet_handle, None).unwrap();
+
+            SignusUtils::store_their_did_from_parts(relay_wallet_handle, relay_did.as_str(), relay_pub_key.as_str(), relay_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(relay_wallet_handle, receiver_did.as_str(), receiver_pub_key.as_str(), receiver_ver_key.as_str(), endpoint).unwrap();
+            SignusUtils::store_their_did_from_parts(receiver_wallet_handle, relay_did.as_str(), relay_pub_key.as_str(), relay_ver_key.as_str(), endpoint).unwrap();
+
+            AgentUtils::listen(0, relay_wallet_handle, endpoint, None, None).unwrap();
+            AgentUtils::mailbox_host(relay_wallet_handle, receiver_did.as_str()).unwrap();
+
+            let sender_conn_handle = AgentUtils::connect(0, relay_wallet_handle, relay_did.as_str(), relay_did.as_str(), None).unwrap();
+            AgentUtils::mailbox_forward(sender_conn_handle, relay_wallet_handle, receiver_did.as_str(), "msg_to_offline_receiver").unwrap();
+
+            let receiver_conn_handle = AgentUtils::connect(0, receiver_wallet_handle, receiver_did.as_str(), relay_did.as_str(), None).unwrap();
+
+            // Messages are received from relay, but can't be decrypted without the key of receiver
+            assert_eq!(AgentUtils::mailbox_pull(receiver_conn_handle, other_wallet_handle).unwrap_err(),
+                       ErrorCode::WalletNotFoundError);
+
+            let messages_json = AgentUtils::mailbox_pull(receiver_conn_handle, receiver_wallet_handle).unwrap();
+            let messages: serde_json::Value = serde_json::from_str(&messages_json).unwrap();
+            let messages = messages.as_array().unwrap();
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0]["message"].as_str().unwrap(), "msg_to_offline_receiver");
+
+            assert_eq!(AgentUtils::mailbox_pull(receiver_conn_handle, receiver_wallet_handle).unwrap(), "[]");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_claim {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_claim_issue_works_for_unknown_exchange() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool27", "wallet27", "default").unwrap();
+
+            assert_eq!(AgentUtils::claim_issue(wallet_handle, "unknown_exchange_id", &AnoncredsUtils::get_gvt_claim_json()).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+            assert_eq!(AgentUtils::claim_request(wallet_handle, "unknown_exchange_id", "master_secret_name").unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+            assert_eq!(AgentUtils::claim_exchanges(wallet_handle).unwrap(), "[]");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_proof {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_proof_present_works_for_unknown_exchange() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool28", "wallet28", "default").unwrap();
+
+            let requested_claims_json = r#"{"self_attested_attributes":{},"requested_attrs":{},"requested_predicates":{}}"#;
+            assert_eq!(AgentUtils::proof_present(wallet_handle, "unknown_exchange_id", requested_claims_json, "master_secret_name").unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+            assert_eq!(AgentUtils::proof_exchanges(wallet_handle).unwrap(), "[]");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sovrin_agent_invitation {
+        use super::*;
+
+        #[test]
+        fn sovrin_agent_create_invitation_works_for_incorrect_listener_handle() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool29", "wallet29", "default").unwrap();
+
+            assert_eq!(AgentUtils::create_invitation(wallet_handle, wallet_handle, None).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
     mod sovrin_agent_close_connection {
         use super::*;
 
@@ -234,9 +1028,9 @@ mod medium_cases {
             let wallet_handle = WalletUtils::create_and_open_wallet("pool6", "wallet6", "default").unwrap();
             let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
             let endpoint = "tcp://127.0.0.1:9707";
-            let _ = AgentUtils::listen(wallet_handle, endpoint, None,
+            let _ = AgentUtils::listen(0, wallet_handle, endpoint, None,
                                        Some(Box::new(move |_, msg| {
-                                           wait_msg_from_cli_send.send(msg).unwrap();
+                                           wait_msg_from_cli_send.send(AgentUtils::message_body(&msg)).unwrap();
                                        }))).unwrap();
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
             let conn_handle = AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
@@ -262,9 +1056,9 @@ mod medium_cases {
             let wallet_handle = WalletUtils::create_and_open_wallet("pool9", "wallet9", "default").unwrap();
             let (did, ver_key, pub_key): (String, String, String) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
             let endpoint = "tcp://127.0.0.1:9709";
-            AgentUtils::listen(wallet_handle, endpoint, None,
+            AgentUtils::listen(0, wallet_handle, endpoint, None,
                                                      Some(Box::new(move |_, msg| {
-                                                         wait_msg_from_cli_send.send(msg).unwrap();
+                                                         wait_msg_from_cli_send.send(AgentUtils::message_body(&msg)).unwrap();
                                                      }))).unwrap();
             SignusUtils::store_their_did_from_parts(wallet_handle, did.as_str(), pub_key.as_str(), ver_key.as_str(), endpoint).unwrap();
             let conn_handle = AgentUtils::connect(0, wallet_handle, did.as_str(), did.as_str(), None).unwrap();
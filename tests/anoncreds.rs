@@ -310,6 +310,39 @@ mod high_cases {
         }
     }
 
+    mod issuer_revoke_claim {
+        use super::*;
+
+        #[test]
+        fn issuer_revoke_claim_works() {
+            AnoncredsUtils::init_common_wallet();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "revoke_claim_wallet", "default").unwrap();
+            let schema = AnoncredsUtils::get_gvt_schema_json(1);
+            let revoc_reg_json = AnoncredsUtils::create_revoc_reg_and_set_link(wallet_handle, &schema, 1, 2).unwrap();
+
+            let revoc_reg_update_json = AnoncredsUtils::issuer_revoke_claim(wallet_handle, 1, 2, 1).unwrap();
+            assert_ne!(revoc_reg_json, revoc_reg_update_json);
+        }
+
+        #[test]
+        fn issuer_revoke_claim_with_ledger_update_works() {
+            AnoncredsUtils::init_common_wallet();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "revoke_claim_with_ledger_update_wallet", "default").unwrap();
+            let schema = AnoncredsUtils::get_gvt_schema_json(1);
+            AnoncredsUtils::create_revoc_reg_and_set_link(wallet_handle, &schema, 1, 2).unwrap();
+
+            let (_, revoc_reg_entry_request_json) =
+                AnoncredsUtils::issuer_revoke_claim_with_ledger_update(wallet_handle, "NcYxiDXkpYi6ov5FcYDi1e", 1, 2, 1).unwrap();
+            let request: serde_json::Value = serde_json::from_str(&revoc_reg_entry_request_json).unwrap();
+            assert_eq!(request["identifier"].as_str().unwrap(), "NcYxiDXkpYi6ov5FcYDi1e");
+            assert_eq!(request["operation"]["type"].as_str().unwrap(), "114");
+            assert_eq!(request["operation"]["ref"].as_i64().unwrap(), 2);
+            assert!(request["operation"]["data"].as_str().unwrap().contains(r#""revoked":[1]"#));
+        }
+    }
+
     mod prover_store_claim {
         use super::*;
 
@@ -916,6 +949,46 @@ mod medium_cases {
         }
     }
 
+    mod issuer_revoke_claim {
+        use super::*;
+
+        #[test]
+        fn issuer_revoke_claim_works_for_claim_def_seq_no_of_other_claim_def() {
+            AnoncredsUtils::init_common_wallet();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "revoke_claim_other_claim_def_wallet", "default").unwrap();
+            let schema = AnoncredsUtils::get_gvt_schema_json(1);
+            AnoncredsUtils::create_revoc_reg_and_set_link(wallet_handle, &schema, 1, 2).unwrap();
+
+            let res = AnoncredsUtils::issuer_revoke_claim(wallet_handle, 3, 2, 1);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
+        #[test]
+        fn issuer_revoke_claim_works_for_revoc_reg_without_seq_no_link() {
+            AnoncredsUtils::init_common_wallet();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "revoke_claim_without_link_wallet", "default").unwrap();
+            let schema = AnoncredsUtils::get_gvt_schema_json(1);
+            AnoncredsUtils::create_revoc_reg_and_set_link(wallet_handle, &schema, 1, 2).unwrap();
+
+            let res = AnoncredsUtils::issuer_revoke_claim(wallet_handle, 1, 4, 1);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+        }
+
+        #[test]
+        fn issuer_revoke_claim_with_ledger_update_works_for_invalid_submitter_did() {
+            AnoncredsUtils::init_common_wallet();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "revoke_claim_invalid_submitter_wallet", "default").unwrap();
+            let schema = AnoncredsUtils::get_gvt_schema_json(1);
+            AnoncredsUtils::create_revoc_reg_and_set_link(wallet_handle, &schema, 1, 2).unwrap();
+
+            let res = AnoncredsUtils::issuer_revoke_claim_with_ledger_update(wallet_handle, "invalid_base58_did", 1, 2, 1);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+    }
+
     mod prover_store_claim {
         use super::*;
 
@@ -1,5 +1,6 @@
 extern crate sovrin;
 
+extern crate rust_base58;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
@@ -178,6 +179,105 @@ mod high_cases {
         }
     }
 
+    mod ledger_cache {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_submit_request_with_cache_works_for_cached_reply() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_submit_request_with_cache_works_for_cached_reply";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let get_nym_request = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request, None).unwrap();
+
+            // Another request for the same nym has different reqId, but must be answered from the cache
+            let get_nym_request_2 = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            assert_ne!(get_nym_request, get_nym_request_2);
+            let get_nym_response_2 = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request_2, None).unwrap();
+
+            // Cached reply has reqId of the request it's returned for
+            let get_nym_request_2: serde_json::Value = serde_json::from_str(&get_nym_request_2).unwrap();
+            let get_nym_response: serde_json::Value = serde_json::from_str(&get_nym_response).unwrap();
+            let mut get_nym_response_2: serde_json::Value = serde_json::from_str(&get_nym_response_2).unwrap();
+            assert_eq!(get_nym_request_2["reqId"], get_nym_response_2["result"]["reqId"]);
+
+            get_nym_response_2["result"]["reqId"] = get_nym_response["result"]["reqId"].clone();
+            assert_eq!(get_nym_response, get_nym_response_2);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_submit_request_with_cache_works_for_refresh() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_submit_request_with_cache_works_for_refresh";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let get_nym_request = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request, None).unwrap();
+
+            let get_nym_request_2 = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response_2 = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request_2, Some(r#"{"refresh":true}"#)).unwrap();
+            assert_ne!(get_nym_response, get_nym_response_2);
+
+            // Refreshed reply replaces the cached one
+            let get_nym_request_3 = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response_3 = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request_3, None).unwrap();
+
+            let get_nym_response_2: serde_json::Value = serde_json::from_str(&get_nym_response_2).unwrap();
+            let mut get_nym_response_3: serde_json::Value = serde_json::from_str(&get_nym_response_3).unwrap();
+            get_nym_response_3["result"]["reqId"] = get_nym_response_2["result"]["reqId"].clone();
+            assert_eq!(get_nym_response_2, get_nym_response_3);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_purge_ledger_cache_works() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_purge_ledger_cache_works";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let get_nym_request = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request, None).unwrap();
+
+            LedgerUtils::purge_ledger_cache(wallet_handle, Some("nym")).unwrap();
+
+            let get_nym_request_2 = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response_2 = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request_2, None).unwrap();
+            assert_ne!(get_nym_response, get_nym_response_2);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_purge_ledger_cache_works_for_empty_cache() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            LedgerUtils::purge_ledger_cache(wallet_handle, None).unwrap();
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
     mod nym_requests {
         use super::*;
 
@@ -425,6 +525,67 @@ mod high_cases {
         }
     }
 
+    mod resolve_did {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_resolve_did_works() {
+            use rust_base58::{FromBase58, ToBase58};
+
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_resolve_did_works";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (my_did, my_verkey, my_pk) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
+
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &my_did, Some(&my_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
+
+            let raw = format!(r#"{{"endpoint":{{"ha":"127.0.0.1:5555","verkey":"{}"}}}}"#, my_pk);
+            let attrib_request = LedgerUtils::build_attrib_request(&my_did, &my_did, None, Some(&raw), None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &my_did, &attrib_request).unwrap();
+
+            let ddo_json = LedgerUtils::resolve_did(pool_handle, wallet_handle, &my_did).unwrap();
+            let ddo: serde_json::Value = serde_json::from_str(&ddo_json).unwrap();
+
+            let mut my_full_verkey = my_did.from_base58().unwrap();
+            my_full_verkey.extend(my_verkey[1..].from_base58().unwrap());
+
+            assert_eq!(ddo["did"], my_did.as_str());
+            assert_eq!(ddo["verkey"], my_full_verkey.to_base58().as_str());
+            assert_eq!(ddo["endpoint"]["address"], "127.0.0.1:5555");
+            assert_eq!(ddo["endpoint"]["transport_key"], my_pk.as_str());
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_resolve_did_works_for_did_without_endpoint() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_resolve_did_works_for_did_without_endpoint";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (trustee_did, trustee_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let ddo_json = LedgerUtils::resolve_did(pool_handle, wallet_handle, &trustee_did).unwrap();
+            let ddo: serde_json::Value = serde_json::from_str(&ddo_json).unwrap();
+
+            assert_eq!(ddo["did"], trustee_did.as_str());
+            assert_eq!(ddo["verkey"], trustee_verkey.as_str());
+            assert_eq!(ddo["role"], "TRUSTEE");
+            assert!(ddo["endpoint"].is_null());
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
     mod schema_requests {
         use super::*;
 
@@ -531,6 +692,18 @@ mod high_cases {
             assert!(node_request.contains(expected_result));
         }
 
+        #[test]
+        fn sovrin_build_node_request_works_for_demote() {
+            let identifier = "identifier";
+            let dest = "dest";
+            let data = r#"{"alias":"some", "services": []}"#;
+
+            let expected_result = r#""identifier":"identifier","operation":{"type":"0","dest":"dest","data":{"alias":"some","services":[]}}"#;
+
+            let node_request = LedgerUtils::build_node_request(identifier, dest, data).unwrap();
+            assert!(node_request.contains(expected_result));
+        }
+
         #[test]
         #[cfg(feature = "local_nodes_pool")]
         fn sovrin_send_node_request_works_without_signature() {
@@ -593,6 +766,92 @@ mod high_cases {
         }
     }
 
+    mod pool_upgrade_requests {
+        use super::*;
+
+        #[test]
+        fn sovrin_build_pool_upgrade_request_works_for_start_action() {
+            let identifier = "identifier";
+            let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+            let schedule = r#"{"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv":"2017-12-25T10:25:58.271857+00:00"}"#;
+
+            let expected_result = format!(r#""identifier":"identifier","operation":{{"type":"109","name":"upgrade","version":"1.0.1","action":"start","sha256":"{}","timeout":10,"schedule":{},"reinstall":false,"force":false}}"#,
+                                          sha256, schedule);
+
+            let request = LedgerUtils::build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "start", sha256,
+                                                                  Some(10), Some(schedule), None, false, false).unwrap();
+            assert!(request.contains(&expected_result));
+        }
+
+        #[test]
+        fn sovrin_build_pool_upgrade_request_works_for_cancel_action() {
+            let identifier = "identifier";
+            let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+            let expected_result = format!(r#""identifier":"identifier","operation":{{"type":"109","name":"upgrade","version":"1.0.1","action":"cancel","sha256":"{}","justification":"some","reinstall":false,"force":false}}"#,
+                                          sha256);
+
+            let request = LedgerUtils::build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "cancel", sha256,
+                                                                  None, None, Some("some"), false, false).unwrap();
+            assert!(request.contains(&expected_result));
+        }
+
+        #[test]
+        fn sovrin_build_pool_config_request_works() {
+            let identifier = "identifier";
+
+            let expected_result = r#""identifier":"identifier","operation":{"type":"111","writes":true,"force":false}"#;
+
+            let request = LedgerUtils::build_pool_config_request(identifier, true, false).unwrap();
+            assert!(request.contains(expected_result));
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_send_pool_config_request_works_for_wrong_role() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_send_pool_config_request_works_for_wrong_role";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Steward1","cid":true}"#).unwrap();
+
+            let request = LedgerUtils::build_pool_config_request(&my_did, true, false).unwrap();
+
+            let res = LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &my_did, &request);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::LedgerInvalidTransaction);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod revoc_reg_requests {
+        use super::*;
+
+        #[test]
+        fn sovrin_build_get_revoc_reg_def_request_works() {
+            let identifier = "identifier";
+            let origin = "origin";
+
+            let expected_result = r#""identifier":"identifier","operation":{"type":"115","ref":1,"origin":"origin"}"#;
+
+            let request = LedgerUtils::build_get_revoc_reg_def_request(identifier, 1, origin).unwrap();
+            assert!(request.contains(expected_result));
+        }
+
+        #[test]
+        fn sovrin_build_get_revoc_reg_request_works() {
+            let identifier = "identifier";
+
+            let expected_result = r#""identifier":"identifier","operation":{"type":"116","ref":2}"#;
+
+            let request = LedgerUtils::build_get_revoc_reg_request(identifier, 2).unwrap();
+            assert!(request.contains(expected_result));
+        }
+    }
+
     mod claim_def_requests {
         use super::*;
 
@@ -1087,6 +1346,17 @@ mod medium_cases {
             assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
         }
 
+        #[test]
+        fn sovrin_build_node_request_works_for_partial_address() {
+            let identifier = "identifier";
+            let dest = "dest";
+            let data = r#"{"node_ip":"ip", "node_port": 1, "alias":"some", "services": ["VALIDATOR"]}"#;
+
+            let res = LedgerUtils::build_node_request(identifier, dest, data);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
         #[test]
         #[cfg(feature = "local_nodes_pool")]
         fn sovrin_send_node_request_works_for_wrong_role() {
@@ -1157,4 +1427,167 @@ mod medium_cases {
             assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
         }
     }
-}
\ No newline at end of file
+
+    mod pool_upgrade_requests {
+        use super::*;
+
+        #[test]
+        fn sovrin_build_pool_upgrade_request_works_for_invalid_action() {
+            let identifier = "identifier";
+            let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+            let res = LedgerUtils::build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "stop", sha256,
+                                                              None, None, None, false, false);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
+        #[test]
+        fn sovrin_build_pool_upgrade_request_works_for_start_action_without_schedule() {
+            let identifier = "identifier";
+            let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+            let res = LedgerUtils::build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "start", sha256,
+                                                              None, None, None, false, false);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
+        #[test]
+        fn sovrin_build_pool_upgrade_request_works_for_invalid_schedule() {
+            let identifier = "identifier";
+            let sha256 = "f284bdc3c1c9e24a494e285cb387c69510f28de51c15bb93179d9c7f28705398";
+
+            let res = LedgerUtils::build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "start", sha256,
+                                                              None, Some("[]"), None, false, false);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
+        #[test]
+        fn sovrin_build_pool_upgrade_request_works_for_invalid_sha256() {
+            let identifier = "identifier";
+
+            let res = LedgerUtils::build_pool_upgrade_request(identifier, "upgrade", "1.0.1", "cancel", "sha256",
+                                                              None, None, None, false, false);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+    }
+
+    mod revoc_reg_requests {
+        use super::*;
+
+        #[test]
+        fn sovrin_build_revoc_reg_def_request_works_for_invalid_data_json() {
+            let identifier = "identifier";
+            let data = r#"{"claim_def_seq_no":1}"#;
+
+            let res = LedgerUtils::build_revoc_reg_def_request(identifier, 1, data);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
+        #[test]
+        fn sovrin_build_revoc_reg_entry_request_works_for_invalid_data_json() {
+            let identifier = "identifier";
+            let data = r#"{"issued":[],"revoked":[1]}"#;
+
+            let res = LedgerUtils::build_revoc_reg_entry_request(identifier, 2, data);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+
+        #[test]
+        fn sovrin_build_get_revoc_reg_def_request_works_for_invalid_origin() {
+            let identifier = "identifier";
+            let origin = "invalid_base58_0OIl";
+
+            let res = LedgerUtils::build_get_revoc_reg_def_request(identifier, 1, origin);
+            assert!(res.is_err());
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+        }
+    }
+
+    mod ledger_cache {
+        use super::*;
+
+        #[test]
+        fn sovrin_purge_ledger_cache_works_for_unknown_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = LedgerUtils::purge_ledger_cache(wallet_handle, Some("attrib"));
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_purge_ledger_cache_works_for_invalid_wallet_handle() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = LedgerUtils::purge_ledger_cache(wallet_handle + 1, None);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletInvalidHandle);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_submit_request_with_cache_works_for_invalid_options() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_submit_request_with_cache_works_for_invalid_options";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let get_nym_request = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let res = LedgerUtils::submit_request_with_cache(pool_handle, wallet_handle, &get_nym_request, Some(r#"{"ttl":"some"}"#));
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod resolve_did {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_resolve_did_works_for_unknown_did() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_resolve_did_works_for_unknown_did";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = LedgerUtils::resolve_did(pool_handle, wallet_handle, &my_did);
+            assert_eq!(res.unwrap_err(), ErrorCode::LedgerNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_resolve_did_works_for_invalid_did() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_resolve_did_works_for_invalid_did";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let res = LedgerUtils::resolve_did(pool_handle, wallet_handle, "invalid_base58_0OIl");
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+}
@@ -0,0 +1,172 @@
+extern crate sovrin;
+
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
+
+#[macro_use]
+mod utils;
+
+use utils::pairwise::PairwiseUtils;
+use utils::signus::SignusUtils;
+use utils::test::TestUtils;
+use utils::wallet::WalletUtils;
+
+use sovrin::api::ErrorCode;
+
+fn _create_dids(wallet_handle: i32) -> (String, String) {
+    let (my_did, _, _) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+    let (their_did, their_verkey, their_pk) = SignusUtils::create_and_store_my_did(wallet_handle, None).unwrap();
+    SignusUtils::store_their_did_from_parts(wallet_handle, &their_did, &their_pk, &their_verkey, "127.0.0.1:9700").unwrap();
+    (my_did, their_did)
+}
+
+mod high_cases {
+    use super::*;
+
+    mod create_pairwise {
+        use super::*;
+
+        #[test]
+        fn sovrin_create_pairwise_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (my_did, their_did) = _create_dids(wallet_handle);
+
+            PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did,
+                                           Some(r#"{"endpoint":"127.0.0.1:9701","transport_key":"key","metadata":"meta"}"#)).unwrap();
+
+            let pairwise: serde_json::Value = serde_json::from_str(&PairwiseUtils::get_pairwise(wallet_handle, &their_did).unwrap()).unwrap();
+            assert_eq!(pairwise["my_did"].as_str().unwrap(), my_did);
+            assert_eq!(pairwise["their_did"].as_str().unwrap(), their_did);
+            assert_eq!(pairwise["endpoint"].as_str().unwrap(), "127.0.0.1:9701");
+            assert_eq!(pairwise["transport_key"].as_str().unwrap(), "key");
+            assert_eq!(pairwise["metadata"].as_str().unwrap(), "meta");
+            assert_eq!(pairwise["state"].as_str().unwrap(), "created");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod list_pairwise {
+        use super::*;
+
+        #[test]
+        fn sovrin_list_pairwise_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            assert_eq!(PairwiseUtils::list_pairwise(wallet_handle).unwrap(), "[]");
+
+            let (my_did, their_did) = _create_dids(wallet_handle);
+            PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did, None).unwrap();
+
+            let pairwise_list: serde_json::Value = serde_json::from_str(&PairwiseUtils::list_pairwise(wallet_handle).unwrap()).unwrap();
+            let pairwise_list = pairwise_list.as_array().unwrap();
+            assert_eq!(pairwise_list.len(), 1);
+            assert_eq!(pairwise_list[0]["my_did"].as_str().unwrap(), my_did);
+            assert_eq!(pairwise_list[0]["their_did"].as_str().unwrap(), their_did);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod update_pairwise {
+        use super::*;
+
+        #[test]
+        fn sovrin_update_pairwise_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (my_did, their_did) = _create_dids(wallet_handle);
+            PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did, Some(r#"{"metadata":"meta"}"#)).unwrap();
+
+            PairwiseUtils::update_pairwise(wallet_handle, &their_did, r#"{"endpoint":"127.0.0.1:9701"}"#).unwrap();
+
+            let pairwise: serde_json::Value = serde_json::from_str(&PairwiseUtils::get_pairwise(wallet_handle, &their_did).unwrap()).unwrap();
+            assert_eq!(pairwise["endpoint"].as_str().unwrap(), "127.0.0.1:9701");
+            assert_eq!(pairwise["metadata"].as_str().unwrap(), "meta");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod delete_pairwise {
+        use super::*;
+
+        #[test]
+        fn sovrin_delete_pairwise_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (my_did, their_did) = _create_dids(wallet_handle);
+            PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did, None).unwrap();
+
+            PairwiseUtils::delete_pairwise(wallet_handle, &their_did).unwrap();
+
+            assert_eq!(PairwiseUtils::get_pairwise(wallet_handle, &their_did).unwrap_err(), ErrorCode::WalletNotFoundError);
+            assert_eq!(PairwiseUtils::list_pairwise(wallet_handle).unwrap(), "[]");
+
+            TestUtils::cleanup_storage();
+        }
+    }
+}
+
+mod medium_cases {
+    use super::*;
+
+    mod create_pairwise {
+        use super::*;
+
+        #[test]
+        fn sovrin_create_pairwise_works_for_unknown_my_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (_, their_did) = _create_dids(wallet_handle);
+
+            assert_eq!(PairwiseUtils::create_pairwise(wallet_handle, &their_did, "unknown_did", None).unwrap_err(),
+                       ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_create_pairwise_works_for_twice() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (my_did, their_did) = _create_dids(wallet_handle);
+            PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did, None).unwrap();
+
+            assert_eq!(PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did, None).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod update_pairwise {
+        use super::*;
+
+        #[test]
+        fn sovrin_update_pairwise_works_for_invalid_json() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (my_did, their_did) = _create_dids(wallet_handle);
+            PairwiseUtils::create_pairwise(wallet_handle, &their_did, &my_did, None).unwrap();
+
+            assert_eq!(PairwiseUtils::update_pairwise(wallet_handle, &their_did, r#"{"endpoint":1}"#).unwrap_err(),
+                       ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+}
@@ -38,7 +38,27 @@ mod high_cases {
             let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
 
             assert_eq!(my_did.from_base58().unwrap().len(), 16);
-            assert_eq!(my_verkey.from_base58().unwrap().len(), 32);
+            assert!(my_verkey.starts_with("~"));
+            assert_eq!(my_verkey[1..].from_base58().unwrap().len(), 16);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "secp256k1_rust")]
+        fn sovrin_create_my_did_works_for_secp256k1_crypto_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"crypto_type":"secp256k1"}"#).unwrap();
+
+            assert_eq!(my_did.from_base58().unwrap().len(), 16);
+            assert_eq!(my_verkey.from_base58().unwrap().len(), 33);
+
+            let message = r#"{"reqId":1496822211362017764}"#;
+            let signed_msg = SignusUtils::sign(wallet_handle, &my_did, message).unwrap();
+            assert!(signed_msg.contains("signature"));
 
             TestUtils::cleanup_storage();
         }
@@ -52,7 +72,7 @@ mod high_cases {
             let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}"#).unwrap();
 
             assert_eq!(my_did, "NcYxiDXkpYi6ov5FcYDi1e");
-            assert_eq!(my_verkey, "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW");
+            assert_eq!(my_verkey, "~B7k8kTMqRfiyWoQYf6fTn4");
 
             TestUtils::cleanup_storage();
         }
@@ -145,6 +165,92 @@ mod high_cases {
         }
     }
 
+    mod replace_keys_start {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_start_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
+            let signature = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+
+            let (new_verkey, _) = SignusUtils::replace_keys_start(wallet_handle, &my_did, "{}").unwrap();
+            assert_ne!(my_verkey, new_verkey);
+
+            let pending_signature = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+            assert_eq!(signature, pending_signature);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_replace_keys_start_works_for_invalid_handle() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = SignusUtils::replace_keys_start(wallet_handle + 1, &my_did, "{}");
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletInvalidHandle);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod replace_keys_apply {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_apply_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
+            let signature = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+
+            SignusUtils::replace_keys_start(wallet_handle, &my_did, "{}").unwrap();
+            SignusUtils::replace_keys_apply(wallet_handle, &my_did).unwrap();
+
+            let new_signature = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+            assert_ne!(signature, new_signature);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod replace_keys_on_ledger {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_on_ledger_works() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_replace_keys_on_ledger_works";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &my_did, Some(&my_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
+
+            let (new_verkey, _) = SignusUtils::replace_keys_on_ledger(wallet_handle, pool_handle, &my_did, "{}").unwrap();
+            assert_ne!(my_verkey, new_verkey);
+
+            let get_nym_request = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response = LedgerUtils::submit_request(pool_handle, &get_nym_request).unwrap();
+            assert!(get_nym_response.contains(&new_verkey));
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
     mod store_their_did {
         use super::*;
 
@@ -198,6 +304,43 @@ mod high_cases {
 
             TestUtils::cleanup_storage();
         }
+
+        #[test]
+        fn sovrin_store_their_did_works_for_abbreviated_verkey() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let identity_json = r#"{"did":"NcYxiDXkpYi6ov5FcYDi1e", "verkey":"~B7k8kTMqRfiyWoQYf6fTn4"}"#;
+            SignusUtils::store_their_did(wallet_handle, identity_json).unwrap();
+
+            let dids_json = SignusUtils::list_their_dids_with_meta(wallet_handle).unwrap();
+            let dids: serde_json::Value = serde_json::from_str(&dids_json).unwrap();
+            assert_eq!(dids[0]["verkey"].as_str().unwrap(), "CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW");
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_store_their_did_works_for_abbreviated_verkey_of_my_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (did, verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let message = r#"{"reqId":1496822211362017764}"#;
+            let signed_msg = SignusUtils::sign(wallet_handle, &did, message).unwrap();
+
+            let their_wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet2", "default").unwrap();
+
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, did, verkey);
+            SignusUtils::store_their_did(their_wallet_handle, &identity_json).unwrap();
+
+            let valid = SignusUtils::verify(their_wallet_handle, -1, &did, &signed_msg).unwrap();
+            assert!(valid);
+
+            TestUtils::cleanup_storage();
+        }
     }
 
     mod sign {
@@ -261,6 +404,88 @@ mod high_cases {
         }
     }
 
+    mod sign_detached {
+        use super::*;
+
+        #[test]
+        fn sovrin_sign_detached_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let message = vec![0, 159, 146, 150, 255];
+
+            let signature = SignusUtils::sign_detached(wallet_handle, &my_did, &message).unwrap();
+            assert_eq!(signature.len(), 64);
+
+            let valid = SignusUtils::verify_detached_by_key(&my_verkey, &message, &signature).unwrap();
+            assert!(valid);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_verify_detached_by_key_works_for_other_message() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let signature = SignusUtils::sign_detached(wallet_handle, &my_did, "message".as_bytes()).unwrap();
+
+            let valid = SignusUtils::verify_detached_by_key(&my_verkey, "other message".as_bytes(), &signature).unwrap();
+            assert!(!valid);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sign_jws {
+        use super::*;
+
+        #[test]
+        fn sovrin_sign_jws_works() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_sign_jws_works";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (did, verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1"}"#).unwrap();
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, did, verkey);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
+
+            let payload = r#"["not", "an", "object"]"#;
+
+            let jws = SignusUtils::sign_jws(wallet_handle, &did, payload.as_bytes()).unwrap();
+            assert_eq!(jws.split('.').count(), 3);
+
+            let valid = SignusUtils::verify(wallet_handle, pool_handle, &did, &jws).unwrap();
+            assert!(valid);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_sign_jws_works_for_verify_by_key() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let jws = SignusUtils::sign_jws(wallet_handle, &my_did, "payload".as_bytes()).unwrap();
+
+            let valid = SignusUtils::verify_by_key(&my_verkey, &jws).unwrap();
+            assert!(valid);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
     mod verify {
         use super::*;
 
@@ -390,140 +615,721 @@ mod high_cases {
 
             TestUtils::cleanup_storage();
         }
-    }
-}
-
-mod medium_cases {
-    use super::*;
-
-    mod create_my_did {
-        use super::*;
 
         #[test]
-        fn sovrin_create_my_did_works_for_invalid_crypto_type() {
+        fn sovrin_verify_works_for_abbreviated_verkey_from_ledger() {
             TestUtils::cleanup_storage();
+            let pool_name = "sovrin_verify_works_for_abbreviated_verkey_from_ledger";
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
 
-            let res = SignusUtils::create_my_did(wallet_handle, r#"{"crypto_type":"type"}"#);
-            assert_eq!(res.unwrap_err(), ErrorCode::SignusUnknownCryptoError);
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            assert!(my_verkey.starts_with("~"));
 
-            TestUtils::cleanup_storage();
-        }
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &my_did, Some(&my_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
 
-        #[test]
-        fn sovrin_create_my_did_works_for_invalid_seed() {
-            TestUtils::cleanup_storage();
+            let get_nym_request = LedgerUtils::build_get_nym_request(&my_did, &my_did).unwrap();
+            let get_nym_response = LedgerUtils::submit_request(pool_handle, &get_nym_request).unwrap();
+            assert!(get_nym_response.contains(&my_verkey));
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let signed_msg = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
 
-            let res = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"seed"}"#);
-            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+            let valid = SignusUtils::verify(wallet_handle, pool_handle, &my_did, &signed_msg).unwrap();
+            assert!(valid);
 
             TestUtils::cleanup_storage();
         }
+    }
+
+    mod encrypt {
+        use super::*;
 
         #[test]
-        fn sovrin_create_my_did_works_for_invalid_did() {
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_encrypt_works_for_pk_cached_in_wallet() {
             TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_works_for_pk_cached_in_wallet";
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
-
-            let res = SignusUtils::create_my_did(wallet_handle, r#"{"did":"invalid_base58_did"}"#);
-            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
 
-            TestUtils::cleanup_storage();
-        }
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
+            let (their_did, their_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1"}"#).unwrap();
 
-        #[test]
-        fn sovrin_create_my_did_works_for_invalid_json() {
-            TestUtils::cleanup_storage();
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, their_did, their_verkey);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, my_did, my_verkey);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (encrypted_msg, nonce) = SignusUtils::encrypt(wallet_handle, pool_handle, &my_did, &their_did, "test message").unwrap();
 
-            let res = SignusUtils::create_my_did(wallet_handle, r#"{"seed":123}"#);
-            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+            let decrypted_msg = SignusUtils::decrypt(wallet_handle, &their_did, &my_did, &encrypted_msg, &nonce).unwrap();
+            assert_eq!("test message", decrypted_msg);
 
             TestUtils::cleanup_storage();
         }
-    }
-
-    mod replace_keys {
-        use super::*;
 
         #[test]
-        fn sovrin_replace_keys_works_for_not_exists_did() {
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_encrypt_works_for_get_pk_from_ledger() {
             TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_works_for_get_pk_from_ledger";
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
-
-            //TODO may be we must return WalletNotFound in case if key not exists in wallet
-            SignusUtils::replace_keys(wallet_handle, "8wZcEriaNLNKtteJvx7f8i", "{}").unwrap();
-
-            TestUtils::cleanup_storage();
-        }
-    }
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+            let their_wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet2", "default").unwrap();
 
-    mod store_their_did {
-        use super::*;
+            let (trustee_did, trustee_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (their_did, their_verkey, _) = SignusUtils::create_my_did(their_wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
 
-        #[test]
-        fn sovrin_store_their_did_works_for_invalid_crypto_type() {
-            TestUtils::cleanup_storage();
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &their_did, Some(&their_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (encrypted_msg, nonce) = SignusUtils::encrypt(wallet_handle, pool_handle, &trustee_did, &their_did, "test message").unwrap();
 
-            let identity_json = r#"{"did":"8wZcEriaNLNKtteJvx7f8i", "crypto_type":"type"}"#;
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, trustee_did, trustee_verkey);
+            SignusUtils::store_their_did(their_wallet_handle, &identity_json).unwrap();
 
-            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
-            assert_eq!(res.unwrap_err(), ErrorCode::SignusUnknownCryptoError);
+            let decrypted_msg = SignusUtils::decrypt(their_wallet_handle, &their_did, &trustee_did, &encrypted_msg, &nonce).unwrap();
+            assert_eq!("test message", decrypted_msg);
 
             TestUtils::cleanup_storage();
         }
 
         #[test]
-        fn sovrin_store_their_did_works_for_invalid_did() {
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_encrypt_works_for_their_did_without_pk() {
             TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_works_for_their_did_without_pk";
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
 
-            let identity_json = r#"{"did":"invalid_base58_string"}"#;
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
 
-            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
-            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &my_did, Some(&my_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
+
+            let identity_json = format!(r#"{{"did":"{}"}}"#, my_did);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
+
+            SignusUtils::encrypt(wallet_handle, pool_handle, &trustee_did, &my_did, "test message").unwrap();
 
             TestUtils::cleanup_storage();
         }
 
         #[test]
-        fn sovrin_store_their_did_works_for_invalid_verkey() {
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_encrypt_works_for_invalid_wallet_handle() {
             TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_works_for_invalid_wallet_handle";
 
-            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
 
-            let identity_json = r#"{"did":"did", "verkey":"verkey"}"#;
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
 
-            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
-            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+            let res = SignusUtils::encrypt(wallet_handle + 1, pool_handle, &my_did, &my_did, "test message");
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletInvalidHandle);
 
             TestUtils::cleanup_storage();
         }
     }
 
-    mod sign {
+    mod encrypt_sealed {
         use super::*;
 
         #[test]
-        fn sovrin_sign_works_for_invalid_message() {
+        fn sovrin_encrypt_sealed_works_for_pk_cached_in_wallet() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_sealed_works_for_pk_cached_in_wallet";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, my_did, my_verkey);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
+
+            let encrypted_msg = SignusUtils::encrypt_sealed(wallet_handle, pool_handle, &my_did, "test message").unwrap();
+
+            let decrypted_msg = SignusUtils::decrypt_sealed(wallet_handle, &my_did, &encrypted_msg).unwrap();
+            assert_eq!("test message", decrypted_msg);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_encrypt_sealed_works_for_get_pk_from_ledger() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_sealed_works_for_get_pk_from_ledger";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+            let their_wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet2", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (their_did, their_verkey, _) = SignusUtils::create_my_did(their_wallet_handle, "{}").unwrap();
+
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &their_did, Some(&their_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
+
+            let anonymous_wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet3", "default").unwrap();
+            let encrypted_msg = SignusUtils::encrypt_sealed(anonymous_wallet_handle, pool_handle, &their_did, "test message").unwrap();
+
+            let decrypted_msg = SignusUtils::decrypt_sealed(their_wallet_handle, &their_did, &encrypted_msg).unwrap();
+            assert_eq!("test message", decrypted_msg);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod encrypt_envelope {
+        use super::*;
+
+        #[test]
+        fn sovrin_encrypt_envelope_works_for_several_recipients() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (first_did, first_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let (second_did, second_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            SignusUtils::store_their_did(wallet_handle, &format!(r#"{{"did":"{}", "verkey":"{}"}}"#, first_did, first_verkey)).unwrap();
+            SignusUtils::store_their_did(wallet_handle, &format!(r#"{{"did":"{}", "verkey":"{}"}}"#, second_did, second_verkey)).unwrap();
+
+            let message = "test message";
+            let dids_json = format!(r#"["{}", "{}"]"#, first_did, second_did);
+
+            let envelope_json = SignusUtils::encrypt_envelope(wallet_handle, &dids_json, message).unwrap();
+            let envelope: serde_json::Value = serde_json::from_str(&envelope_json).unwrap();
+            assert_eq!(envelope["recipients"].as_array().unwrap().len(), 2);
+
+            assert_eq!(message, SignusUtils::decrypt_envelope(wallet_handle, &first_did, &envelope_json).unwrap());
+            assert_eq!(message, SignusUtils::decrypt_envelope(wallet_handle, &second_did, &envelope_json).unwrap());
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod keys {
+        use super::*;
+        use rust_base58::FromBase58;
+
+        #[test]
+        fn sovrin_create_key_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let verkey = SignusUtils::create_key(wallet_handle, "{}").unwrap();
+            assert_eq!(verkey.from_base58().unwrap().len(), 32);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_sign_verify_by_key_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let verkey = SignusUtils::create_key(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
+
+            let signed_msg = SignusUtils::sign_by_key(wallet_handle, &verkey, r#"{"reqId":1496822211362017764}"#).unwrap();
+
+            let valid = SignusUtils::verify_by_key(&verkey, &signed_msg).unwrap();
+            assert!(valid);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_sign_by_key_works_for_key_of_my_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let signed_msg = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+            let signed_by_key_msg = SignusUtils::sign_by_key(wallet_handle, &my_verkey, r#"{"reqId":1496822211362017764}"#).unwrap();
+            assert_eq!(signed_msg, signed_by_key_msg);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_encrypt_decrypt_by_key_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let my_verkey = SignusUtils::create_key(wallet_handle, "{}").unwrap();
+            let their_verkey = SignusUtils::create_key(wallet_handle, "{}").unwrap();
+
+            let (encrypted_msg, nonce) = SignusUtils::encrypt_by_key(wallet_handle, &my_verkey, &their_verkey, "test message").unwrap();
+
+            let decrypted_msg = SignusUtils::decrypt_by_key(wallet_handle, &their_verkey, &my_verkey, &encrypted_msg, &nonce).unwrap();
+            assert_eq!("test message", decrypted_msg);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_encrypt_decrypt_by_key_works_for_abbreviated_verkeys_of_my_dids() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (_, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let (_, their_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            assert!(my_verkey.starts_with("~"));
+            assert!(their_verkey.starts_with("~"));
+
+            let (encrypted_msg, nonce) = SignusUtils::encrypt_by_key(wallet_handle, &my_verkey, &their_verkey, "test message").unwrap();
+
+            let decrypted_msg = SignusUtils::decrypt_by_key(wallet_handle, &their_verkey, &my_verkey, &encrypted_msg, &nonce).unwrap();
+            assert_eq!("test message", decrypted_msg);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod did_metadata {
+        use super::*;
+
+        #[test]
+        fn sovrin_list_my_dids_with_meta_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let dids_json = SignusUtils::list_my_dids_with_meta(wallet_handle).unwrap();
+            let dids: serde_json::Value = serde_json::from_str(&dids_json).unwrap();
+            let dids = dids.as_array().unwrap();
+
+            assert_eq!(dids.len(), 1);
+            assert_eq!(dids[0]["did"].as_str().unwrap(), my_did);
+            assert_eq!(dids[0]["verkey"].as_str().unwrap(), my_verkey);
+            assert!(dids[0]["metadata"].is_null());
+
+            let mut keys: Vec<&String> = dids[0].as_object().unwrap().keys().collect();
+            keys.sort();
+            assert_eq!(keys, vec!["did", "metadata", "verkey"]);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_did_metadata_works_for_my_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let metadata = r#"{"label":"work"}"#;
+
+            SignusUtils::set_did_metadata(wallet_handle, &my_did, metadata).unwrap();
+
+            let stored_metadata = SignusUtils::get_did_metadata(wallet_handle, &my_did).unwrap();
+            assert_eq!(metadata, stored_metadata);
+
+            let dids_json = SignusUtils::list_my_dids_with_meta(wallet_handle).unwrap();
+            let dids: serde_json::Value = serde_json::from_str(&dids_json).unwrap();
+            assert_eq!(dids[0]["metadata"].as_str().unwrap(), metadata);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_did_metadata_works_for_replace() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            SignusUtils::set_did_metadata(wallet_handle, &my_did, r#"{"label":"work"}"#).unwrap();
+            SignusUtils::set_did_metadata(wallet_handle, &my_did, r#"{"label":"home"}"#).unwrap();
+
+            let stored_metadata = SignusUtils::get_did_metadata(wallet_handle, &my_did).unwrap();
+            assert_eq!(r#"{"label":"home"}"#, stored_metadata);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_list_their_dids_with_meta_works() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let their_did = "8wZcEriaNLNKtteJvx7f8i";
+            let their_verkey = "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL";
+            let identity_json = format!(r#"{{"did":"{}","verkey":"{}"}}"#, their_did, their_verkey);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
+            SignusUtils::set_did_metadata(wallet_handle, their_did, r#"{"label":"friend"}"#).unwrap();
+
+            let dids_json = SignusUtils::list_their_dids_with_meta(wallet_handle).unwrap();
+            let dids: serde_json::Value = serde_json::from_str(&dids_json).unwrap();
+            let dids = dids.as_array().unwrap();
+
+            assert_eq!(dids.len(), 1);
+            assert_eq!(dids[0]["did"].as_str().unwrap(), their_did);
+            assert_eq!(dids[0]["verkey"].as_str().unwrap(), their_verkey);
+            assert_eq!(dids[0]["metadata"].as_str().unwrap(), r#"{"label":"friend"}"#);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod endpoint_for_did {
+        use super::*;
+
+        #[test]
+        fn sovrin_set_endpoint_for_did_works_for_wallet_only() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (did, _, pk) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            SignusUtils::set_endpoint_for_did(wallet_handle, -1, &did, "127.0.0.1:9700", Some(&pk)).unwrap();
+
+            let (address, transport_key) = SignusUtils::get_endpoint_for_did(wallet_handle, -1, &did).unwrap();
+            assert_eq!("127.0.0.1:9700", address);
+            assert_eq!(pk, transport_key);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_endpoint_for_did_works_without_transport_key() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            SignusUtils::set_endpoint_for_did(wallet_handle, -1, &did, "127.0.0.1:9700", None).unwrap();
+
+            let (address, transport_key) = SignusUtils::get_endpoint_for_did(wallet_handle, -1, &did).unwrap();
+            assert_eq!("127.0.0.1:9700", address);
+            assert_eq!("", transport_key);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_endpoint_for_did_works_with_ledger() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_set_endpoint_for_did_works_with_ledger";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+            let other_wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet2", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+            let (my_did, my_verkey, my_pk) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"00000000000000000000000000000My1"}"#).unwrap();
+
+            let nym_request = LedgerUtils::build_nym_request(&trustee_did, &my_did, Some(&my_verkey), None, None).unwrap();
+            LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &trustee_did, &nym_request).unwrap();
+
+            SignusUtils::set_endpoint_for_did(wallet_handle, pool_handle, &my_did, "127.0.0.1:9700", Some(&my_pk)).unwrap();
+
+            let (address, transport_key) = SignusUtils::get_endpoint_for_did(other_wallet_handle, pool_handle, &my_did).unwrap();
+            assert_eq!("127.0.0.1:9700", address);
+            assert_eq!(my_pk, transport_key);
+
+            // Endpoint read from the ledger is stored in the wallet
+            let (address, _) = SignusUtils::get_endpoint_for_did(other_wallet_handle, -1, &my_did).unwrap();
+            assert_eq!("127.0.0.1:9700", address);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+}
+
+mod medium_cases {
+    use super::*;
+
+    mod create_my_did {
+        use super::*;
+
+        #[test]
+        fn sovrin_create_my_did_works_for_invalid_crypto_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::create_my_did(wallet_handle, r#"{"crypto_type":"type"}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::SignusUnknownCryptoError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_create_my_did_works_for_invalid_seed() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"seed"}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_create_my_did_works_for_invalid_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::create_my_did(wallet_handle, r#"{"did":"invalid_base58_did"}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_create_my_did_works_for_invalid_json() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::create_my_did(wallet_handle, r#"{"seed":123}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod replace_keys {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_works_for_not_exists_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            //TODO may be we must return WalletNotFound in case if key not exists in wallet
+            SignusUtils::replace_keys(wallet_handle, "8wZcEriaNLNKtteJvx7f8i", "{}").unwrap();
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod replace_keys_start {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_start_works_for_not_exists_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::replace_keys_start(wallet_handle, "8wZcEriaNLNKtteJvx7f8i", "{}");
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod replace_keys_apply {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_apply_works_without_pending_keys() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = SignusUtils::replace_keys_apply(wallet_handle, &my_did);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod replace_keys_on_ledger {
+        use super::*;
+
+        #[test]
+        fn sovrin_replace_keys_on_ledger_keeps_keys_for_rejected_nym() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_replace_keys_on_ledger_keeps_keys_for_rejected_nym";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let signature = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+
+            let res = SignusUtils::replace_keys_on_ledger(wallet_handle, pool_handle, &my_did, "{}");
+            assert_eq!(res.unwrap_err(), ErrorCode::LedgerInvalidTransaction);
+
+            let current_signature = SignusUtils::sign(wallet_handle, &my_did, r#"{"reqId":1496822211362017764}"#).unwrap();
+            assert_eq!(signature, current_signature);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod store_their_did {
+        use super::*;
+
+        #[test]
+        fn sovrin_store_their_did_works_for_invalid_crypto_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let identity_json = r#"{"did":"8wZcEriaNLNKtteJvx7f8i", "crypto_type":"type"}"#;
+
+            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
+            assert_eq!(res.unwrap_err(), ErrorCode::SignusUnknownCryptoError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_store_their_did_works_for_invalid_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let identity_json = r#"{"did":"invalid_base58_string"}"#;
+
+            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_store_their_did_works_for_invalid_verkey() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let identity_json = r#"{"did":"did", "verkey":"verkey"}"#;
+
+            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_store_their_did_works_for_invalid_abbreviated_verkey() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let identity_json = r#"{"did":"8wZcEriaNLNKtteJvx7f8i", "verkey":"~NcYxiDXkpYi6ov5FcYDi1e0"}"#;
+            let res = SignusUtils::store_their_did(wallet_handle, identity_json);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sign {
+        use super::*;
+
+        #[test]
+        fn sovrin_sign_works_for_invalid_message() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{}"#).unwrap();
+
+            let message = r#"1495034346617224651"#;
+
+            let res = SignusUtils::sign(wallet_handle, &my_did, message);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sign_detached {
+        use super::*;
+
+        #[test]
+        fn sovrin_sign_detached_works_for_unknown_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::sign_detached(wallet_handle, "8wZcEriaNLNKtteJvx7f8i", "message".as_bytes());
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_sign_detached_works_for_empty_message() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"cid":true}"#).unwrap();
+
+            let signature = SignusUtils::sign_detached(wallet_handle, &my_did, &[]).unwrap();
+
+            let valid = SignusUtils::verify_detached_by_key(&my_verkey, &[], &signature).unwrap();
+            assert!(valid);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod sign_jws {
+        use super::*;
+
+        #[test]
+        fn sovrin_verify_works_for_invalid_jws() {
             TestUtils::cleanup_storage();
 
             let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
 
-            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{}"#).unwrap();
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
 
-            let message = r#"1495034346617224651"#;
+            let jws = SignusUtils::sign_jws(wallet_handle, &my_did, "payload".as_bytes()).unwrap();
+            let parts: Vec<&str> = jws.split('.').collect();
 
-            let res = SignusUtils::sign(wallet_handle, &my_did, message);
+            let res = SignusUtils::verify_by_key(&my_verkey, &format!("{}.{}.{}", parts[0], parts[1], "not+base64"));
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            let res = SignusUtils::verify_by_key(&my_verkey, &format!("{}.{}", parts[0], parts[1]));
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "secp256k1_rust")]
+        fn sovrin_sign_jws_works_for_secp256k1_crypto_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"crypto_type":"secp256k1"}"#).unwrap();
+
+            let res = SignusUtils::sign_jws(wallet_handle, &my_did, "payload".as_bytes());
             assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
 
             TestUtils::cleanup_storage();
@@ -648,4 +1454,301 @@ mod medium_cases {
             TestUtils::cleanup_storage();
         }
     }
+
+    mod encrypt {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_encrypt_works_for_unknown_nym() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_works_for_unknown_nym";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let (their_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"0000000000000000000000000000Fake"}"#).unwrap();
+
+            let res = SignusUtils::encrypt(wallet_handle, pool_handle, &my_did, &their_did, "test message");
+            assert_eq!(res.unwrap_err(), ErrorCode::LedgerNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        #[cfg(feature = "local_nodes_pool")]
+        fn sovrin_encrypt_works_for_unknown_my_did() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_works_for_unknown_my_did";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let res = SignusUtils::encrypt(wallet_handle, pool_handle, "8wZcEriaNLNKtteJvx7f8i", &trustee_did, "test message");
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod encrypt_sealed {
+        use super::*;
+
+        #[test]
+        fn sovrin_encrypt_sealed_works_for_unknown_nym() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_encrypt_sealed_works_for_unknown_nym";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let res = SignusUtils::encrypt_sealed(wallet_handle, pool_handle, "8wZcEriaNLNKtteJvx7f8i", "test message");
+            assert_eq!(res.unwrap_err(), ErrorCode::LedgerNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_decrypt_sealed_works_for_other_did() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_decrypt_sealed_works_for_other_did";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (my_did, my_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let (other_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let identity_json = format!(r#"{{"did":"{}", "verkey":"{}"}}"#, my_did, my_verkey);
+            SignusUtils::store_their_did(wallet_handle, &identity_json).unwrap();
+
+            let encrypted_msg = SignusUtils::encrypt_sealed(wallet_handle, pool_handle, &my_did, "test message").unwrap();
+
+            let res = SignusUtils::decrypt_sealed(wallet_handle, &other_did, &encrypted_msg);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_decrypt_sealed_works_for_unknown_my_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::decrypt_sealed(wallet_handle, "8wZcEriaNLNKtteJvx7f8i", "encrypted");
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod encrypt_envelope {
+        use super::*;
+
+        #[test]
+        fn sovrin_encrypt_envelope_works_for_unknown_their_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::encrypt_envelope(wallet_handle, r#"["8wZcEriaNLNKtteJvx7f8i"]"#, "test message");
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_encrypt_envelope_works_for_invalid_dids_json() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::encrypt_envelope(wallet_handle, r#"{"did":"8wZcEriaNLNKtteJvx7f8i"}"#, "test message");
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_decrypt_envelope_works_for_not_recipient() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (recipient_did, recipient_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+            let (other_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            SignusUtils::store_their_did(wallet_handle, &format!(r#"{{"did":"{}", "verkey":"{}"}}"#, recipient_did, recipient_verkey)).unwrap();
+
+            let envelope_json = SignusUtils::encrypt_envelope(wallet_handle, &format!(r#"["{}"]"#, recipient_did), "test message").unwrap();
+
+            let res = SignusUtils::decrypt_envelope(wallet_handle, &other_did, &envelope_json);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod keys {
+        use super::*;
+
+        #[test]
+        fn sovrin_create_key_works_for_invalid_json() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::create_key(wallet_handle, r#"{"seed":1}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_create_key_works_for_unknown_crypto_type() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::create_key(wallet_handle, r#"{"crypto_type":"type"}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::SignusUnknownCryptoError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_sign_by_key_works_for_unknown_key() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::sign_by_key(wallet_handle, "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL", r#"{"reqId":1496822211362017764}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_sign_by_key_works_for_unknown_abbreviated_verkey() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = SignusUtils::sign_by_key(wallet_handle, "~B7k8kTMqRfiyWoQYf6fTn4", r#"{"reqId":1496822211362017764}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_verify_by_key_works_for_other_key() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let verkey = SignusUtils::create_key(wallet_handle, "{}").unwrap();
+            let other_verkey = SignusUtils::create_key(wallet_handle, "{}").unwrap();
+
+            let signed_msg = SignusUtils::sign_by_key(wallet_handle, &verkey, r#"{"reqId":1496822211362017764}"#).unwrap();
+
+            let valid = SignusUtils::verify_by_key(&other_verkey, &signed_msg).unwrap();
+            assert!(!valid);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod did_metadata {
+        use super::*;
+
+        #[test]
+        fn sovrin_get_did_metadata_works_for_no_metadata() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = SignusUtils::get_did_metadata(wallet_handle, &my_did);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_did_metadata_works_for_unknown_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::set_did_metadata(wallet_handle, "8wZcEriaNLNKtteJvx7f8i", r#"{"label":"work"}"#);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_did_metadata_works_for_invalid_json() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let (my_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = SignusUtils::set_did_metadata(wallet_handle, &my_did, "not a json");
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+    }
+
+    mod endpoint_for_did {
+        use super::*;
+
+        #[test]
+        fn sovrin_get_endpoint_for_did_works_for_unknown_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+            let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+            let res = SignusUtils::get_endpoint_for_did(wallet_handle, -1, &did);
+            assert_eq!(res.unwrap_err(), ErrorCode::WalletNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_set_endpoint_for_did_works_for_invalid_did() {
+            TestUtils::cleanup_storage();
+
+            let wallet_handle = WalletUtils::create_and_open_wallet("pool1", "wallet1", "default").unwrap();
+
+            let res = SignusUtils::set_endpoint_for_did(wallet_handle, -1, "invalid_base58_0OIl", "127.0.0.1:9700", None);
+            assert_eq!(res.unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+            TestUtils::cleanup_storage();
+        }
+
+        #[test]
+        fn sovrin_get_endpoint_for_did_works_for_did_without_endpoint_on_ledger() {
+            TestUtils::cleanup_storage();
+            let pool_name = "sovrin_get_endpoint_for_did_works_for_did_without_endpoint_on_ledger";
+
+            let pool_handle = PoolUtils::create_and_open_pool_ledger_config(pool_name).unwrap();
+            let wallet_handle = WalletUtils::create_and_open_wallet(pool_name, "wallet1", "default").unwrap();
+
+            let (trustee_did, _, _) = SignusUtils::create_my_did(wallet_handle, r#"{"seed":"000000000000000000000000Trustee1","cid":true}"#).unwrap();
+
+            let res = SignusUtils::get_endpoint_for_did(wallet_handle, pool_handle, &trustee_did);
+            assert_eq!(res.unwrap_err(), ErrorCode::LedgerNotFoundError);
+
+            TestUtils::cleanup_storage();
+        }
+    }
 }
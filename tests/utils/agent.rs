@@ -1,12 +1,33 @@
+extern crate base64;
+extern crate serde_json;
+
 use std::sync::mpsc::{channel};
 use std::ffi::{CString};
+use std::ptr::null;
 
 use sovrin::api::agent::{
+    sovrin_agent_accept_invitation,
+    sovrin_agent_add_identity,
+    sovrin_agent_claim_exchanges,
+    sovrin_agent_claim_issue,
+    sovrin_agent_claim_offer,
+    sovrin_agent_claim_request,
+    sovrin_agent_claim_subscribe,
     sovrin_agent_close_connection,
     sovrin_agent_close_listener,
     sovrin_agent_connect,
+    sovrin_agent_create_invitation,
     sovrin_agent_listen,
+    sovrin_agent_mailbox_forward,
+    sovrin_agent_mailbox_host,
+    sovrin_agent_mailbox_pull,
+    sovrin_agent_proof_exchanges,
+    sovrin_agent_proof_present,
+    sovrin_agent_proof_request,
+    sovrin_agent_proof_subscribe,
+    sovrin_agent_remove_identity,
     sovrin_agent_send,
+    sovrin_agent_send_typed,
 };
 use sovrin::api::ErrorCode;
 
@@ -18,6 +39,25 @@ pub struct AgentUtils {}
 impl AgentUtils {
     pub fn connect(pool_handle: i32, wallet_handle: i32, sender_did: &str, receiver_did: &str,
                    on_msg: Option<Box<Fn(i32, String) + Send>>) -> Result<i32, ErrorCode> {
+        AgentUtils::connect_with_options(pool_handle, wallet_handle, sender_did, receiver_did, None, on_msg, None)
+    }
+
+    pub fn connect_with_options(pool_handle: i32, wallet_handle: i32, sender_did: &str, receiver_did: &str,
+                                options_json: Option<&str>,
+                                on_msg: Option<Box<Fn(i32, String) + Send>>,
+                                on_delivery: Option<Box<Fn(i32, ErrorCode, i32) + Send>>) -> Result<i32, ErrorCode> {
+        AgentUtils::_connect(pool_handle, wallet_handle, Some(sender_did), receiver_did, options_json, on_msg, on_delivery)
+    }
+
+    pub fn connect_pairwise(pool_handle: i32, wallet_handle: i32, their_did: &str,
+                            on_msg: Option<Box<Fn(i32, String) + Send>>) -> Result<i32, ErrorCode> {
+        AgentUtils::_connect(pool_handle, wallet_handle, None, their_did, None, on_msg, None)
+    }
+
+    fn _connect(pool_handle: i32, wallet_handle: i32, sender_did: Option<&str>, receiver_did: &str,
+                options_json: Option<&str>,
+                on_msg: Option<Box<Fn(i32, String) + Send>>,
+                on_delivery: Option<Box<Fn(i32, ErrorCode, i32) + Send>>) -> Result<i32, ErrorCode> {
         let (sender, receiver) = channel();
         let closure = Box::new(move |err, connection_handle| { sender.send((err, connection_handle)).unwrap(); });
         let (cmd_connect, cb) = CallbackUtils::closure_to_agent_connect_cb(closure);
@@ -27,11 +67,20 @@ impl AgentUtils {
                 on_msg(conn_handle, msg);
             }
         })); //TODO make as parameter?
+        let delivery_cb = on_delivery.map(|on_delivery| CallbackUtils::closure_to_agent_delivery_cb(Box::new(move |conn_handle, err, msg_id| {
+            info!("On connection {} message {} delivered with result {:?}", conn_handle, msg_id, err);
+            on_delivery(conn_handle, err, msg_id);
+        })));
+
+        let sender_did_str = sender_did.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+        let options_json_str = options_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
 
         let err = sovrin_agent_connect(cmd_connect, pool_handle, wallet_handle,
-                                       CString::new(sender_did).unwrap().as_ptr(),
+                                       if sender_did.is_some() { sender_did_str.as_ptr() } else { null() },
                                        CString::new(receiver_did).unwrap().as_ptr(),
-                                       cb, msg_cb);
+                                       if options_json.is_some() { options_json_str.as_ptr() } else { null() },
+                                       cb, msg_cb,
+                                       delivery_cb.and_then(|(_, delivery_cb)| delivery_cb));
         if err != ErrorCode::Success {
             return Err(err);
         }
@@ -41,13 +90,24 @@ impl AgentUtils {
             return Err(err);
         }
         CallbackUtils::closure_map_ids(cb_id, conn_handle);
+        if let Some((delivery_cb_id, _)) = delivery_cb {
+            CallbackUtils::closure_map_delivery_ids(delivery_cb_id, conn_handle);
+        }
 
         Ok(conn_handle)
     }
 
-    pub fn listen(wallet_handle: i32, endpoint: &str,
+    pub fn listen(pool_handle: i32, wallet_handle: i32, endpoint: &str,
                   on_connect: Option<Box<Fn(i32, i32) + Send>>,
                   on_msg: Option<Box<Fn(i32, String) + Send>>) -> Result<i32, ErrorCode> {
+        AgentUtils::listen_with_options(pool_handle, wallet_handle, endpoint, None, None, on_connect, on_msg, None)
+    }
+
+    pub fn listen_with_options(pool_handle: i32, wallet_handle: i32, endpoint: &str,
+                               identities_json: Option<&str>, policy_json: Option<&str>,
+                               on_connect: Option<Box<Fn(i32, i32) + Send>>,
+                               on_msg: Option<Box<Fn(i32, String) + Send>>,
+                               on_event: Option<Box<Fn(i32, String) + Send>>) -> Result<i32, ErrorCode> {
         let (sender, receiver) = channel();
         let on_msg = Box::new(move |conn_handle, err, msg| {
             info!("On connection {} received (with error {:?}) agent message (CLI->SRV): {}", conn_handle, err, msg);
@@ -66,10 +126,24 @@ impl AgentUtils {
         });
         let (on_connect_cb_id, on_connect) = CallbackUtils::closure_to_agent_connected_cb(on_connect);
 
+        let on_event = Box::new(move |listener_handle, err, event_json| {
+            info!("Event on listener {}, err {:?}: {}", listener_handle, err, event_json);
+            if let Some(ref on_event) = on_event {
+                on_event(listener_handle, event_json);
+            }
+        });
+        let (on_event_cb_id, on_event) = CallbackUtils::closure_to_agent_listener_event_cb(on_event);
+
         let cb = Box::new(move |err, listener_handle| sender.send((err, listener_handle)).unwrap());
         let (cmd_id, cb) = CallbackUtils::closure_to_agent_listen_cb(cb);
 
-        let res = sovrin_agent_listen(cmd_id, wallet_handle, CString::new(endpoint).unwrap().as_ptr(), cb, on_connect, on_msg);
+        let identities_json_str = identities_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+        let policy_json_str = policy_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let res = sovrin_agent_listen(cmd_id, pool_handle, wallet_handle, CString::new(endpoint).unwrap().as_ptr(),
+                                      if identities_json.is_some() { identities_json_str.as_ptr() } else { null() },
+                                      if policy_json.is_some() { policy_json_str.as_ptr() } else { null() },
+                                      cb, on_connect, on_msg, on_event, None);
 
         if res != ErrorCode::Success {
             return Err(res);
@@ -77,6 +151,7 @@ impl AgentUtils {
 
         let (res, listener_handle) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
         CallbackUtils::closure_map_ids(on_connect_cb_id, listener_handle);
+        CallbackUtils::closure_map_listener_event_ids(on_event_cb_id, listener_handle);
         if res != ErrorCode::Success {
             return Err(res);
         }
@@ -84,10 +159,10 @@ impl AgentUtils {
         Ok(listener_handle)
     }
 
-    pub fn send(conn_handle: i32, msg: &str) -> Result<(), ErrorCode> {
+    pub fn send(conn_handle: i32, msg: &str) -> Result<i32, ErrorCode> {
         let (send_sender, send_receiver) = channel();
         let (send_cmd_id, send_cb) = CallbackUtils::closure_to_agent_send_cb(
-            Box::new(move |err_code| send_sender.send(err_code).unwrap())
+            Box::new(move |err_code, msg_id| send_sender.send((err_code, msg_id)).unwrap())
         );
 
         let res = sovrin_agent_send(send_cmd_id, conn_handle, CString::new(msg).unwrap().as_ptr(), send_cb);
@@ -95,14 +170,368 @@ impl AgentUtils {
             return Err(res);
         }
 
-        let res = send_receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        let (res, msg_id) = send_receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
         if res != ErrorCode::Success {
             return Err(res)
         }
 
+        Ok(msg_id)
+    }
+
+    pub fn send_typed(conn_handle: i32, msg_type: &str, thread_id: Option<&str>, body: &[u8], sign: bool) -> Result<i32, ErrorCode> {
+        let (send_sender, send_receiver) = channel();
+        let (send_cmd_id, send_cb) = CallbackUtils::closure_to_agent_send_cb(
+            Box::new(move |err_code, msg_id| send_sender.send((err_code, msg_id)).unwrap())
+        );
+
+        let thread_id_str = thread_id.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let res = sovrin_agent_send_typed(send_cmd_id,
+                                          conn_handle,
+                                          CString::new(msg_type).unwrap().as_ptr(),
+                                          if thread_id.is_some() { thread_id_str.as_ptr() } else { null() },
+                                          body.as_ptr() as *const u8,
+                                          body.len() as u32,
+                                          sign,
+                                          send_cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, msg_id) = send_receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res)
+        }
+
+        Ok(msg_id)
+    }
+
+    /// Returns decoded body of received message envelope.
+    pub fn envelope_body(envelope_json: &str) -> Vec<u8> {
+        let envelope: serde_json::Value = serde_json::from_str(envelope_json).unwrap();
+        base64::decode_config(envelope["body"].as_str().unwrap(), base64::URL_SAFE_NO_PAD).unwrap()
+    }
+
+    pub fn message_body(envelope_json: &str) -> String {
+        String::from_utf8(AgentUtils::envelope_body(envelope_json)).unwrap()
+    }
+
+    pub fn mailbox_host(wallet_handle: i32, did: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_mailbox_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let res = sovrin_agent_mailbox_host(cmd_id, wallet_handle, CString::new(did).unwrap().as_ptr(), cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
+    pub fn mailbox_forward(conn_handle: i32, wallet_handle: i32, receiver_did: &str, msg: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_mailbox_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let res = sovrin_agent_mailbox_forward(cmd_id, conn_handle, wallet_handle,
+                                               CString::new(receiver_did).unwrap().as_ptr(),
+                                               CString::new(msg).unwrap().as_ptr(), cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
         Ok(())
     }
 
+    pub fn mailbox_pull(conn_handle: i32, wallet_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_mailbox_pull_cb(Box::new(move |res, messages_json| {
+            sender.send((res, messages_json)).unwrap();
+        }));
+
+        let res = sovrin_agent_mailbox_pull(cmd_id, conn_handle, wallet_handle, cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, messages_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(messages_json)
+    }
+
+    pub fn claim_subscribe(wallet_handle: i32, options_json: Option<&str>, on_event: Box<Fn(String) + Send>) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_claim_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let on_event = Box::new(move |wallet_handle, err, event_json| {
+            info!("Claim exchange event of wallet {}, err {:?}: {}", wallet_handle, err, event_json);
+            on_event(event_json);
+        });
+        let (on_event_cb_id, on_event) = CallbackUtils::closure_to_agent_claim_event_cb(on_event);
+        CallbackUtils::closure_map_claim_event_ids(on_event_cb_id, wallet_handle);
+
+        let options_json_str = options_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let res = sovrin_agent_claim_subscribe(cmd_id, wallet_handle,
+                                               if options_json.is_some() { options_json_str.as_ptr() } else { null() },
+                                               cb, on_event);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
+    pub fn claim_offer(conn_handle: i32, claim_offer_json: &str, claim_def_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_claim_json_cb(Box::new(move |res, exchange_id| {
+            sender.send((res, exchange_id)).unwrap();
+        }));
+
+        let res = sovrin_agent_claim_offer(cmd_id, conn_handle,
+                                           CString::new(claim_offer_json).unwrap().as_ptr(),
+                                           CString::new(claim_def_json).unwrap().as_ptr(),
+                                           cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, exchange_id) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(exchange_id)
+    }
+
+    pub fn claim_request(wallet_handle: i32, exchange_id: &str, master_secret_name: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_claim_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let res = sovrin_agent_claim_request(cmd_id, wallet_handle,
+                                             CString::new(exchange_id).unwrap().as_ptr(),
+                                             CString::new(master_secret_name).unwrap().as_ptr(),
+                                             cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
+    pub fn claim_issue(wallet_handle: i32, exchange_id: &str, claim_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_claim_json_cb(Box::new(move |res, revoc_reg_update_json| {
+            sender.send((res, revoc_reg_update_json)).unwrap();
+        }));
+
+        let res = sovrin_agent_claim_issue(cmd_id, wallet_handle,
+                                           CString::new(exchange_id).unwrap().as_ptr(),
+                                           CString::new(claim_json).unwrap().as_ptr(),
+                                           -1, -1, cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, revoc_reg_update_json) = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(revoc_reg_update_json)
+    }
+
+    pub fn claim_exchanges(wallet_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_claim_json_cb(Box::new(move |res, exchanges_json| {
+            sender.send((res, exchanges_json)).unwrap();
+        }));
+
+        let res = sovrin_agent_claim_exchanges(cmd_id, wallet_handle, cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, exchanges_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(exchanges_json)
+    }
+
+    pub fn proof_subscribe(pool_handle: i32, wallet_handle: i32, on_event: Box<Fn(String) + Send>) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_proof_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let on_event = Box::new(move |wallet_handle, err, event_json| {
+            info!("Proof exchange event of wallet {}, err {:?}: {}", wallet_handle, err, event_json);
+            on_event(event_json);
+        });
+        let (on_event_cb_id, on_event) = CallbackUtils::closure_to_agent_proof_event_cb(on_event);
+        CallbackUtils::closure_map_proof_event_ids(on_event_cb_id, wallet_handle);
+
+        let res = sovrin_agent_proof_subscribe(cmd_id, pool_handle, wallet_handle, cb, on_event);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
+    pub fn proof_request(conn_handle: i32, proof_req_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_proof_json_cb(Box::new(move |res, exchange_id| {
+            sender.send((res, exchange_id)).unwrap();
+        }));
+
+        let res = sovrin_agent_proof_request(cmd_id, conn_handle, CString::new(proof_req_json).unwrap().as_ptr(), cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, exchange_id) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(exchange_id)
+    }
+
+    pub fn proof_present(wallet_handle: i32, exchange_id: &str, requested_claims_json: &str, master_secret_name: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_proof_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let res = sovrin_agent_proof_present(cmd_id, wallet_handle,
+                                             CString::new(exchange_id).unwrap().as_ptr(),
+                                             CString::new(requested_claims_json).unwrap().as_ptr(),
+                                             CString::new(master_secret_name).unwrap().as_ptr(),
+                                             cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
+    pub fn proof_exchanges(wallet_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_proof_json_cb(Box::new(move |res, exchanges_json| {
+            sender.send((res, exchanges_json)).unwrap();
+        }));
+
+        let res = sovrin_agent_proof_exchanges(cmd_id, wallet_handle, cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, exchanges_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(exchanges_json)
+    }
+
+    pub fn create_invitation(listener_handle: i32, wallet_handle: i32, label: Option<&str>) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_invitation_cb(Box::new(move |res, invitation_json| {
+            sender.send((res, invitation_json)).unwrap();
+        }));
+
+        let label_str = label.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let res = sovrin_agent_create_invitation(cmd_id, listener_handle, wallet_handle,
+                                                 if label.is_some() { label_str.as_ptr() } else { null() },
+                                                 cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (res, invitation_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(invitation_json)
+    }
+
+    pub fn accept_invitation(wallet_handle: i32, invitation_json: &str, options_json: Option<&str>,
+                             on_msg: Option<Box<Fn(i32, String) + Send>>) -> Result<(i32, String), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_accept_invitation_cb(Box::new(move |err, conn_handle, my_did| {
+            sender.send((err, conn_handle, my_did)).unwrap();
+        }));
+        let (cb_id, msg_cb) = CallbackUtils::closure_to_agent_message_cb(Box::new(move |conn_handle, err, msg| {
+            info!("On connection {} received (with error {:?}) agent message (SRV->CLI): {}", conn_handle, err, msg);
+            if let Some(ref on_msg) = on_msg {
+                on_msg(conn_handle, msg);
+            }
+        }));
+
+        let options_json_str = options_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let res = sovrin_agent_accept_invitation(cmd_id, wallet_handle,
+                                                 CString::new(invitation_json).unwrap().as_ptr(),
+                                                 if options_json.is_some() { options_json_str.as_ptr() } else { null() },
+                                                 cb, msg_cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let (err, conn_handle, my_did) = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+        CallbackUtils::closure_map_ids(cb_id, conn_handle);
+
+        Ok((conn_handle, my_did))
+    }
+
     pub fn close_connection(conn_handle: i32) -> Result<(), ErrorCode> {
         let (sender, receiver) = channel();
         let (cmd_id, cb) = CallbackUtils::closure_to_agent_close_cb(Box::new(move |res| {
@@ -122,6 +551,44 @@ impl AgentUtils {
         Ok(())
     }
 
+    pub fn add_identity(listener_handle: i32, wallet_handle: i32, did: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_identity_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let res = sovrin_agent_add_identity(cmd_id, listener_handle, wallet_handle, CString::new(did).unwrap().as_ptr(), cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_identity(listener_handle: i32, wallet_handle: i32, did: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (cmd_id, cb) = CallbackUtils::closure_to_agent_identity_cb(Box::new(move |res| {
+            sender.send(res).unwrap();
+        }));
+
+        let res = sovrin_agent_remove_identity(cmd_id, listener_handle, wallet_handle, CString::new(did).unwrap().as_ptr(), cb);
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        let res = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if res != ErrorCode::Success {
+            return Err(res);
+        }
+
+        Ok(())
+    }
+
     pub fn close_listener(listener_handle: i32) -> Result<(), ErrorCode> {
         let (sender, receiver) = channel();
         let (cmd_id, cb) = CallbackUtils::closure_to_agent_close_cb(Box::new(move |res| {
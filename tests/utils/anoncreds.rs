@@ -2,6 +2,9 @@ use sovrin::api::ErrorCode;
 use sovrin::api::anoncreds::{
     sovrin_issuer_create_and_store_claim_def,
     sovrin_issuer_create_claim,
+    sovrin_issuer_create_and_store_revoc_reg,
+    sovrin_issuer_revoke_claim,
+    sovrin_issuer_revoke_claim_with_ledger_update,
     sovrin_prover_create_master_secret,
     sovrin_prover_create_and_store_claim_req,
     sovrin_prover_store_claim,
@@ -232,6 +235,104 @@ impl AnoncredsUtils {
         Ok((revoc_reg_update_json, xclaim_json))
     }
 
+    pub fn create_revoc_reg_and_set_link(wallet_handle: i32, schema: &str, claim_def_seq_no: i32, revoc_reg_seq_no: i32) -> Result<String, ErrorCode> {
+        let (_, claim_def_uuid) = AnoncredsUtils::issuer_create_claim_definition(wallet_handle, &schema, None, true)?;
+        WalletUtils::wallet_set_seq_no_for_value(wallet_handle, &claim_def_uuid, claim_def_seq_no)?;
+        let (revoc_reg_json, revoc_reg_uuid) = AnoncredsUtils::issuer_create_and_store_revoc_reg(wallet_handle, claim_def_seq_no, 5)?;
+        WalletUtils::wallet_set_seq_no_for_value(wallet_handle, &revoc_reg_uuid, revoc_reg_seq_no)?;
+        Ok(revoc_reg_json)
+    }
+
+    pub fn issuer_create_and_store_revoc_reg(wallet_handle: i32, claim_def_seq_no: i32, max_claim_num: i32) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, revoc_reg_json, revoc_reg_uuid| {
+            sender.send((err, revoc_reg_json, revoc_reg_uuid)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_issuer_create_and_store_revoc_reg_cb(cb);
+
+        let err = sovrin_issuer_create_and_store_revoc_reg(command_handle,
+                                                           wallet_handle,
+                                                           claim_def_seq_no,
+                                                           max_claim_num,
+                                                           cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, revoc_reg_json, revoc_reg_uuid) = receiver.recv().unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((revoc_reg_json, revoc_reg_uuid))
+    }
+
+    pub fn issuer_revoke_claim(wallet_handle: i32, claim_def_seq_no: i32, revoc_reg_seq_no: i32, user_revoc_index: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, revoc_reg_update_json| {
+            sender.send((err, revoc_reg_update_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_issuer_revoke_claim_cb(cb);
+
+        let err = sovrin_issuer_revoke_claim(command_handle,
+                                             wallet_handle,
+                                             claim_def_seq_no,
+                                             revoc_reg_seq_no,
+                                             user_revoc_index,
+                                             cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, revoc_reg_update_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(revoc_reg_update_json)
+    }
+
+    pub fn issuer_revoke_claim_with_ledger_update(wallet_handle: i32, submitter_did: &str, claim_def_seq_no: i32,
+                                                  revoc_reg_seq_no: i32, user_revoc_index: i32) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, revoc_reg_update_json, revoc_reg_entry_request_json| {
+            sender.send((err, revoc_reg_update_json, revoc_reg_entry_request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_issuer_revoke_claim_with_ledger_update_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+
+        let err = sovrin_issuer_revoke_claim_with_ledger_update(command_handle,
+                                                                wallet_handle,
+                                                                submitter_did.as_ptr(),
+                                                                claim_def_seq_no,
+                                                                revoc_reg_seq_no,
+                                                                user_revoc_index,
+                                                                cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, revoc_reg_update_json, revoc_reg_entry_request_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((revoc_reg_update_json, revoc_reg_entry_request_json))
+    }
+
     pub fn prover_store_claim(wallet_handle: i32, claims_json: &str) -> Result<(), ErrorCode> {
         let (sender, receiver) = channel();
 
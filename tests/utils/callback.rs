@@ -4,6 +4,7 @@ use sovrin::api::ErrorCode;
 
 use self::libc::c_char;
 use std::ffi::CStr;
+use std::slice;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 use std::sync::Mutex;
@@ -17,6 +18,19 @@ lazy_static! {
     static ref CLOSURE_CB_MAP: Mutex<HashMap<i32, i32>> = Default::default();
 }
 
+lazy_static! {
+    static ref LISTENER_EVENT_CB_MAP: Mutex<HashMap<i32, i32>> = Default::default();
+}
+
+lazy_static! {
+    static ref DELIVERY_CB_MAP: Mutex<HashMap<i32, i32>> = Default::default();
+}
+
+lazy_static! {
+    static ref CLAIM_EVENT_CB_MAP: Mutex<HashMap<i32, i32>> = Default::default();
+    static ref PROOF_EVENT_CB_MAP: Mutex<HashMap<i32, i32>> = Default::default();
+}
+
 pub struct CallbackUtils {}
 
 impl CallbackUtils {
@@ -323,6 +337,54 @@ impl CallbackUtils {
         (command_handle, Some(create_claim_callback))
     }
 
+    pub fn closure_to_issuer_revoke_claim_cb(closure: Box<FnMut(ErrorCode, String) + Send>) -> (i32,
+                                                                                              Option<extern fn(command_handle: i32,
+                                                                                                               err: ErrorCode,
+                                                                                                               revoc_reg_update_json: *const c_char)>) {
+        lazy_static! {
+            static ref REVOKE_CLAIM_CALLBACKS: Mutex < HashMap < i32, Box < FnMut(ErrorCode, String) + Send > >> = Default::default();
+        }
+
+        extern "C" fn revoke_claim_callback(command_handle: i32, err: ErrorCode, revoc_reg_update_json: *const c_char) {
+            let mut callbacks = REVOKE_CLAIM_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let revoc_reg_update_json = unsafe { CStr::from_ptr(revoc_reg_update_json).to_str().unwrap().to_string() };
+            cb(err, revoc_reg_update_json)
+        }
+
+        let mut callbacks = REVOKE_CLAIM_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(revoke_claim_callback))
+    }
+
+    pub fn closure_to_issuer_revoke_claim_with_ledger_update_cb(closure: Box<FnMut(ErrorCode, String, String) + Send>) -> (i32,
+                                                                                                                           Option<extern fn(command_handle: i32,
+                                                                                                                                            err: ErrorCode,
+                                                                                                                                            revoc_reg_update_json: *const c_char,
+                                                                                                                                            revoc_reg_entry_request_json: *const c_char)>) {
+        lazy_static! {
+            static ref REVOKE_CLAIM_WITH_LEDGER_UPDATE_CALLBACKS: Mutex < HashMap < i32, Box < FnMut(ErrorCode, String, String) + Send > >> = Default::default();
+        }
+
+        extern "C" fn revoke_claim_with_ledger_update_callback(command_handle: i32, err: ErrorCode,
+                                                               revoc_reg_update_json: *const c_char,
+                                                               revoc_reg_entry_request_json: *const c_char) {
+            let mut callbacks = REVOKE_CLAIM_WITH_LEDGER_UPDATE_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let revoc_reg_update_json = unsafe { CStr::from_ptr(revoc_reg_update_json).to_str().unwrap().to_string() };
+            let revoc_reg_entry_request_json = unsafe { CStr::from_ptr(revoc_reg_entry_request_json).to_str().unwrap().to_string() };
+            cb(err, revoc_reg_update_json, revoc_reg_entry_request_json)
+        }
+
+        let mut callbacks = REVOKE_CLAIM_WITH_LEDGER_UPDATE_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(revoke_claim_with_ledger_update_callback))
+    }
+
     pub fn closure_to_prover_store_claim_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
                                                                                        Option<extern fn(command_handle: i32,
                                                                                                         err: ErrorCode)>) {
@@ -658,14 +720,84 @@ impl CallbackUtils {
         (command_handle, Some(callback))
     }
 
-    pub fn closure_to_agent_send_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
-                                                                               Option<extern fn(command_handle: i32,
-                                                                                                err: ErrorCode)>) {
+    pub fn closure_to_agent_listener_event_cb(closure: Box<FnMut(i32, ErrorCode, String) + Send>)
+                                              -> (i32, Option<extern fn(listener_handle: i32, err: ErrorCode, event_json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(i32, ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_listener_event_callback(listener_handle: i32, err: ErrorCode, event_json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let event_json = unsafe { CStr::from_ptr(event_json).to_str().unwrap().to_string() };
+            let cb_id: i32 = *LISTENER_EVENT_CB_MAP.lock().unwrap().get(&listener_handle).unwrap();
+            callbacks.get_mut(&cb_id).unwrap()(listener_handle, err, event_json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let cb_id = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(cb_id, closure);
+
+        (cb_id, Some(agent_listener_event_callback))
+    }
+
+    pub fn closure_map_listener_event_ids(cb_id: i32, listener_handle: i32) {
+        let mut map = LISTENER_EVENT_CB_MAP.lock().unwrap();
+        map.insert(listener_handle, cb_id);
+    }
+
+    pub fn closure_to_agent_send_cb(closure: Box<FnMut(ErrorCode, i32) + Send>) -> (i32,
+                                                                                    Option<extern fn(command_handle: i32,
+                                                                                                     err: ErrorCode,
+                                                                                                     message_id: i32)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, i32) + Send>>> = Default::default();
+        }
+
+        extern "C" fn callback(command_handle: i32, err: ErrorCode, message_id: i32) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err, message_id)
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(callback))
+    }
+
+    pub fn closure_to_agent_delivery_cb(closure: Box<FnMut(i32, ErrorCode, i32) + Send>)
+                                        -> (i32, Option<extern fn(connection_handle: i32, err: ErrorCode, message_id: i32)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(i32, ErrorCode, i32) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_delivery_callback(conn_handle: i32, err: ErrorCode, message_id: i32) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let cb_id: i32 = *DELIVERY_CB_MAP.lock().unwrap().get(&conn_handle).unwrap();
+            callbacks.get_mut(&cb_id).unwrap()(conn_handle, err, message_id);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let cb_id = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(cb_id, closure);
+
+        (cb_id, Some(agent_delivery_callback))
+    }
+
+    pub fn closure_map_delivery_ids(cb_id: i32, conn_handle: i32) {
+        let mut map = DELIVERY_CB_MAP.lock().unwrap();
+        map.insert(conn_handle, cb_id);
+    }
+
+    pub fn closure_to_agent_identity_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                                   Option<extern fn(command_handle: i32,
+                                                                                                    err: ErrorCode)>) {
         lazy_static! {
             static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
         }
 
-        extern "C" fn callback(command_handle: i32, err: ErrorCode) {
+        extern "C" fn agent_identity_callback(command_handle: i32, err: ErrorCode) {
             let mut callbacks = CALLBACKS.lock().unwrap();
             let mut cb = callbacks.remove(&command_handle).unwrap();
             cb(err)
@@ -675,7 +807,227 @@ impl CallbackUtils {
         let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
         callbacks.insert(command_handle, closure);
 
-        (command_handle, Some(callback))
+        (command_handle, Some(agent_identity_callback))
+    }
+
+    pub fn closure_to_agent_mailbox_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                                  Option<extern fn(command_handle: i32,
+                                                                                                   err: ErrorCode)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_mailbox_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_mailbox_callback))
+    }
+
+    pub fn closure_to_agent_mailbox_pull_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                            -> (i32,
+                                                Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                                 messages_json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_mailbox_pull_callback(command_handle: i32, err: ErrorCode, messages_json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let messages_json = unsafe { CStr::from_ptr(messages_json).to_str().unwrap().to_string() };
+            cb(err, messages_json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_mailbox_pull_callback))
+    }
+
+    pub fn closure_to_agent_claim_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                                Option<extern fn(command_handle: i32,
+                                                                                                 err: ErrorCode)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_claim_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_claim_callback))
+    }
+
+    pub fn closure_to_agent_claim_json_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                          -> (i32,
+                                              Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                               json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_claim_json_callback(command_handle: i32, err: ErrorCode, json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let json = unsafe { CStr::from_ptr(json).to_str().unwrap().to_string() };
+            cb(err, json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_claim_json_callback))
+    }
+
+    pub fn closure_to_agent_claim_event_cb(closure: Box<FnMut(i32, ErrorCode, String) + Send>)
+                                           -> (i32, Option<extern fn(wallet_handle: i32, err: ErrorCode, event_json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(i32, ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_claim_event_callback(wallet_handle: i32, err: ErrorCode, event_json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let event_json = unsafe { CStr::from_ptr(event_json).to_str().unwrap().to_string() };
+            let cb_id: i32 = *CLAIM_EVENT_CB_MAP.lock().unwrap().get(&wallet_handle).unwrap();
+            callbacks.get_mut(&cb_id).unwrap()(wallet_handle, err, event_json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let cb_id = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(cb_id, closure);
+
+        (cb_id, Some(agent_claim_event_callback))
+    }
+
+    pub fn closure_map_claim_event_ids(cb_id: i32, wallet_handle: i32) {
+        let mut map = CLAIM_EVENT_CB_MAP.lock().unwrap();
+        map.insert(wallet_handle, cb_id);
+    }
+
+    pub fn closure_to_agent_proof_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                                Option<extern fn(command_handle: i32,
+                                                                                                 err: ErrorCode)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_proof_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_proof_callback))
+    }
+
+    pub fn closure_to_agent_proof_json_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                          -> (i32,
+                                              Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                               json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_proof_json_callback(command_handle: i32, err: ErrorCode, json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let json = unsafe { CStr::from_ptr(json).to_str().unwrap().to_string() };
+            cb(err, json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_proof_json_callback))
+    }
+
+    pub fn closure_to_agent_proof_event_cb(closure: Box<FnMut(i32, ErrorCode, String) + Send>)
+                                           -> (i32, Option<extern fn(wallet_handle: i32, err: ErrorCode, event_json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(i32, ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_proof_event_callback(wallet_handle: i32, err: ErrorCode, event_json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let event_json = unsafe { CStr::from_ptr(event_json).to_str().unwrap().to_string() };
+            let cb_id: i32 = *PROOF_EVENT_CB_MAP.lock().unwrap().get(&wallet_handle).unwrap();
+            callbacks.get_mut(&cb_id).unwrap()(wallet_handle, err, event_json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let cb_id = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(cb_id, closure);
+
+        (cb_id, Some(agent_proof_event_callback))
+    }
+
+    pub fn closure_map_proof_event_ids(cb_id: i32, wallet_handle: i32) {
+        let mut map = PROOF_EVENT_CB_MAP.lock().unwrap();
+        map.insert(wallet_handle, cb_id);
+    }
+
+    pub fn closure_to_agent_invitation_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                          -> (i32,
+                                              Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                               invitation_json: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_invitation_callback(command_handle: i32, err: ErrorCode, invitation_json: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let invitation_json = unsafe { CStr::from_ptr(invitation_json).to_str().unwrap().to_string() };
+            cb(err, invitation_json);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_invitation_callback))
+    }
+
+    pub fn closure_to_agent_accept_invitation_cb(closure: Box<FnMut(ErrorCode, i32, String) + Send>)
+                                                 -> (i32,
+                                                     Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                                      connection_handle: i32, my_did: *const c_char)>) {
+        lazy_static! {
+            static ref CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, i32, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn agent_accept_invitation_callback(command_handle: i32, err: ErrorCode, connection_handle: i32, my_did: *const c_char) {
+            let mut callbacks = CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let my_did = unsafe { CStr::from_ptr(my_did).to_str().unwrap().to_string() };
+            cb(err, connection_handle, my_did);
+        }
+
+        let mut callbacks = CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(agent_accept_invitation_callback))
     }
 
     pub fn closure_to_agent_close_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
@@ -808,4 +1160,68 @@ impl CallbackUtils {
 
         (command_handle, Some(replace_keys_callback))
     }
+
+    pub fn closure_to_sign_detached_cb(closure: Box<FnMut(ErrorCode, Vec<u8>) + Send>)
+                                       -> (i32,
+                                           Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                            signature_raw: *const u8, signature_len: u32)>) {
+        lazy_static! {
+            static ref SIGN_DETACHED_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, Vec<u8>) + Send>>> = Default::default();
+        }
+
+        extern "C" fn sign_detached_callback(command_handle: i32, err: ErrorCode, signature_raw: *const u8, signature_len: u32) {
+            let mut callbacks = SIGN_DETACHED_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let signature = unsafe { slice::from_raw_parts(signature_raw, signature_len as usize) }.to_vec();
+            cb(err, signature);
+        }
+
+        let mut callbacks = SIGN_DETACHED_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(sign_detached_callback))
+    }
+
+    pub fn closure_to_pairwise_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                             Option<extern fn(command_handle: i32,
+                                                                                              err: ErrorCode)>) {
+        lazy_static! {
+            static ref PAIRWISE_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn pairwise_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = PAIRWISE_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let mut callbacks = PAIRWISE_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(pairwise_callback))
+    }
+
+    pub fn closure_to_pairwise_json_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                       -> (i32,
+                                           Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                            pairwise_json: *const c_char)>) {
+        lazy_static! {
+            static ref PAIRWISE_JSON_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn pairwise_json_callback(command_handle: i32, err: ErrorCode, pairwise_json: *const c_char) {
+            let mut callbacks = PAIRWISE_JSON_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let pairwise_json = unsafe { CStr::from_ptr(pairwise_json).to_str().unwrap().to_string() };
+            cb(err, pairwise_json);
+        }
+
+        let mut callbacks = PAIRWISE_JSON_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(pairwise_json_callback))
+    }
 }
@@ -4,6 +4,9 @@ use sovrin::api::ErrorCode;
 use sovrin::api::ledger::{
     sovrin_sign_and_submit_request,
     sovrin_submit_request,
+    sovrin_submit_request_with_cache,
+    sovrin_purge_ledger_cache,
+    sovrin_resolve_did,
     sovrin_build_get_ddo_request,
     sovrin_build_attrib_request,
     sovrin_build_get_attrib_request,
@@ -13,7 +16,13 @@ use sovrin::api::ledger::{
     sovrin_build_claim_def_txn,
     sovrin_build_get_claim_def_txn,
     sovrin_build_node_request,
-    sovrin_build_nym_request
+    sovrin_build_nym_request,
+    sovrin_build_pool_upgrade_request,
+    sovrin_build_pool_config_request,
+    sovrin_build_revoc_reg_def_request,
+    sovrin_build_get_revoc_reg_def_request,
+    sovrin_build_revoc_reg_entry_request,
+    sovrin_build_get_revoc_reg_request
 };
 
 use utils::callback::CallbackUtils;
@@ -89,6 +98,100 @@ impl LedgerUtils {
         Ok(request_result_json)
     }
 
+    pub fn submit_request_with_cache(pool_handle: i32, wallet_handle: i32, request_json: &str, options_json: Option<&str>) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_result_json| {
+            sender.send((err, request_result_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_submit_request_cb(cb);
+
+        let request_json = CString::new(request_json).unwrap();
+        let options_json_str = options_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let err =
+            sovrin_submit_request_with_cache(command_handle,
+                                             pool_handle,
+                                             wallet_handle,
+                                             request_json.as_ptr(),
+                                             if options_json.is_some() { options_json_str.as_ptr() } else { null() },
+                                             cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_result_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_result_json)
+    }
+
+    pub fn purge_ledger_cache(wallet_handle: i32, cache_type: Option<&str>) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_delete_wallet_cb(cb);
+
+        let cache_type_str = cache_type.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let err =
+            sovrin_purge_ledger_cache(command_handle,
+                                      wallet_handle,
+                                      if cache_type.is_some() { cache_type_str.as_ptr() } else { null() },
+                                      cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve_did(pool_handle: i32, wallet_handle: i32, did: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, ddo_json| {
+            sender.send((err, ddo_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_submit_request_cb(cb);
+
+        let did = CString::new(did).unwrap();
+
+        let err =
+            sovrin_resolve_did(command_handle,
+                               pool_handle,
+                               wallet_handle,
+                               did.as_ptr(),
+                               cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, ddo_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(ddo_json)
+    }
+
     pub fn build_get_ddo_request(submitter_did: &str, target_did: &str) -> Result<String, ErrorCode> {
         let (sender, receiver) = channel();
 
@@ -424,4 +527,207 @@ impl LedgerUtils {
 
         Ok(request_json)
     }
-}
\ No newline at end of file
+
+    pub fn build_pool_upgrade_request(submitter_did: &str, name: &str, version: &str, action: &str, sha256: &str, timeout: Option<u32>,
+                                      schedule: Option<&str>, justification: Option<&str>, reinstall: bool, force: bool) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_build_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let name = CString::new(name).unwrap();
+        let version = CString::new(version).unwrap();
+        let action = CString::new(action).unwrap();
+        let sha256 = CString::new(sha256).unwrap();
+
+        let schedule_str = schedule.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+        let justification_str = justification.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let err =
+            sovrin_build_pool_upgrade_request(command_handle,
+                                              submitter_did.as_ptr(),
+                                              name.as_ptr(),
+                                              version.as_ptr(),
+                                              action.as_ptr(),
+                                              sha256.as_ptr(),
+                                              timeout.map(|t| t as i32).unwrap_or(-1),
+                                              if schedule.is_some() { schedule_str.as_ptr() } else { null() },
+                                              if justification.is_some() { justification_str.as_ptr() } else { null() },
+                                              reinstall,
+                                              force,
+                                              cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn build_pool_config_request(submitter_did: &str, writes: bool, force: bool) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_build_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+
+        let err =
+            sovrin_build_pool_config_request(command_handle,
+                                             submitter_did.as_ptr(),
+                                             writes,
+                                             force,
+                                             cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn build_revoc_reg_def_request(submitter_did: &str, xref: i32, data: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_build_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let data = CString::new(data).unwrap();
+
+        let err =
+            sovrin_build_revoc_reg_def_request(command_handle,
+                                               submitter_did.as_ptr(),
+                                               xref,
+                                               data.as_ptr(),
+                                               cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn build_get_revoc_reg_def_request(submitter_did: &str, xref: i32, origin: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_build_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let origin = CString::new(origin).unwrap();
+
+        let err =
+            sovrin_build_get_revoc_reg_def_request(command_handle,
+                                                   submitter_did.as_ptr(),
+                                                   xref,
+                                                   origin.as_ptr(),
+                                                   cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn build_revoc_reg_entry_request(submitter_did: &str, xref: i32, data: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_build_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let data = CString::new(data).unwrap();
+
+        let err =
+            sovrin_build_revoc_reg_entry_request(command_handle,
+                                                 submitter_did.as_ptr(),
+                                                 xref,
+                                                 data.as_ptr(),
+                                                 cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn build_get_revoc_reg_request(submitter_did: &str, xref: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_build_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+
+        let err =
+            sovrin_build_get_revoc_reg_request(command_handle,
+                                               submitter_did.as_ptr(),
+                                               xref,
+                                               cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+}
@@ -10,6 +10,7 @@ pub mod signus;
 pub mod wallet;
 pub mod ledger;
 pub mod anoncreds;
+pub mod pairwise;
 pub mod types;
 
 #[macro_use]
@@ -0,0 +1,123 @@
+use std::sync::mpsc::{channel};
+use std::ffi::{CString};
+use std::ptr::null;
+
+use sovrin::api::pairwise::{
+    sovrin_create_pairwise,
+    sovrin_delete_pairwise,
+    sovrin_get_pairwise,
+    sovrin_list_pairwise,
+    sovrin_update_pairwise,
+};
+use sovrin::api::ErrorCode;
+
+use utils::callback::CallbackUtils;
+use utils::timeout::TimeoutUtils;
+
+pub struct PairwiseUtils {}
+
+impl PairwiseUtils {
+    pub fn create_pairwise(wallet_handle: i32, their_did: &str, my_did: &str, pairwise_json: Option<&str>) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (command_handle, cb) = CallbackUtils::closure_to_pairwise_cb(Box::new(move |err| {
+            sender.send(err).unwrap();
+        }));
+
+        let pairwise_json_str = pairwise_json.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let err = sovrin_create_pairwise(command_handle, wallet_handle,
+                                         CString::new(their_did).unwrap().as_ptr(),
+                                         CString::new(my_did).unwrap().as_ptr(),
+                                         if pairwise_json.is_some() { pairwise_json_str.as_ptr() } else { null() },
+                                         cb);
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_pairwise(wallet_handle: i32, their_did: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (command_handle, cb) = CallbackUtils::closure_to_pairwise_json_cb(Box::new(move |err, pairwise_json| {
+            sender.send((err, pairwise_json)).unwrap();
+        }));
+
+        let err = sovrin_get_pairwise(command_handle, wallet_handle, CString::new(their_did).unwrap().as_ptr(), cb);
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, pairwise_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(pairwise_json)
+    }
+
+    pub fn list_pairwise(wallet_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let (command_handle, cb) = CallbackUtils::closure_to_pairwise_json_cb(Box::new(move |err, pairwise_list_json| {
+            sender.send((err, pairwise_list_json)).unwrap();
+        }));
+
+        let err = sovrin_list_pairwise(command_handle, wallet_handle, cb);
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, pairwise_list_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(pairwise_list_json)
+    }
+
+    pub fn update_pairwise(wallet_handle: i32, their_did: &str, pairwise_json: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (command_handle, cb) = CallbackUtils::closure_to_pairwise_cb(Box::new(move |err| {
+            sender.send(err).unwrap();
+        }));
+
+        let err = sovrin_update_pairwise(command_handle, wallet_handle,
+                                         CString::new(their_did).unwrap().as_ptr(),
+                                         CString::new(pairwise_json).unwrap().as_ptr(),
+                                         cb);
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn delete_pairwise(wallet_handle: i32, their_did: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let (command_handle, cb) = CallbackUtils::closure_to_pairwise_cb(Box::new(move |err| {
+            sender.send(err).unwrap();
+        }));
+
+        let err = sovrin_delete_pairwise(command_handle, wallet_handle, CString::new(their_did).unwrap().as_ptr(), cb);
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
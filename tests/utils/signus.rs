@@ -1,12 +1,36 @@
 use std::sync::mpsc::{channel};
 use std::ffi::{CString};
+use std::ptr::null;
 
 use sovrin::api::signus::{
     sovrin_sign,
+    sovrin_sign_detached,
+    sovrin_sign_jws,
     sovrin_create_and_store_my_did,
     sovrin_store_their_did,
     sovrin_replace_keys,
-    sovrin_verify_signature
+    sovrin_replace_keys_start,
+    sovrin_replace_keys_apply,
+    sovrin_replace_keys_on_ledger,
+    sovrin_verify_signature,
+    sovrin_encrypt,
+    sovrin_decrypt,
+    sovrin_encrypt_sealed,
+    sovrin_decrypt_sealed,
+    sovrin_encrypt_envelope,
+    sovrin_decrypt_envelope,
+    sovrin_create_key,
+    sovrin_sign_by_key,
+    sovrin_verify_by_key,
+    sovrin_verify_detached_by_key,
+    sovrin_encrypt_by_key,
+    sovrin_decrypt_by_key,
+    sovrin_list_my_dids_with_meta,
+    sovrin_list_their_dids_with_meta,
+    sovrin_set_did_metadata,
+    sovrin_get_did_metadata,
+    sovrin_set_endpoint_for_did,
+    sovrin_get_endpoint_for_did
 };
 use sovrin::api::ErrorCode;
 
@@ -198,6 +222,101 @@ impl SignusUtils {
         Ok((my_verkey, my_pk))
     }
 
+    pub fn replace_keys_start(wallet_handle: i32, did: &str, identity_json: &str) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, verkey, public_key| {
+            sender.send((err, verkey, public_key)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_replace_keys_cb(cb);
+
+        let did = CString::new(did).unwrap();
+        let identity_json = CString::new(identity_json).unwrap();
+
+        let err =
+            sovrin_replace_keys_start(command_handle,
+                                      wallet_handle,
+                                      did.as_ptr(),
+                                      identity_json.as_ptr(),
+                                      cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, my_verkey, my_pk) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((my_verkey, my_pk))
+    }
+
+    pub fn replace_keys_apply(wallet_handle: i32, did: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_delete_wallet_cb(cb);
+
+        let did = CString::new(did).unwrap();
+
+        let err =
+            sovrin_replace_keys_apply(command_handle,
+                                      wallet_handle,
+                                      did.as_ptr(),
+                                      cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn replace_keys_on_ledger(wallet_handle: i32, pool_handle: i32, did: &str, identity_json: &str) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, verkey, public_key| {
+            sender.send((err, verkey, public_key)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_replace_keys_cb(cb);
+
+        let did = CString::new(did).unwrap();
+        let identity_json = CString::new(identity_json).unwrap();
+
+        let err =
+            sovrin_replace_keys_on_ledger(command_handle,
+                                          wallet_handle,
+                                          pool_handle,
+                                          did.as_ptr(),
+                                          identity_json.as_ptr(),
+                                          cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, my_verkey, my_pk) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((my_verkey, my_pk))
+    }
+
     pub fn verify(wallet_handle: i32, pool_handle: i32, did: &str, signed_msg: &str) -> Result<bool, ErrorCode> {
         let (sender, receiver) = channel();
 
@@ -230,4 +349,592 @@ impl SignusUtils {
 
         Ok(valid)
     }
-}
\ No newline at end of file
+
+    pub fn set_endpoint_for_did(wallet_handle: i32, pool_handle: i32, did: &str, address: &str, transport_key: Option<&str>) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_delete_wallet_cb(cb);
+
+        let did = CString::new(did).unwrap();
+        let address = CString::new(address).unwrap();
+        let transport_key_str = transport_key.map(|s| CString::new(s).unwrap()).unwrap_or(CString::new("").unwrap());
+
+        let err =
+            sovrin_set_endpoint_for_did(command_handle,
+                                        wallet_handle,
+                                        pool_handle,
+                                        did.as_ptr(),
+                                        address.as_ptr(),
+                                        if transport_key.is_some() { transport_key_str.as_ptr() } else { null() },
+                                        cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_endpoint_for_did(wallet_handle: i32, pool_handle: i32, did: &str) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, address, transport_key| {
+            sender.send((err, address, transport_key)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_replace_keys_cb(cb);
+
+        let did = CString::new(did).unwrap();
+
+        let err =
+            sovrin_get_endpoint_for_did(command_handle,
+                                        wallet_handle,
+                                        pool_handle,
+                                        did.as_ptr(),
+                                        cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, address, transport_key) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((address, transport_key))
+    }
+
+    pub fn encrypt(wallet_handle: i32, pool_handle: i32, my_did: &str, did: &str, msg: &str) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, encrypted_msg, nonce| {
+            sender.send((err, encrypted_msg, nonce)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_replace_keys_cb(cb);
+
+        let my_did = CString::new(my_did).unwrap();
+        let did = CString::new(did).unwrap();
+        let msg = CString::new(msg).unwrap();
+
+        let err =
+            sovrin_encrypt(command_handle,
+                           wallet_handle,
+                           pool_handle,
+                           my_did.as_ptr(),
+                           did.as_ptr(),
+                           msg.as_ptr(),
+                           cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, encrypted_msg, nonce) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((encrypted_msg, nonce))
+    }
+
+    pub fn decrypt(wallet_handle: i32, my_did: &str, did: &str, encrypted_msg: &str, nonce: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, decrypted_msg| {
+            sender.send((err, decrypted_msg)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let my_did = CString::new(my_did).unwrap();
+        let did = CString::new(did).unwrap();
+        let encrypted_msg = CString::new(encrypted_msg).unwrap();
+        let nonce = CString::new(nonce).unwrap();
+
+        let err =
+            sovrin_decrypt(command_handle,
+                           wallet_handle,
+                           my_did.as_ptr(),
+                           did.as_ptr(),
+                           encrypted_msg.as_ptr(),
+                           nonce.as_ptr(),
+                           cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, decrypted_msg) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(decrypted_msg)
+    }
+
+    pub fn encrypt_sealed(wallet_handle: i32, pool_handle: i32, did: &str, msg: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, encrypted_msg| {
+            sender.send((err, encrypted_msg)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let did = CString::new(did).unwrap();
+        let msg = CString::new(msg).unwrap();
+
+        let err =
+            sovrin_encrypt_sealed(command_handle,
+                                  wallet_handle,
+                                  pool_handle,
+                                  did.as_ptr(),
+                                  msg.as_ptr(),
+                                  cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, encrypted_msg) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(encrypted_msg)
+    }
+
+    pub fn decrypt_sealed(wallet_handle: i32, my_did: &str, encrypted_msg: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, decrypted_msg| {
+            sender.send((err, decrypted_msg)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let my_did = CString::new(my_did).unwrap();
+        let encrypted_msg = CString::new(encrypted_msg).unwrap();
+
+        let err =
+            sovrin_decrypt_sealed(command_handle,
+                                  wallet_handle,
+                                  my_did.as_ptr(),
+                                  encrypted_msg.as_ptr(),
+                                  cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, decrypted_msg) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(decrypted_msg)
+    }
+
+    pub fn create_key(wallet_handle: i32, key_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, verkey| {
+            sender.send((err, verkey)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let key_json = CString::new(key_json).unwrap();
+
+        let err = sovrin_create_key(command_handle, wallet_handle, key_json.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, verkey) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(verkey)
+    }
+
+    pub fn sign_by_key(wallet_handle: i32, verkey: &str, msg: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, signed_msg| {
+            sender.send((err, signed_msg)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let verkey = CString::new(verkey).unwrap();
+        let msg = CString::new(msg).unwrap();
+
+        let err = sovrin_sign_by_key(command_handle, wallet_handle, verkey.as_ptr(), msg.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, signed_msg) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(signed_msg)
+    }
+
+    pub fn verify_by_key(verkey: &str, signed_msg: &str) -> Result<bool, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, valid| {
+            sender.send((err, valid)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_verify_signature_cb(cb);
+
+        let verkey = CString::new(verkey).unwrap();
+        let signed_msg = CString::new(signed_msg).unwrap();
+
+        let err = sovrin_verify_by_key(command_handle, verkey.as_ptr(), signed_msg.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, valid) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(valid)
+    }
+
+    pub fn encrypt_by_key(wallet_handle: i32, my_verkey: &str, their_verkey: &str, msg: &str) -> Result<(String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, encrypted_msg, nonce| {
+            sender.send((err, encrypted_msg, nonce)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_replace_keys_cb(cb);
+
+        let my_verkey = CString::new(my_verkey).unwrap();
+        let their_verkey = CString::new(their_verkey).unwrap();
+        let msg = CString::new(msg).unwrap();
+
+        let err =
+            sovrin_encrypt_by_key(command_handle,
+                                  wallet_handle,
+                                  my_verkey.as_ptr(),
+                                  their_verkey.as_ptr(),
+                                  msg.as_ptr(),
+                                  cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, encrypted_msg, nonce) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((encrypted_msg, nonce))
+    }
+
+    pub fn decrypt_by_key(wallet_handle: i32, my_verkey: &str, their_verkey: &str, encrypted_msg: &str, nonce: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, decrypted_msg| {
+            sender.send((err, decrypted_msg)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let my_verkey = CString::new(my_verkey).unwrap();
+        let their_verkey = CString::new(their_verkey).unwrap();
+        let encrypted_msg = CString::new(encrypted_msg).unwrap();
+        let nonce = CString::new(nonce).unwrap();
+
+        let err =
+            sovrin_decrypt_by_key(command_handle,
+                                  wallet_handle,
+                                  my_verkey.as_ptr(),
+                                  their_verkey.as_ptr(),
+                                  encrypted_msg.as_ptr(),
+                                  nonce.as_ptr(),
+                                  cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, decrypted_msg) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(decrypted_msg)
+    }
+
+    pub fn list_my_dids_with_meta(wallet_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, dids_json| {
+            sender.send((err, dids_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let err = sovrin_list_my_dids_with_meta(command_handle, wallet_handle, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, dids_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(dids_json)
+    }
+
+    pub fn list_their_dids_with_meta(wallet_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, dids_json| {
+            sender.send((err, dids_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let err = sovrin_list_their_dids_with_meta(command_handle, wallet_handle, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, dids_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(dids_json)
+    }
+
+    pub fn set_did_metadata(wallet_handle: i32, did: &str, metadata: &str) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_delete_wallet_cb(cb);
+
+        let did = CString::new(did).unwrap();
+        let metadata = CString::new(metadata).unwrap();
+
+        let err = sovrin_set_did_metadata(command_handle, wallet_handle, did.as_ptr(), metadata.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_did_metadata(wallet_handle: i32, did: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, metadata| {
+            sender.send((err, metadata)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let did = CString::new(did).unwrap();
+
+        let err = sovrin_get_did_metadata(command_handle, wallet_handle, did.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, metadata) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(metadata)
+    }
+
+    pub fn sign_detached(wallet_handle: i32, did: &str, msg: &[u8]) -> Result<Vec<u8>, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, signature| {
+            sender.send((err, signature)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_detached_cb(cb);
+
+        let did = CString::new(did).unwrap();
+
+        let err = sovrin_sign_detached(command_handle, wallet_handle, did.as_ptr(), msg.as_ptr(), msg.len() as u32, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, signature) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(signature)
+    }
+
+    pub fn sign_jws(wallet_handle: i32, did: &str, payload: &[u8]) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, jws| {
+            sender.send((err, jws)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let did = CString::new(did).unwrap();
+
+        let err = sovrin_sign_jws(command_handle, wallet_handle, did.as_ptr(), payload.as_ptr(), payload.len() as u32, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, jws) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(jws)
+    }
+
+    pub fn verify_detached_by_key(verkey: &str, msg: &[u8], signature: &[u8]) -> Result<bool, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, valid| {
+            sender.send((err, valid)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_verify_signature_cb(cb);
+
+        let verkey = CString::new(verkey).unwrap();
+
+        let err = sovrin_verify_detached_by_key(command_handle, verkey.as_ptr(),
+                                                msg.as_ptr(), msg.len() as u32,
+                                                signature.as_ptr(), signature.len() as u32,
+                                                cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, valid) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(valid)
+    }
+
+    pub fn encrypt_envelope(wallet_handle: i32, dids_json: &str, msg: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, envelope_json| {
+            sender.send((err, envelope_json)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let dids_json = CString::new(dids_json).unwrap();
+        let msg = CString::new(msg).unwrap();
+
+        let err = sovrin_encrypt_envelope(command_handle, wallet_handle, dids_json.as_ptr(), msg.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, envelope_json) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(envelope_json)
+    }
+
+    pub fn decrypt_envelope(wallet_handle: i32, my_did: &str, envelope_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+
+        let cb = Box::new(move |err, decrypted_msg| {
+            sender.send((err, decrypted_msg)).unwrap();
+        });
+
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_cb(cb);
+
+        let my_did = CString::new(my_did).unwrap();
+        let envelope_json = CString::new(envelope_json).unwrap();
+
+        let err = sovrin_decrypt_envelope(command_handle, wallet_handle, my_did.as_ptr(), envelope_json.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, decrypted_msg) = receiver.recv_timeout(TimeoutUtils::long_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(decrypted_msg)
+    }
+}
@@ -45,6 +45,15 @@ public enum ErrorCode {
     // IO Error
     CommonIOError(111),
 
+    // Caller passed invalid value as param 10 (null, invalid json and etc..)
+    CommonInvalidParam10(112),
+
+    // Caller passed invalid value as param 11 (null, invalid json and etc..)
+    CommonInvalidParam11(113),
+
+    // Caller passed invalid value as param 12 (null, invalid json and etc..)
+    CommonInvalidParam12(114),
+
     // Wallet errors
     // Caller passed invalid wallet handle
     WalletInvalidHandle(200),
@@ -83,6 +92,9 @@ public enum ErrorCode {
     // Attempt to send transaction without the necessary privileges
     LedgerSecurityError(305),
 
+    // Requested entity (e.g. DID) is not found on the ledger
+    LedgerNotFoundError(306),
+
     // Crypto errors
     // Revocation registry is full and creation of new registry is necessary
     AnoncredsRevocationRegistryFullError(400),